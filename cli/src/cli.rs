@@ -16,6 +16,9 @@
 
 //! Polkadot CLI library.
 
+use std::path::PathBuf;
+
+use sc_service::config::MultiaddrWithPeerId;
 use structopt::StructOpt;
 
 #[allow(missing_docs)]
@@ -91,6 +94,40 @@ pub struct RunCmd {
 	/// elapsed (i.e. until a block at height `pause_block + delay` is imported).
 	#[structopt(long = "grandpa-pause", number_of_values(2))]
 	pub grandpa_pause: Vec<u32>,
+
+	/// Maximum number of incoming connections on the validation peer-set.
+	#[structopt(long = "validation-in-peers", value_name = "COUNT", default_value = "25")]
+	pub validation_in_peers: u32,
+
+	/// Maximum number of outgoing connections on the validation peer-set.
+	#[structopt(long = "validation-out-peers", value_name = "COUNT", default_value = "25")]
+	pub validation_out_peers: u32,
+
+	/// Peers to always stay connected to on the validation peer-set.
+	///
+	/// Reserved peers don't take up any of the slots of the peer-set.
+	#[structopt(long = "validation-reserved-nodes", value_name = "ADDR")]
+	pub validation_reserved_nodes: Vec<MultiaddrWithPeerId>,
+
+	/// Maximum number of incoming connections on the collation peer-set.
+	#[structopt(long = "collation-in-peers", value_name = "COUNT", default_value = "25")]
+	pub collation_in_peers: u32,
+
+	/// Maximum number of outgoing connections on the collation peer-set.
+	#[structopt(long = "collation-out-peers", value_name = "COUNT", default_value = "0")]
+	pub collation_out_peers: u32,
+
+	/// Peers to always stay connected to on the collation peer-set.
+	///
+	/// Reserved peers don't take up any of the slots of the peer-set.
+	#[structopt(long = "collation-reserved-nodes", value_name = "ADDR")]
+	pub collation_reserved_nodes: Vec<MultiaddrWithPeerId>,
+
+	/// Record the signals and messages routed by the overseer to the given file, for debugging.
+	///
+	/// The file is truncated if it exists.
+	#[structopt(long = "overseer-recording", value_name = "PATH", parse(from_os_str))]
+	pub overseer_recording: Option<PathBuf>,
 }
 
 #[allow(missing_docs)]
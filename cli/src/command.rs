@@ -17,7 +17,7 @@
 use log::info;
 use service::{IdentifyVariant, self};
 use sc_cli::{SubstrateCli, Result, RuntimeVersion, Role};
-use crate::cli::{Cli, Subcommand};
+use crate::cli::{Cli, RunCmd, Subcommand};
 
 fn get_exec_name() -> Option<String> {
 	std::env::current_exe()
@@ -114,6 +114,21 @@ fn set_default_ss58_version(spec: &Box<dyn service::ChainSpec>) {
 	sp_core::crypto::set_default_ss58_version(ss58_version);
 }
 
+fn peer_sets_config(cmd: &RunCmd) -> service::PeerSetsConfig {
+	service::PeerSetsConfig {
+		validation: service::PeerSetConfig {
+			in_peers: cmd.validation_in_peers,
+			out_peers: cmd.validation_out_peers,
+			reserved_nodes: cmd.validation_reserved_nodes.clone(),
+		},
+		collation: service::PeerSetConfig {
+			in_peers: cmd.collation_in_peers,
+			out_peers: cmd.collation_out_peers,
+			reserved_nodes: cmd.collation_reserved_nodes.clone(),
+		},
+	}
+}
+
 /// Parses polkadot specific CLI arguments and run the service.
 pub fn run() -> Result<()> {
 	let cli = Cli::from_args();
@@ -130,6 +145,8 @@ pub fn run() -> Result<()> {
 			} else {
 				Some((cli.run.grandpa_pause[0], cli.run.grandpa_pause[1]))
 			};
+			let peer_sets = peer_sets_config(&cli.run);
+			let overseer_recording = cli.run.overseer_recording.clone();
 
 			if chain_spec.is_kusama() {
 				info!("----------------------------");
@@ -148,6 +165,8 @@ pub fn run() -> Result<()> {
 						config,
 						service::IsCollator::No,
 						grandpa_pause,
+						peer_sets,
+						overseer_recording,
 					).map(|full| full.task_manager),
 				}
 			})
@@ -525,12 +525,17 @@ where
 				FromOverseer::Signal(OverseerSignal::ActiveLeaves(
 					ActiveLeavesUpdate { activated, .. })
 				) => {
-					for activated in activated.into_iter() {
-						process_block_activated(ctx, &subsystem.inner, activated, &subsystem.metrics).await?;
+					for leaf in activated.into_iter() {
+						// Blocks between the previous leaf on this fork and the new one
+						// may have included candidates as well, oldest first.
+						let blocks = leaf.new_ancestry.iter().rev().copied().chain(std::iter::once(leaf.hash));
+						for hash in blocks {
+							process_block_activated(ctx, &subsystem.inner, hash, &subsystem.metrics).await?;
+						}
 					}
 				}
-				FromOverseer::Signal(OverseerSignal::BlockFinalized(hash)) => {
-					process_block_finalized(subsystem, ctx, &subsystem.inner, hash).await?;
+				FromOverseer::Signal(OverseerSignal::BlockFinalized(_hash, number)) => {
+					process_block_finalized(subsystem, &subsystem.inner, number)?;
 				}
 				FromOverseer::Communication { msg } => {
 					process_message(subsystem, ctx, msg).await?;
@@ -555,20 +560,14 @@ where
 /// The state of data has to be changed from
 /// `CandidateState::Included` to `CandidateState::Finalized` and their pruning times have
 /// to be updated to `now` + keep_finalized_{block, chunk}_for`.
-#[tracing::instrument(level = "trace", skip(subsystem, ctx, db), fields(subsystem = LOG_TARGET))]
-async fn process_block_finalized<Context>(
+#[tracing::instrument(level = "trace", skip(subsystem, db), fields(subsystem = LOG_TARGET))]
+fn process_block_finalized(
 	subsystem: &AvailabilityStoreSubsystem,
-	ctx: &mut Context,
 	db: &Arc<dyn KeyValueDB>,
-	hash: Hash,
-) -> Result<(), Error>
-where
-	Context: SubsystemContext<Message=AvailabilityStoreMessage>
-{
+	block_number: BlockNumber,
+) -> Result<(), Error> {
 	let _timer = subsystem.metrics.time_process_block_finalized();
 
-	let block_number = get_block_number(ctx, hash).await?;
-
 	if let Some(mut pov_pruning) = pov_pruning(db) {
 		// Since the records are sorted by time in which they need to be pruned and not by block
 		// numbers we have to iterate through the whole collection here.
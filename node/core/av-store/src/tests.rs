@@ -181,7 +181,7 @@ fn runtime_api_error_does_not_stop_the_subsystem() {
 		overseer_signal(
 			&mut virtual_overseer,
 			OverseerSignal::ActiveLeaves(ActiveLeavesUpdate {
-				activated: smallvec![new_leaf.clone()],
+				activated: smallvec![test_helpers::activated_leaf(new_leaf)],
 				deactivated: smallvec![],
 			}),
 		).await;
@@ -215,6 +215,40 @@ fn runtime_api_error_does_not_stop_the_subsystem() {
 	});
 }
 
+#[test]
+fn candidate_events_of_new_ancestry_are_processed() {
+	let store = Arc::new(kvdb_memorydb::create(columns::NUM_COLUMNS));
+
+	test_harness(PruningConfig::default(), store, |test_harness| async move {
+		let TestHarness { mut virtual_overseer } = test_harness;
+		let ancestor_1 = Hash::repeat_byte(0x01);
+		let ancestor_2 = Hash::repeat_byte(0x02);
+		let new_leaf = Hash::repeat_byte(0x03);
+
+		let mut leaf = test_helpers::activated_leaf(new_leaf);
+		leaf.new_ancestry = vec![ancestor_2, ancestor_1];
+
+		overseer_signal(
+			&mut virtual_overseer,
+			OverseerSignal::ActiveLeaves(ActiveLeavesUpdate::start_work(leaf)),
+		).await;
+
+		// the ancestry is processed oldest first, followed by the leaf itself.
+		for expected in vec![ancestor_1, ancestor_2, new_leaf] {
+			assert_matches!(
+				overseer_recv(&mut virtual_overseer).await,
+				AllMessages::RuntimeApi(RuntimeApiMessage::Request(
+					relay_parent,
+					RuntimeApiRequest::CandidateEvents(tx),
+				)) => {
+					assert_eq!(relay_parent, expected);
+					tx.send(Ok(Vec::new())).unwrap();
+				}
+			);
+		}
+	});
+}
+
 #[test]
 fn store_chunk_works() {
 	let store = Arc::new(kvdb_memorydb::create(columns::NUM_COLUMNS));
@@ -515,7 +549,7 @@ fn stored_data_kept_until_finalized() {
 		overseer_signal(
 			&mut virtual_overseer,
 			OverseerSignal::ActiveLeaves(ActiveLeavesUpdate {
-				activated: smallvec![new_leaf.clone()],
+				activated: smallvec![test_helpers::activated_leaf(new_leaf)],
 				deactivated: smallvec![],
 			}),
 		).await;
@@ -543,20 +577,9 @@ fn stored_data_kept_until_finalized() {
 
 		overseer_signal(
 			&mut virtual_overseer,
-			OverseerSignal::BlockFinalized(new_leaf)
+			OverseerSignal::BlockFinalized(new_leaf, 10)
 		).await;
 
-		assert_matches!(
-			overseer_recv(&mut virtual_overseer).await,
-			AllMessages::ChainApi(ChainApiMessage::BlockNumber(
-				hash,
-				tx,
-			)) => {
-				assert_eq!(hash, new_leaf);
-				tx.send(Ok(Some(10))).unwrap();
-			}
-		);
-
 		// Wait for a half of the time finalized data should be available for
 		Delay::new(test_state.pruning_config.keep_finalized_block_for / 2).await;
 
@@ -630,7 +653,7 @@ fn stored_chunk_kept_until_finalized() {
 		overseer_signal(
 			&mut virtual_overseer,
 			OverseerSignal::ActiveLeaves(ActiveLeavesUpdate {
-				activated: smallvec![new_leaf.clone()],
+				activated: smallvec![test_helpers::activated_leaf(new_leaf)],
 				deactivated: smallvec![],
 			}),
 		).await;
@@ -658,20 +681,9 @@ fn stored_chunk_kept_until_finalized() {
 
 		overseer_signal(
 			&mut virtual_overseer,
-			OverseerSignal::BlockFinalized(new_leaf)
+			OverseerSignal::BlockFinalized(new_leaf, 10)
 		).await;
 
-		assert_matches!(
-			overseer_recv(&mut virtual_overseer).await,
-			AllMessages::ChainApi(ChainApiMessage::BlockNumber(
-				hash,
-				tx,
-			)) => {
-				assert_eq!(hash, new_leaf);
-				tx.send(Ok(Some(10))).unwrap();
-			}
-		);
-
 		// Wait for a half of the time finalized data should be available for
 		Delay::new(test_state.pruning_config.keep_finalized_block_for / 2).await;
 
@@ -779,7 +791,10 @@ fn forkfullness_works() {
 		overseer_signal(
 			&mut virtual_overseer,
 			OverseerSignal::ActiveLeaves(ActiveLeavesUpdate {
-				activated: smallvec![new_leaf_1.clone(), new_leaf_2.clone()],
+				activated: smallvec![
+					test_helpers::activated_leaf(new_leaf_1),
+					test_helpers::activated_leaf(new_leaf_2),
+				],
 				deactivated: smallvec![],
 			}),
 		).await;
@@ -812,20 +827,9 @@ fn forkfullness_works() {
 
 		overseer_signal(
 			&mut virtual_overseer,
-			OverseerSignal::BlockFinalized(new_leaf_1)
+			OverseerSignal::BlockFinalized(new_leaf_1, 5)
 		).await;
 
-		assert_matches!(
-			overseer_recv(&mut virtual_overseer).await,
-			AllMessages::ChainApi(ChainApiMessage::BlockNumber(
-				hash,
-				tx,
-			)) => {
-				assert_eq!(hash, new_leaf_1);
-				tx.send(Ok(Some(5))).unwrap();
-			}
-		);
-
 
 		// Data of both candidates should be still present in the DB.
 		assert_eq!(
@@ -1201,7 +1201,9 @@ mod tests {
 	) {
 		// Start work on some new parent.
 		virtual_overseer.send(FromOverseer::Signal(
-			OverseerSignal::ActiveLeaves(ActiveLeavesUpdate::start_work(test_state.relay_parent)))
+			OverseerSignal::ActiveLeaves(ActiveLeavesUpdate::start_work(
+				polkadot_node_subsystem_test_helpers::activated_leaf(test_state.relay_parent),
+			)))
 		).await;
 
 		// Check that subsystem job issues a request for a validator set.
@@ -36,7 +36,7 @@ use polkadot_node_subsystem_util::{
 use polkadot_primitives::v1::{Block, BlockId, Hash, ParachainHost};
 use std::sync::Arc;
 
-use sp_api::{ProvideRuntimeApi};
+use sp_api::{ApiExt, ProvideRuntimeApi};
 
 use futures::prelude::*;
 
@@ -80,7 +80,7 @@ async fn run<Client>(
 		match ctx.recv().await? {
 			FromOverseer::Signal(OverseerSignal::Conclude) => return Ok(()),
 			FromOverseer::Signal(OverseerSignal::ActiveLeaves(_)) => {},
-			FromOverseer::Signal(OverseerSignal::BlockFinalized(_)) => {},
+			FromOverseer::Signal(OverseerSignal::BlockFinalized(..)) => {},
 			FromOverseer::Communication { msg } => match msg {
 				RuntimeApiMessage::Request(relay_parent, request) => make_runtime_api_request(
 					&*subsystem.client,
@@ -113,7 +113,32 @@ fn make_runtime_api_request<Client>(
 				.map_err(|e| RuntimeApiError::from(format!("{:?}", e)));
 			metrics.on_request(res.is_ok());
 			let _ = sender.send(res);
-		}}
+		}};
+		// Only query the runtime if its `ParachainHost` API is at least at the given version.
+		($api_name:ident ($($param:expr),*), $sender:expr, since = $version:expr) => {{
+			let supported = client.runtime_api()
+				.has_api_with::<dyn ParachainHost<Block>, _>(
+					&BlockId::Hash(relay_parent),
+					|version| version >= $version,
+				)
+				.map_err(|e| RuntimeApiError::from(format!("{:?}", e)));
+
+			match supported {
+				Ok(true) => query!($api_name($($param),*), $sender),
+				Ok(false) => {
+					metrics.on_request(false);
+					let _ = $sender.send(Err(RuntimeApiError::from(format!(
+						"`{}` requires version {} of the `ParachainHost` API",
+						stringify!($api_name),
+						$version,
+					))));
+				}
+				Err(e) => {
+					metrics.on_request(false);
+					let _ = $sender.send(Err(e));
+				}
+			}
+		}};
 	}
 
 	match request {
@@ -137,6 +162,7 @@ fn make_runtime_api_request<Client>(
 		Request::ValidatorDiscovery(ids, sender) => query!(validator_discovery(ids), sender),
 		Request::DmqContents(id, sender) => query!(dmq_contents(id), sender),
 		Request::InboundHrmpChannelsContents(id, sender) => query!(inbound_hrmp_channels_contents(id), sender),
+		Request::ParaLimits(id, sender) => query!(para_limits(id), sender, since = 2),
 	}
 }
 
@@ -202,7 +228,7 @@ mod tests {
 		ValidatorId, ValidatorIndex, GroupRotationInfo, CoreState, PersistedValidationData,
 		Id as ParaId, OccupiedCoreAssumption, ValidationData, SessionIndex, ValidationCode,
 		CommittedCandidateReceipt, CandidateEvent, AuthorityDiscoveryId, InboundDownwardMessage,
-		BlockNumber, InboundHrmpMessage,
+		BlockNumber, InboundHrmpMessage, ParaLimits, MessageQueueChain, MessageQueueChainHead,
 	};
 	use polkadot_node_subsystem_test_helpers as test_helpers;
 	use sp_core::testing::TaskExecutor;
@@ -223,6 +249,7 @@ mod tests {
 		candidate_events: Vec<CandidateEvent>,
 		dmq_contents: HashMap<ParaId, Vec<InboundDownwardMessage>>,
 		hrmp_channels: HashMap<ParaId, BTreeMap<ParaId, Vec<InboundHrmpMessage>>>,
+		para_limits: HashMap<ParaId, ParaLimits>,
 	}
 
 	impl ProvideRuntimeApi<Block> for MockRuntimeApi {
@@ -338,6 +365,14 @@ mod tests {
 			) -> BTreeMap<ParaId, Vec<InboundHrmpMessage>> {
 				self.hrmp_channels.get(&recipient).map(|q| q.clone()).unwrap_or_default()
 			}
+
+			fn para_limits(&self, para_id: ParaId) -> Option<ParaLimits> {
+				self.para_limits.get(&para_id).cloned()
+			}
+
+			fn mqc_head(&self, _queue: MessageQueueChain) -> Option<MessageQueueChainHead> {
+				None
+			}
 		}
 	}
 
@@ -796,6 +831,49 @@ mod tests {
 		futures::executor::block_on(future::join(subsystem_task, test_task));
 	}
 
+	#[test]
+	fn requests_para_limits() {
+		let (ctx, mut ctx_handle) = test_helpers::make_subsystem_context(TaskExecutor::new());
+
+		let relay_parent = [1; 32].into();
+		let para_a = 5.into();
+		let para_b = 6.into();
+
+		let limits = ParaLimits {
+			max_pov_size: 4096,
+			hrmp_max_inbound_channels: 10,
+			..Default::default()
+		};
+
+		let runtime_api = Arc::new({
+			let mut runtime_api = MockRuntimeApi::default();
+			runtime_api.para_limits.insert(para_a, limits.clone());
+			runtime_api
+		});
+
+		let subsystem = RuntimeApiSubsystem::new(runtime_api.clone(), Metrics(None));
+		let subsystem_task = run(ctx, subsystem).map(|x| x.unwrap());
+		let test_task = async move {
+			let (tx, rx) = oneshot::channel();
+			ctx_handle.send(FromOverseer::Communication {
+				msg: RuntimeApiMessage::Request(relay_parent, Request::ParaLimits(para_a, tx)),
+			}).await;
+
+			assert_eq!(rx.await.unwrap().unwrap(), Some(limits));
+
+			let (tx, rx) = oneshot::channel();
+			ctx_handle.send(FromOverseer::Communication {
+				msg: RuntimeApiMessage::Request(relay_parent, Request::ParaLimits(para_b, tx)),
+			}).await;
+
+			assert_eq!(rx.await.unwrap().unwrap(), None);
+
+			ctx_handle.send(FromOverseer::Signal(OverseerSignal::Conclude)).await;
+		};
+
+		futures::executor::block_on(future::join(subsystem_task, test_task));
+	}
+
 	#[test]
 	fn requests_historical_code() {
 		let (ctx, mut ctx_handle) = test_helpers::make_subsystem_context(TaskExecutor::new());
@@ -33,6 +33,7 @@ use sp_keystore::{CryptoStore, SyncCryptoStorePtr};
 use polkadot_erasure_coding::branch_hash;
 use polkadot_node_network_protocol::{
 	v1 as protocol_v1, NetworkBridgeEvent, PeerId, ReputationChange as Rep, View,
+	request_response::{IncomingRequest, v1::{ChunkFetchingRequest, ChunkFetchingResponse}},
 };
 use polkadot_node_subsystem_util::metrics::{self, prometheus};
 use polkadot_primitives::v1::{
@@ -333,6 +334,9 @@ where
 		NetworkBridgeEvent::OurViewChange(view) => {
 			handle_our_view_change(ctx, keystore, state, view, metrics).await?;
 		}
+		NetworkBridgeEvent::NewGossipTopology(_) => {
+			// Only statements and bitfields are gossiped along the grid.
+		}
 		NetworkBridgeEvent::PeerMessage(remote, msg) => {
 			let gossiped_availability = match msg {
 				protocol_v1::AvailabilityDistributionMessage::Chunk(candidate_hash, chunk) => {
@@ -350,6 +354,32 @@ where
 	Ok(())
 }
 
+/// Answer a request for an erasure chunk with the chunk from the availability store, if we have it.
+#[tracing::instrument(level = "trace", skip(ctx, req), fields(subsystem = LOG_TARGET))]
+async fn answer_chunk_request<Context>(
+	ctx: &mut Context,
+	req: IncomingRequest<ChunkFetchingRequest>,
+) -> Result<()>
+where
+	Context: SubsystemContext<Message = AvailabilityDistributionMessage>,
+{
+	let chunk = query_chunk(ctx, req.payload.candidate_hash, req.payload.index).await?;
+
+	let response = match chunk {
+		Some(chunk) => ChunkFetchingResponse::Chunk(chunk),
+		None => ChunkFetchingResponse::NoSuchChunk,
+	};
+
+	if req.send_response(response).is_err() {
+		tracing::debug!(
+			target: LOG_TARGET,
+			"Chunk request was no longer awaited when answering it",
+		);
+	}
+
+	Ok(())
+}
+
 /// Handle the changes necessary when our view changes.
 #[tracing::instrument(level = "trace", skip(ctx, keystore, metrics), fields(subsystem = LOG_TARGET))]
 async fn handle_our_view_change<Context>(
@@ -793,13 +823,24 @@ impl AvailabilityDistributionSubsystem {
 						);
 					}
 				}
+				FromOverseer::Communication {
+					msg: AvailabilityDistributionMessage::ChunkFetchingRequest(req),
+				} => {
+					if let Err(e) = answer_chunk_request(&mut ctx, req).await {
+						tracing::warn!(
+							target: LOG_TARGET,
+							err = ?e,
+							"Failed to answer chunk request",
+						);
+					}
+				}
 				FromOverseer::Signal(OverseerSignal::ActiveLeaves(ActiveLeavesUpdate {
 					activated: _,
 					deactivated: _,
 				})) => {
 					// handled at view change
 				}
-				FromOverseer::Signal(OverseerSignal::BlockFinalized(_)) => {}
+				FromOverseer::Signal(OverseerSignal::BlockFinalized(..)) => {}
 				FromOverseer::Signal(OverseerSignal::Conclude) => {
 					return Ok(());
 				}
@@ -448,7 +448,7 @@ fn reputation_verification() {
 		overseer_signal(
 			&mut virtual_overseer,
 			OverseerSignal::ActiveLeaves(ActiveLeavesUpdate {
-				activated: smallvec![current.clone()],
+				activated: smallvec![test_helpers::activated_leaf(current)],
 				deactivated: smallvec![],
 			}),
 		)
@@ -970,3 +970,49 @@ fn k_ancestors_in_session() {
 
 	executor::block_on(future::join(test_fut, sut).timeout(Duration::from_millis(1000)));
 }
+
+#[test]
+fn chunk_requests_are_answered_from_the_store() {
+	let test_state = TestState::default();
+
+	let pov = PoV {
+		block_data: BlockData(vec![42, 43, 44]),
+	};
+	let available_data = make_available_data(&test_state, pov);
+	let chunks = derive_erasure_chunks_with_proofs(test_state.validators.len(), &available_data);
+	let candidate_hash = CandidateHash(Hash::repeat_byte(0xAB));
+
+	test_harness(test_state.keystore.clone(), |test_harness| async move {
+		let mut virtual_overseer = test_harness.virtual_overseer;
+
+		for (index, stored) in vec![(1, Some(chunks[1].clone())), (2, None)] {
+			let (tx, rx) = oneshot::channel();
+			overseer_send(
+				&mut virtual_overseer,
+				AvailabilityDistributionMessage::ChunkFetchingRequest(IncomingRequest::new(
+					PeerId::random(),
+					ChunkFetchingRequest { candidate_hash, index },
+					tx,
+				)),
+			)
+			.await;
+
+			assert_matches!(
+				overseer_recv(&mut virtual_overseer).await,
+				AllMessages::AvailabilityStore(
+					AvailabilityStoreMessage::QueryChunk(hash, idx, store_tx)
+				) => {
+					assert_eq!(hash, candidate_hash);
+					assert_eq!(idx, index);
+					store_tx.send(stored.clone()).unwrap();
+				}
+			);
+
+			let response = ChunkFetchingResponse::decode(&mut &rx.await.unwrap()[..]).unwrap();
+			match stored {
+				Some(chunk) => assert_eq!(response, ChunkFetchingResponse::Chunk(chunk)),
+				None => assert_eq!(response, ChunkFetchingResponse::NoSuchChunk),
+			}
+		}
+	});
+}
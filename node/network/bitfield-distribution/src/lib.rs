@@ -30,8 +30,10 @@ use polkadot_subsystem::{
 	ActiveLeavesUpdate, FromOverseer, OverseerSignal, SpawnedSubsystem, Subsystem, SubsystemContext, SubsystemResult,
 };
 use polkadot_node_subsystem_util::metrics::{self, prometheus};
-use polkadot_primitives::v1::{Hash, SignedAvailabilityBitfield, SigningContext, ValidatorId};
-use polkadot_node_network_protocol::{v1 as protocol_v1, PeerId, NetworkBridgeEvent, View, ReputationChange};
+use polkadot_primitives::v1::{BlockNumber, Hash, SignedAvailabilityBitfield, SigningContext, ValidatorId};
+use polkadot_node_network_protocol::{
+	v1 as protocol_v1, PeerId, NetworkBridgeEvent, View, ReputationChange, GossipTopology,
+};
 use std::collections::{HashMap, HashSet};
 
 const COST_SIGNATURE_INVALID: ReputationChange =
@@ -87,6 +89,9 @@ struct ProtocolState {
 	/// Our current view.
 	view: View,
 
+	/// The gossip topology of the current session.
+	gossip_topology: GossipTopology,
+
 	/// Additional data particular to a relay parent.
 	per_relay_parent: HashMap<Hash, PerRelayParentData>,
 }
@@ -94,6 +99,9 @@ struct ProtocolState {
 /// Data for a particular relay parent.
 #[derive(Debug, Clone, Default)]
 struct PerRelayParentData {
+	/// Number of the relay parent.
+	block_number: BlockNumber,
+
 	/// Signing context for a particular relay parent.
 	signing_context: SigningContext,
 
@@ -178,11 +186,23 @@ impl BitfieldDistribution {
 				FromOverseer::Signal(OverseerSignal::ActiveLeaves(ActiveLeavesUpdate { activated, deactivated })) => {
 					let _timer = self.metrics.time_active_leaves_update();
 
-					for relay_parent in activated {
+					for leaf in activated {
+						let relay_parent = leaf.hash;
 						tracing::trace!(target: LOG_TARGET, relay_parent = %relay_parent, "activated");
-						// query basic system parameters once
-						match query_basics(&mut ctx, relay_parent).await {
-							Ok(Some((validator_set, signing_context))) => {
+						let session_index = match leaf.session_index {
+							Some(session_index) => session_index,
+							None => {
+								tracing::debug!(
+									target: LOG_TARGET,
+									relay_parent = %relay_parent,
+									"Session of active leaf is unknown, ignoring it",
+								);
+								continue;
+							}
+						};
+						// query the validator set once
+						match query_validator_set(&mut ctx, relay_parent).await {
+							Ok(Some(validator_set)) => {
 								// If our runtime API fails, we don't take down the node,
 								// but we might alter peers' reputations erroneously as a result
 								// of not having the correct bookkeeping. If we have lost a race
@@ -191,14 +211,18 @@ impl BitfieldDistribution {
 								let _ = state.per_relay_parent.insert(
 									relay_parent,
 									PerRelayParentData {
-										signing_context,
+										block_number: leaf.number,
+										signing_context: SigningContext {
+											parent_hash: relay_parent,
+											session_index,
+										},
 										validator_set,
 										..Default::default()
 									},
 								);
 							}
 							Err(e) => {
-								tracing::warn!(target: LOG_TARGET, err = ?e, "query_basics has failed");
+								tracing::warn!(target: LOG_TARGET, err = ?e, "query_validator_set has failed");
 							}
 							_ => {},
 						}
@@ -209,8 +233,9 @@ impl BitfieldDistribution {
 						// defer the cleanup to the view change
 					}
 				}
-				FromOverseer::Signal(OverseerSignal::BlockFinalized(hash)) => {
-					tracing::trace!(target: LOG_TARGET, hash = %hash, "block finalized");
+				FromOverseer::Signal(OverseerSignal::BlockFinalized(hash, number)) => {
+					tracing::trace!(target: LOG_TARGET, hash = %hash, number = %number, "block finalized");
+					handle_block_finalized(&mut state, number);
 				}
 				FromOverseer::Signal(OverseerSignal::Conclude) => {
 					tracing::trace!(target: LOG_TARGET, "Conclude");
@@ -287,7 +312,7 @@ where
 		signed_availability,
 	};
 
-	relay_message(ctx, job_data, peer_views, validator, msg).await;
+	relay_message(ctx, job_data, peer_views, &state.gossip_topology, validator, msg).await;
 
 	metrics.on_own_bitfield_gossipped();
 }
@@ -295,11 +320,13 @@ where
 /// Distribute a given valid and signature checked bitfield message.
 ///
 /// Can be originated by another subsystem or received via network from another peer.
+/// It is sent to the interested peers chosen by the gossip topology.
 #[tracing::instrument(level = "trace", skip(ctx), fields(subsystem = LOG_TARGET))]
 async fn relay_message<Context>(
 	ctx: &mut Context,
 	job_data: &mut PerRelayParentData,
 	peer_views: &mut HashMap<PeerId, View>,
+	gossip_topology: &GossipTopology,
 	validator: ValidatorId,
 	message: BitfieldGossipMessage,
 )
@@ -318,29 +345,25 @@ where
 	))
 	.await;
 
-	// pass on the bitfield distribution to all interested peers
-	let interested_peers = peer_views
-		.iter()
-		.filter_map(|(peer, view)| {
+	// pass on the bitfield distribution to the interested peers chosen by the gossip topology
+	let interested_peers = gossip_topology.targets(
+		peer_views
+			.iter()
 			// check interest in the peer in this message's relay parent
-			if view.contains(&message.relay_parent) {
-				let message_needed = job_data.message_from_validator_needed_by_peer(&peer, &validator);
-				// track the message as sent for this peer
-				job_data.message_sent_to_peer
-					.entry(peer.clone())
-					.or_default()
-					.insert(validator.clone());
-
-				if message_needed {
-					Some(peer.clone())
-				} else {
-					None
-				}
-			} else {
-				None
-			}
-		})
-		.collect::<Vec<PeerId>>();
+			.filter(|(peer, view)| {
+				view.contains(&message.relay_parent)
+					&& job_data.message_from_validator_needed_by_peer(peer, &validator)
+			})
+			.map(|(peer, _)| peer.clone())
+	);
+
+	// track the message as sent for these peers
+	for peer in &interested_peers {
+		job_data.message_sent_to_peer
+			.entry(peer.clone())
+			.or_default()
+			.insert(validator.clone());
+	}
 
 	if interested_peers.is_empty() {
 		tracing::trace!(
@@ -443,7 +466,14 @@ where
 		}
 		one_per_validator.insert(validator.clone(), message.clone());
 
-		relay_message(ctx, job_data, &mut state.peer_views, validator, message).await;
+		relay_message(
+			ctx,
+			job_data,
+			&mut state.peer_views,
+			&state.gossip_topology,
+			validator,
+			message,
+		).await;
 
 		modify_reputation(ctx, origin, BENEFIT_VALID_MESSAGE_FIRST).await
 	} else {
@@ -480,6 +510,9 @@ where
 		NetworkBridgeEvent::OurViewChange(view) => {
 			handle_our_view_change(state, view);
 		}
+		NetworkBridgeEvent::NewGossipTopology(topology) => {
+			state.gossip_topology = topology;
+		}
 		NetworkBridgeEvent::PeerMessage(remote, message) => {
 			match message {
 				protocol_v1::BitfieldDistributionMessage::Bitfield(relay_parent, bitfield) => {
@@ -516,6 +549,19 @@ fn handle_our_view_change(state: &mut ProtocolState, view: View) {
 	}
 }
 
+/// Drop the data of relay parents at or below the height of a finalized block, unless they
+/// are still in our view.
+///
+/// With instant finality the finalized block is often still our leaf, and bitfields for it are
+/// still gossiped. Any other relay parent at or below the finalized height is never going to be
+/// an active leaf again.
+#[tracing::instrument(level = "trace", skip(state), fields(subsystem = LOG_TARGET))]
+fn handle_block_finalized(state: &mut ProtocolState, finalized_number: BlockNumber) {
+	let view = &state.view;
+	state.per_relay_parent.retain(|relay_parent, data| {
+		view.contains(relay_parent) || data.block_number > finalized_number
+	});
+}
 
 // Send the difference between two views which were not sent
 // to that particular peer.
@@ -610,38 +656,26 @@ where
 	}
 }
 
-/// Query our validator set and signing context for a particular relay parent.
+/// Query our validator set for a particular relay parent.
 #[tracing::instrument(level = "trace", skip(ctx), fields(subsystem = LOG_TARGET))]
-async fn query_basics<Context>(
+async fn query_validator_set<Context>(
 	ctx: &mut Context,
 	relay_parent: Hash,
-) -> SubsystemResult<Option<(Vec<ValidatorId>, SigningContext)>>
+) -> SubsystemResult<Option<Vec<ValidatorId>>>
 where
 	Context: SubsystemContext<Message = BitfieldDistributionMessage>,
 {
 	let (validators_tx, validators_rx) = oneshot::channel();
-	let (session_tx, session_rx) = oneshot::channel();
 
-	let query_validators = AllMessages::RuntimeApi(RuntimeApiMessage::Request(
-		relay_parent.clone(),
+	ctx.send_message(AllMessages::RuntimeApi(RuntimeApiMessage::Request(
+		relay_parent,
 		RuntimeApiRequest::Validators(validators_tx),
-	));
-
-	let query_signing = AllMessages::RuntimeApi(RuntimeApiMessage::Request(
-		relay_parent.clone(),
-		RuntimeApiRequest::SessionIndexForChild(session_tx),
-	));
+	))).await;
 
-	ctx.send_messages(std::iter::once(query_validators).chain(std::iter::once(query_signing)))
-		.await;
-
-	match (validators_rx.await?, session_rx.await?) {
-		(Ok(v), Ok(s)) => Ok(Some((
-			v,
-			SigningContext { parent_hash: relay_parent, session_index: s },
-		))),
-		(Err(e), _) | (_, Err(e)) => {
-			tracing::warn!(target: LOG_TARGET, err = ?e, "Failed to fetch basics from runtime API");
+	match validators_rx.await? {
+		Ok(v) => Ok(Some(v)),
+		Err(e) => {
+			tracing::warn!(target: LOG_TARGET, err = ?e, "Failed to fetch the validator set from runtime API");
 			Ok(None)
 		}
 	}
@@ -754,7 +788,7 @@ mod test {
 	use std::sync::Arc;
 	use std::time::Duration;
 	use assert_matches::assert_matches;
-	use polkadot_node_network_protocol::ObservedRole;
+	use polkadot_node_network_protocol::{ObservedRole, grid_topology::RANDOM_GOSSIP_PEERS};
 
 	macro_rules! view {
 		( $( $hash:expr ),* $(,)? ) => [
@@ -785,6 +819,7 @@ mod test {
 			per_relay_parent: hashmap! {
 				relay_parent.clone() =>
 					PerRelayParentData {
+						block_number: 1,
 						signing_context,
 						validator_set: vec![validator.clone()],
 						one_per_validator: hashmap! {
@@ -799,6 +834,7 @@ mod test {
 				.map(|peer| (peer, view!(relay_parent)))
 				.collect(),
 			view: view!(relay_parent),
+			gossip_topology: Default::default(),
 		}
 	}
 
@@ -820,6 +856,7 @@ mod test {
 		state.per_relay_parent = view.0.iter().map(|relay_parent| {(
 				relay_parent.clone(),
 				PerRelayParentData {
+					block_number: 1,
 					signing_context: signing_context.clone(),
 					validator_set: vec![validator.clone().into()],
 					one_per_validator: hashmap!{},
@@ -1121,6 +1158,7 @@ mod test {
 				&mut ctx,
 				state.per_relay_parent.get_mut(&hash).unwrap(),
 				&mut state.peer_views,
+				&state.gossip_topology,
 				validator.clone(),
 				msg.clone(),
 			).await;
@@ -1153,6 +1191,7 @@ mod test {
 				&mut ctx,
 				state.per_relay_parent.get_mut(&hash).unwrap(),
 				&mut state.peer_views,
+				&state.gossip_topology,
 				validator.clone(),
 				msg.clone(),
 			).await;
@@ -1173,6 +1212,79 @@ mod test {
 		});
 	}
 
+	#[test]
+	fn relay_message_along_gossip_topology() {
+		let hash = Hash::random();
+		let peers: Vec<PeerId> = (0..10).map(|_| PeerId::random()).collect();
+
+		// validator 0 key pair
+		let (mut state, signing_context, keystore, validator) = state_with_view(view![hash], hash.clone());
+
+		let payload = AvailabilityBitfield(bitvec![bitvec::order::Lsb0, u8; 1u8; 32]);
+		let signed_bitfield = executor::block_on(Signed::<AvailabilityBitfield>::sign(
+			&keystore,
+			payload,
+			&signing_context,
+			0,
+			&validator,
+		)).expect("should be signed");
+
+		for peer in &peers {
+			state.peer_views.insert(peer.clone(), view![hash]);
+		}
+		state.gossip_topology = GossipTopology {
+			session_index: 1,
+			neighbors: peers[..2].iter().cloned().collect(),
+		};
+
+		let msg = BitfieldGossipMessage {
+			relay_parent: hash.clone(),
+			signed_availability: signed_bitfield.clone(),
+		};
+
+		let pool = sp_core::testing::TaskExecutor::new();
+		let (mut ctx, mut handle) =
+			make_subsystem_context::<BitfieldDistributionMessage, _>(pool);
+
+		executor::block_on(async move {
+			let job_data = state.per_relay_parent.get_mut(&hash).unwrap();
+			relay_message(
+				&mut ctx,
+				job_data,
+				&mut state.peer_views,
+				&state.gossip_topology,
+				validator.clone(),
+				msg.clone(),
+			).await;
+
+			assert_matches!(
+				handle.recv().await,
+				AllMessages::Provisioner(ProvisionerMessage::ProvisionableData(..))
+			);
+
+			assert_matches!(
+				handle.recv().await,
+				AllMessages::NetworkBridge(
+					NetworkBridgeMessage::SendValidationMessage(sent_to, send_msg),
+				) => {
+					// All neighbors and a random sample of the other peers.
+					assert_eq!(sent_to.len(), 2 + RANDOM_GOSSIP_PEERS);
+					assert!(sent_to.contains(&peers[0]));
+					assert!(sent_to.contains(&peers[1]));
+					assert_eq!(send_msg, msg.clone().into_validation_protocol());
+
+					// Only the peers we sent to are tracked.
+					for peer in &peers {
+						assert_eq!(
+							job_data.message_from_validator_needed_by_peer(peer, &validator),
+							!sent_to.contains(peer),
+						);
+					}
+				}
+			);
+		});
+	}
+
 	#[test]
 	fn changing_view() {
 		let _ = env_logger::builder()
@@ -1416,4 +1528,34 @@ mod test {
 			);
 		});
 	}
+
+	#[test]
+	fn finalized_relay_parents_are_pruned() {
+		let hash_a: Hash = [0; 32].into();
+		let hash_b: Hash = [1; 32].into();
+
+		let (mut state, _, _, _) = state_with_view(view![hash_a, hash_b], hash_a);
+		state.per_relay_parent.get_mut(&hash_b).unwrap().block_number = 2;
+		state.view = view![hash_b];
+
+		handle_block_finalized(&mut state, 1);
+		assert!(!state.per_relay_parent.contains_key(&hash_a));
+		assert!(state.per_relay_parent.contains_key(&hash_b));
+
+		state.view = view![];
+
+		handle_block_finalized(&mut state, 2);
+		assert!(state.per_relay_parent.is_empty());
+	}
+
+	#[test]
+	fn finalized_leaf_is_not_pruned() {
+		let hash_a: Hash = [0; 32].into();
+
+		let (mut state, _, _, _) = state_with_view(view![hash_a], hash_a);
+
+		// `hash_a` is finalized while still being our leaf.
+		handle_block_finalized(&mut state, 1);
+		assert!(state.per_relay_parent.contains_key(&hash_a));
+	}
 }
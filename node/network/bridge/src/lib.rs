@@ -23,10 +23,11 @@
 use parity_scale_codec::{Encode, Decode};
 use futures::prelude::*;
 use futures::future::BoxFuture;
-use futures::stream::BoxStream;
-use futures::channel::mpsc;
+use futures::stream::{BoxStream, FuturesUnordered};
+use futures::channel::{mpsc, oneshot};
 
 use sc_network::Event as NetworkEvent;
+use sp_keystore::SyncCryptoStorePtr;
 
 use polkadot_subsystem::{
 	ActiveLeavesUpdate, FromOverseer, OverseerSignal, Subsystem, SubsystemContext, SpawnedSubsystem, SubsystemError,
@@ -35,37 +36,44 @@ use polkadot_subsystem::{
 use polkadot_subsystem::messages::{
 	NetworkBridgeMessage, AllMessages, AvailabilityDistributionMessage,
 	BitfieldDistributionMessage, PoVDistributionMessage, StatementDistributionMessage,
-	CollatorProtocolMessage,
+	CollatorProtocolMessage, RuntimeApiMessage, RuntimeApiRequest,
+};
+use polkadot_node_subsystem_util::{self as util, metrics::{self, prometheus}};
+use polkadot_primitives::v1::{
+	AuthorityDiscoveryId, Block, Hash, SessionIndex, ValidatorId, ValidatorIndex,
 };
-use polkadot_primitives::v1::{AuthorityDiscoveryId, Block, Hash};
 use polkadot_node_network_protocol::{
-	ObservedRole, ReputationChange, PeerId, PeerSet, View, NetworkBridgeEvent, v1 as protocol_v1
+	ObservedRole, ReputationChange, PeerId, PeerSet, ProtocolVersion, View, NetworkBridgeEvent,
+	v1 as protocol_v1, v2 as protocol_v2, GossipTopology, grid_topology::SessionGridTopology, peer_set::{PeerSetConfig, PeerSetsConfig},
+	request_response::{OutgoingRequest, Requests},
 };
 
-use std::collections::{HashMap, hash_map};
-use std::iter::ExactSizeIterator;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::convert::TryFrom;
+use std::fmt::Debug;
 use std::pin::Pin;
 use std::sync::Arc;
 
 
 mod validator_discovery;
 
+mod multiplexer;
+pub use multiplexer::RequestMultiplexer;
+use multiplexer::RequestMultiplexError;
+
 /// The maximum amount of heads a peer is allowed to have in their view at any time.
 ///
 /// We use the same limit to compute the view sent to peers locally.
 const MAX_VIEW_HEADS: usize = 5;
 
-/// The protocol name for the validation peer-set.
-pub const VALIDATION_PROTOCOL_NAME: &'static str = "/polkadot/validation/1";
-/// The protocol name for the collation peer-set.
-pub const COLLATION_PROTOCOL_NAME: &'static str = "/polkadot/collation/1";
-
 const MALFORMED_MESSAGE_COST: ReputationChange
 	= ReputationChange::new(-500, "Malformed Network-bridge message");
 const UNCONNECTED_PEERSET_COST: ReputationChange
 	= ReputationChange::new(-50, "Message sent to un-connected peer-set");
 const MALFORMED_VIEW_COST: ReputationChange
 	= ReputationChange::new(-500, "Malformed view");
+const MALFORMED_REQUEST_COST: ReputationChange
+	= ReputationChange::new(-500, "Malformed request");
 
 // network bridge log target
 const LOG_TARGET: &'static str = "network_bridge";
@@ -81,13 +89,27 @@ pub enum WireMessage<M> {
 	ViewUpdate(View),
 }
 
-/// Information about the notifications protocol. Should be used during network configuration
-/// or shortly after startup to register the protocol with the network service.
+impl<M> WireMessage<M> {
+	/// Convert the protocol message, if any, to the message type of another protocol version.
+	fn try_into_version<N: TryFrom<M>>(self) -> Result<WireMessage<N>, N::Error> {
+		Ok(match self {
+			WireMessage::ProtocolMessage(message) => WireMessage::ProtocolMessage(N::try_from(message)?),
+			WireMessage::ViewUpdate(view) => WireMessage::ViewUpdate(view),
+		})
+	}
+}
+
+/// Information about the notifications protocols. Should be used during network configuration
+/// or shortly after startup to register the protocols with the network service.
+///
+/// Every version of the protocol of a peer-set is registered as a protocol of its own. Peers open
+/// all the versions they support, and we speak the highest one open with each peer.
 pub fn notifications_protocol_info() -> Vec<std::borrow::Cow<'static, str>> {
-	vec![
-		VALIDATION_PROTOCOL_NAME.into(),
-		COLLATION_PROTOCOL_NAME.into(),
-	]
+	PeerSet::ALL.iter()
+		.flat_map(|peer_set| ProtocolVersion::ALL.iter().map(move |version| {
+			peer_set.get_protocol_name_static(*version).into()
+		}))
+		.collect()
 }
 
 /// An action to be carried out by the network.
@@ -95,16 +117,17 @@ pub fn notifications_protocol_info() -> Vec<std::borrow::Cow<'static, str>> {
 pub enum NetworkAction {
 	/// Note a change in reputation for a peer.
 	ReputationChange(PeerId, ReputationChange),
-	/// Write a notification to a given peer on the given peer-set.
-	WriteNotification(PeerId, PeerSet, Vec<u8>),
+	/// Write a notification to a given peer on the protocol of the given peer-set and version.
+	WriteNotification(PeerId, PeerSet, ProtocolVersion, Vec<u8>),
+	/// Disconnect from a given peer.
+	DisconnectPeer(PeerId),
 }
 
 /// An abstraction over networking for the purposes of this subsystem.
 pub trait Network: Send + 'static {
 	/// Get a stream of all events occurring on the network. This may include events unrelated
 	/// to the Polkadot protocol - the user of this function should filter only for events related
-	/// to the [`VALIDATION_PROTOCOL_NAME`](VALIDATION_PROTOCOL_NAME)
-	/// or [`COLLATION_PROTOCOL_NAME`](COLLATION_PROTOCOL_NAME)
+	/// to the protocols of the [`PeerSet`](PeerSet)s.
 	fn event_stream(&mut self) -> BoxStream<'static, NetworkEvent>;
 
 	/// Get access to an underlying sink for all network actions.
@@ -121,14 +144,24 @@ pub trait Network: Send + 'static {
 		}.boxed()
 	}
 
-	/// Write a notification to a peer on the given peer-set's protocol.
-	fn write_notification(&mut self, who: PeerId, peer_set: PeerSet, message: Vec<u8>)
-		-> BoxFuture<SubsystemResult<()>>
-	{
+	/// Write a notification to a peer on the protocol of the given peer-set and version.
+	fn write_notification(
+		&mut self,
+		who: PeerId,
+		peer_set: PeerSet,
+		version: ProtocolVersion,
+		message: Vec<u8>,
+	) -> BoxFuture<SubsystemResult<()>> {
 		async move {
-			self.action_sink().send(NetworkAction::WriteNotification(who, peer_set, message)).await
+			self.action_sink().send(NetworkAction::WriteNotification(who, peer_set, version, message)).await
 		}.boxed()
 	}
+
+	/// Send a request to a peer via its request/response protocol.
+	///
+	/// The raw response, or the reason why there is none, is sent back on the channel contained in
+	/// the request.
+	fn start_request(&mut self, req: Requests);
 }
 
 impl Network for Arc<sc_network::NetworkService<Block, Hash>> {
@@ -157,20 +190,12 @@ impl Network for Arc<sc_network::NetworkService<Block, Hash>> {
 						peer,
 						cost_benefit,
 					),
-					NetworkAction::WriteNotification(peer, peer_set, message) => {
-						match peer_set {
-							PeerSet::Validation => self.0.write_notification(
-								peer,
-								VALIDATION_PROTOCOL_NAME.into(),
-								message,
-							),
-							PeerSet::Collation => self.0.write_notification(
-								peer,
-								COLLATION_PROTOCOL_NAME.into(),
-								message,
-							),
-						}
-					}
+					NetworkAction::WriteNotification(peer, peer_set, version, message) => self.0.write_notification(
+						peer,
+						peer_set.get_protocol_name_static(version).into(),
+						message,
+					),
+					NetworkAction::DisconnectPeer(peer) => self.0.disconnect_peer(peer),
 				}
 
 				Ok(())
@@ -187,23 +212,56 @@ impl Network for Arc<sc_network::NetworkService<Block, Hash>> {
 
 		Box::pin(ActionSink(&**self))
 	}
+
+	fn start_request(&mut self, req: Requests) {
+		let (protocol, OutgoingRequest { peer, payload, pending_response }) = req.encode_request();
+
+		sc_network::NetworkService::start_request(
+			&**self,
+			peer,
+			protocol.get_protocol_name_static().into(),
+			payload,
+			pending_response,
+		);
+	}
 }
 
 /// The network bridge subsystem.
 pub struct NetworkBridge<N, AD> {
 	network_service: N,
 	authority_discovery_service: AD,
+	request_multiplexer: RequestMultiplexer,
+	keystore: SyncCryptoStorePtr,
+	metrics: Metrics,
+	peer_sets: PeerSetsConfig,
 }
 
 impl<N, AD> NetworkBridge<N, AD> {
 	/// Create a new network bridge subsystem with underlying network service and authority discovery service.
 	///
-	/// This assumes that the network service has had the notifications protocol for the network
+	/// This assumes that the network service has had the notifications protocols of the network
 	/// bridge already registered. See [`notifications_protocol_info`](notifications_protocol_info).
-	pub fn new(network_service: N, authority_discovery_service: AD) -> Self {
+	/// The same goes for the request/response protocols, whose configurations are returned along
+	/// with the `request_multiplexer` by [`RequestMultiplexer::new`](RequestMultiplexer::new).
+	///
+	/// The `keystore` is used to find our position in the gossip topology of each session.
+	/// The `metrics` report the number of peers connected on each peer-set.
+	/// The `peer_sets` configure the slots and reserved nodes of each peer-set.
+	pub fn new(
+		network_service: N,
+		authority_discovery_service: AD,
+		request_multiplexer: RequestMultiplexer,
+		keystore: SyncCryptoStorePtr,
+		metrics: Metrics,
+		peer_sets: PeerSetsConfig,
+	) -> Self {
 		NetworkBridge {
 			network_service,
 			authority_discovery_service,
+			request_multiplexer,
+			keystore,
+			metrics,
+			peer_sets,
 		}
 	}
 }
@@ -217,10 +275,21 @@ impl<Net, AD, Context> Subsystem<Context> for NetworkBridge<Net, AD>
 	fn start(self, ctx: Context) -> SpawnedSubsystem {
 		// Swallow error because failure is fatal to the node and we log with more precision
 		// within `run_network`.
-		let Self { network_service, authority_discovery_service } = self;
+		let Self {
+			network_service,
+			authority_discovery_service,
+			request_multiplexer,
+			keystore,
+			metrics,
+			peer_sets,
+		} = self;
 		let future = run_network(
 				network_service,
 				authority_discovery_service,
+				request_multiplexer,
+				keystore,
+				metrics,
+				peer_sets,
 				ctx,
 			)
 			.map_err(|e| {
@@ -237,6 +306,38 @@ impl<Net, AD, Context> Subsystem<Context> for NetworkBridge<Net, AD>
 struct PeerData {
 	/// Latest view sent by the peer.
 	view: View,
+	/// The highest protocol version open with the peer, which we speak with it.
+	version: ProtocolVersion,
+	/// All protocol versions the peer opened on the peer-set.
+	versions: BTreeSet<ProtocolVersion>,
+	/// Whether the peer is reserved on the peer-set and doesn't take up a slot.
+	reserved: bool,
+}
+
+/// The slots of a peer-set.
+///
+/// The network shares its connection slots between all notifications protocols and doesn't tell
+/// us the direction of a connection, so the bridge enforces the incoming and outgoing slots of
+/// each peer-set together. The network's own incoming and outgoing limits are raised to cover
+/// those of every peer-set.
+struct PeerSetSlots {
+	max_peers: usize,
+	/// The configured reserved nodes of the peer-set.
+	reserved_nodes: HashSet<PeerId>,
+}
+
+impl PeerSetSlots {
+	fn new(config: &PeerSetConfig) -> Self {
+		PeerSetSlots {
+			max_peers: config.in_peers.saturating_add(config.out_peers) as usize,
+			reserved_nodes: config.reserved_nodes.iter().map(|addr| addr.peer_id.clone()).collect(),
+		}
+	}
+
+	/// Whether a peer that is not reserved can be accepted next to the given peers.
+	fn has_free_slot(&self, peers: &HashMap<PeerId, PeerData>) -> bool {
+		peers.values().filter(|data| !data.reserved).count() < self.max_peers
+	}
 }
 
 #[derive(Debug)]
@@ -245,19 +346,30 @@ enum Action {
 	SendCollationMessage(Vec<PeerId>, protocol_v1::CollationProtocol),
 	ConnectToValidators {
 		validator_ids: Vec<AuthorityDiscoveryId>,
+		peer_set: PeerSet,
 		connected: mpsc::Sender<(AuthorityDiscoveryId, PeerId)>,
 	},
 	ReportPeer(PeerId, ReputationChange),
+	SendRequests(Vec<Requests>),
 
 	ActiveLeaves(ActiveLeavesUpdate),
 
-	PeerConnected(PeerSet, PeerId, ObservedRole),
-	PeerDisconnected(PeerSet, PeerId),
+	PeerConnected(PeerSet, ProtocolVersion, PeerId, ObservedRole),
+	PeerDisconnected(PeerSet, ProtocolVersion, PeerId),
 	PeerMessages(
 		PeerId,
 		Vec<WireMessage<protocol_v1::ValidationProtocol>>,
 		Vec<WireMessage<protocol_v1::CollationProtocol>>,
 	),
+	/// An incoming request, to be routed to the subsystem responsible for its protocol.
+	DispatchRequest(AllMessages),
+	/// The validators next to us in the gossip topology of a session were determined at a relay
+	/// parent.
+	GossipNeighbors(SessionIndex, Hash, Vec<ValidatorId>),
+	/// The authorities of the validators next to us in the gossip topology of a session arrived.
+	GossipNeighborAuthorities(SessionIndex, Vec<AuthorityDiscoveryId>),
+	/// A validator next to us in the gossip topology of the current session connected.
+	GossipNeighborConnected(PeerId),
 
 	Abort,
 	Nop,
@@ -277,10 +389,11 @@ fn action_from_overseer_message(
 				=> Action::SendValidationMessage(peers, msg),
 			NetworkBridgeMessage::SendCollationMessage(peers, msg)
 				=> Action::SendCollationMessage(peers, msg),
-			NetworkBridgeMessage::ConnectToValidators { validator_ids, connected }
-				=> Action::ConnectToValidators { validator_ids, connected },
+			NetworkBridgeMessage::ConnectToValidators { validator_ids, peer_set, connected }
+				=> Action::ConnectToValidators { validator_ids, peer_set, connected },
+			NetworkBridgeMessage::SendRequests(reqs) => Action::SendRequests(reqs),
 		},
-		Ok(FromOverseer::Signal(OverseerSignal::BlockFinalized(_)))
+		Ok(FromOverseer::Signal(OverseerSignal::BlockFinalized(..)))
 			=> Action::Nop,
 		Err(e) => {
 			tracing::warn!(target: LOG_TARGET, err = ?e, "Shutting down Network Bridge due to error");
@@ -289,8 +402,12 @@ fn action_from_overseer_message(
 	}
 }
 
-#[tracing::instrument(level = "trace", fields(subsystem = LOG_TARGET))]
-fn action_from_network_message(event: Option<NetworkEvent>) -> Action {
+#[tracing::instrument(level = "trace", skip(validation_peers, collation_peers), fields(subsystem = LOG_TARGET))]
+fn action_from_network_message(
+	event: Option<NetworkEvent>,
+	validation_peers: &HashMap<PeerId, PeerData>,
+	collation_peers: &HashMap<PeerId, PeerData>,
+) -> Action {
 	match event {
 		None => {
 			tracing::info!(target: LOG_TARGET, "Shutting down Network Bridge: underlying event stream concluded");
@@ -298,42 +415,42 @@ fn action_from_network_message(event: Option<NetworkEvent>) -> Action {
 		}
 		Some(NetworkEvent::Dht(_)) => Action::Nop,
 		Some(NetworkEvent::NotificationStreamOpened { remote, protocol, role }) => {
-			let role = role.into();
-			match protocol {
-				x if x == VALIDATION_PROTOCOL_NAME
-					=> Action::PeerConnected(PeerSet::Validation, remote, role),
-				x if x == COLLATION_PROTOCOL_NAME
-					=> Action::PeerConnected(PeerSet::Collation, remote, role),
-				_ => Action::Nop,
+			match PeerSet::from_protocol_name(&protocol) {
+				Some((peer_set, version)) => Action::PeerConnected(peer_set, version, remote, role.into()),
+				None => Action::Nop,
 			}
 		}
 		Some(NetworkEvent::NotificationStreamClosed { remote, protocol }) => {
-			match protocol {
-				x if x == VALIDATION_PROTOCOL_NAME
-					=> Action::PeerDisconnected(PeerSet::Validation, remote),
-				x if x == COLLATION_PROTOCOL_NAME
-					=> Action::PeerDisconnected(PeerSet::Collation, remote),
-				_ => Action::Nop,
+			match PeerSet::from_protocol_name(&protocol) {
+				Some((peer_set, version)) => Action::PeerDisconnected(peer_set, version, remote),
+				None => Action::Nop,
 			}
 		}
 		Some(NetworkEvent::NotificationsReceived { remote, messages }) => {
-			let v_messages: Result<Vec<_>, _> = messages.iter()
-				.filter(|(protocol, _)| protocol == &VALIDATION_PROTOCOL_NAME)
-				.map(|(_, msg_bytes)| WireMessage::decode(&mut msg_bytes.as_ref()))
-				.collect();
+			let messages_on = |peer_set| messages.iter().filter_map(move |(protocol, msg_bytes)| {
+				match PeerSet::from_protocol_name(protocol) {
+					Some((p, version)) if p == peer_set => Some((version, msg_bytes.as_ref())),
+					_ => None,
+				}
+			});
+
+			let v_messages = decode_peer_messages::<protocol_v1::ValidationProtocol, protocol_v2::ValidationProtocol>(
+				validation_peers.get(&remote),
+				messages_on(PeerSet::Validation),
+			);
 
 			let v_messages = match v_messages {
-				Err(_) => return Action::ReportPeer(remote, MALFORMED_MESSAGE_COST),
+				Err(rep) => return Action::ReportPeer(remote, rep),
 				Ok(v) => v,
 			};
 
-			let c_messages: Result<Vec<_>, _> = messages.iter()
-				.filter(|(protocol, _)| protocol == &COLLATION_PROTOCOL_NAME)
-				.map(|(_, msg_bytes)| WireMessage::decode(&mut msg_bytes.as_ref()))
-				.collect();
+			let c_messages = decode_peer_messages::<protocol_v1::CollationProtocol, protocol_v2::CollationProtocol>(
+				collation_peers.get(&remote),
+				messages_on(PeerSet::Collation),
+			);
 
 			match c_messages {
-				Err(_) => Action::ReportPeer(remote, MALFORMED_MESSAGE_COST),
+				Err(rep) => Action::ReportPeer(remote, rep),
 				Ok(c_messages) => if v_messages.is_empty() && c_messages.is_empty() {
 					Action::Nop
 				} else {
@@ -344,6 +461,107 @@ fn action_from_network_message(event: Option<NetworkEvent>) -> Action {
 	}
 }
 
+/// Decode the messages a peer sent on a peer-set, according to the protocol version each was
+/// received on, and convert them to the v1 messages that subsystems work with.
+///
+/// `V1` and `V2` are the message types of the peer-set at each version.
+fn decode_peer_messages<'a, V1, V2>(
+	peer_data: Option<&PeerData>,
+	messages: impl Iterator<Item = (ProtocolVersion, &'a [u8])>,
+) -> Result<Vec<WireMessage<V1>>, ReputationChange>
+	where
+		V1: Decode + TryFrom<V2>,
+		V2: Decode,
+{
+	let mut messages = messages.peekable();
+	if messages.peek().is_none() {
+		return Ok(Vec::new());
+	}
+
+	if peer_data.is_none() {
+		return Err(UNCONNECTED_PEERSET_COST);
+	}
+
+	messages.map(|(version, mut msg_bytes)| {
+		let message = match version {
+			ProtocolVersion::V1 => WireMessage::<V1>::decode(&mut msg_bytes).ok(),
+			ProtocolVersion::V2 => WireMessage::<V2>::decode(&mut msg_bytes).ok()
+				.and_then(|message| message.try_into_version().ok()),
+		};
+
+		message.ok_or(MALFORMED_MESSAGE_COST)
+	}).collect()
+}
+
+#[tracing::instrument(level = "trace", fields(subsystem = LOG_TARGET))]
+fn action_from_incoming_request(
+	req: Result<AllMessages, RequestMultiplexError>,
+) -> Action {
+	match req {
+		Ok(msg) => Action::DispatchRequest(msg),
+		Err(RequestMultiplexError { peer, error }) => {
+			tracing::debug!(target: LOG_TARGET, err = ?error, "Decoding incoming request failed");
+			Action::ReportPeer(peer, MALFORMED_REQUEST_COST)
+		}
+	}
+}
+
+/// Determine the validators next to us in the gossip topology of `session_index`, once the
+/// validator set of the session arrives.
+///
+/// Resolves to `Action::Nop` if we are not a validator in the session.
+#[tracing::instrument(level = "trace", skip(validators, keystore), fields(subsystem = LOG_TARGET))]
+async fn gossip_neighbors(
+	validators: util::RuntimeApiReceiver<Vec<ValidatorId>>,
+	keystore: SyncCryptoStorePtr,
+	relay_parent: Hash,
+	session_index: SessionIndex,
+) -> Action {
+	let validators = match validators.await {
+		Ok(Ok(validators)) => validators,
+		Ok(Err(e)) => {
+			tracing::debug!(target: LOG_TARGET, err = ?e, session_index, "Failed to fetch validators");
+			return Action::Nop;
+		}
+		Err(_) => return Action::Nop,
+	};
+
+	let our_index = match util::signing_key(&validators, keystore).await
+		.and_then(|key| validators.iter().position(|v| v == &key))
+	{
+		Some(index) => index as ValidatorIndex,
+		None => return Action::Nop,
+	};
+
+	let neighbors = SessionGridTopology::new(session_index, validators.len())
+		.neighbors(our_index)
+		.into_iter()
+		.map(|index| validators[index as usize].clone())
+		.collect();
+
+	Action::GossipNeighbors(session_index, relay_parent, neighbors)
+}
+
+/// Collect the authorities of our neighbors in the gossip topology of `session_index`, once the
+/// runtime API answers.
+#[tracing::instrument(level = "trace", skip(authorities), fields(subsystem = LOG_TARGET))]
+async fn gossip_neighbor_authorities(
+	authorities: util::RuntimeApiReceiver<Vec<Option<AuthorityDiscoveryId>>>,
+	session_index: SessionIndex,
+) -> Action {
+	match authorities.await {
+		Ok(Ok(authorities)) => Action::GossipNeighborAuthorities(
+			session_index,
+			authorities.into_iter().filter_map(|id| id).collect(),
+		),
+		Ok(Err(e)) => {
+			tracing::debug!(target: LOG_TARGET, err = ?e, session_index, "Failed to fetch neighbor authorities");
+			Action::Nop
+		}
+		Err(_) => Action::Nop,
+	}
+}
+
 fn construct_view(live_heads: &[Hash]) -> View {
 	View(live_heads.iter().rev().take(MAX_VIEW_HEADS).cloned().collect())
 }
@@ -365,12 +583,14 @@ async fn update_view(
 	send_validation_message(
 		net,
 		validation_peers.keys().cloned(),
+		validation_peers,
 		WireMessage::ViewUpdate(new_view.clone()),
 	).await?;
 
 	send_collation_message(
 		net,
 		collation_peers.keys().cloned(),
+		collation_peers,
 		WireMessage::ViewUpdate(new_view.clone()),
 	).await?;
 
@@ -407,7 +627,7 @@ async fn handle_peer_messages<M>(
 
 	let mut outgoing_messages = Vec::with_capacity(messages.len());
 	for message in messages {
-		outgoing_messages.push(match message {
+		let event = match message {
 			WireMessage::ViewUpdate(new_view) => {
 				if new_view.0.len() > MAX_VIEW_HEADS {
 					net.report_peer(
@@ -430,53 +650,115 @@ async fn handle_peer_messages<M>(
 			WireMessage::ProtocolMessage(message) => {
 				NetworkBridgeEvent::PeerMessage(peer.clone(), message)
 			}
-		})
+		};
+
+		outgoing_messages.push(event);
 	}
 
 	Ok(outgoing_messages)
 }
 
-#[tracing::instrument(level = "trace", skip(net, peers), fields(subsystem = LOG_TARGET))]
+#[tracing::instrument(level = "trace", skip(net, peers, peer_data), fields(subsystem = LOG_TARGET))]
 async fn send_validation_message<I>(
 	net: &mut impl Network,
 	peers: I,
+	peer_data: &HashMap<PeerId, PeerData>,
 	message: WireMessage<protocol_v1::ValidationProtocol>,
 ) -> SubsystemResult<()>
 	where
 		I: IntoIterator<Item=PeerId>,
-		I::IntoIter: ExactSizeIterator,
 {
-	send_message(net, peers, PeerSet::Validation, message).await
+	send_message::<_, protocol_v2::ValidationProtocol, _>(
+		net,
+		peers,
+		PeerSet::Validation,
+		peer_data,
+		message,
+	).await
 }
 
-#[tracing::instrument(level = "trace", skip(net, peers), fields(subsystem = LOG_TARGET))]
+#[tracing::instrument(level = "trace", skip(net, peers, peer_data), fields(subsystem = LOG_TARGET))]
 async fn send_collation_message<I>(
 	net: &mut impl Network,
 	peers: I,
+	peer_data: &HashMap<PeerId, PeerData>,
 	message: WireMessage<protocol_v1::CollationProtocol>,
 ) -> SubsystemResult<()>
 	where
-	I: IntoIterator<Item=PeerId>,
-	I::IntoIter: ExactSizeIterator,
+		I: IntoIterator<Item=PeerId>,
 {
-	send_message(net, peers, PeerSet::Collation, message).await
+	send_message::<_, protocol_v2::CollationProtocol, _>(
+		net,
+		peers,
+		PeerSet::Collation,
+		peer_data,
+		message,
+	).await
 }
 
-async fn send_message<M, I>(
+/// Send a message to the given peers, each in the highest protocol version open with them.
+///
+/// `V1` and `V2` are the message types of the peer-set at each version. Peers that aren't
+/// connected on the peer-set are skipped.
+async fn send_message<V1, V2, I>(
 	net: &mut impl Network,
 	peers: I,
 	peer_set: PeerSet,
-	message: WireMessage<M>,
+	peer_data: &HashMap<PeerId, PeerData>,
+	message: WireMessage<V1>,
 ) -> SubsystemResult<()>
 	where
-		M: Encode + Clone,
+		V1: Encode + Clone,
+		V2: Encode + TryFrom<V1>,
+		V2::Error: Debug,
 		I: IntoIterator<Item=PeerId>,
-		I::IntoIter: ExactSizeIterator,
 {
+	let (v1_peers, v2_peers): (Vec<_>, Vec<_>) = peers.into_iter()
+		.filter_map(|peer| peer_data.get(&peer).map(|data| (peer, data.version)))
+		.partition(|(_, version)| *version == ProtocolVersion::V1);
+
+	if !v2_peers.is_empty() {
+		match message.clone().try_into_version::<V2>() {
+			Ok(v2_message) => write_notifications(
+				net,
+				v2_peers.into_iter().map(|(peer, _)| peer).collect(),
+				peer_set,
+				ProtocolVersion::V2,
+				v2_message.encode(),
+			).await?,
+			Err(e) => tracing::warn!(
+				target: LOG_TARGET,
+				err = ?e,
+				?peer_set,
+				"Failed to convert message to protocol version 2",
+			),
+		}
+	}
+
+	if !v1_peers.is_empty() {
+		write_notifications(
+			net,
+			v1_peers.into_iter().map(|(peer, _)| peer).collect(),
+			peer_set,
+			ProtocolVersion::V1,
+			message.encode(),
+		).await?;
+	}
+
+	Ok(())
+}
+
+async fn write_notifications(
+	net: &mut impl Network,
+	peers: Vec<PeerId>,
+	peer_set: PeerSet,
+	version: ProtocolVersion,
+	message: Vec<u8>,
+) -> SubsystemResult<()> {
 	let mut message_producer = stream::iter({
 		let peers = peers.into_iter();
 		let n_peers = peers.len();
-		let mut message = Some(message.encode());
+		let mut message = Some(message);
 
 		peers.enumerate().map(move |(i, peer)| {
 			// optimization: avoid cloning the message for the last peer in the
@@ -491,7 +773,7 @@ async fn send_message<M, I>(
 					.clone()
 			};
 
-			Ok(NetworkAction::WriteNotification(peer, peer_set, message))
+			Ok(NetworkAction::WriteNotification(peer, peer_set, version, message))
 		})
 	});
 
@@ -562,10 +844,14 @@ async fn dispatch_collation_events_to_all<I>(
 	ctx.send_messages(events.into_iter().flat_map(messages_for)).await
 }
 
-#[tracing::instrument(skip(network_service, authority_discovery_service, ctx), fields(subsystem = LOG_TARGET))]
+#[tracing::instrument(skip(network_service, authority_discovery_service, request_multiplexer, keystore, metrics, peer_sets, ctx), fields(subsystem = LOG_TARGET))]
 async fn run_network<N, AD>(
 	mut network_service: N,
 	mut authority_discovery_service: AD,
+	mut request_multiplexer: RequestMultiplexer,
+	keystore: SyncCryptoStorePtr,
+	metrics: Metrics,
+	peer_sets: PeerSetsConfig,
 	mut ctx: impl SubsystemContext<Message=NetworkBridgeMessage>,
 ) -> SubsystemResult<()>
 where
@@ -581,7 +867,39 @@ where
 	let mut validation_peers: HashMap<PeerId, PeerData> = HashMap::new();
 	let mut collation_peers: HashMap<PeerId, PeerData> = HashMap::new();
 
-	let mut validator_discovery = validator_discovery::Service::<N, AD>::new();
+	let validation_slots = PeerSetSlots::new(&peer_sets.validation);
+	let collation_slots = PeerSetSlots::new(&peer_sets.collation);
+
+	// ask the network to stay connected to the reserved nodes of each peer-set
+	for peer_set in PeerSet::ALL.iter().copied() {
+		let reserved_nodes: HashSet<_> = peer_sets.get(peer_set).reserved_nodes.iter()
+			.map(|addr| addr.concat())
+			.collect();
+
+		if reserved_nodes.is_empty() {
+			continue;
+		}
+
+		if let Err(e) = validator_discovery::Network::add_to_priority_group(
+			&mut network_service,
+			peer_set.get_reserved_priority_group().to_owned(),
+			reserved_nodes,
+		).await {
+			tracing::warn!(target: LOG_TARGET, err = ?e, ?peer_set, "Failed to add reserved nodes");
+		}
+	}
+
+	let mut validation_discovery = validator_discovery::Service::<N, AD>::new(PeerSet::Validation);
+	let mut collation_discovery = validator_discovery::Service::<N, AD>::new(PeerSet::Collation);
+
+	// The gossip topology of the most recent session, `None` until the first leaf is activated.
+	let mut gossip_topology: Option<GossipTopology> = None;
+	// Our neighbors in the gossip topology, as they connect. Dropping the receiver revokes the
+	// connection request.
+	let mut gossip_connections: mpsc::Receiver<(AuthorityDiscoveryId, PeerId)> = mpsc::channel(0).1;
+	// Pending runtime API requests for the gossip topology, awaited here so the bridge doesn't
+	// stall on them.
+	let mut gossip_requests: FuturesUnordered<BoxFuture<'static, Action>> = FuturesUnordered::new();
 
 	loop {
 
@@ -592,7 +910,15 @@ where
 
 			futures::select! {
 				subsystem_msg = subsystem_next => action_from_overseer_message(subsystem_msg),
-				net_event = net_event_next => action_from_network_message(net_event),
+				net_event = net_event_next => action_from_network_message(
+					net_event,
+					&validation_peers,
+					&collation_peers,
+				),
+				req = request_multiplexer.select_next_some() => action_from_incoming_request(req),
+				neighbor = gossip_connections.select_next_some()
+					=> Action::GossipNeighborConnected(neighbor.1),
+				action = gossip_requests.select_next_some() => action,
 			}
 		};
 
@@ -600,24 +926,30 @@ where
 			Action::Nop => {}
 			Action::Abort => return Ok(()),
 
-			Action::SendValidationMessage(peers, msg) => send_message(
+			Action::SendValidationMessage(peers, msg) => send_validation_message(
 					&mut network_service,
 					peers,
-					PeerSet::Validation,
+					&validation_peers,
 					WireMessage::ProtocolMessage(msg),
 			).await?,
 
-			Action::SendCollationMessage(peers, msg) => send_message(
+			Action::SendCollationMessage(peers, msg) => send_collation_message(
 					&mut network_service,
 					peers,
-					PeerSet::Collation,
+					&collation_peers,
 					WireMessage::ProtocolMessage(msg),
 			).await?,
 
 			Action::ConnectToValidators {
 				validator_ids,
+				peer_set,
 				connected,
 			} => {
+				let validator_discovery = match peer_set {
+					PeerSet::Validation => &mut validation_discovery,
+					PeerSet::Collation => &mut collation_discovery,
+				};
+
 				let (ns, ads) = validator_discovery.on_request(
 					validator_ids,
 					connected,
@@ -630,8 +962,24 @@ where
 
 			Action::ReportPeer(peer, rep) => network_service.report_peer(peer, rep).await?,
 
+			Action::SendRequests(reqs) => {
+				for req in reqs {
+					network_service.start_request(req);
+				}
+			}
+
+			Action::DispatchRequest(msg) => ctx.send_message(msg).await,
+
 			Action::ActiveLeaves(ActiveLeavesUpdate { activated, deactivated }) => {
-				live_heads.extend(activated);
+				// leaves of an unknown session can't tell us about a new one.
+				let new_session = activated.iter()
+					.filter_map(|leaf| leaf.session_index.map(|session_index| (leaf.hash, session_index)))
+					.filter(|(_, session_index)| gossip_topology.as_ref()
+						.map_or(true, |topology| *session_index > topology.session_index)
+					)
+					.max_by_key(|(_, session_index)| *session_index);
+
+				live_heads.extend(activated.into_iter().map(|leaf| leaf.hash));
 				live_heads.retain(|h| !deactivated.contains(h));
 
 				update_view(
@@ -642,68 +990,200 @@ where
 					&validation_peers,
 					&collation_peers,
 				).await?;
+
+				if let Some((relay_parent, session_index)) = new_session {
+					gossip_connections = mpsc::channel(0).1;
+
+					match util::request_validators_ctx(relay_parent, &mut ctx).await {
+						Ok(validators) => gossip_requests.push(
+							gossip_neighbors(validators, keystore.clone(), relay_parent, session_index).boxed()
+						),
+						Err(e) => tracing::debug!(
+							target: LOG_TARGET,
+							err = ?e,
+							session_index,
+							"Failed to determine gossip neighbors",
+						),
+					}
+
+					let topology = GossipTopology {
+						session_index,
+						neighbors: HashSet::new(),
+					};
+					gossip_topology = Some(topology.clone());
+
+					dispatch_validation_event_to_all(
+						NetworkBridgeEvent::NewGossipTopology(topology),
+						&mut ctx,
+					).await;
+				}
 			}
 
-			Action::PeerConnected(peer_set, peer, role) => {
-				let peer_map = match peer_set {
-					PeerSet::Validation => &mut validation_peers,
-					PeerSet::Collation => &mut collation_peers,
+			Action::GossipNeighbors(session_index, relay_parent, neighbors) => {
+				// the session is already superseded.
+				if gossip_topology.as_ref().map_or(true, |topology| topology.session_index != session_index) {
+					continue;
+				}
+
+				let (tx, rx) = oneshot::channel();
+				ctx.send_message(AllMessages::RuntimeApi(RuntimeApiMessage::Request(
+					relay_parent,
+					RuntimeApiRequest::ValidatorDiscovery(neighbors, tx),
+				))).await;
+
+				gossip_requests.push(gossip_neighbor_authorities(rx, session_index).boxed());
+			}
+
+			Action::GossipNeighborAuthorities(session_index, authorities) => {
+				if gossip_topology.as_ref().map_or(true, |topology| topology.session_index != session_index) {
+					continue;
+				}
+
+				let (connected, connected_rx) = mpsc::channel(authorities.len());
+				gossip_connections = connected_rx;
+
+				let (ns, ads) = validation_discovery.on_request(
+					authorities,
+					connected,
+					network_service,
+					authority_discovery_service,
+				).await;
+				network_service = ns;
+				authority_discovery_service = ads;
+			}
+
+			Action::GossipNeighborConnected(peer) => {
+				if let Some(topology) = gossip_topology.as_mut() {
+					if topology.neighbors.insert(peer) {
+						dispatch_validation_event_to_all(
+							NetworkBridgeEvent::NewGossipTopology(topology.clone()),
+							&mut ctx,
+						).await;
+					}
+				}
+			}
+
+			Action::PeerConnected(peer_set, version, peer, role) => {
+				let (peer_map, other_peer_map, validator_discovery, slots) = match peer_set {
+					PeerSet::Validation => (
+						&mut validation_peers,
+						&collation_peers,
+						&mut validation_discovery,
+						&validation_slots,
+					),
+					PeerSet::Collation => (
+						&mut collation_peers,
+						&validation_peers,
+						&mut collation_discovery,
+						&collation_slots,
+					),
 				};
 
-				validator_discovery.on_peer_connected(&peer, &mut authority_discovery_service).await;
-
-				match peer_map.entry(peer.clone()) {
-					hash_map::Entry::Occupied(_) => continue,
-					hash_map::Entry::Vacant(vacant) => {
-						let _ = vacant.insert(PeerData {
-							view: View(Vec::new()),
-						});
-
-						match peer_set {
-							PeerSet::Validation => dispatch_validation_events_to_all(
-								vec![
-									NetworkBridgeEvent::PeerConnected(peer.clone(), role),
-									NetworkBridgeEvent::PeerViewChange(
-										peer,
-										View(Default::default()),
-									),
-								],
-								&mut ctx,
-							).await,
-							PeerSet::Collation => dispatch_collation_events_to_all(
-								vec![
-									NetworkBridgeEvent::PeerConnected(peer.clone(), role),
-									NetworkBridgeEvent::PeerViewChange(
-										peer,
-										View(Default::default()),
-									),
-								],
-								&mut ctx,
-							).await,
-						}
+				let requested = validator_discovery.on_peer_connected(&peer, &mut authority_discovery_service).await;
+
+				if let Some(peer_data) = peer_map.get_mut(&peer) {
+					// the peer opened another version of the protocol of the peer-set.
+					let _ = peer_data.versions.insert(version);
+					peer_data.version = peer_data.version.max(version);
+					continue;
+				}
+
+				let reserved = requested || slots.reserved_nodes.contains(&peer);
+				if !reserved && !slots.has_free_slot(peer_map) {
+					tracing::debug!(
+						target: LOG_TARGET,
+						?peer,
+						?peer_set,
+						"No free slot on the peer-set, refusing peer",
+					);
+
+					// The network can only disconnect a peer from all protocols at once, so a
+					// peer that holds a slot on the other peer-set stays connected and is only
+					// ignored on this one.
+					if !other_peer_map.contains_key(&peer) {
+						network_service.action_sink().send(NetworkAction::DisconnectPeer(peer)).await?;
 					}
+
+					continue;
+				}
+
+				let _ = peer_map.insert(peer.clone(), PeerData {
+					view: View(Vec::new()),
+					version,
+					versions: std::iter::once(version).collect(),
+					reserved,
+				});
+
+				metrics.note_peer_count(peer_set, peer_map.len());
+
+				match peer_set {
+					PeerSet::Validation => dispatch_validation_events_to_all(
+						vec![
+							NetworkBridgeEvent::PeerConnected(peer.clone(), role),
+							NetworkBridgeEvent::PeerViewChange(
+								peer,
+								View(Default::default()),
+							),
+						],
+						&mut ctx,
+					).await,
+					PeerSet::Collation => dispatch_collation_events_to_all(
+						vec![
+							NetworkBridgeEvent::PeerConnected(peer.clone(), role),
+							NetworkBridgeEvent::PeerViewChange(
+								peer,
+								View(Default::default()),
+							),
+						],
+						&mut ctx,
+					).await,
 				}
 			}
-			Action::PeerDisconnected(peer_set, peer) => {
-				let peer_map = match peer_set {
-					PeerSet::Validation => &mut validation_peers,
-					PeerSet::Collation => &mut collation_peers,
+			Action::PeerDisconnected(peer_set, version, peer) => {
+				let (peer_map, validator_discovery) = match peer_set {
+					PeerSet::Validation => (&mut validation_peers, &mut validation_discovery),
+					PeerSet::Collation => (&mut collation_peers, &mut collation_discovery),
 				};
 
+				if let Some(peer_data) = peer_map.get_mut(&peer) {
+					let _ = peer_data.versions.remove(&version);
+
+					// the peer is still connected on another version of the protocol.
+					if let Some(highest) = peer_data.versions.iter().next_back() {
+						peer_data.version = *highest;
+						continue;
+					}
+				}
+
 				validator_discovery.on_peer_disconnected(&peer);
 
-				if peer_map.remove(&peer).is_some() {
-					match peer_set {
-						PeerSet::Validation => dispatch_validation_event_to_all(
-							NetworkBridgeEvent::PeerDisconnected(peer),
-							&mut ctx,
-						).await,
-						PeerSet::Collation => dispatch_collation_event_to_all(
-							NetworkBridgeEvent::PeerDisconnected(peer),
-							&mut ctx,
-						).await,
+				if peer_set == PeerSet::Validation {
+					if let Some(topology) = gossip_topology.as_mut() {
+						if topology.neighbors.remove(&peer) {
+							dispatch_validation_event_to_all(
+								NetworkBridgeEvent::NewGossipTopology(topology.clone()),
+								&mut ctx,
+							).await;
+						}
 					}
 				}
+
+				if peer_map.remove(&peer).is_none() {
+					continue;
+				}
+
+				metrics.note_peer_count(peer_set, peer_map.len());
+
+				match peer_set {
+					PeerSet::Validation => dispatch_validation_event_to_all(
+						NetworkBridgeEvent::PeerDisconnected(peer),
+						&mut ctx,
+					).await,
+					PeerSet::Collation => dispatch_collation_event_to_all(
+						NetworkBridgeEvent::PeerDisconnected(peer),
+						&mut ctx,
+					).await,
+				}
 			},
 			Action::PeerMessages(peer, v_messages, c_messages) => {
 				if !v_messages.is_empty() {
@@ -732,6 +1212,40 @@ where
 	}
 }
 
+#[derive(Clone)]
+struct MetricsInner {
+	peer_count: prometheus::GaugeVec<prometheus::U64>,
+}
+
+/// Network bridge metrics.
+#[derive(Default, Clone)]
+pub struct Metrics(Option<MetricsInner>);
+
+impl Metrics {
+	fn note_peer_count(&self, peer_set: PeerSet, count: usize) {
+		if let Some(metrics) = &self.0 {
+			metrics.peer_count.with_label_values(&[peer_set.name()]).set(count as u64);
+		}
+	}
+}
+
+impl metrics::Metrics for Metrics {
+	fn try_register(registry: &prometheus::Registry) -> Result<Self, prometheus::PrometheusError> {
+		let metrics = MetricsInner {
+			peer_count: prometheus::register(
+				prometheus::GaugeVec::new(
+					prometheus::Opts::new(
+						"parachain_peer_count",
+						"The number of peers connected on a peer-set.",
+					),
+					&["peer_set"],
+				)?,
+				registry,
+			)?,
+		};
+		Ok(Metrics(Some(metrics)))
+	}
+}
 
 #[cfg(test)]
 mod tests {
@@ -745,54 +1259,77 @@ mod tests {
 	use parking_lot::Mutex;
 	use assert_matches::assert_matches;
 
+	use futures::channel::oneshot;
 	use polkadot_subsystem::messages::{StatementDistributionMessage, BitfieldDistributionMessage};
+	use polkadot_node_network_protocol::request_response::{
+		Protocol, v1::{ChunkFetchingRequest, ChunkFetchingResponse, CollationFetchingRequest},
+	};
+	use polkadot_primitives::v1::{BlockData, CandidateHash, PoV};
 	use polkadot_node_subsystem_test_helpers::{
-		SingleItemSink, SingleItemStream, TestSubsystemContextHandle,
+		SingleItemSink, SingleItemStream, TestSubsystemContextHandle, activated_leaf,
 	};
+	use sc_keystore::LocalKeystore;
 	use sc_network::Multiaddr;
+	use sp_application_crypto::AppKey;
 	use sp_keyring::Sr25519Keyring;
+	use sp_keystore::SyncCryptoStore;
 
 	// The subsystem's view of the network - only supports a single call to `event_stream`.
 	struct TestNetwork {
 		net_events: Arc<Mutex<Option<SingleItemStream<NetworkEvent>>>>,
 		action_tx: mpsc::UnboundedSender<NetworkAction>,
+		request_tx: mpsc::UnboundedSender<Requests>,
 	}
 
-	struct TestAuthorityDiscovery;
+	#[derive(Clone)]
+	struct TestAuthorityDiscovery {
+		by_peer_id: Arc<Mutex<HashMap<PeerId, AuthorityDiscoveryId>>>,
+	}
 
 	// The test's view of the network. This receives updates from the subsystem in the form
 	// of `NetworkAction`s.
 	struct TestNetworkHandle {
 		action_rx: mpsc::UnboundedReceiver<NetworkAction>,
 		net_tx: SingleItemSink<NetworkEvent>,
+		request_rx: mpsc::UnboundedReceiver<Requests>,
+		request_configs: Vec<sc_network::config::RequestResponseConfig>,
+		authority_discovery: TestAuthorityDiscovery,
 	}
 
 	fn new_test_network() -> (
 		TestNetwork,
 		TestNetworkHandle,
 		TestAuthorityDiscovery,
+		RequestMultiplexer,
 	) {
 		let (net_tx, net_rx) = polkadot_node_subsystem_test_helpers::single_item_sink();
 		let (action_tx, action_rx) = mpsc::unbounded();
+		let (request_tx, request_rx) = mpsc::unbounded();
+		let (request_multiplexer, request_configs) = RequestMultiplexer::new();
+		let authority_discovery = TestAuthorityDiscovery {
+			by_peer_id: Arc::new(Mutex::new(HashMap::new())),
+		};
 
 		(
 			TestNetwork {
 				net_events: Arc::new(Mutex::new(Some(net_rx))),
 				action_tx,
+				request_tx,
 			},
 			TestNetworkHandle {
 				action_rx,
 				net_tx,
+				request_rx,
+				request_configs,
+				authority_discovery: authority_discovery.clone(),
 			},
-			TestAuthorityDiscovery,
+			authority_discovery,
+			request_multiplexer,
 		)
 	}
 
-	fn peer_set_protocol(peer_set: PeerSet) -> std::borrow::Cow<'static, str> {
-		match peer_set {
-			PeerSet::Validation => VALIDATION_PROTOCOL_NAME.into(),
-			PeerSet::Collation => COLLATION_PROTOCOL_NAME.into(),
-		}
+	fn peer_set_protocol(peer_set: PeerSet, version: ProtocolVersion) -> std::borrow::Cow<'static, str> {
+		peer_set.get_protocol_name_static(version).into()
 	}
 
 	impl Network for TestNetwork {
@@ -808,6 +1345,10 @@ mod tests {
 		{
 			Box::pin((&mut self.action_tx).sink_map_err(Into::into))
 		}
+
+		fn start_request(&mut self, req: Requests) {
+			self.request_tx.unbounded_send(req).expect("test network handle dropped");
+		}
 	}
 
 	#[async_trait]
@@ -827,12 +1368,17 @@ mod tests {
 			None
 		}
 
-		async fn get_authority_id_by_peer_id(&mut self, _peer_id: PeerId) -> Option<AuthorityDiscoveryId> {
-			None
+		async fn get_authority_id_by_peer_id(&mut self, peer_id: PeerId) -> Option<AuthorityDiscoveryId> {
+			self.by_peer_id.lock().get(&peer_id).cloned()
 		}
 	}
 
 	impl TestNetworkHandle {
+		// Make the authority discovery resolve `peer` to `authority`.
+		fn set_authority(&mut self, peer: PeerId, authority: AuthorityDiscoveryId) {
+			let _ = self.authority_discovery.by_peer_id.lock().insert(peer, authority);
+		}
+
 		// Get the next network action.
 		async fn next_network_action(&mut self) -> NetworkAction {
 			self.action_rx.next().await.expect("subsystem concluded early")
@@ -848,31 +1394,81 @@ mod tests {
 			v
 		}
 
+		// Connect a peer speaking v1, the version the messages of the subsystems are in.
 		async fn connect_peer(&mut self, peer: PeerId, peer_set: PeerSet, role: ObservedRole) {
+			self.connect_peer_with_version(peer, peer_set, ProtocolVersion::V1, role).await
+		}
+
+		// Open the protocol of the given version of the peer-set with a peer.
+		async fn connect_peer_with_version(
+			&mut self,
+			peer: PeerId,
+			peer_set: PeerSet,
+			version: ProtocolVersion,
+			role: ObservedRole,
+		) {
 			self.send_network_event(NetworkEvent::NotificationStreamOpened {
 				remote: peer,
-				protocol: peer_set_protocol(peer_set),
+				protocol: peer_set_protocol(peer_set, version),
 				role: role.into(),
 			}).await;
 		}
 
 		async fn disconnect_peer(&mut self, peer: PeerId, peer_set: PeerSet) {
+			self.disconnect_peer_with_version(peer, peer_set, ProtocolVersion::V1).await
+		}
+
+		async fn disconnect_peer_with_version(&mut self, peer: PeerId, peer_set: PeerSet, version: ProtocolVersion) {
 			self.send_network_event(NetworkEvent::NotificationStreamClosed {
 				remote: peer,
-				protocol: peer_set_protocol(peer_set),
+				protocol: peer_set_protocol(peer_set, version),
 			}).await;
 		}
 
 		async fn peer_message(&mut self, peer: PeerId, peer_set: PeerSet, message: Vec<u8>) {
+			self.peer_message_with_version(peer, peer_set, ProtocolVersion::V1, message).await
+		}
+
+		async fn peer_message_with_version(
+			&mut self,
+			peer: PeerId,
+			peer_set: PeerSet,
+			version: ProtocolVersion,
+			message: Vec<u8>,
+		) {
 			self.send_network_event(NetworkEvent::NotificationsReceived {
 				remote: peer,
-				messages: vec![(peer_set_protocol(peer_set), message.into())],
+				messages: vec![(peer_set_protocol(peer_set, version), message.into())],
 			}).await;
 		}
 
 		async fn send_network_event(&mut self, event: NetworkEvent) {
 			self.net_tx.send(event).await.expect("subsystem concluded early");
 		}
+
+		// Get the next request the subsystem started.
+		async fn next_request(&mut self) -> Requests {
+			self.request_rx.next().await.expect("subsystem concluded early")
+		}
+
+		// Deliver a raw request from a peer on the given protocol.
+		fn incoming_request(&mut self, protocol: Protocol, peer: PeerId, payload: Vec<u8>)
+			-> oneshot::Receiver<Vec<u8>>
+		{
+			let (tx, rx) = oneshot::channel();
+			self.request_configs.iter_mut()
+				.find(|c| c.name == protocol.get_protocol_name_static())
+				.and_then(|c| c.inbound_queue.as_mut())
+				.expect("all protocols are configured with an inbound queue")
+				.try_send(sc_network::config::IncomingRequest {
+					peer,
+					payload,
+					pending_response: tx,
+				})
+				.expect("inbound queue is not full");
+
+			rx
+		}
 	}
 
 	// network actions are sensitive to ordering of `PeerId`s within a `HashMap`, so
@@ -887,13 +1483,32 @@ mod tests {
 	}
 
 	fn test_harness<T: Future<Output=()>>(test: impl FnOnce(TestHarness) -> T) {
+		test_harness_with_peer_sets(PeerSetsConfig::default(), test)
+	}
+
+	fn test_harness_with_peer_sets<T: Future<Output=()>>(
+		peer_sets: PeerSetsConfig,
+		test: impl FnOnce(TestHarness) -> T,
+	) {
 		let pool = sp_core::testing::TaskExecutor::new();
-		let (network, network_handle, discovery) = new_test_network();
+		let (network, network_handle, discovery, request_multiplexer) = new_test_network();
 		let (context, virtual_overseer) = polkadot_node_subsystem_test_helpers::make_subsystem_context(pool);
 
+		// We are Alice.
+		let keystore: SyncCryptoStorePtr = Arc::new(LocalKeystore::in_memory());
+		SyncCryptoStore::sr25519_generate_new(
+			&*keystore,
+			ValidatorId::ID,
+			Some(&Sr25519Keyring::Alice.to_seed()),
+		).expect("Insert key into keystore");
+
 		let network_bridge = run_network(
 			network,
 			discovery,
+			request_multiplexer,
+			keystore,
+			Metrics::default(),
+			peer_sets,
 			context,
 		)
 			.map_err(|_| panic!("subsystem execution failed"))
@@ -977,7 +1592,7 @@ mod tests {
 			let hash_a = Hash::from([1; 32]);
 
 			virtual_overseer.send(
-				FromOverseer::Signal(OverseerSignal::ActiveLeaves(ActiveLeavesUpdate::start_work(hash_a)))
+				FromOverseer::Signal(OverseerSignal::ActiveLeaves(ActiveLeavesUpdate::start_work(activated_leaf(hash_a))))
 			).await;
 
 			let actions = network_handle.next_network_actions(2).await;
@@ -990,6 +1605,7 @@ mod tests {
 				&NetworkAction::WriteNotification(
 					peer_a,
 					PeerSet::Validation,
+					ProtocolVersion::V1,
 					wire_message.clone(),
 				),
 			));
@@ -999,6 +1615,7 @@ mod tests {
 				&NetworkAction::WriteNotification(
 					peer_b,
 					PeerSet::Validation,
+					ProtocolVersion::V1,
 					wire_message.clone(),
 				),
 			));
@@ -1165,7 +1782,7 @@ mod tests {
 			let hash_a = Hash::from([1; 32]);
 
 			virtual_overseer.send(
-				FromOverseer::Signal(OverseerSignal::ActiveLeaves(ActiveLeavesUpdate::start_work(hash_a)))
+				FromOverseer::Signal(OverseerSignal::ActiveLeaves(ActiveLeavesUpdate::start_work(activated_leaf(hash_a))))
 			).await;
 
 			let actions = network_handle.next_network_actions(1).await;
@@ -1178,6 +1795,7 @@ mod tests {
 				&NetworkAction::WriteNotification(
 					peer.clone(),
 					PeerSet::Collation,
+					ProtocolVersion::V1,
 					wire_message.clone(),
 				),
 			));
@@ -1397,6 +2015,7 @@ mod tests {
 					NetworkAction::WriteNotification(
 						peer.clone(),
 						PeerSet::Validation,
+						ProtocolVersion::V1,
 						WireMessage::ProtocolMessage(message).encode(),
 					)
 				);
@@ -1425,10 +2044,503 @@ mod tests {
 					NetworkAction::WriteNotification(
 						peer.clone(),
 						PeerSet::Collation,
+						ProtocolVersion::V1,
 						WireMessage::ProtocolMessage(message).encode(),
 					)
 				);
 			}
 		});
 	}
+
+	#[test]
+	fn requests_are_started_and_responses_decoded() {
+		test_harness(|test_harness| async move {
+			let TestHarness { mut network_handle, mut virtual_overseer } = test_harness;
+
+			let peer = PeerId::random();
+			let payload = ChunkFetchingRequest {
+				candidate_hash: CandidateHash(Hash::repeat_byte(1)),
+				index: 2,
+			};
+			let (req, response) = OutgoingRequest::new(peer.clone(), payload);
+
+			virtual_overseer.send(FromOverseer::Communication {
+				msg: NetworkBridgeMessage::SendRequests(vec![Requests::ChunkFetching(req)]),
+			}).await;
+
+			let (protocol, outgoing) = network_handle.next_request().await.encode_request();
+			assert_eq!(protocol, Protocol::ChunkFetching);
+			assert_eq!(outgoing.peer, peer);
+			assert_eq!(outgoing.payload, payload.encode());
+
+			outgoing.pending_response
+				.send(Ok(ChunkFetchingResponse::NoSuchChunk.encode()))
+				.unwrap();
+
+			assert_eq!(response.await.unwrap(), ChunkFetchingResponse::NoSuchChunk);
+		});
+	}
+
+	#[test]
+	fn incoming_requests_are_routed_to_subsystems() {
+		test_harness(|test_harness| async move {
+			let TestHarness { mut network_handle, mut virtual_overseer } = test_harness;
+
+			let peer = PeerId::random();
+			let payload = CollationFetchingRequest {
+				relay_parent: Hash::repeat_byte(1),
+				para_id: 5.into(),
+			};
+
+			let _response = network_handle.incoming_request(
+				Protocol::CollationFetching,
+				peer.clone(),
+				payload.encode(),
+			);
+
+			assert_matches!(
+				virtual_overseer.recv().await,
+				AllMessages::CollatorProtocol(
+					CollatorProtocolMessage::CollationFetchingRequest(req)
+				) => {
+					assert_eq!(req.peer, peer);
+					assert_eq!(req.payload, payload);
+				}
+			);
+
+			// Undecodable requests are punished.
+			let _response = network_handle.incoming_request(
+				Protocol::CollationFetching,
+				peer.clone(),
+				vec![0xFF],
+			);
+
+			assert_eq!(
+				network_handle.next_network_action().await,
+				NetworkAction::ReputationChange(peer, MALFORMED_REQUEST_COST),
+			);
+		});
+	}
+
+	#[test]
+	fn gossip_topology_sent_on_new_session() {
+		test_harness(|test_harness| async move {
+			let TestHarness { mut network_handle, mut virtual_overseer } = test_harness;
+
+			let keyrings = [
+				Sr25519Keyring::Alice,
+				Sr25519Keyring::Bob,
+				Sr25519Keyring::Charlie,
+				Sr25519Keyring::Dave,
+			];
+			let validators: Vec<ValidatorId> = keyrings.iter().map(|k| k.public().into()).collect();
+			let hash_a = Hash::repeat_byte(1);
+			let leaf = activated_leaf(hash_a);
+			let session_index = leaf.session_index.unwrap();
+
+			virtual_overseer.send(
+				FromOverseer::Signal(OverseerSignal::ActiveLeaves(ActiveLeavesUpdate::start_work(leaf)))
+			).await;
+
+			assert_sends_validation_event_to_all(
+				NetworkBridgeEvent::OurViewChange(View(vec![hash_a])),
+				&mut virtual_overseer,
+			).await;
+
+			assert_sends_collation_event_to_all(
+				NetworkBridgeEvent::OurViewChange(View(vec![hash_a])),
+				&mut virtual_overseer,
+			).await;
+
+			let validators_tx = assert_matches!(
+				virtual_overseer.recv().await,
+				AllMessages::RuntimeApi(RuntimeApiMessage::Request(
+					relay_parent,
+					RuntimeApiRequest::Validators(tx),
+				)) => {
+					assert_eq!(relay_parent, hash_a);
+					tx
+				}
+			);
+
+			// the topology is announced without waiting for the runtime API.
+			assert_sends_validation_event_to_all(
+				NetworkBridgeEvent::NewGossipTopology(GossipTopology {
+					session_index,
+					neighbors: HashSet::new(),
+				}),
+				&mut virtual_overseer,
+			).await;
+
+			validators_tx.send(Ok(validators.clone())).unwrap();
+
+			// Alice is validator 0.
+			let neighbors = SessionGridTopology::new(session_index, validators.len()).neighbors(0);
+			let neighbor_authority: AuthorityDiscoveryId = keyrings[neighbors[0] as usize].public().into();
+
+			assert_matches!(
+				virtual_overseer.recv().await,
+				AllMessages::RuntimeApi(RuntimeApiMessage::Request(
+					relay_parent,
+					RuntimeApiRequest::ValidatorDiscovery(ids, tx),
+				)) => {
+					assert_eq!(relay_parent, hash_a);
+					let expected: Vec<_> = neighbors.iter().map(|i| validators[*i as usize].clone()).collect();
+					assert_eq!(ids, expected);
+
+					let authorities = neighbors.iter()
+						.map(|i| Some(keyrings[*i as usize].public().into()))
+						.collect();
+					tx.send(Ok(authorities)).unwrap();
+				}
+			);
+
+			let peer = PeerId::random();
+			network_handle.set_authority(peer.clone(), neighbor_authority);
+			network_handle.connect_peer(peer.clone(), PeerSet::Validation, ObservedRole::Full).await;
+
+			assert_sends_validation_event_to_all(
+				NetworkBridgeEvent::PeerConnected(peer.clone(), ObservedRole::Full),
+				&mut virtual_overseer,
+			).await;
+
+			assert_sends_validation_event_to_all(
+				NetworkBridgeEvent::PeerViewChange(peer.clone(), View(Default::default())),
+				&mut virtual_overseer,
+			).await;
+
+			assert_sends_validation_event_to_all(
+				NetworkBridgeEvent::NewGossipTopology(GossipTopology {
+					session_index,
+					neighbors: vec![peer.clone()].into_iter().collect(),
+				}),
+				&mut virtual_overseer,
+			).await;
+
+			// the neighbor leaves the topology when it disconnects.
+			network_handle.disconnect_peer(peer.clone(), PeerSet::Validation).await;
+
+			assert_sends_validation_event_to_all(
+				NetworkBridgeEvent::NewGossipTopology(GossipTopology {
+					session_index,
+					neighbors: HashSet::new(),
+				}),
+				&mut virtual_overseer,
+			).await;
+
+			assert_sends_validation_event_to_all(
+				NetworkBridgeEvent::PeerDisconnected(peer),
+				&mut virtual_overseer,
+			).await;
+		});
+	}
+
+	#[test]
+	fn leaves_of_unknown_session_leave_the_gossip_topology_alone() {
+		test_harness(|test_harness| async move {
+			let TestHarness { network_handle: _network_handle, mut virtual_overseer } = test_harness;
+
+			let hash_a = Hash::repeat_byte(1);
+			let hash_b = Hash::repeat_byte(2);
+			let mut leaf_a = activated_leaf(hash_a);
+			leaf_a.session_index = None;
+
+			virtual_overseer.send(
+				FromOverseer::Signal(OverseerSignal::ActiveLeaves(ActiveLeavesUpdate::start_work(leaf_a)))
+			).await;
+
+			assert_sends_validation_event_to_all(
+				NetworkBridgeEvent::OurViewChange(View(vec![hash_a])),
+				&mut virtual_overseer,
+			).await;
+
+			assert_sends_collation_event_to_all(
+				NetworkBridgeEvent::OurViewChange(View(vec![hash_a])),
+				&mut virtual_overseer,
+			).await;
+
+			virtual_overseer.send(
+				FromOverseer::Signal(OverseerSignal::ActiveLeaves(ActiveLeavesUpdate::start_work(
+					activated_leaf(hash_b),
+				)))
+			).await;
+
+			assert_sends_validation_event_to_all(
+				NetworkBridgeEvent::OurViewChange(View(vec![hash_b, hash_a])),
+				&mut virtual_overseer,
+			).await;
+
+			assert_sends_collation_event_to_all(
+				NetworkBridgeEvent::OurViewChange(View(vec![hash_b, hash_a])),
+				&mut virtual_overseer,
+			).await;
+
+			// only the leaf of a known session asks for the validators of the new session.
+			assert_matches!(
+				virtual_overseer.recv().await,
+				AllMessages::RuntimeApi(RuntimeApiMessage::Request(
+					relay_parent,
+					RuntimeApiRequest::Validators(_),
+				)) => {
+					assert_eq!(relay_parent, hash_b);
+				}
+			);
+		});
+	}
+
+	#[test]
+	fn v1_and_v2_peers_interoperate() {
+		test_harness(|test_harness| async move {
+			let TestHarness {
+				mut network_handle,
+				mut virtual_overseer,
+			} = test_harness;
+
+			let peer_a = PeerId::random();
+			let peer_b = PeerId::random();
+
+			// `peer_a` only supports v1, while `peer_b` opens both versions.
+			network_handle.connect_peer_with_version(
+				peer_a.clone(),
+				PeerSet::Validation,
+				ProtocolVersion::V1,
+				ObservedRole::Full,
+			).await;
+			for version in ProtocolVersion::ALL.iter().copied() {
+				network_handle.connect_peer_with_version(
+					peer_b.clone(),
+					PeerSet::Validation,
+					version,
+					ObservedRole::Full,
+				).await;
+			}
+
+			// bridge will inform about all connected peers, once.
+			for peer in &[peer_a.clone(), peer_b.clone()] {
+				assert_sends_validation_event_to_all(
+					NetworkBridgeEvent::PeerConnected(peer.clone(), ObservedRole::Full),
+					&mut virtual_overseer,
+				).await;
+
+				assert_sends_validation_event_to_all(
+					NetworkBridgeEvent::PeerViewChange(peer.clone(), View(Default::default())),
+					&mut virtual_overseer,
+				).await;
+			}
+
+			let pov_distribution_message = protocol_v1::PoVDistributionMessage::SendPoV(
+				[0; 32].into(),
+				[1; 32].into(),
+				PoV { block_data: BlockData(vec![1; 128]) },
+			);
+
+			let message = protocol_v1::ValidationProtocol::PoVDistribution(
+				pov_distribution_message.clone(),
+			);
+			let v2_message = protocol_v2::ValidationProtocol::try_from(message.clone())
+				.expect("PoV can be compressed");
+
+			// each peer gets the message in its own version.
+
+			virtual_overseer.send(FromOverseer::Communication {
+				msg: NetworkBridgeMessage::SendValidationMessage(
+					vec![peer_a.clone(), peer_b.clone()],
+					message.clone(),
+				)
+			}).await;
+
+			let actions = network_handle.next_network_actions(2).await;
+			assert!(network_actions_contains(
+				&actions,
+				&NetworkAction::WriteNotification(
+					peer_a.clone(),
+					PeerSet::Validation,
+					ProtocolVersion::V1,
+					WireMessage::ProtocolMessage(message.clone()).encode(),
+				),
+			));
+			assert!(network_actions_contains(
+				&actions,
+				&NetworkAction::WriteNotification(
+					peer_b.clone(),
+					PeerSet::Validation,
+					ProtocolVersion::V2,
+					WireMessage::ProtocolMessage(v2_message.clone()).encode(),
+				),
+			));
+
+			// and the messages of both peers reach the subsystem in the same representation.
+
+			network_handle.peer_message(
+				peer_a.clone(),
+				PeerSet::Validation,
+				WireMessage::ProtocolMessage(message.clone()).encode(),
+			).await;
+			network_handle.peer_message_with_version(
+				peer_b.clone(),
+				PeerSet::Validation,
+				ProtocolVersion::V2,
+				WireMessage::ProtocolMessage(v2_message).encode(),
+			).await;
+
+			for peer in &[peer_a, peer_b.clone()] {
+				assert_matches!(
+					virtual_overseer.recv().await,
+					AllMessages::PoVDistribution(
+						PoVDistributionMessage::NetworkBridgeUpdateV1(
+							NetworkBridgeEvent::PeerMessage(p, m)
+						)
+					) => {
+						assert_eq!(&p, peer);
+						assert_eq!(m, pov_distribution_message);
+					}
+				);
+			}
+
+			// an uncompressed PoV on the v2 protocol is garbage.
+
+			network_handle.peer_message_with_version(
+				peer_b.clone(),
+				PeerSet::Validation,
+				ProtocolVersion::V2,
+				WireMessage::ProtocolMessage(message.clone()).encode(),
+			).await;
+
+			assert_eq!(
+				network_handle.next_network_action().await,
+				NetworkAction::ReputationChange(peer_b.clone(), MALFORMED_MESSAGE_COST),
+			);
+
+			// once `peer_b` closes v2, it stays connected and gets messages in v1.
+
+			network_handle.disconnect_peer_with_version(
+				peer_b.clone(),
+				PeerSet::Validation,
+				ProtocolVersion::V2,
+			).await;
+
+			virtual_overseer.send(FromOverseer::Communication {
+				msg: NetworkBridgeMessage::SendValidationMessage(
+					vec![peer_b.clone()],
+					message.clone(),
+				)
+			}).await;
+
+			assert_eq!(
+				network_handle.next_network_action().await,
+				NetworkAction::WriteNotification(
+					peer_b,
+					PeerSet::Validation,
+					ProtocolVersion::V1,
+					WireMessage::ProtocolMessage(message).encode(),
+				),
+			);
+		});
+	}
+
+	#[test]
+	fn peers_beyond_the_slots_are_refused() {
+		let reserved_peer = PeerId::random();
+
+		let mut peer_sets = PeerSetsConfig::default();
+		peer_sets.validation.in_peers = 1;
+		peer_sets.validation.out_peers = 0;
+		peer_sets.validation.reserved_nodes = vec![sc_network::config::MultiaddrWithPeerId {
+			multiaddr: "/ip4/127.0.0.1/tcp/30333".parse().unwrap(),
+			peer_id: reserved_peer.clone(),
+		}];
+
+		test_harness_with_peer_sets(peer_sets, |test_harness| async move {
+			let TestHarness {
+				mut network_handle,
+				mut virtual_overseer,
+			} = test_harness;
+
+			let peer_a = PeerId::random();
+			let peer_b = PeerId::random();
+			let peer_c = PeerId::random();
+
+			network_handle.connect_peer(peer_a.clone(), PeerSet::Validation, ObservedRole::Full).await;
+
+			assert_sends_validation_event_to_all(
+				NetworkBridgeEvent::PeerConnected(peer_a.clone(), ObservedRole::Full),
+				&mut virtual_overseer,
+			).await;
+
+			assert_sends_validation_event_to_all(
+				NetworkBridgeEvent::PeerViewChange(peer_a.clone(), View(Default::default())),
+				&mut virtual_overseer,
+			).await;
+
+			// the only slot is taken, so `peer_b` is disconnected.
+
+			network_handle.connect_peer(peer_b.clone(), PeerSet::Validation, ObservedRole::Full).await;
+
+			assert_eq!(
+				network_handle.next_network_action().await,
+				NetworkAction::DisconnectPeer(peer_b.clone()),
+			);
+
+			// a peer holding a slot on the other peer-set stays connected, but is ignored.
+
+			network_handle.connect_peer(peer_c.clone(), PeerSet::Collation, ObservedRole::Full).await;
+
+			assert_sends_collation_event_to_all(
+				NetworkBridgeEvent::PeerConnected(peer_c.clone(), ObservedRole::Full),
+				&mut virtual_overseer,
+			).await;
+
+			assert_sends_collation_event_to_all(
+				NetworkBridgeEvent::PeerViewChange(peer_c.clone(), View(Default::default())),
+				&mut virtual_overseer,
+			).await;
+
+			network_handle.connect_peer(peer_c.clone(), PeerSet::Validation, ObservedRole::Full).await;
+			network_handle.peer_message(
+				peer_c.clone(),
+				PeerSet::Validation,
+				WireMessage::<protocol_v1::ValidationProtocol>::ViewUpdate(View(Default::default())).encode(),
+			).await;
+
+			assert_eq!(
+				network_handle.next_network_action().await,
+				NetworkAction::ReputationChange(peer_c, UNCONNECTED_PEERSET_COST),
+			);
+
+			// reserved nodes don't take up a slot.
+
+			network_handle.connect_peer(reserved_peer.clone(), PeerSet::Validation, ObservedRole::Full).await;
+
+			assert_sends_validation_event_to_all(
+				NetworkBridgeEvent::PeerConnected(reserved_peer.clone(), ObservedRole::Full),
+				&mut virtual_overseer,
+			).await;
+
+			assert_sends_validation_event_to_all(
+				NetworkBridgeEvent::PeerViewChange(reserved_peer.clone(), View(Default::default())),
+				&mut virtual_overseer,
+			).await;
+
+			// and `peer_b` is accepted once `peer_a` freed up the slot.
+
+			network_handle.disconnect_peer(peer_a.clone(), PeerSet::Validation).await;
+
+			assert_sends_validation_event_to_all(
+				NetworkBridgeEvent::PeerDisconnected(peer_a),
+				&mut virtual_overseer,
+			).await;
+
+			network_handle.connect_peer(peer_b.clone(), PeerSet::Validation, ObservedRole::Full).await;
+
+			assert_sends_validation_event_to_all(
+				NetworkBridgeEvent::PeerConnected(peer_b.clone(), ObservedRole::Full),
+				&mut virtual_overseer,
+			).await;
+
+			assert_sends_validation_event_to_all(
+				NetworkBridgeEvent::PeerViewChange(peer_b, View(Default::default())),
+				&mut virtual_overseer,
+			).await;
+		});
+	}
 }
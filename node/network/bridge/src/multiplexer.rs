@@ -0,0 +1,201 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Routing of incoming requests to the subsystems responsible for their protocol.
+
+use std::pin::Pin;
+
+use futures::channel::mpsc;
+use futures::stream::{FusedStream, Stream};
+use futures::task::{Context, Poll};
+
+use parity_scale_codec::{Decode, Error as DecodingError};
+use sc_network::config::{self as network, RequestResponseConfig};
+
+use polkadot_node_network_protocol::request_response::{IncomingRequest, IsRequest, Protocol};
+use polkadot_node_network_protocol::PeerId;
+use polkadot_subsystem::messages::{
+	AllMessages, AvailabilityDistributionMessage, CollatorProtocolMessage, PoVDistributionMessage,
+};
+
+/// Multiplex incoming network requests.
+///
+/// This multiplexer consumes all request streams and makes them a `Stream` of a single message
+/// type, useful for the network bridge to send them via the `Overseer` to other subsystems.
+///
+/// The resulting stream will end once any of its input ends.
+pub struct RequestMultiplexer {
+	receivers: Vec<(Protocol, mpsc::Receiver<network::IncomingRequest>)>,
+	next_poll: usize,
+}
+
+/// An incoming request could not be decoded.
+#[derive(Debug)]
+pub struct RequestMultiplexError {
+	/// The peer that sent the request, for reporting.
+	pub peer: PeerId,
+	/// The error that occurred.
+	pub error: DecodingError,
+}
+
+impl RequestMultiplexer {
+	/// Create a new `RequestMultiplexer`.
+	///
+	/// This function uses `Protocol::get_config` for each protocol and returns the configurations,
+	/// which need to be registered with the network on startup.
+	pub fn new() -> (Self, Vec<RequestResponseConfig>) {
+		let (receivers, cfgs): (Vec<_>, Vec<_>) = Protocol::ALL
+			.iter()
+			.map(|p| {
+				let (rx, cfg) = p.get_config();
+				((*p, rx), cfg)
+			})
+			.unzip();
+
+		(
+			Self {
+				receivers,
+				next_poll: 0,
+			},
+			cfgs,
+		)
+	}
+}
+
+impl Stream for RequestMultiplexer {
+	type Item = Result<AllMessages, RequestMultiplexError>;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+		let this = self.get_mut();
+		let len = this.receivers.len();
+		let mut count = len;
+		let mut i = this.next_poll;
+		let mut result = Poll::Ready(None);
+
+		// Poll the protocols round robin, so a busy protocol can't starve the others.
+		while count > 0 {
+			// % is safe, because count is initialized to len and the loop is only entered if
+			// len > 0.
+			let (protocol, rx) = &mut this.receivers[i % len];
+			i += 1;
+			count -= 1;
+
+			match Pin::new(rx).poll_next(cx) {
+				Poll::Pending => result = Poll::Pending,
+				// The network dropped the sender, which only happens on shutdown.
+				Poll::Ready(None) => return Poll::Ready(None),
+				Poll::Ready(Some(v)) => {
+					result = Poll::Ready(Some(multiplex_single(*protocol, v)));
+					break
+				}
+			}
+		}
+
+		this.next_poll = i;
+		result
+	}
+}
+
+impl FusedStream for RequestMultiplexer {
+	fn is_terminated(&self) -> bool {
+		self.receivers.iter().any(|(_, rx)| rx.is_terminated())
+	}
+}
+
+/// Convert a single raw incoming request into the message for the responsible subsystem.
+fn multiplex_single(
+	protocol: Protocol,
+	req: network::IncomingRequest,
+) -> Result<AllMessages, RequestMultiplexError> {
+	let r = match protocol {
+		Protocol::ChunkFetching => AllMessages::AvailabilityDistribution(
+			AvailabilityDistributionMessage::ChunkFetchingRequest(decode_with_peer(req)?),
+		),
+		Protocol::CollationFetching => AllMessages::CollatorProtocol(
+			CollatorProtocolMessage::CollationFetchingRequest(decode_with_peer(req)?),
+		),
+		Protocol::PoVFetching => AllMessages::PoVDistribution(
+			PoVDistributionMessage::PoVFetchingRequest(decode_with_peer(req)?),
+		),
+	};
+
+	Ok(r)
+}
+
+fn decode_with_peer<Req>(
+	req: network::IncomingRequest,
+) -> Result<IncomingRequest<Req>, RequestMultiplexError>
+where
+	Req: IsRequest + Decode,
+	Req::Response: parity_scale_codec::Encode,
+{
+	IncomingRequest::from_network(req)
+		.map_err(|(peer, error)| RequestMultiplexError { peer, error })
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use futures::channel::oneshot;
+	use futures::executor;
+	use futures::prelude::*;
+	use assert_matches::assert_matches;
+	use parity_scale_codec::Encode;
+
+	use polkadot_node_network_protocol::request_response::v1;
+	use polkadot_primitives::v1::Hash;
+
+	#[test]
+	fn requests_are_routed_by_protocol() {
+		let (mut multiplexer, mut configs) = RequestMultiplexer::new();
+		let pov_config = configs.iter_mut()
+			.find(|c| c.name == Protocol::PoVFetching.get_protocol_name_static())
+			.unwrap();
+		let mut inbound = pov_config.inbound_queue.take().unwrap();
+
+		let payload = v1::PoVFetchingRequest {
+			relay_parent: Hash::repeat_byte(1),
+			pov_hash: Hash::repeat_byte(2),
+		};
+		let peer = PeerId::random();
+		let (tx, _rx) = oneshot::channel();
+		inbound.try_send(network::IncomingRequest {
+			peer: peer.clone(),
+			payload: payload.encode(),
+			pending_response: tx,
+		}).unwrap();
+
+		assert_matches!(
+			executor::block_on(multiplexer.next()),
+			Some(Ok(AllMessages::PoVDistribution(PoVDistributionMessage::PoVFetchingRequest(req)))) => {
+				assert_eq!(req.peer, peer);
+				assert_eq!(req.payload, payload);
+			}
+		);
+
+		let (tx, _rx) = oneshot::channel();
+		inbound.try_send(network::IncomingRequest {
+			peer: peer.clone(),
+			payload: vec![0xFF],
+			pending_response: tx,
+		}).unwrap();
+
+		assert_matches!(
+			executor::block_on(multiplexer.next()),
+			Some(Err(RequestMultiplexError { peer: p, .. })) if p == peer
+		);
+	}
+}
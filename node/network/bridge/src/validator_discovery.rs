@@ -25,10 +25,9 @@ use futures::channel::mpsc;
 
 use sc_network::multiaddr::{Multiaddr, Protocol};
 use sc_authority_discovery::Service as AuthorityDiscoveryService;
-use polkadot_node_network_protocol::PeerId;
+use polkadot_node_network_protocol::{PeerId, PeerSet};
 use polkadot_primitives::v1::{AuthorityDiscoveryId, Block, Hash};
 
-const PRIORITY_GROUP: &'static str = "parachain_validators";
 const LOG_TARGET: &str = "validator_discovery";
 
 /// An abstraction over networking for the purposes of validator discovery service.
@@ -133,8 +132,11 @@ fn peer_id_from_multiaddr(addr: &Multiaddr) -> Option<PeerId> {
 	})
 }
 
+/// Connects to the validators requested on one peer-set.
 pub(super) struct Service<N, AD> {
-	// Peers that are connected to us and authority ids associated to them.
+	// The peer-set whose validators priority group the requested validators are added to.
+	peer_set: PeerSet,
+	// Peers that are connected to us on the peer-set and authority ids associated to them.
 	connected_peers: HashMap<PeerId, HashSet<AuthorityDiscoveryId>>,
 	// The `u64` counts the number of pending non-revoked requests for this validator
 	// note: the validators in this map are not necessarily present
@@ -147,8 +149,9 @@ pub(super) struct Service<N, AD> {
 }
 
 impl<N: Network, AD: AuthorityDiscovery> Service<N, AD> {
-	pub fn new() -> Self {
+	pub fn new(peer_set: PeerSet) -> Self {
 		Self {
+			peer_set,
 			connected_peers: HashMap::new(),
 			requested_validators: HashMap::new(),
 			non_revoked_discovery_requests: Vec::new(),
@@ -190,7 +193,7 @@ impl<N: Network, AD: AuthorityDiscovery> Service<N, AD> {
 		result
 	}
 
-	/// On a new connection request, a priority group update will be issued.
+	/// On a new connection request, a priority group update of the peer-set will be issued.
 	/// It will ask the network to connect to the validators and not disconnect
 	/// from them at least until all the pending requests containing them are revoked.
 	///
@@ -276,13 +279,16 @@ impl<N: Network, AD: AuthorityDiscovery> Service<N, AD> {
 		// ask the network to connect to these nodes and not disconnect
 		// from them until removed from the priority group
 		if let Err(e) = network_service.add_to_priority_group(
-			PRIORITY_GROUP.to_owned(),
+			self.peer_set.get_validators_priority_group().to_owned(),
 			multiaddr_to_add,
 		).await {
 			tracing::warn!(target: LOG_TARGET, err = ?e, "AuthorityDiscoveryService returned an invalid multiaddress");
 		}
 		// the addresses are known to be valid
-		let _ = network_service.remove_from_priority_group(PRIORITY_GROUP.to_owned(), multiaddr_to_remove).await;
+		let _ = network_service.remove_from_priority_group(
+			self.peer_set.get_validators_priority_group().to_owned(),
+			multiaddr_to_remove,
+		).await;
 
 		let pending = validator_ids.iter()
 			.cloned()
@@ -299,8 +305,10 @@ impl<N: Network, AD: AuthorityDiscovery> Service<N, AD> {
 	}
 
 	/// Should be called when a peer connected.
+	///
+	/// Returns `true` if the peer is a validator that is currently requested on the peer-set.
 	#[tracing::instrument(level = "trace", skip(self, authority_discovery_service), fields(subsystem = LOG_TARGET))]
-	pub async fn on_peer_connected(&mut self, peer_id: &PeerId, authority_discovery_service: &mut AD) {
+	pub async fn on_peer_connected(&mut self, peer_id: &PeerId, authority_discovery_service: &mut AD) -> bool {
 		// check if it's an authority we've been waiting for
 		let maybe_authority = authority_discovery_service.get_authority_id_by_peer_id(peer_id.clone()).await;
 		if let Some(authority) = maybe_authority {
@@ -308,9 +316,12 @@ impl<N: Network, AD: AuthorityDiscovery> Service<N, AD> {
 				let _ = request.on_authority_connected(&authority, peer_id);
 			}
 
+			let requested = self.requested_validators.contains_key(&authority);
 			self.connected_peers.entry(peer_id.clone()).or_default().insert(authority);
+			requested
 		} else {
 			self.connected_peers.insert(peer_id.clone(), Default::default());
+			false
 		}
 	}
 
@@ -330,7 +341,7 @@ mod tests {
 
 
 	fn new_service() -> Service<TestNetwork, TestAuthorityDiscovery> {
-		Service::new()
+		Service::new(PeerSet::Validation)
 	}
 
 	fn new_network() -> (TestNetwork, TestAuthorityDiscovery) {
@@ -339,7 +350,13 @@ mod tests {
 
 	#[derive(Default)]
 	struct TestNetwork {
-		priority_group: HashSet<Multiaddr>,
+		priority_groups: HashMap<String, HashSet<Multiaddr>>,
+	}
+
+	impl TestNetwork {
+		fn priority_group(&self, peer_set: PeerSet) -> HashSet<Multiaddr> {
+			self.priority_groups.get(peer_set.get_validators_priority_group()).cloned().unwrap_or_default()
+		}
 	}
 
 	#[derive(Default)]
@@ -367,13 +384,13 @@ mod tests {
 
 	#[async_trait]
 	impl Network for TestNetwork {
-		async fn add_to_priority_group(&mut self, _group_id: String, multiaddresses: HashSet<Multiaddr>) -> Result<(), String> {
-			self.priority_group.extend(multiaddresses.into_iter());
+		async fn add_to_priority_group(&mut self, group_id: String, multiaddresses: HashSet<Multiaddr>) -> Result<(), String> {
+			self.priority_groups.entry(group_id).or_default().extend(multiaddresses.into_iter());
 			Ok(())
 		}
 
-		async fn remove_from_priority_group(&mut self, _group_id: String, multiaddresses: HashSet<Multiaddr>) -> Result<(), String> {
-			self.priority_group.retain(|elem| !multiaddresses.contains(elem));
+		async fn remove_from_priority_group(&mut self, group_id: String, multiaddresses: HashSet<Multiaddr>) -> Result<(), String> {
+			self.priority_groups.entry(group_id).or_default().retain(|elem| !multiaddresses.contains(elem));
 			Ok(())
 		}
 	}
@@ -570,7 +587,8 @@ mod tests {
 
 			let _ = receiver.next().await.unwrap();
 			assert_eq!(service.non_revoked_discovery_requests.len(), 1);
-			assert_eq!(ns.priority_group.len(), 2);
+			assert_eq!(ns.priority_group(PeerSet::Validation).len(), 2);
+			assert!(ns.priority_group(PeerSet::Collation).is_empty());
 
 			// revoke the second request
 			drop(receiver);
@@ -586,7 +604,7 @@ mod tests {
 
 			let _ = receiver.next().await.unwrap();
 			assert_eq!(service.non_revoked_discovery_requests.len(), 1);
-			assert_eq!(ns.priority_group.len(), 1);
+			assert_eq!(ns.priority_group(PeerSet::Validation).len(), 1);
 		});
 	}
 
@@ -622,4 +640,29 @@ mod tests {
 			assert!(service.connected_peers.get(&validator_peer_id).unwrap().contains(&validator_id));
 		});
 	}
+
+	#[test]
+	fn connected_peers_report_whether_they_were_requested() {
+		let mut service = new_service();
+
+		let (ns, ads) = new_network();
+
+		let peer_ids: Vec<_> = ads.by_peer_id.keys().cloned().collect();
+		let authority_ids: Vec<_> = peer_ids.iter().map(|p| ads.by_peer_id[p].clone()).collect();
+
+		futures::executor::block_on(async move {
+			let (sender, _receiver) = mpsc::channel(1);
+
+			let (_, mut ads) = service.on_request(
+				vec![authority_ids[0].clone()],
+				sender,
+				ns,
+				ads,
+			).await;
+
+			assert!(service.on_peer_connected(&peer_ids[0], &mut ads).await);
+			assert!(!service.on_peer_connected(&peer_ids[1], &mut ads).await);
+			assert!(!service.on_peer_connected(&PeerId::random(), &mut ads).await);
+		});
+	}
 }
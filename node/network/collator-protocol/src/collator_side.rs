@@ -31,7 +31,8 @@ use polkadot_subsystem::{
 	},
 };
 use polkadot_node_network_protocol::{
-	v1 as protocol_v1, View, PeerId, NetworkBridgeEvent, RequestId,
+	v1 as protocol_v1, View, PeerId, PeerSet, NetworkBridgeEvent, RequestId,
+	request_response::{IncomingRequest, v1::{CollationFetchingRequest, CollationFetchingResponse}},
 };
 use polkadot_node_subsystem_util::{
 	validator_discovery,
@@ -143,6 +144,11 @@ impl ValidatorGroup {
 		}
 	}
 
+	/// Returns `true` if we advertised our collation to the given peer.
+	fn has_advertised_to(&self, peer: &PeerId) -> bool {
+		self.peer_ids.get(peer).map_or(false, |validator_id| self.advertised_to.contains(validator_id))
+	}
+
 	/// Should be called after we advertised our collation to the given `peer` to keep track of it.
 	fn advertised_to_peer(&mut self, peer: &PeerId) {
 		if let Some(validator_id) = self.peer_ids.get(peer) {
@@ -212,6 +218,13 @@ impl State {
 	fn peer_interested_in_leaf(&self, peer: &PeerId, relay_parent: &Hash) -> bool {
 		self.peer_views.get(peer).map(|v| v.contains(relay_parent)).unwrap_or(false)
 	}
+
+	/// Returns `true` if we advertised our collation for the leaf `relay_parent` to the given `peer`.
+	///
+	/// Only those peers are served the collation.
+	fn advertised_to(&self, peer: &PeerId, relay_parent: &Hash) -> bool {
+		self.our_validators_groups.get(relay_parent).map_or(false, |g| g.has_advertised_to(peer))
+	}
 }
 
 /// Distribute a collation.
@@ -366,6 +379,7 @@ async fn connect_to_validators(
 		ctx,
 		relay_parent,
 		validators,
+		PeerSet::Collation,
 	).await?;
 
 	state.connection_requests.put(relay_parent, request);
@@ -484,11 +498,67 @@ async fn process_msg(
 				);
 			}
 		},
+		CollationFetchingRequest(req) => {
+			answer_collation_request(state, req);
+		}
 	}
 
 	Ok(())
 }
 
+/// Answer a request for a collation we hold for the requested relay-parent.
+///
+/// A request we can't answer, or from a peer we did not advertise the collation to, is dropped,
+/// which makes it fail on the requesting side.
+#[tracing::instrument(level = "trace", skip(state, req), fields(subsystem = LOG_TARGET))]
+fn answer_collation_request(
+	state: &mut State,
+	req: IncomingRequest<CollationFetchingRequest>,
+) {
+	let CollationFetchingRequest { relay_parent, para_id } = req.payload;
+
+	match state.collating_on {
+		Some(our_para_id) if our_para_id == para_id => {
+			if !state.advertised_to(&req.peer, &relay_parent) {
+				tracing::debug!(
+					target: LOG_TARGET,
+					peer = %req.peer,
+					relay_parent = %relay_parent,
+					"CollationFetchingRequest from a peer we did not advertise to",
+				);
+				return;
+			}
+
+			if let Some((receipt, pov)) = state.collations.get(&relay_parent).cloned() {
+				if req.send_response(CollationFetchingResponse::Collation(receipt, pov)).is_ok() {
+					state.metrics.on_collation_sent();
+				} else {
+					tracing::debug!(
+						target: LOG_TARGET,
+						relay_parent = %relay_parent,
+						"Collation request was no longer awaited when answering it",
+					);
+				}
+			}
+		}
+		Some(our_para_id) => {
+			tracing::warn!(
+				target: LOG_TARGET,
+				for_para_id = %para_id,
+				our_para_id = %our_para_id,
+				"received a CollationFetchingRequest for unexpected para_id",
+			);
+		}
+		None => {
+			tracing::warn!(
+				target: LOG_TARGET,
+				for_para_id = %para_id,
+				"received a CollationFetchingRequest while not collating on any para",
+			);
+		}
+	}
+}
+
 /// Issue a response to a previously requested collation.
 #[tracing::instrument(level = "trace", skip(ctx, state, pov), fields(subsystem = LOG_TARGET))]
 async fn send_collation(
@@ -542,7 +612,14 @@ async fn handle_incoming_peer_message(
 			match state.collating_on {
 				Some(our_para_id) => {
 					if our_para_id == para_id {
-						if let Some(collation) = state.collations.get(&relay_parent).cloned() {
+						if !state.advertised_to(&origin, &relay_parent) {
+							tracing::debug!(
+								target: LOG_TARGET,
+								peer = %origin,
+								relay_parent = %relay_parent,
+								"RequestCollation from a peer we did not advertise to",
+							);
+						} else if let Some(collation) = state.collations.get(&relay_parent).cloned() {
 							send_collation(ctx, state, request_id, origin, collation.0, collation.1).await;
 						}
 					} else {
@@ -651,6 +728,9 @@ async fn handle_network_msg(
 		PeerMessage(remote, msg) => {
 			handle_incoming_peer_message(ctx, state, remote, msg).await?;
 		}
+		NewGossipTopology(_) => {
+			// The gossip topology only concerns the validation peer-set.
+		}
 	}
 
 	Ok(())
@@ -715,7 +795,7 @@ pub(crate) async fn run(
 					}
 				},
 				Signal(ActiveLeaves(_update)) => {}
-				Signal(BlockFinalized(_)) => {}
+				Signal(BlockFinalized(..)) => {}
 				Signal(Conclude) => return Ok(()),
 			}
 		}
@@ -729,7 +809,8 @@ mod tests {
 	use std::time::Duration;
 
 	use assert_matches::assert_matches;
-	use futures::{executor, future, Future, channel::mpsc};
+	use futures::{executor, future, Future, channel::{mpsc, oneshot}};
+	use parity_scale_codec::Decode;
 	use smallvec::smallvec;
 
 	use sp_core::crypto::Pair;
@@ -1004,7 +1085,7 @@ mod tests {
 		overseer_signal(
 			virtual_overseer,
 			OverseerSignal::ActiveLeaves(ActiveLeavesUpdate {
-				activated: smallvec![test_state.relay_parent],
+				activated: smallvec![test_helpers::activated_leaf(test_state.relay_parent)],
 				deactivated: smallvec![],
 			}),
 		).await;
@@ -1111,6 +1192,7 @@ mod tests {
 			overseer_recv(virtual_overseer).await,
 			AllMessages::NetworkBridge(
 				NetworkBridgeMessage::ConnectToValidators {
+					peer_set: PeerSet::Collation,
 					connected,
 					..
 				}
@@ -1327,6 +1409,101 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn collation_requests_are_answered() {
+		let test_state = TestState::default();
+
+		test_harness(test_state.our_collator_pair.public(), |test_harness| async move {
+			let mut virtual_overseer = test_harness.virtual_overseer;
+
+			setup_system(&mut virtual_overseer, &test_state).await;
+
+			let DistributeCollation { mut connected, candidate, pov_block } =
+				distribute_collation(&mut virtual_overseer, &test_state).await;
+			test_state.current_group_validator_authority_ids()
+				.into_iter()
+				.zip(test_state.current_group_validator_peer_ids())
+				.for_each(|r| connected.try_send(r).unwrap());
+
+			for peer_id in test_state.current_group_validator_peer_ids() {
+				expect_declare_msg(&mut virtual_overseer, &test_state, &peer_id).await;
+			}
+
+			let peer = test_state.current_group_validator_peer_ids()[0].clone();
+			let other_peer = test_state.current_group_validator_peer_ids()[1].clone();
+
+			// We have not advertised to anyone yet, so the request is dropped.
+			let (tx, rx) = oneshot::channel();
+			overseer_send(
+				&mut virtual_overseer,
+				CollatorProtocolMessage::CollationFetchingRequest(IncomingRequest::new(
+					peer.clone(),
+					CollationFetchingRequest {
+						relay_parent: test_state.relay_parent,
+						para_id: test_state.para_id,
+					},
+					tx,
+				)),
+			).await;
+
+			assert!(rx.await.is_err());
+
+			send_peer_view_change(&mut virtual_overseer, &peer, vec![test_state.relay_parent]).await;
+			expect_advertise_collation_msg(&mut virtual_overseer, &test_state, &peer, test_state.relay_parent).await;
+
+			let (tx, rx) = oneshot::channel();
+			overseer_send(
+				&mut virtual_overseer,
+				CollatorProtocolMessage::CollationFetchingRequest(IncomingRequest::new(
+					peer.clone(),
+					CollationFetchingRequest {
+						relay_parent: test_state.relay_parent,
+						para_id: test_state.para_id,
+					},
+					tx,
+				)),
+			).await;
+
+			let raw = rx.await.expect("Collation request is answered");
+			assert_eq!(
+				CollationFetchingResponse::decode(&mut &raw[..]).unwrap(),
+				CollationFetchingResponse::Collation(candidate, pov_block),
+			);
+
+			// A validator of the group we did not advertise to is not served.
+			let (tx, rx) = oneshot::channel();
+			overseer_send(
+				&mut virtual_overseer,
+				CollatorProtocolMessage::CollationFetchingRequest(IncomingRequest::new(
+					other_peer,
+					CollationFetchingRequest {
+						relay_parent: test_state.relay_parent,
+						para_id: test_state.para_id,
+					},
+					tx,
+				)),
+			).await;
+
+			assert!(rx.await.is_err());
+
+			// We have no collation for other relay parents, so the request is dropped.
+			let (tx, rx) = oneshot::channel();
+			overseer_send(
+				&mut virtual_overseer,
+				CollatorProtocolMessage::CollationFetchingRequest(IncomingRequest::new(
+					peer,
+					CollationFetchingRequest {
+						relay_parent: Hash::repeat_byte(0xFF),
+						para_id: test_state.para_id,
+					},
+					tx,
+				)),
+			).await;
+
+			assert!(rx.await.is_err());
+		});
+	}
+
 	/// This test ensures that we declare a collator at a validator by sending the `Declare` message as soon as the
 	/// collator is aware of the validator being connected.
 	#[test]
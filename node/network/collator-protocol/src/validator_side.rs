@@ -22,7 +22,7 @@ use futures::{
 	StreamExt,
 	FutureExt,
 	channel::oneshot,
-	future::BoxFuture,
+	future::{BoxFuture, Either},
 	stream::FuturesUnordered,
 };
 
@@ -38,6 +38,10 @@ use polkadot_subsystem::{
 use polkadot_node_network_protocol::{
 	v1 as protocol_v1, View, PeerId, ReputationChange as Rep, RequestId,
 	NetworkBridgeEvent,
+	request_response::{
+		OutgoingRequest, Requests, RequestError,
+		v1::{CollationFetchingRequest, CollationFetchingResponse},
+	},
 };
 use polkadot_node_subsystem_util::{
 	TimeoutExt as _,
@@ -124,6 +128,8 @@ impl metrics::Metrics for Metrics {
 #[derive(Debug)]
 enum CollationRequestResult {
 	Received(RequestId),
+	Response(RequestId, CandidateReceipt, PoV),
+	Failed(RequestId, RequestError),
 	Timeout(RequestId),
 }
 
@@ -135,6 +141,9 @@ struct CollationRequest {
 	// chain has moved forward and this request is no longer relevant.
 	received: oneshot::Receiver<()>,
 
+	// The response of the collator, sent over the collation fetching protocol.
+	response: BoxFuture<'static, std::result::Result<CollationFetchingResponse, RequestError>>,
+
 	// The timeout of this request.
 	timeout: Duration,
 
@@ -148,13 +157,17 @@ impl CollationRequest {
 
 		let CollationRequest {
 			received,
+			response,
 			timeout,
 			request_id,
 		} = self;
 
-		match received.timeout(timeout).await {
+		match futures::future::select(received, response).timeout(timeout).await {
 			None => Timeout(request_id),
-			Some(_) => Received(request_id),
+			Some(Either::Left(_)) => Received(request_id),
+			Some(Either::Right((Ok(CollationFetchingResponse::Collation(receipt, pov)), _))) =>
+				Response(request_id, receipt, pov),
+			Some(Either::Right((Err(e), _))) => Failed(request_id, e),
 		}
 	}
 }
@@ -439,8 +452,17 @@ where
 		result,
 	};
 
+	let (full_request, response) = OutgoingRequest::new(
+		peer_id.clone(),
+		CollationFetchingRequest {
+			relay_parent,
+			para_id,
+		},
+	);
+
 	let request = CollationRequest {
 		received: rx,
+		response: response.boxed(),
 		timeout: state.request_timeout,
 		request_id,
 	};
@@ -451,17 +473,8 @@ where
 
 	state.requests_in_progress.push(request.wait().boxed());
 
-	let wire_message = protocol_v1::CollatorProtocolMessage::RequestCollation(
-		request_id,
-		relay_parent,
-		para_id,
-	);
-
 	ctx.send_message(AllMessages::NetworkBridge(
-		NetworkBridgeMessage::SendCollationMessage(
-			vec![peer_id],
-			protocol_v1::CollationProtocol::CollatorProtocol(wire_message),
-		)
+		NetworkBridgeMessage::SendRequests(vec![Requests::CollationFetching(full_request)])
 	)).await;
 }
 
@@ -625,6 +638,9 @@ where
 		PeerMessage(remote, msg) => {
 			process_incoming_peer_message(ctx, state, remote, msg).await;
 		}
+		NewGossipTopology(_) => {
+			// The gossip topology only concerns the validation peer-set.
+		}
 	}
 
 	Ok(())
@@ -680,6 +696,12 @@ where
 				);
 			}
 		}
+		CollationFetchingRequest(_) => {
+			tracing::warn!(
+				target: LOG_TARGET,
+				"CollationFetchingRequest message is not expected on the validator side of the protocol",
+			);
+		}
 	}
 }
 
@@ -709,7 +731,7 @@ where
 
 			match msg {
 				Communication { msg } => process_msg(&mut ctx, msg, &mut state).await,
-				Signal(BlockFinalized(_)) => {}
+				Signal(BlockFinalized(..)) => {}
 				Signal(ActiveLeaves(_)) => {}
 				Signal(Conclude) => { break }
 			}
@@ -729,6 +751,17 @@ where
 				CollationRequestResult::Received(id) => {
 					state.requests_info.remove(&id);
 				}
+				CollationRequestResult::Response(id, receipt, pov) => {
+					if let Some((_, _, origin)) = find_val_in_map(&state.requested_collations, &id) {
+						received_collation(&mut ctx, &mut state, origin, id, receipt, pov).await;
+					}
+				}
+				// The collator failed to deliver the collation it advertised, which we treat
+				// like a request that timed out.
+				CollationRequestResult::Failed(id, err) => {
+					tracing::debug!(target: LOG_TARGET, id, err = ?err, "request failed");
+					request_timed_out(&mut ctx, &mut state, id).await;
+				}
 			}
 		}
 
@@ -752,6 +785,7 @@ mod tests {
 	use sp_core::crypto::Pair;
 	use assert_matches::assert_matches;
 	use futures_timer::Delay;
+	use parity_scale_codec::Encode;
 
 	use polkadot_primitives::v1::{BlockData, CollatorPair};
 	use polkadot_subsystem_testhelpers as test_helpers;
@@ -981,23 +1015,21 @@ mod tests {
 				)
 			).await;
 
-			assert_matches!(
+			// Keep the request around, dropping it would fail it right away.
+			let _request = assert_matches!(
 				overseer_recv(&mut virtual_overseer).await,
-				AllMessages::NetworkBridge(NetworkBridgeMessage::SendCollationMessage(
-					peers,
-					protocol_v1::CollationProtocol::CollatorProtocol(
-						protocol_v1::CollatorProtocolMessage::RequestCollation(
-							_id,
-							relay_parent,
-							para_id,
-						)
-					)
-				)
-			) => {
-				assert_eq!(relay_parent, test_state.relay_parent);
-				assert_eq!(peers, vec![peer_b.clone()]);
-				assert_eq!(para_id, test_state.chain_ids[0]);
-			});
+				AllMessages::NetworkBridge(NetworkBridgeMessage::SendRequests(mut reqs)) => {
+					assert_eq!(reqs.len(), 1);
+					assert_matches!(reqs.pop().unwrap(), Requests::CollationFetching(req) => {
+						assert_eq!(req.peer, peer_b);
+						assert_eq!(req.payload, CollationFetchingRequest {
+							relay_parent: test_state.relay_parent,
+							para_id: test_state.chain_ids[0],
+						});
+						req
+					})
+				}
+			);
 
 			// Don't send a response and we shoud see reputation penalties to the
 			// collator.
@@ -1235,81 +1267,51 @@ mod tests {
 				)
 			).await;
 
-			let (request_id, peer_id) = assert_matches!(
+			let request = assert_matches!(
 				overseer_recv(&mut virtual_overseer).await,
-				AllMessages::NetworkBridge(NetworkBridgeMessage::SendCollationMessage(
-					peers,
-					protocol_v1::CollationProtocol::CollatorProtocol(
-						protocol_v1::CollatorProtocolMessage::RequestCollation(
-							id,
-							relay_parent,
-							para_id,
-						)
-					)
-				)
-			) => {
-				assert_eq!(relay_parent, test_state.relay_parent);
-				assert_eq!(para_id, test_state.chain_ids[0]);
-				(id, peers[0].clone())
-			});
+				AllMessages::NetworkBridge(NetworkBridgeMessage::SendRequests(mut reqs)) => {
+					assert_eq!(reqs.len(), 1);
+					assert_matches!(reqs.pop().unwrap(), Requests::CollationFetching(req) => {
+						assert_eq!(req.payload.relay_parent, test_state.relay_parent);
+						assert_eq!(req.payload.para_id, test_state.chain_ids[0]);
+						req
+					})
+				}
+			);
 
 			let mut candidate_a = CandidateReceipt::default();
 			candidate_a.descriptor.para_id = test_state.chain_ids[0];
 			candidate_a.descriptor.relay_parent = test_state.relay_parent;
 
-			overseer_send(
-				&mut virtual_overseer,
-				CollatorProtocolMessage::NetworkBridgeUpdateV1(
-					NetworkBridgeEvent::PeerMessage(
-						peer_id,
-						protocol_v1::CollatorProtocolMessage::Collation(
-							request_id,
-							candidate_a.clone(),
-							PoV {
-								block_data: BlockData(vec![]),
-							},
-						)
-					)
-				)
-			).await;
+			request.pending_response.send(Ok(CollationFetchingResponse::Collation(
+				candidate_a.clone(),
+				PoV {
+					block_data: BlockData(vec![]),
+				},
+			).encode())).unwrap();
 
-			let (request_id, peer_id) = assert_matches!(
+			let request = assert_matches!(
 				overseer_recv(&mut virtual_overseer).await,
-				AllMessages::NetworkBridge(NetworkBridgeMessage::SendCollationMessage(
-					peers,
-					protocol_v1::CollationProtocol::CollatorProtocol(
-						protocol_v1::CollatorProtocolMessage::RequestCollation(
-							id,
-							relay_parent,
-							para_id,
-						)
-					)
-				)
-			) => {
-				assert_eq!(relay_parent, test_state.relay_parent);
-				assert_eq!(para_id, test_state.chain_ids[0]);
-				(id, peers[0].clone())
-			});
+				AllMessages::NetworkBridge(NetworkBridgeMessage::SendRequests(mut reqs)) => {
+					assert_eq!(reqs.len(), 1);
+					assert_matches!(reqs.pop().unwrap(), Requests::CollationFetching(req) => {
+						assert_eq!(req.payload.relay_parent, test_state.relay_parent);
+						assert_eq!(req.payload.para_id, test_state.chain_ids[0]);
+						req
+					})
+				}
+			);
 
 			let mut candidate_b = CandidateReceipt::default();
 			candidate_b.descriptor.para_id = test_state.chain_ids[0];
 			candidate_b.descriptor.relay_parent = test_state.relay_parent;
 
-			overseer_send(
-				&mut virtual_overseer,
-				CollatorProtocolMessage::NetworkBridgeUpdateV1(
-					NetworkBridgeEvent::PeerMessage(
-						peer_id,
-						protocol_v1::CollatorProtocolMessage::Collation(
-							request_id,
-							candidate_b.clone(),
-							PoV {
-								block_data: BlockData(vec![1, 2, 3]),
-							},
-						)
-					)
-				)
-			).await;
+			request.pending_response.send(Ok(CollationFetchingResponse::Collation(
+				candidate_b.clone(),
+				PoV {
+					block_data: BlockData(vec![1, 2, 3]),
+				},
+			).encode())).unwrap();
 
 			let collation_0 = rx_0.await.unwrap();
 			let collation_1 = rx_1.await.unwrap();
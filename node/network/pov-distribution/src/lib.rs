@@ -23,7 +23,7 @@
 #![warn(missing_docs)]
 
 use polkadot_primitives::v1::{
-	Hash, PoV, CandidateDescriptor, ValidatorId, Id as ParaId, CoreIndex, CoreState,
+	Hash, BlockNumber, PoV, CandidateDescriptor, ValidatorId, Id as ParaId, CoreIndex, CoreState,
 };
 use polkadot_subsystem::{
 	ActiveLeavesUpdate, OverseerSignal, SubsystemContext, SubsystemResult, SubsystemError, Subsystem,
@@ -40,7 +40,8 @@ use polkadot_node_subsystem_util::{
 	metrics::{self, prometheus},
 };
 use polkadot_node_network_protocol::{
-	v1 as protocol_v1, ReputationChange as Rep, NetworkBridgeEvent, PeerId, View,
+	v1 as protocol_v1, ReputationChange as Rep, NetworkBridgeEvent, PeerId, PeerSet, View,
+	request_response::{IncomingRequest, v1::{PoVFetchingRequest, PoVFetchingResponse}},
 };
 
 use futures::prelude::*;
@@ -115,6 +116,9 @@ struct BlockBasedState {
 	fetching: HashMap<Hash, Vec<oneshot::Sender<Arc<PoV>>>>,
 
 	n_validators: usize,
+
+	/// The number of the relay parent.
+	block_number: BlockNumber,
 }
 
 #[derive(Default)]
@@ -152,8 +156,9 @@ async fn handle_signal(
 		OverseerSignal::ActiveLeaves(ActiveLeavesUpdate { activated, deactivated }) => {
 			let _timer = state.metrics.time_handle_signal();
 
-			for relay_parent in activated {
-				match request_validators_ctx(relay_parent.clone(), ctx).await {
+			for leaf in activated {
+				let relay_parent = leaf.hash;
+				match request_validators_ctx(relay_parent, ctx).await {
 					Ok(vals_rx) => {
 						let n_validators = match vals_rx.await? {
 							Ok(v) => v.len(),
@@ -177,6 +182,7 @@ async fn handle_signal(
 							known: HashMap::new(),
 							fetching: HashMap::new(),
 							n_validators,
+							block_number: leaf.number,
 						});
 					}
 					Err(e) => {
@@ -197,10 +203,31 @@ async fn handle_signal(
 
 			Ok(false)
 		}
-		OverseerSignal::BlockFinalized(_) => Ok(false),
+		OverseerSignal::BlockFinalized(_hash, number) => {
+			prune_finalized(state, number);
+			Ok(false)
+		}
 	}
 }
 
+/// Drop the state of relay parents at or below the height of a finalized block,
+/// along with the connection requests made for them.
+///
+/// Relay parents still in our view are kept: with instant finality the finalized block is often
+/// still our leaf, and we keep distributing PoVs for it.
+fn prune_finalized(state: &mut State, finalized_number: BlockNumber) {
+	let our_view = &state.our_view;
+	let connection_requests = &mut state.connection_requests;
+	state.relay_parent_state.retain(|relay_parent, block_state| {
+		if our_view.contains(relay_parent) || block_state.block_number > finalized_number {
+			true
+		} else {
+			connection_requests.remove(relay_parent);
+			false
+		}
+	});
+}
+
 /// Notify peers that we are awaiting a given PoV hash.
 ///
 /// This only notifies peers who have the relay parent in their view.
@@ -405,6 +432,7 @@ async fn handle_fetch(
 							ctx,
 							relay_parent,
 							relevant_validators.clone(),
+							PeerSet::Validation,
 						).await {
 							Ok(new_connection_request) => {
 								state.connection_requests.put(relay_parent, new_connection_request);
@@ -678,6 +706,31 @@ async fn handle_network_update(
 		NetworkBridgeEvent::OurViewChange(view) => {
 			state.our_view = view;
 		}
+		NetworkBridgeEvent::NewGossipTopology(_) => {
+			// PoVs are only sent to peers awaiting them.
+		}
+	}
+}
+
+/// Answer a request for a PoV with the PoV, if we know it under the requested relay-parent.
+#[tracing::instrument(level = "trace", skip(state, req), fields(subsystem = LOG_TARGET))]
+fn handle_pov_request(
+	state: &State,
+	req: IncomingRequest<PoVFetchingRequest>,
+) {
+	let pov = state.relay_parent_state.get(&req.payload.relay_parent)
+		.and_then(|s| s.known.get(&req.payload.pov_hash));
+
+	let response = match pov {
+		Some(pov) => PoVFetchingResponse::PoV((**pov).clone()),
+		None => PoVFetchingResponse::NoSuchPoV,
+	};
+
+	if req.send_response(response).is_err() {
+		tracing::debug!(
+			target: LOG_TARGET,
+			"PoV request was no longer awaited when answering it",
+		);
 	}
 }
 
@@ -740,6 +793,8 @@ impl PoVDistribution {
 									&mut ctx,
 									event,
 								).await,
+							PoVDistributionMessage::PoVFetchingRequest(req) =>
+								handle_pov_request(&state, req),
 						}
 					}
 				}
@@ -3,6 +3,7 @@ use super::*;
 use std::time::Duration;
 
 use assert_matches::assert_matches;
+use parity_scale_codec::Decode;
 use futures::executor;
 use tracing::trace;
 use smallvec::smallvec;
@@ -211,7 +212,7 @@ fn ask_validators_for_povs() {
 		overseer_signal(
 			&mut virtual_overseer,
 			OverseerSignal::ActiveLeaves(ActiveLeavesUpdate {
-				activated: smallvec![test_state.relay_parent.clone()],
+				activated: smallvec![test_helpers::activated_leaf(test_state.relay_parent)],
 				deactivated: smallvec![],
 			}),
 		).await;
@@ -390,7 +391,7 @@ fn ask_validators_for_povs() {
 		overseer_signal(
 			&mut virtual_overseer,
 			OverseerSignal::ActiveLeaves(ActiveLeavesUpdate {
-				activated: smallvec![next_leaf.clone()],
+				activated: smallvec![test_helpers::activated_leaf(next_leaf)],
 				deactivated: smallvec![current.clone()],
 			})
 		).await;
@@ -525,6 +526,7 @@ fn distributes_to_those_awaiting_and_completes_local() {
 				known: HashMap::new(),
 				fetching: HashMap::new(),
 				n_validators: 10,
+				block_number: 1,
 			};
 
 			b.fetching.insert(pov_hash, vec![pov_send]);
@@ -594,6 +596,50 @@ fn distributes_to_those_awaiting_and_completes_local() {
 	});
 }
 
+#[test]
+fn pov_requests_are_answered_with_known_povs() {
+	let hash_a: Hash = [0; 32].into();
+	let hash_b: Hash = [1; 32].into();
+
+	let pov = make_pov(vec![1, 2, 3]);
+	let pov_hash = pov.hash();
+
+	let state = State {
+		relay_parent_state: {
+			let mut s = HashMap::new();
+			let mut b = BlockBasedState {
+				known: HashMap::new(),
+				fetching: HashMap::new(),
+				n_validators: 10,
+				block_number: 1,
+			};
+
+			b.known.insert(pov_hash, Arc::new(pov.clone()));
+			s.insert(hash_a, b);
+			s
+		},
+		peer_state: HashMap::new(),
+		our_view: View(vec![hash_a]),
+		metrics: Default::default(),
+		connection_requests: Default::default(),
+	};
+
+	let request = |relay_parent| {
+		let (tx, rx) = oneshot::channel();
+		handle_pov_request(&state, IncomingRequest::new(
+			PeerId::random(),
+			PoVFetchingRequest { relay_parent, pov_hash },
+			tx,
+		));
+
+		let raw = executor::block_on(rx).unwrap();
+		PoVFetchingResponse::decode(&mut &raw[..]).unwrap()
+	};
+
+	assert_eq!(request(hash_a), PoVFetchingResponse::PoV(pov.clone()));
+	// The PoV is only known under the relay-parent it was distributed in.
+	assert_eq!(request(hash_b), PoVFetchingResponse::NoSuchPoV);
+}
 
 #[test]
 fn we_inform_peers_with_same_view_we_are_awaiting() {
@@ -615,6 +661,7 @@ fn we_inform_peers_with_same_view_we_are_awaiting() {
 				known: HashMap::new(),
 				fetching: HashMap::new(),
 				n_validators: 10,
+				block_number: 1,
 			};
 
 			s.insert(hash_a, b);
@@ -801,6 +848,7 @@ fn peer_view_change_leads_to_us_informing() {
 				known: HashMap::new(),
 				fetching: HashMap::new(),
 				n_validators: 10,
+				block_number: 1,
 			};
 
 			// pov_a is still being fetched, whereas the fetch of pov_b has already
@@ -871,6 +919,7 @@ fn peer_complete_fetch_and_is_rewarded() {
 				known: HashMap::new(),
 				fetching: HashMap::new(),
 				n_validators: 10,
+				block_number: 1,
 			};
 
 			// pov is being fetched.
@@ -967,6 +1016,7 @@ fn peer_punished_for_sending_bad_pov() {
 				known: HashMap::new(),
 				fetching: HashMap::new(),
 				n_validators: 10,
+				block_number: 1,
 			};
 
 			// pov is being fetched.
@@ -1035,6 +1085,7 @@ fn peer_punished_for_sending_unexpected_pov() {
 				known: HashMap::new(),
 				fetching: HashMap::new(),
 				n_validators: 10,
+				block_number: 1,
 			};
 
 			s.insert(hash_a, b);
@@ -1098,6 +1149,7 @@ fn peer_punished_for_sending_pov_out_of_our_view() {
 				known: HashMap::new(),
 				fetching: HashMap::new(),
 				n_validators: 10,
+				block_number: 1,
 			};
 
 			s.insert(hash_a, b);
@@ -1158,6 +1210,7 @@ fn peer_reported_for_awaiting_too_much() {
 				known: HashMap::new(),
 				fetching: HashMap::new(),
 				n_validators,
+				block_number: 1,
 			};
 
 			s.insert(hash_a, b);
@@ -1239,12 +1292,14 @@ fn peer_reported_for_awaiting_outside_their_view() {
 				known: HashMap::new(),
 				fetching: HashMap::new(),
 				n_validators: 10,
+				block_number: 1,
 			});
 
 			s.insert(hash_b, BlockBasedState {
 				known: HashMap::new(),
 				fetching: HashMap::new(),
 				n_validators: 10,
+				block_number: 1,
 			});
 
 			s
@@ -1309,6 +1364,7 @@ fn peer_reported_for_awaiting_outside_our_view() {
 				known: HashMap::new(),
 				fetching: HashMap::new(),
 				n_validators: 10,
+				block_number: 1,
 			});
 
 			s
@@ -1379,6 +1435,7 @@ fn peer_complete_fetch_leads_to_us_completing_others() {
 				known: HashMap::new(),
 				fetching: HashMap::new(),
 				n_validators: 10,
+				block_number: 1,
 			};
 
 			// pov is being fetched.
@@ -1468,6 +1525,7 @@ fn peer_completing_request_no_longer_awaiting() {
 				known: HashMap::new(),
 				fetching: HashMap::new(),
 				n_validators: 10,
+				block_number: 1,
 			};
 
 			// pov is being fetched.
@@ -1521,3 +1579,45 @@ fn peer_completing_request_no_longer_awaiting() {
 		assert!(!state.peer_state[&peer_a].awaited[&hash_a].contains(&pov_hash));
 	});
 }
+
+#[test]
+fn finalized_relay_parents_are_pruned() {
+	let hash_a: Hash = [0; 32].into();
+	let hash_b: Hash = [1; 32].into();
+
+	let block_state = |block_number| BlockBasedState {
+		known: HashMap::new(),
+		fetching: HashMap::new(),
+		n_validators: 10,
+		block_number,
+	};
+
+	let mut state = State::default();
+	state.relay_parent_state.insert(hash_a, block_state(1));
+	state.relay_parent_state.insert(hash_b, block_state(2));
+
+	prune_finalized(&mut state, 1);
+	assert!(!state.relay_parent_state.contains_key(&hash_a));
+	assert!(state.relay_parent_state.contains_key(&hash_b));
+
+	prune_finalized(&mut state, 2);
+	assert!(state.relay_parent_state.is_empty());
+}
+
+#[test]
+fn finalized_leaf_is_not_pruned() {
+	let hash_a: Hash = [0; 32].into();
+
+	let mut state = State::default();
+	state.our_view = View(vec![hash_a]);
+	state.relay_parent_state.insert(hash_a, BlockBasedState {
+		known: HashMap::new(),
+		fetching: HashMap::new(),
+		n_validators: 10,
+		block_number: 1,
+	});
+
+	// `hash_a` is finalized while still being our leaf.
+	prune_finalized(&mut state, 1);
+	assert!(state.relay_parent_state.contains_key(&hash_a));
+}
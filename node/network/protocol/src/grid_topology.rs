@@ -0,0 +1,192 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! The 2D grid topology used for gossiping among validators.
+//!
+//! The validators of a session are shuffled deterministically and laid out row by row on a grid
+//! of width `ceil(sqrt(n))`. Each validator sends gossip to the validators in its row and its
+//! column first, so any message reaches every validator in two hops while each validator only
+//! talks to `O(sqrt(n))` others.
+
+use std::collections::HashSet;
+
+use parity_scale_codec::Encode;
+use polkadot_primitives::v1::{BlakeTwo256, HashT, SessionIndex, ValidatorIndex};
+use rand::seq::SliceRandom;
+
+use super::PeerId;
+
+/// The number of peers outside of our row and column a message is gossiped to, as a backup for
+/// the grid.
+pub const RANDOM_GOSSIP_PEERS: usize = 4;
+
+/// The grid layout of the validators of a session.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionGridTopology {
+	/// Validator indices in the order they are placed on the grid, row by row.
+	shuffled: Vec<ValidatorIndex>,
+	/// The position of each validator on the grid, indexed by validator index.
+	positions: Vec<usize>,
+	/// The number of columns of the grid.
+	width: usize,
+}
+
+impl SessionGridTopology {
+	/// Compute the grid of the `n_validators` validators of the given session.
+	///
+	/// Every node computes the same grid for the same session, without any communication.
+	pub fn new(session_index: SessionIndex, n_validators: usize) -> Self {
+		let mut shuffled: Vec<ValidatorIndex> = (0..n_validators as ValidatorIndex).collect();
+		shuffled.sort_by_cached_key(|index| BlakeTwo256::hash(&(session_index, *index).encode()));
+
+		let mut positions = vec![0; n_validators];
+		for (position, index) in shuffled.iter().enumerate() {
+			positions[*index as usize] = position;
+		}
+
+		let width = (n_validators as f64).sqrt().ceil() as usize;
+
+		SessionGridTopology {
+			shuffled,
+			positions,
+			width,
+		}
+	}
+
+	/// The validators in the same row or column as `validator`, excluding `validator` itself.
+	///
+	/// Returns an empty list for unknown validators.
+	pub fn neighbors(&self, validator: ValidatorIndex) -> Vec<ValidatorIndex> {
+		let position = match self.positions.get(validator as usize) {
+			Some(position) => *position,
+			None => return Vec::new(),
+		};
+
+		let row_start = position - position % self.width;
+		let row = row_start..std::cmp::min(row_start + self.width, self.shuffled.len());
+		let column = (position % self.width..self.shuffled.len()).step_by(self.width);
+
+		row.chain(column)
+			.filter(|p| *p != position)
+			.map(|p| self.shuffled[p])
+			.collect()
+	}
+}
+
+/// The gossip topology of the current session, as seen by the local validator.
+///
+/// Sent by the network bridge whenever the session changes or a neighbor connects.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GossipTopology {
+	/// The session the topology was computed for.
+	pub session_index: SessionIndex,
+	/// The connected peers of the validators in our row and column of the grid.
+	///
+	/// Gossip should be sent to these peers first.
+	pub neighbors: HashSet<PeerId>,
+}
+
+impl GossipTopology {
+	/// Choose the peers to gossip a message to among the given interested `peers`: all of our
+	/// neighbors and [`RANDOM_GOSSIP_PEERS`] randomly chosen others.
+	///
+	/// Before any neighbor is known, all `peers` are chosen.
+	pub fn targets(&self, peers: impl IntoIterator<Item = PeerId>) -> Vec<PeerId> {
+		if self.neighbors.is_empty() {
+			return peers.into_iter().collect();
+		}
+
+		let (mut targets, mut others): (Vec<_>, Vec<_>) = peers.into_iter()
+			.partition(|peer| self.neighbors.contains(peer));
+
+		others.shuffle(&mut rand::thread_rng());
+		others.truncate(RANDOM_GOSSIP_PEERS);
+		targets.extend(others);
+
+		targets
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn neighbors_are_rows_and_columns() {
+		let topology = SessionGridTopology::new(1, 10);
+		assert_eq!(topology.width, 4);
+
+		for validator in 0..10 {
+			let neighbors = topology.neighbors(validator);
+			let position = topology.positions[validator as usize];
+
+			assert!(!neighbors.contains(&validator));
+			assert_eq!(neighbors.len(), neighbors.iter().collect::<HashSet<_>>().len());
+
+			for neighbor in neighbors {
+				let other = topology.positions[neighbor as usize];
+				assert!(
+					other / topology.width == position / topology.width
+						|| other % topology.width == position % topology.width
+				);
+			}
+		}
+
+		// The last row is incomplete: position 9 is in row 2 with position 8 and in column 1
+		// with positions 1 and 5.
+		assert_eq!(topology.neighbors(topology.shuffled[9]).len(), 3);
+		assert!(topology.neighbors(10).is_empty());
+	}
+
+	#[test]
+	fn topology_is_deterministic_per_session() {
+		assert_eq!(SessionGridTopology::new(1, 100), SessionGridTopology::new(1, 100));
+		assert_ne!(SessionGridTopology::new(1, 100), SessionGridTopology::new(2, 100));
+	}
+
+	#[test]
+	fn every_validator_is_reachable_in_two_hops() {
+		let topology = SessionGridTopology::new(5, 50);
+
+		for validator in 0..50 {
+			let mut reached: HashSet<_> = topology.neighbors(validator).into_iter().collect();
+			for neighbor in reached.clone() {
+				reached.extend(topology.neighbors(neighbor));
+			}
+			let _ = reached.remove(&validator);
+
+			assert_eq!(reached.len(), 49);
+		}
+	}
+
+	#[test]
+	fn targets_are_neighbors_and_random_peers() {
+		let peers: Vec<_> = (0..20).map(|_| PeerId::random()).collect();
+
+		let topology = GossipTopology::default();
+		assert_eq!(topology.targets(peers.clone()), peers);
+
+		let topology = GossipTopology {
+			session_index: 1,
+			neighbors: peers[..3].iter().cloned().collect(),
+		};
+		let targets = topology.targets(peers.clone());
+
+		assert_eq!(targets.len(), 3 + RANDOM_GOSSIP_PEERS);
+		assert!(peers[..3].iter().all(|peer| targets.contains(peer)));
+		assert!(targets.iter().all(|peer| peers.contains(peer)));
+	}
+}
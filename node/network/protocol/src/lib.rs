@@ -26,11 +26,36 @@ use std::fmt;
 
 pub use sc_network::{ReputationChange, PeerId};
 
+pub mod grid_topology;
+pub mod peer_set;
+pub mod request_response;
+
+pub use grid_topology::GossipTopology;
+pub use peer_set::PeerSet;
+
 /// A unique identifier of a request.
 pub type RequestId = u64;
 
-/// A version of the protocol.
-pub type ProtocolVersion = u32;
+/// A version of the notification protocols of the peer-sets.
+///
+/// Each version of a peer-set is a notifications protocol of its own, and the highest version
+/// open with a peer is spoken with it. Whatever the version, subsystems work with the [`v1`](v1)
+/// messages, which the network bridge converts from and to the messages of that version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ProtocolVersion {
+	/// The first version of the protocols.
+	V1,
+	/// The second version of the protocols, which compresses PoVs.
+	V2,
+}
+
+impl ProtocolVersion {
+	/// All versions we support, oldest first.
+	pub const ALL: [ProtocolVersion; 2] = [ProtocolVersion::V1, ProtocolVersion::V2];
+
+	/// The version we prefer to speak with peers.
+	pub const CURRENT: ProtocolVersion = ProtocolVersion::V2;
+}
 
 /// An error indicating that this the over-arching message type had the wrong variant
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -45,15 +70,6 @@ impl fmt::Display for WrongVariant {
 impl std::error::Error for WrongVariant {}
 
 
-/// The peer-sets that the network manages. Different subsystems will use different peer-sets.
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub enum PeerSet {
-	/// The validation peer-set is responsible for all messages related to candidate validation and communication among validators.
-	Validation,
-	/// The collation peer-set is used for validator<>collator communication.
-	Collation,
-}
-
 /// The advertised role of a node.
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum ObservedRole {
@@ -105,6 +121,11 @@ pub enum NetworkBridgeEvent<M> {
 
 	/// Our `View` has changed.
 	OurViewChange(View),
+
+	/// The gossip topology of the current session has changed.
+	///
+	/// Only sent to subsystems on the validation peer-set.
+	NewGossipTopology(GossipTopology),
 }
 
 macro_rules! impl_try_from {
@@ -155,6 +176,8 @@ impl<M> NetworkBridgeEvent<M> {
 				=> NetworkBridgeEvent::PeerViewChange(peer.clone(), view.clone()),
 			NetworkBridgeEvent::OurViewChange(ref view)
 				=> NetworkBridgeEvent::OurViewChange(view.clone()),
+			NetworkBridgeEvent::NewGossipTopology(ref topology)
+				=> NetworkBridgeEvent::NewGossipTopology(topology.clone()),
 		})
 	}
 }
@@ -289,3 +312,299 @@ pub mod v1 {
 
 	impl_try_from!(CollationProtocol, CollatorProtocol, CollatorProtocolMessage);
 }
+
+/// v2 protocol types.
+///
+/// PoVs are compressed on the wire, all other messages are the same as in [`v1`](super::v1).
+pub mod v2 {
+	use polkadot_primitives::v1::{
+		Hash, CollatorId, Id as ParaId, CandidateReceipt, PoV,
+	};
+	use parity_scale_codec::{Encode, Decode};
+	use std::convert::TryFrom;
+	use std::fmt;
+	use std::io::Read;
+	use super::{RequestId, v1, request_response::MAX_POV_SIZE};
+
+	pub use super::v1::{
+		AvailabilityDistributionMessage, BitfieldDistributionMessage, StatementDistributionMessage,
+	};
+
+	/// The zstd compression level used for PoVs.
+	const COMPRESSION_LEVEL: i32 = 3;
+
+	/// An error compressing or decompressing a PoV.
+	#[derive(Debug, Clone, Copy, PartialEq)]
+	pub enum CompressedPoVError {
+		/// The PoV could not be compressed.
+		Compress,
+		/// The compressed PoV is not valid zstd.
+		Decompress,
+		/// The decompressed PoV would be larger than the maximum PoV size.
+		TooLarge,
+		/// The decompressed PoV could not be decoded.
+		Decode,
+	}
+
+	impl fmt::Display for CompressedPoVError {
+		fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+			match self {
+				CompressedPoVError::Compress => write!(formatter, "Failed to compress PoV"),
+				CompressedPoVError::Decompress => write!(formatter, "Failed to decompress PoV"),
+				CompressedPoVError::TooLarge => write!(formatter, "Decompressed PoV is too large"),
+				CompressedPoVError::Decode => write!(formatter, "Failed to decode decompressed PoV"),
+			}
+		}
+	}
+
+	impl std::error::Error for CompressedPoVError {}
+
+	/// A SCALE-encoded PoV, compressed with zstd.
+	#[derive(Debug, Clone, Encode, Decode, PartialEq)]
+	pub struct CompressedPoV(Vec<u8>);
+
+	impl CompressedPoV {
+		/// Compress the given PoV.
+		pub fn compress(pov: &PoV) -> Result<Self, CompressedPoVError> {
+			zstd::encode_all(pov.encode().as_slice(), COMPRESSION_LEVEL)
+				.map(CompressedPoV)
+				.map_err(|_| CompressedPoVError::Compress)
+		}
+
+		/// Decompress the PoV.
+		///
+		/// Decompression stops as soon as the PoV turns out to be larger than the maximum PoV
+		/// size, so that a small message can't make us allocate arbitrary amounts of memory.
+		pub fn decompress(&self) -> Result<PoV, CompressedPoVError> {
+			let mut decompressed = Vec::new();
+			zstd::stream::read::Decoder::new(self.0.as_slice())
+				.map_err(|_| CompressedPoVError::Decompress)?
+				.take(MAX_POV_SIZE + 1)
+				.read_to_end(&mut decompressed)
+				.map_err(|_| CompressedPoVError::Decompress)?;
+
+			if decompressed.len() as u64 > MAX_POV_SIZE {
+				return Err(CompressedPoVError::TooLarge);
+			}
+
+			PoV::decode(&mut decompressed.as_slice()).map_err(|_| CompressedPoVError::Decode)
+		}
+	}
+
+	/// Network messages used by the PoV distribution subsystem.
+	#[derive(Debug, Clone, Encode, Decode, PartialEq)]
+	pub enum PoVDistributionMessage {
+		/// Notification that we are awaiting the given PoVs (by hash) against a
+		/// specific relay-parent hash.
+		#[codec(index = "0")]
+		Awaiting(Hash, Vec<Hash>),
+		/// Notification of an awaited PoV, in a given relay-parent context.
+		/// (relay_parent, pov_hash, compressed_pov)
+		#[codec(index = "1")]
+		SendPoV(Hash, Hash, CompressedPoV),
+	}
+
+	impl TryFrom<v1::PoVDistributionMessage> for PoVDistributionMessage {
+		type Error = CompressedPoVError;
+
+		fn try_from(message: v1::PoVDistributionMessage) -> Result<Self, Self::Error> {
+			Ok(match message {
+				v1::PoVDistributionMessage::Awaiting(relay_parent, pov_hashes)
+					=> PoVDistributionMessage::Awaiting(relay_parent, pov_hashes),
+				v1::PoVDistributionMessage::SendPoV(relay_parent, pov_hash, pov)
+					=> PoVDistributionMessage::SendPoV(relay_parent, pov_hash, CompressedPoV::compress(&pov)?),
+			})
+		}
+	}
+
+	impl TryFrom<PoVDistributionMessage> for v1::PoVDistributionMessage {
+		type Error = CompressedPoVError;
+
+		fn try_from(message: PoVDistributionMessage) -> Result<Self, Self::Error> {
+			Ok(match message {
+				PoVDistributionMessage::Awaiting(relay_parent, pov_hashes)
+					=> v1::PoVDistributionMessage::Awaiting(relay_parent, pov_hashes),
+				PoVDistributionMessage::SendPoV(relay_parent, pov_hash, pov)
+					=> v1::PoVDistributionMessage::SendPoV(relay_parent, pov_hash, pov.decompress()?),
+			})
+		}
+	}
+
+	/// Network messages used by the collator protocol subsystem
+	#[derive(Debug, Clone, Encode, Decode, PartialEq)]
+	pub enum CollatorProtocolMessage {
+		/// Declare the intent to advertise collations under a collator ID.
+		#[codec(index = "0")]
+		Declare(CollatorId),
+		/// Advertise a collation to a validator. Can only be sent once the peer has declared
+		/// that they are a collator with given ID.
+		#[codec(index = "1")]
+		AdvertiseCollation(Hash, ParaId),
+		/// Request the advertised collation at that relay-parent.
+		#[codec(index = "2")]
+		RequestCollation(RequestId, Hash, ParaId),
+		/// A requested collation.
+		#[codec(index = "3")]
+		Collation(RequestId, CandidateReceipt, CompressedPoV),
+	}
+
+	impl TryFrom<v1::CollatorProtocolMessage> for CollatorProtocolMessage {
+		type Error = CompressedPoVError;
+
+		fn try_from(message: v1::CollatorProtocolMessage) -> Result<Self, Self::Error> {
+			Ok(match message {
+				v1::CollatorProtocolMessage::Declare(id)
+					=> CollatorProtocolMessage::Declare(id),
+				v1::CollatorProtocolMessage::AdvertiseCollation(relay_parent, para_id)
+					=> CollatorProtocolMessage::AdvertiseCollation(relay_parent, para_id),
+				v1::CollatorProtocolMessage::RequestCollation(request_id, relay_parent, para_id)
+					=> CollatorProtocolMessage::RequestCollation(request_id, relay_parent, para_id),
+				v1::CollatorProtocolMessage::Collation(request_id, receipt, pov)
+					=> CollatorProtocolMessage::Collation(request_id, receipt, CompressedPoV::compress(&pov)?),
+			})
+		}
+	}
+
+	impl TryFrom<CollatorProtocolMessage> for v1::CollatorProtocolMessage {
+		type Error = CompressedPoVError;
+
+		fn try_from(message: CollatorProtocolMessage) -> Result<Self, Self::Error> {
+			Ok(match message {
+				CollatorProtocolMessage::Declare(id)
+					=> v1::CollatorProtocolMessage::Declare(id),
+				CollatorProtocolMessage::AdvertiseCollation(relay_parent, para_id)
+					=> v1::CollatorProtocolMessage::AdvertiseCollation(relay_parent, para_id),
+				CollatorProtocolMessage::RequestCollation(request_id, relay_parent, para_id)
+					=> v1::CollatorProtocolMessage::RequestCollation(request_id, relay_parent, para_id),
+				CollatorProtocolMessage::Collation(request_id, receipt, pov)
+					=> v1::CollatorProtocolMessage::Collation(request_id, receipt, pov.decompress()?),
+			})
+		}
+	}
+
+	/// All network messages on the validation peer-set.
+	#[derive(Debug, Clone, Encode, Decode, PartialEq)]
+	pub enum ValidationProtocol {
+		/// Availability distribution messages
+		#[codec(index = "0")]
+		AvailabilityDistribution(AvailabilityDistributionMessage),
+		/// Bitfield distribution messages
+		#[codec(index = "1")]
+		BitfieldDistribution(BitfieldDistributionMessage),
+		/// PoV Distribution messages
+		#[codec(index = "2")]
+		PoVDistribution(PoVDistributionMessage),
+		/// Statement distribution messages
+		#[codec(index = "3")]
+		StatementDistribution(StatementDistributionMessage),
+	}
+
+	impl_try_from!(ValidationProtocol, AvailabilityDistribution, AvailabilityDistributionMessage);
+	impl_try_from!(ValidationProtocol, BitfieldDistribution, BitfieldDistributionMessage);
+	impl_try_from!(ValidationProtocol, PoVDistribution, PoVDistributionMessage);
+	impl_try_from!(ValidationProtocol, StatementDistribution, StatementDistributionMessage);
+
+	impl TryFrom<v1::ValidationProtocol> for ValidationProtocol {
+		type Error = CompressedPoVError;
+
+		fn try_from(message: v1::ValidationProtocol) -> Result<Self, Self::Error> {
+			Ok(match message {
+				v1::ValidationProtocol::AvailabilityDistribution(m)
+					=> ValidationProtocol::AvailabilityDistribution(m),
+				v1::ValidationProtocol::BitfieldDistribution(m)
+					=> ValidationProtocol::BitfieldDistribution(m),
+				v1::ValidationProtocol::PoVDistribution(m)
+					=> ValidationProtocol::PoVDistribution(TryFrom::try_from(m)?),
+				v1::ValidationProtocol::StatementDistribution(m)
+					=> ValidationProtocol::StatementDistribution(m),
+			})
+		}
+	}
+
+	impl TryFrom<ValidationProtocol> for v1::ValidationProtocol {
+		type Error = CompressedPoVError;
+
+		fn try_from(message: ValidationProtocol) -> Result<Self, Self::Error> {
+			Ok(match message {
+				ValidationProtocol::AvailabilityDistribution(m)
+					=> v1::ValidationProtocol::AvailabilityDistribution(m),
+				ValidationProtocol::BitfieldDistribution(m)
+					=> v1::ValidationProtocol::BitfieldDistribution(m),
+				ValidationProtocol::PoVDistribution(m)
+					=> v1::ValidationProtocol::PoVDistribution(TryFrom::try_from(m)?),
+				ValidationProtocol::StatementDistribution(m)
+					=> v1::ValidationProtocol::StatementDistribution(m),
+			})
+		}
+	}
+
+	/// All network messages on the collation peer-set.
+	#[derive(Debug, Clone, Encode, Decode, PartialEq)]
+	pub enum CollationProtocol {
+		/// Collator protocol messages
+		#[codec(index = "0")]
+		CollatorProtocol(CollatorProtocolMessage),
+	}
+
+	impl_try_from!(CollationProtocol, CollatorProtocol, CollatorProtocolMessage);
+
+	impl TryFrom<v1::CollationProtocol> for CollationProtocol {
+		type Error = CompressedPoVError;
+
+		fn try_from(message: v1::CollationProtocol) -> Result<Self, Self::Error> {
+			match message {
+				v1::CollationProtocol::CollatorProtocol(m)
+					=> Ok(CollationProtocol::CollatorProtocol(TryFrom::try_from(m)?)),
+			}
+		}
+	}
+
+	impl TryFrom<CollationProtocol> for v1::CollationProtocol {
+		type Error = CompressedPoVError;
+
+		fn try_from(message: CollationProtocol) -> Result<Self, Self::Error> {
+			match message {
+				CollationProtocol::CollatorProtocol(m)
+					=> Ok(v1::CollationProtocol::CollatorProtocol(TryFrom::try_from(m)?)),
+			}
+		}
+	}
+
+	#[cfg(test)]
+	mod tests {
+		use super::*;
+		use polkadot_primitives::v1::BlockData;
+
+		#[test]
+		fn compressed_pov_round_trips() {
+			let pov = PoV { block_data: BlockData(vec![42; 1024]) };
+
+			let compressed = CompressedPoV::compress(&pov).unwrap();
+			assert!(compressed.0.len() < pov.encoded_size());
+			assert_eq!(compressed.decompress(), Ok(pov));
+		}
+
+		#[test]
+		fn oversized_pov_is_not_decompressed() {
+			let pov = PoV { block_data: BlockData(vec![0; MAX_POV_SIZE as usize + 1]) };
+
+			let compressed = CompressedPoV::compress(&pov).unwrap();
+			assert_eq!(compressed.decompress(), Err(CompressedPoVError::TooLarge));
+		}
+
+		#[test]
+		fn messages_convert_between_versions() {
+			let message = v1::ValidationProtocol::PoVDistribution(
+				v1::PoVDistributionMessage::SendPoV(
+					Hash::repeat_byte(1),
+					Hash::repeat_byte(2),
+					PoV { block_data: BlockData(vec![1, 2, 3]) },
+				),
+			);
+
+			let v2_message = ValidationProtocol::try_from(message.clone()).unwrap();
+			assert_ne!(v2_message.encode(), message.encode());
+			assert_eq!(v1::ValidationProtocol::try_from(v2_message), Ok(message));
+		}
+	}
+}
@@ -0,0 +1,181 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! The peer-sets of the notification protocols and their slots.
+//!
+//! Each peer-set has its own incoming and outgoing connection slots and reserved peers, so that
+//! connections on one peer-set can't crowd out the connections on the other. The network only
+//! knows about a single pool of connections, so the slots are enforced by the network bridge, and
+//! reserved peers are kept connected through a priority group per peer-set.
+
+use sc_network::config::MultiaddrWithPeerId;
+
+use super::ProtocolVersion;
+
+/// The peer-sets that the network manages. Different subsystems will use different peer-sets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PeerSet {
+	/// The validation peer-set is responsible for all messages related to candidate validation and communication among validators.
+	Validation,
+	/// The collation peer-set is used for validator<>collator communication.
+	Collation,
+}
+
+impl PeerSet {
+	/// All peer-sets.
+	pub const ALL: [PeerSet; 2] = [PeerSet::Validation, PeerSet::Collation];
+
+	/// The name of the notifications protocol of this peer-set at the given version.
+	pub const fn get_protocol_name_static(self, version: ProtocolVersion) -> &'static str {
+		match (self, version) {
+			(PeerSet::Validation, ProtocolVersion::V1) => "/polkadot/validation/1",
+			(PeerSet::Validation, ProtocolVersion::V2) => "/polkadot/validation/2",
+			(PeerSet::Collation, ProtocolVersion::V1) => "/polkadot/collation/1",
+			(PeerSet::Collation, ProtocolVersion::V2) => "/polkadot/collation/2",
+		}
+	}
+
+	/// The peer-set and version of a notifications protocol, if it is one of ours.
+	pub fn from_protocol_name(name: &str) -> Option<(PeerSet, ProtocolVersion)> {
+		Self::ALL.iter()
+			.flat_map(|peer_set| ProtocolVersion::ALL.iter().map(move |version| (*peer_set, *version)))
+			.find(|(peer_set, version)| peer_set.get_protocol_name_static(*version) == name)
+	}
+
+	/// The name of this peer-set, for logs and metrics.
+	pub const fn name(self) -> &'static str {
+		match self {
+			PeerSet::Validation => "validation",
+			PeerSet::Collation => "collation",
+		}
+	}
+
+	/// The priority group that validators we are asked to connect to on this peer-set are
+	/// added to.
+	pub const fn get_validators_priority_group(self) -> &'static str {
+		match self {
+			// Kept the name of the group validators were added to before there were multiple
+			// peer-sets.
+			PeerSet::Validation => "parachain_validators",
+			PeerSet::Collation => "parachain_collation_validators",
+		}
+	}
+
+	/// The priority group that the configured reserved nodes of this peer-set are added to.
+	pub const fn get_reserved_priority_group(self) -> &'static str {
+		match self {
+			PeerSet::Validation => "parachain_validation_reserved",
+			PeerSet::Collation => "parachain_collation_reserved",
+		}
+	}
+}
+
+/// The connection slots and reserved peers of a peer-set.
+///
+/// Validators we are asked to connect to are treated as reserved peers at runtime. Reserved
+/// peers don't take up any slots.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PeerSetConfig {
+	/// The maximum number of incoming connections from peers that are not reserved.
+	pub in_peers: u32,
+	/// The maximum number of outgoing connections to peers that are not reserved.
+	pub out_peers: u32,
+	/// Peers to always stay connected to.
+	pub reserved_nodes: Vec<MultiaddrWithPeerId>,
+}
+
+/// The configuration of all peer-sets.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PeerSetsConfig {
+	/// The configuration of the validation peer-set.
+	pub validation: PeerSetConfig,
+	/// The configuration of the collation peer-set.
+	pub collation: PeerSetConfig,
+}
+
+impl PeerSetsConfig {
+	/// The configuration of the given peer-set.
+	pub fn get(&self, peer_set: PeerSet) -> &PeerSetConfig {
+		match peer_set {
+			PeerSet::Validation => &self.validation,
+			PeerSet::Collation => &self.collation,
+		}
+	}
+
+	/// The highest number of incoming connections any peer-set allows.
+	pub fn max_in_peers(&self) -> u32 {
+		PeerSet::ALL.iter().map(|peer_set| self.get(*peer_set).in_peers).max().unwrap_or(0)
+	}
+
+	/// The highest number of outgoing connections any peer-set allows.
+	pub fn max_out_peers(&self) -> u32 {
+		PeerSet::ALL.iter().map(|peer_set| self.get(*peer_set).out_peers).max().unwrap_or(0)
+	}
+}
+
+impl Default for PeerSetsConfig {
+	fn default() -> Self {
+		PeerSetsConfig {
+			validation: PeerSetConfig {
+				in_peers: 25,
+				out_peers: 25,
+				reserved_nodes: Vec::new(),
+			},
+			// Collators connect to the validators of their para as reserved peers, so validators
+			// don't need to connect out to anyone.
+			collation: PeerSetConfig {
+				in_peers: 25,
+				out_peers: 0,
+				reserved_nodes: Vec::new(),
+			},
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn protocol_names_round_trip() {
+		for peer_set in PeerSet::ALL.iter().copied() {
+			for version in ProtocolVersion::ALL.iter().copied() {
+				assert_eq!(
+					PeerSet::from_protocol_name(peer_set.get_protocol_name_static(version)),
+					Some((peer_set, version)),
+				);
+			}
+		}
+
+		assert_eq!(PeerSet::from_protocol_name("/polkadot/unknown/1"), None);
+	}
+
+	#[test]
+	fn peer_sets_have_independent_priority_groups() {
+		assert_ne!(
+			PeerSet::Validation.get_validators_priority_group(),
+			PeerSet::Collation.get_validators_priority_group(),
+		);
+		assert_ne!(
+			PeerSet::Validation.get_reserved_priority_group(),
+			PeerSet::Collation.get_reserved_priority_group(),
+		);
+
+		for peer_set in PeerSet::ALL.iter() {
+			assert_ne!(peer_set.get_validators_priority_group(), peer_set.get_reserved_priority_group());
+		}
+	}
+}
@@ -0,0 +1,432 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Request/response protocols used in Polkadot.
+//!
+//! Each protocol in [`Protocol`](Protocol) is a libp2p request-response protocol with its own
+//! name, size limits and timeout, registered with the network via
+//! [`Protocol::get_config`](Protocol::get_config). Subsystems issue requests by sending
+//! [`Requests`](Requests) to the network bridge and receive the decoded response on a `oneshot`.
+//! Incoming requests are decoded by the network bridge and routed to the subsystem owning the
+//! protocol as an [`IncomingRequest`](IncomingRequest), which carries the channel for the answer.
+
+use std::fmt;
+use std::time::Duration;
+
+use futures::channel::{mpsc, oneshot};
+use futures::prelude::*;
+use parity_scale_codec::{Decode, Encode, Error as DecodingError};
+
+use sc_network::config::{IncomingRequest as NetworkIncomingRequest, RequestResponseConfig};
+use sc_network::RequestFailure;
+
+use super::PeerId;
+
+/// Maximum size of a PoV we are willing to transfer.
+///
+/// Matches the `max_pov_size` of the host configuration in our chain specs.
+pub(crate) const MAX_POV_SIZE: u64 = 50 * 1024 * 1024;
+
+/// Responses carrying a PoV also carry some metadata, e.g. a candidate receipt.
+const POV_RESPONSE_SIZE: u64 = MAX_POV_SIZE + 10_000;
+
+/// Requests are small, a few hashes and indices at most.
+const MAX_REQUEST_SIZE: u64 = 1_000;
+
+/// Incoming requests which can't be handed to the network bridge because it is lagging behind are
+/// dropped by the network.
+const INBOUND_QUEUE_SIZE: usize = 100;
+
+/// All request/response protocols used by Polkadot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Protocol {
+	/// Protocol for fetching erasure chunks from validators.
+	ChunkFetching,
+	/// Protocol for fetching collations from collators.
+	CollationFetching,
+	/// Protocol for fetching PoVs from validators.
+	PoVFetching,
+}
+
+impl Protocol {
+	/// All protocols, in the order their configurations should be registered with the network.
+	pub const ALL: [Protocol; 3] = [
+		Protocol::ChunkFetching,
+		Protocol::CollationFetching,
+		Protocol::PoVFetching,
+	];
+
+	/// Get the configuration of this protocol, along with the receiver of incoming requests.
+	///
+	/// The configuration has to be registered with the network on startup, the receiver should be
+	/// handed to the network bridge.
+	pub fn get_config(self) -> (mpsc::Receiver<NetworkIncomingRequest>, RequestResponseConfig) {
+		let (tx, rx) = mpsc::channel(INBOUND_QUEUE_SIZE);
+		let (max_response_size, request_timeout) = match self {
+			// Chunks are a fraction of a PoV, but should arrive quickly as we fetch them from many
+			// validators at once.
+			Protocol::ChunkFetching => (POV_RESPONSE_SIZE, Duration::from_secs(1)),
+			// Collators are allowed some slack, as they might need to fetch the PoV themselves.
+			Protocol::CollationFetching => (POV_RESPONSE_SIZE, Duration::from_secs(2)),
+			Protocol::PoVFetching => (POV_RESPONSE_SIZE, Duration::from_secs(2)),
+		};
+
+		let config = RequestResponseConfig {
+			name: self.get_protocol_name_static().into(),
+			max_request_size: MAX_REQUEST_SIZE,
+			max_response_size,
+			request_timeout,
+			inbound_queue: Some(tx),
+		};
+
+		(rx, config)
+	}
+
+	/// Get the protocol name of this protocol.
+	pub const fn get_protocol_name_static(self) -> &'static str {
+		match self {
+			Protocol::ChunkFetching => "/polkadot/req_chunk/1",
+			Protocol::CollationFetching => "/polkadot/req_collation/1",
+			Protocol::PoVFetching => "/polkadot/req_pov/1",
+		}
+	}
+
+	/// Find the protocol with the given name.
+	pub fn from_protocol_name(name: &str) -> Option<Protocol> {
+		Protocol::ALL.iter().copied().find(|p| p.get_protocol_name_static() == name)
+	}
+}
+
+/// Common properties of any request type.
+pub trait IsRequest {
+	/// The type of the response to this request.
+	type Response;
+
+	/// The protocol this request is sent over.
+	const PROTOCOL: Protocol;
+}
+
+/// All requests that can be sent to the network bridge.
+#[derive(Debug)]
+pub enum Requests {
+	/// Request an erasure chunk from a validator.
+	ChunkFetching(OutgoingRequest<v1::ChunkFetchingRequest>),
+	/// Request a collation from a collator.
+	CollationFetching(OutgoingRequest<v1::CollationFetchingRequest>),
+	/// Request a PoV from a validator.
+	PoVFetching(OutgoingRequest<v1::PoVFetchingRequest>),
+}
+
+impl Requests {
+	/// Get the protocol this request is sent over.
+	pub fn get_protocol(&self) -> Protocol {
+		match self {
+			Requests::ChunkFetching(_) => Protocol::ChunkFetching,
+			Requests::CollationFetching(_) => Protocol::CollationFetching,
+			Requests::PoVFetching(_) => Protocol::PoVFetching,
+		}
+	}
+
+	/// Encode the request for sending it over the network.
+	///
+	/// The response channel is passed along untouched, the decoding of the response happens in the
+	/// future returned by [`OutgoingRequest::new`](OutgoingRequest::new).
+	pub fn encode_request(self) -> (Protocol, OutgoingRequest<Vec<u8>>) {
+		match self {
+			Requests::ChunkFetching(r) => r.encode_request(),
+			Requests::CollationFetching(r) => r.encode_request(),
+			Requests::PoVFetching(r) => r.encode_request(),
+		}
+	}
+}
+
+/// The result of an outgoing request, as seen by the network.
+pub type OutgoingResult = Result<Vec<u8>, RequestFailure>;
+
+/// A request to be sent to a peer.
+#[derive(Debug)]
+pub struct OutgoingRequest<Req> {
+	/// The peer to send the request to.
+	pub peer: PeerId,
+	/// The actual request.
+	pub payload: Req,
+	/// Where the raw response, or the reason why there is none, should be sent to.
+	pub pending_response: oneshot::Sender<OutgoingResult>,
+}
+
+/// Reasons why an outgoing request did not yield a response.
+#[derive(Debug)]
+pub enum RequestError {
+	/// The response could not be decoded.
+	InvalidResponse(DecodingError),
+	/// The request failed on the network level, e.g. it timed out or the response was too large.
+	NetworkError(RequestFailure),
+	/// The network bridge dropped the request without answering.
+	Canceled(oneshot::Canceled),
+}
+
+impl fmt::Display for RequestError {
+	fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			RequestError::InvalidResponse(e) => write!(formatter, "Response could not be decoded: {}", e),
+			RequestError::NetworkError(e) => write!(formatter, "Request failed: {}", e),
+			RequestError::Canceled(_) => write!(formatter, "Request was canceled"),
+		}
+	}
+}
+
+impl std::error::Error for RequestError {}
+
+impl<Req> OutgoingRequest<Req>
+where
+	Req: IsRequest + Encode,
+	Req::Response: Decode,
+{
+	/// Create a new request to be sent to `peer`.
+	///
+	/// Returns the request, to be wrapped in [`Requests`](Requests) and sent to the network
+	/// bridge, and a future resolving to the decoded response.
+	pub fn new(
+		peer: PeerId,
+		payload: Req,
+	) -> (Self, impl Future<Output = Result<Req::Response, RequestError>>) {
+		let (tx, rx) = oneshot::channel();
+		let request = Self {
+			peer,
+			payload,
+			pending_response: tx,
+		};
+
+		(request, receive_response::<Req>(rx))
+	}
+
+	fn encode_request(self) -> (Protocol, OutgoingRequest<Vec<u8>>) {
+		let OutgoingRequest { peer, payload, pending_response } = self;
+		let encoded = OutgoingRequest {
+			peer,
+			payload: payload.encode(),
+			pending_response,
+		};
+
+		(Req::PROTOCOL, encoded)
+	}
+}
+
+async fn receive_response<Req>(
+	rx: oneshot::Receiver<OutgoingResult>,
+) -> Result<Req::Response, RequestError>
+where
+	Req: IsRequest,
+	Req::Response: Decode,
+{
+	let raw = rx.await
+		.map_err(RequestError::Canceled)?
+		.map_err(RequestError::NetworkError)?;
+
+	Req::Response::decode(&mut raw.as_ref()).map_err(RequestError::InvalidResponse)
+}
+
+/// A request received from a peer, decoded and routed to the subsystem responsible for its
+/// protocol.
+#[derive(Debug)]
+pub struct IncomingRequest<Req> {
+	/// The peer which sent the request.
+	pub peer: PeerId,
+	/// The decoded request.
+	pub payload: Req,
+	pending_response: oneshot::Sender<Vec<u8>>,
+}
+
+impl<Req> IncomingRequest<Req>
+where
+	Req: IsRequest + Decode,
+	Req::Response: Encode,
+{
+	/// Create a new incoming request from a raw request of the network.
+	///
+	/// Fails if the payload can't be decoded. The caller should punish the peer in that case.
+	pub fn from_network(request: NetworkIncomingRequest) -> Result<Self, (PeerId, DecodingError)> {
+		let NetworkIncomingRequest { peer, payload, pending_response } = request;
+		match Req::decode(&mut payload.as_ref()) {
+			Ok(payload) => Ok(Self::new(peer, payload, pending_response)),
+			Err(e) => Err((peer, e)),
+		}
+	}
+
+	/// Create a new incoming request from an already decoded payload.
+	pub fn new(peer: PeerId, payload: Req, pending_response: oneshot::Sender<Vec<u8>>) -> Self {
+		Self {
+			peer,
+			payload,
+			pending_response,
+		}
+	}
+
+	/// Send the response back to the requesting peer.
+	///
+	/// Dropping the request without calling this function will make the request fail on the
+	/// requesting side.
+	///
+	/// Fails if the network is no longer waiting for the response, e.g. because the request timed
+	/// out.
+	pub fn send_response(self, response: Req::Response) -> Result<(), ()> {
+		self.pending_response.send(response.encode()).map_err(|_| ())
+	}
+}
+
+/// v1 request/response types.
+pub mod v1 {
+	use parity_scale_codec::{Decode, Encode};
+
+	use polkadot_primitives::v1::{
+		CandidateHash, CandidateReceipt, ErasureChunk, Hash, Id as ParaId, PoV, ValidatorIndex,
+	};
+
+	use super::{IsRequest, Protocol};
+
+	/// Request an availability chunk.
+	#[derive(Debug, Clone, Copy, PartialEq, Encode, Decode)]
+	pub struct ChunkFetchingRequest {
+		/// Hash of the candidate the chunk belongs to.
+		pub candidate_hash: CandidateHash,
+		/// The index of the chunk to fetch.
+		pub index: ValidatorIndex,
+	}
+
+	/// Response to a `ChunkFetchingRequest`.
+	#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+	pub enum ChunkFetchingResponse {
+		/// The requested chunk.
+		#[codec(index = "0")]
+		Chunk(ErasureChunk),
+		/// The node does not have the requested chunk.
+		#[codec(index = "1")]
+		NoSuchChunk,
+	}
+
+	impl IsRequest for ChunkFetchingRequest {
+		type Response = ChunkFetchingResponse;
+		const PROTOCOL: Protocol = Protocol::ChunkFetching;
+	}
+
+	/// Request the advertised collation at a relay-parent.
+	#[derive(Debug, Clone, Copy, PartialEq, Encode, Decode)]
+	pub struct CollationFetchingRequest {
+		/// Relay parent the collation was built on.
+		pub relay_parent: Hash,
+		/// The para id of the collation.
+		pub para_id: ParaId,
+	}
+
+	/// Response to a `CollationFetchingRequest`.
+	#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+	pub enum CollationFetchingResponse {
+		/// The requested collation.
+		#[codec(index = "0")]
+		Collation(CandidateReceipt, PoV),
+	}
+
+	impl IsRequest for CollationFetchingRequest {
+		type Response = CollationFetchingResponse;
+		const PROTOCOL: Protocol = Protocol::CollationFetching;
+	}
+
+	/// Request a PoV in a given relay-parent context.
+	#[derive(Debug, Clone, Copy, PartialEq, Encode, Decode)]
+	pub struct PoVFetchingRequest {
+		/// The relay-parent the candidate of the PoV was seconded under.
+		pub relay_parent: Hash,
+		/// The hash of the requested PoV.
+		pub pov_hash: Hash,
+	}
+
+	/// Response to a `PoVFetchingRequest`.
+	#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+	pub enum PoVFetchingResponse {
+		/// The requested PoV.
+		#[codec(index = "0")]
+		PoV(PoV),
+		/// The node does not know the requested PoV.
+		#[codec(index = "1")]
+		NoSuchPoV,
+	}
+
+	impl IsRequest for PoVFetchingRequest {
+		type Response = PoVFetchingResponse;
+		const PROTOCOL: Protocol = Protocol::PoVFetching;
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use futures::executor;
+	use polkadot_primitives::v1::{CandidateHash, Hash};
+
+	#[test]
+	fn protocol_names_are_unique_and_resolvable() {
+		for protocol in Protocol::ALL.iter() {
+			let name = protocol.get_protocol_name_static();
+			assert_eq!(Protocol::from_protocol_name(name), Some(*protocol));
+		}
+
+		assert_eq!(Protocol::from_protocol_name("/polkadot/validation/1"), None);
+	}
+
+	#[test]
+	fn responses_are_decoded_for_the_requester() {
+		let request = v1::ChunkFetchingRequest {
+			candidate_hash: CandidateHash(Hash::repeat_byte(1)),
+			index: 3,
+		};
+		let (outgoing, response) = OutgoingRequest::new(PeerId::random(), request);
+		let (protocol, encoded) = Requests::ChunkFetching(outgoing).encode_request();
+
+		assert_eq!(protocol, Protocol::ChunkFetching);
+		assert_eq!(encoded.payload, request.encode());
+
+		// Answer like the responding side would.
+		let (tx, rx) = oneshot::channel();
+		let incoming = IncomingRequest::<v1::ChunkFetchingRequest>::from_network(NetworkIncomingRequest {
+			peer: encoded.peer.clone(),
+			payload: encoded.payload,
+			pending_response: tx,
+		}).unwrap();
+		assert_eq!(incoming.payload, request);
+		incoming.send_response(v1::ChunkFetchingResponse::NoSuchChunk).unwrap();
+
+		let raw = executor::block_on(rx).unwrap();
+		encoded.pending_response.send(Ok(raw)).unwrap();
+
+		assert_eq!(
+			executor::block_on(response).unwrap(),
+			v1::ChunkFetchingResponse::NoSuchChunk,
+		);
+	}
+
+	#[test]
+	fn undecodable_requests_are_rejected() {
+		let (tx, _rx) = oneshot::channel();
+		let peer = PeerId::random();
+		let result = IncomingRequest::<v1::PoVFetchingRequest>::from_network(NetworkIncomingRequest {
+			peer: peer.clone(),
+			payload: vec![1, 2, 3],
+			pending_response: tx,
+		});
+
+		assert!(matches!(result, Err((ref p, _)) if *p == peer));
+	}
+}
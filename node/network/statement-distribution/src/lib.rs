@@ -35,10 +35,10 @@ use polkadot_node_subsystem_util::{
 };
 use node_primitives::SignedFullStatement;
 use polkadot_primitives::v1::{
-	Hash, CompactStatement, ValidatorIndex, ValidatorId, SigningContext, ValidatorSignature, CandidateHash,
+	Hash, BlockNumber, CompactStatement, ValidatorIndex, ValidatorId, SigningContext, ValidatorSignature, CandidateHash,
 };
 use polkadot_node_network_protocol::{
-	v1 as protocol_v1, View, PeerId, ReputationChange as Rep, NetworkBridgeEvent,
+	v1 as protocol_v1, View, PeerId, ReputationChange as Rep, NetworkBridgeEvent, GossipTopology,
 };
 
 use futures::prelude::*;
@@ -86,6 +86,19 @@ impl<C> Subsystem<C> for StatementDistribution
 	}
 }
 
+/// Drop the statements of heads at or below the height of a finalized block, unless they are
+/// still in our view.
+///
+/// With instant finality the finalized block is often still our leaf, and we keep working on it.
+/// Any other head at or below the finalized height is finalized or on a dead fork.
+fn prune_finalized_heads(
+	active_heads: &mut HashMap<Hash, ActiveHeadData>,
+	our_view: &View,
+	finalized_number: BlockNumber,
+) {
+	active_heads.retain(|hash, head| our_view.contains(hash) || head.block_number > finalized_number);
+}
+
 impl StatementDistribution {
 	/// Create a new Statement Distribution Subsystem
 	pub fn new(metrics: Metrics) -> StatementDistribution {
@@ -153,6 +166,25 @@ struct PeerRelayParentKnowledge {
 }
 
 impl PeerRelayParentKnowledge {
+	/// Whether the peer can accept a statement with this fingerprint from us, without altering
+	/// internal state.
+	fn can_send(&self, fingerprint: &(CompactStatement, ValidatorIndex)) -> bool {
+		let already_known = self.sent_statements.contains(fingerprint)
+			|| self.received_statements.contains(fingerprint);
+
+		if already_known {
+			return false;
+		}
+
+		match fingerprint.0 {
+			CompactStatement::Candidate(_) => true,
+			// The peer can only accept Valid and Invalid statements for which it is aware
+			// of the corresponding candidate.
+			CompactStatement::Valid(ref h) | CompactStatement::Invalid(ref h)
+				=> self.known_candidates.contains(h),
+		}
+	}
+
 	/// Attempt to update our view of the peer's knowledge with this statement's fingerprint based
 	/// on something that we would like to send to the peer.
 	///
@@ -166,10 +198,7 @@ impl PeerRelayParentKnowledge {
 	/// candidate with the given hash.
 	#[tracing::instrument(level = "trace", skip(self), fields(subsystem = LOG_TARGET))]
 	fn send(&mut self, fingerprint: &(CompactStatement, ValidatorIndex)) -> Option<bool> {
-		let already_known = self.sent_statements.contains(fingerprint)
-			|| self.received_statements.contains(fingerprint);
-
-		if already_known {
+		if !self.can_send(fingerprint) {
 			return None;
 		}
 
@@ -181,15 +210,7 @@ impl PeerRelayParentKnowledge {
 
 				self.known_candidates.insert(h.clone())
 			},
-			CompactStatement::Valid(ref h) | CompactStatement::Invalid(ref h) => {
-				// The peer can only accept Valid and Invalid statements for which it is aware
-				// of the corresponding candidate.
-				if !self.known_candidates.contains(h) {
-					return None;
-				}
-
-				false
-			}
+			CompactStatement::Valid(_) | CompactStatement::Invalid(_) => false,
 		};
 
 		self.sent_statements.insert(fingerprint.clone());
@@ -269,6 +290,16 @@ struct PeerData {
 }
 
 impl PeerData {
+	/// Whether the peer can accept a statement with this fingerprint from us, without altering
+	/// internal state.
+	fn can_send(
+		&self,
+		relay_parent: &Hash,
+		fingerprint: &(CompactStatement, ValidatorIndex),
+	) -> bool {
+		self.view_knowledge.get(relay_parent).map_or(false, |k| k.can_send(fingerprint))
+	}
+
 	/// Attempt to update our view of the peer's knowledge with this statement's fingerprint based
 	/// on something that we would like to send to the peer.
 	///
@@ -388,17 +419,24 @@ struct ActiveHeadData {
 	validators: Vec<ValidatorId>,
 	/// The session index this head is at.
 	session_index: sp_staking::SessionIndex,
+	/// The number of this head.
+	block_number: BlockNumber,
 	/// How many `Seconded` statements we've seen per validator.
 	seconded_counts: HashMap<ValidatorIndex, usize>,
 }
 
 impl ActiveHeadData {
-	fn new(validators: Vec<ValidatorId>, session_index: sp_staking::SessionIndex) -> Self {
+	fn new(
+		validators: Vec<ValidatorId>,
+		session_index: sp_staking::SessionIndex,
+		block_number: BlockNumber,
+	) -> Self {
 		ActiveHeadData {
 			candidates: Default::default(),
 			statements: Default::default(),
 			validators,
 			session_index,
+			block_number,
 			seconded_counts: Default::default(),
 		}
 	}
@@ -519,9 +557,10 @@ async fn inform_statement_listeners(
 /// circulates the statement to all peers who have not seen it yet, and
 /// sends all statements dependent on that statement to peers who could previously not receive
 /// them but now can.
-#[tracing::instrument(level = "trace", skip(peers, ctx, active_heads, metrics), fields(subsystem = LOG_TARGET))]
+#[tracing::instrument(level = "trace", skip(peers, gossip_topology, ctx, active_heads, metrics), fields(subsystem = LOG_TARGET))]
 async fn circulate_statement_and_dependents(
 	peers: &mut HashMap<PeerId, PeerData>,
+	gossip_topology: &GossipTopology,
 	active_heads: &mut HashMap<Hash, ActiveHeadData>,
 	ctx: &mut impl SubsystemContext<Message = StatementDistributionMessage>,
 	relay_parent: Hash,
@@ -533,13 +572,13 @@ async fn circulate_statement_and_dependents(
 		None => return,
 	};
 
-	// First circulate the statement directly to all peers needing it.
+	// First circulate the statement directly to the peers needing it chosen by the gossip topology.
 	// The borrow of `active_head` needs to encompass only this (Rust) statement.
 	let outputs: Option<(CandidateHash, Vec<PeerId>)> = {
 		match active_head.note_statement(statement) {
 			NotedStatement::Fresh(stored) => Some((
 				*stored.compact().candidate_hash(),
-				circulate_statement(peers, ctx, relay_parent, stored).await,
+				circulate_statement(peers, gossip_topology, ctx, relay_parent, stored).await,
 			)),
 			_ => None,
 		}
@@ -573,22 +612,29 @@ fn statement_message(relay_parent: Hash, statement: SignedFullStatement)
 	)
 }
 
-/// Circulates a statement to all peers who have not seen it yet, and returns
-/// an iterator over peers who need to have dependent statements sent.
-#[tracing::instrument(level = "trace", skip(peers, ctx), fields(subsystem = LOG_TARGET))]
+/// Circulates a statement to the peers chosen by the gossip topology among those who have not
+/// seen it yet, and returns an iterator over peers who need to have dependent statements sent.
+#[tracing::instrument(level = "trace", skip(peers, gossip_topology, ctx), fields(subsystem = LOG_TARGET))]
 async fn circulate_statement(
 	peers: &mut HashMap<PeerId, PeerData>,
+	gossip_topology: &GossipTopology,
 	ctx: &mut impl SubsystemContext<Message = StatementDistributionMessage>,
 	relay_parent: Hash,
 	stored: &StoredStatement,
 ) -> Vec<PeerId> {
 	let fingerprint = stored.fingerprint();
 
+	let targets = gossip_topology.targets(
+		peers.iter()
+			.filter(|(_, data)| data.can_send(&relay_parent, &fingerprint))
+			.map(|(peer, _)| peer.clone())
+	);
+
 	let mut peers_to_send = HashMap::new();
 
-	for (peer, data) in peers.iter_mut() {
-		if let Some(new_known) = data.send(&relay_parent, &fingerprint) {
-			peers_to_send.insert(peer.clone(), new_known);
+	for peer in targets {
+		if let Some(new_known) = peers.get_mut(&peer).and_then(|data| data.send(&relay_parent, &fingerprint)) {
+			peers_to_send.insert(peer, new_known);
 		}
 	}
 
@@ -795,9 +841,10 @@ async fn update_peer_view_and_send_unlocked(
 	}
 }
 
-#[tracing::instrument(level = "trace", skip(peers, active_heads, ctx, metrics), fields(subsystem = LOG_TARGET))]
+#[tracing::instrument(level = "trace", skip(peers, gossip_topology, active_heads, ctx, metrics), fields(subsystem = LOG_TARGET))]
 async fn handle_network_update(
 	peers: &mut HashMap<PeerId, PeerData>,
+	gossip_topology: &mut GossipTopology,
 	active_heads: &mut HashMap<Hash, ActiveHeadData>,
 	ctx: &mut impl SubsystemContext<Message = StatementDistributionMessage>,
 	our_view: &mut View,
@@ -873,6 +920,9 @@ async fn handle_network_update(
 				}
 			}
 		}
+		NetworkBridgeEvent::NewGossipTopology(topology) => {
+			*gossip_topology = topology;
+		}
 	}
 
 }
@@ -884,6 +934,7 @@ impl StatementDistribution {
 		mut ctx: impl SubsystemContext<Message = StatementDistributionMessage>,
 	) -> SubsystemResult<()> {
 		let mut peers: HashMap<PeerId, PeerData> = HashMap::new();
+		let mut gossip_topology = GossipTopology::default();
 		let mut our_view = View::default();
 		let mut active_heads: HashMap<Hash, ActiveHeadData> = HashMap::new();
 		let mut statement_listeners = StatementListeners::new();
@@ -895,10 +946,22 @@ impl StatementDistribution {
 				FromOverseer::Signal(OverseerSignal::ActiveLeaves(ActiveLeavesUpdate { activated, .. })) => {
 					let _timer = metrics.time_active_leaves_update();
 
-					for relay_parent in activated {
-						let (validators, session_index) = {
+					for leaf in activated {
+						let relay_parent = leaf.hash;
+						let session_index = match leaf.session_index {
+							Some(session_index) => session_index,
+							None => {
+								tracing::debug!(
+									target: LOG_TARGET,
+									%relay_parent,
+									"Session of active leaf is unknown, ignoring it",
+								);
+								continue;
+							}
+						};
+
+						let validators = {
 							let (val_tx, val_rx) = oneshot::channel();
-							let (session_tx, session_rx) = oneshot::channel();
 
 							let val_message = AllMessages::RuntimeApi(
 								RuntimeApiMessage::Request(
@@ -906,20 +969,12 @@ impl StatementDistribution {
 									RuntimeApiRequest::Validators(val_tx),
 								),
 							);
-							let session_message = AllMessages::RuntimeApi(
-								RuntimeApiMessage::Request(
-									relay_parent,
-									RuntimeApiRequest::SessionIndexForChild(session_tx),
-								),
-							);
 
-							ctx.send_messages(
-								std::iter::once(val_message).chain(std::iter::once(session_message))
-							).await;
+							ctx.send_message(val_message).await;
 
-							match (val_rx.await?, session_rx.await?) {
-								(Ok(v), Ok(s)) => (v, s),
-								(Err(e), _) | (_, Err(e)) => {
+							match val_rx.await? {
+								Ok(v) => v,
+								Err(e) => {
 									tracing::warn!(
 										target: LOG_TARGET,
 										err = ?e,
@@ -935,11 +990,11 @@ impl StatementDistribution {
 						};
 
 						active_heads.entry(relay_parent)
-							.or_insert(ActiveHeadData::new(validators, session_index));
+							.or_insert(ActiveHeadData::new(validators, session_index, leaf.number));
 					}
 				}
-				FromOverseer::Signal(OverseerSignal::BlockFinalized(_block_hash)) => {
-					// do nothing
+				FromOverseer::Signal(OverseerSignal::BlockFinalized(_block_hash, number)) => {
+					prune_finalized_heads(&mut active_heads, &our_view, number);
 				}
 				FromOverseer::Signal(OverseerSignal::Conclude) => break,
 				FromOverseer::Communication { msg } => match msg {
@@ -952,6 +1007,7 @@ impl StatementDistribution {
 						).await;
 						circulate_statement_and_dependents(
 							&mut peers,
+							&gossip_topology,
 							&mut active_heads,
 							&mut ctx,
 							relay_parent,
@@ -964,6 +1020,7 @@ impl StatementDistribution {
 
 						handle_network_update(
 							&mut peers,
+							&mut gossip_topology,
 							&mut active_heads,
 							&mut ctx,
 							&mut our_view,
@@ -1071,6 +1128,7 @@ mod tests {
 	use futures::executor::{self, block_on};
 	use sp_keystore::{CryptoStore, SyncCryptoStorePtr, SyncCryptoStore};
 	use sc_keystore::LocalKeystore;
+	use polkadot_node_network_protocol::grid_topology::RANDOM_GOSSIP_PEERS;
 
 	#[test]
 	fn active_head_accepts_only_2_seconded_per_validator() {
@@ -1108,7 +1166,7 @@ mod tests {
 			c
 		};
 
-		let mut head_data = ActiveHeadData::new(validators, session_index);
+		let mut head_data = ActiveHeadData::new(validators, session_index, 1);
 
 		let keystore: SyncCryptoStorePtr = Arc::new(LocalKeystore::in_memory());
 		let alice_public = SyncCryptoStore::sr25519_generate_new(
@@ -1366,7 +1424,7 @@ mod tests {
 		).unwrap();
 
 		let new_head_data = {
-			let mut data = ActiveHeadData::new(validators, session_index);
+			let mut data = ActiveHeadData::new(validators, session_index, 1);
 
 			let noted = data.note_statement(block_on(SignedFullStatement::sign(
 				&keystore,
@@ -1540,6 +1598,7 @@ mod tests {
 
 			let needs_dependents = circulate_statement(
 				&mut peer_data,
+				&GossipTopology::default(),
 				&mut ctx,
 				hash_b,
 				&statement,
@@ -1584,4 +1643,129 @@ mod tests {
 			)
 		});
 	}
+
+	#[test]
+	fn circulated_statement_goes_to_gossip_neighbors_first() {
+		let hash_a: Hash = [1; 32].into();
+
+		let candidate = {
+			let mut c = CommittedCandidateReceipt::default();
+			c.descriptor.relay_parent = hash_a;
+			c.descriptor.para_id = 1.into();
+			c
+		};
+
+		let peers: Vec<PeerId> = (0..10).map(|_| PeerId::random()).collect();
+		let mut peer_data: HashMap<_, _> = peers.iter().map(|peer| (peer.clone(), PeerData {
+			view: View(vec![hash_a]),
+			view_knowledge: vec![(hash_a, Default::default())].into_iter().collect(),
+		})).collect();
+
+		let gossip_topology = GossipTopology {
+			session_index: 1,
+			neighbors: peers[..2].iter().cloned().collect(),
+		};
+
+		let pool = sp_core::testing::TaskExecutor::new();
+		let (mut ctx, mut handle) = polkadot_node_subsystem_test_helpers::make_subsystem_context(pool);
+
+		executor::block_on(async move {
+			let statement = {
+				let signing_context = SigningContext {
+					parent_hash: hash_a,
+					session_index: 1,
+				};
+
+				let keystore: SyncCryptoStorePtr = Arc::new(LocalKeystore::in_memory());
+				let alice_public = CryptoStore::sr25519_generate_new(
+					&*keystore, ValidatorId::ID, Some(&Sr25519Keyring::Alice.to_seed())
+				).await.unwrap();
+
+				let statement = SignedFullStatement::sign(
+					&keystore,
+					Statement::Seconded(candidate),
+					&signing_context,
+					0,
+					&alice_public.into(),
+				).await.expect("should be signed");
+
+				StoredStatement {
+					comparator: StoredStatementComparator {
+						compact: statement.payload().to_compact(),
+						validator_index: 0,
+						signature: statement.signature().clone()
+					},
+					statement,
+				}
+			};
+
+			let needs_dependents = circulate_statement(
+				&mut peer_data,
+				&gossip_topology,
+				&mut ctx,
+				hash_a,
+				&statement,
+			).await;
+
+			assert_eq!(needs_dependents.len(), 2 + RANDOM_GOSSIP_PEERS);
+
+			let fingerprint = (statement.compact().clone(), 0);
+
+			assert_matches!(
+				handle.recv().await,
+				AllMessages::NetworkBridge(NetworkBridgeMessage::SendValidationMessage(
+					to,
+					_,
+				)) => {
+					assert_eq!(to.len(), 2 + RANDOM_GOSSIP_PEERS);
+					assert!(to.contains(&peers[0]));
+					assert!(to.contains(&peers[1]));
+
+					// The peers not chosen can still be sent the statement later.
+					for peer in &peers {
+						assert_eq!(
+							peer_data.get(peer).unwrap().can_send(&hash_a, &fingerprint),
+							!to.contains(peer),
+						);
+					}
+				}
+			)
+		});
+	}
+
+	#[test]
+	fn finalized_heads_are_pruned() {
+		let hash_a: Hash = [1; 32].into();
+		let hash_b: Hash = [2; 32].into();
+		let hash_c: Hash = [3; 32].into();
+
+		let mut active_heads: HashMap<Hash, ActiveHeadData> = vec![
+			(hash_a, ActiveHeadData::new(Vec::new(), 1, 1)),
+			(hash_b, ActiveHeadData::new(Vec::new(), 1, 2)),
+			(hash_c, ActiveHeadData::new(Vec::new(), 1, 3)),
+		].into_iter().collect();
+
+		prune_finalized_heads(&mut active_heads, &View(vec![hash_c]), 2);
+
+		assert!(!active_heads.contains_key(&hash_a));
+		assert!(!active_heads.contains_key(&hash_b));
+		assert!(active_heads.contains_key(&hash_c));
+	}
+
+	#[test]
+	fn finalized_leaf_is_not_pruned() {
+		let hash_a: Hash = [1; 32].into();
+		let hash_b: Hash = [2; 32].into();
+
+		let mut active_heads: HashMap<Hash, ActiveHeadData> = vec![
+			(hash_a, ActiveHeadData::new(Vec::new(), 1, 1)),
+			(hash_b, ActiveHeadData::new(Vec::new(), 1, 2)),
+		].into_iter().collect();
+
+		// `hash_b` is finalized while still being our leaf.
+		prune_finalized_heads(&mut active_heads, &View(vec![hash_b]), 2);
+
+		assert!(!active_heads.contains_key(&hash_a));
+		assert!(active_heads.contains_key(&hash_b));
+	}
 }
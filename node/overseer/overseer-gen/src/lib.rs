@@ -0,0 +1,586 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Generates the subsystem wiring of the overseer.
+//!
+//! The `#[overlord]` attribute is placed on a struct which lists every subsystem the overseer
+//! knows about, one field per subsystem, with the type of the field being the message type
+//! the subsystem handles:
+//!
+//! ```ignore
+//! #[overlord(message_wrapper = AllMessages)]
+//! pub struct OverseenSubsystems {
+//!     /// A candidate validation subsystem.
+//!     candidate_validation: CandidateValidationMessage,
+//!     /// A candidate backing subsystem.
+//!     candidate_backing: CandidateBackingMessage,
+//! }
+//! ```
+//!
+//! From this declaration the following items are generated:
+//!
+//! - the struct itself, with every field turned into an `OverseenSubsystem<Message>`, together
+//!   with methods to broadcast a signal to all subsystems, to conclude all of them, to route
+//!   a message wrapped in `message_wrapper` to the subsystem handling it, to find the label of
+//!   that subsystem and to supervise a subsystem which exited, given its label. The variant of
+//!   the wrapper is the name of the message type without the `Message` suffix, the label of a
+//!   subsystem is the name of its field;
+//! - `AllSubsystems`, generic over the type of every subsystem, with its `dummy` constructor
+//!   and a `replace_<field>` method per subsystem;
+//! - `ChannelCapacities`, holding the capacity of the message channel of every subsystem and
+//!   defaulting to `CHANNEL_CAPACITY` for all of them;
+//! - the `SpawnAllSubsystems` trait, implemented for `AllSubsystems` whenever every subsystem
+//!   is a `SupervisedSubsystem` of its message type.
+//!
+//! The generated code refers to the overseer types by name, so it is expected to be expanded
+//! in a module where `OverseenSubsystem`, `SupervisedSubsystem`, `SubsystemSpawner`,
+//! `SubsystemExit`, `DummySubsystem`, `OverseerSignal`, `SubsystemResult`, `SpawnNamed` and
+//! `CHANNEL_CAPACITY` are in scope.
+//!
+//! The message wrapper itself is needed by every subsystem to talk to the others, so it has to
+//! live in a crate the overseer depends on. It is generated there by a second declaration of the
+//! same subsystems with the `wrapper_only` argument:
+//!
+//! ```ignore
+//! /// A message type tying together all message types that are used across Subsystems.
+//! #[overlord(message_wrapper = AllMessages, wrapper_only)]
+//! #[derive(Debug)]
+//! pub struct OverseenSubsystems {
+//!     /// Message for the validation subsystem.
+//!     candidate_validation: CandidateValidationMessage,
+//!     /// Message for the candidate backing subsystem.
+//!     candidate_backing: CandidateBackingMessage,
+//! }
+//! ```
+//!
+//! This expands to nothing but the `AllMessages` enum, with one variant per subsystem named as
+//! described above and the attributes of the struct and its fields carried over. The two
+//! declarations can't drift apart: the routing generated for the overseer matches the wrapper
+//! exhaustively, so a subsystem missing from either of them fails to compile.
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use syn::parse::{Parse, ParseStream};
+use syn::{Attribute, Fields, Ident, ItemStruct, Path, Token, Type};
+
+/// Generate the overseer's subsystem wiring from a declaration of its subsystems.
+///
+/// See the crate level documentation for details.
+#[proc_macro_attribute]
+pub fn overlord(attr: TokenStream, item: TokenStream) -> TokenStream {
+	impl_overlord(attr.into(), item.into())
+		.unwrap_or_else(|err| err.to_compile_error())
+		.into()
+}
+
+/// Arguments of the `#[overlord]` attribute.
+struct OverlordArgs {
+	/// The enum wrapping the messages of all subsystems.
+	message_wrapper: Path,
+	/// Whether to generate the message wrapper instead of the overseer's subsystem wiring.
+	wrapper_only: bool,
+}
+
+impl Parse for OverlordArgs {
+	fn parse(input: ParseStream) -> syn::Result<Self> {
+		let key: Ident = input.parse()?;
+		if key != "message_wrapper" {
+			return Err(syn::Error::new(key.span(), "expected `message_wrapper = <Path>`"));
+		}
+		input.parse::<Token![=]>()?;
+		let message_wrapper = input.parse()?;
+
+		let wrapper_only = if input.is_empty() {
+			false
+		} else {
+			input.parse::<Token![,]>()?;
+			let flag: Ident = input.parse()?;
+			if flag != "wrapper_only" {
+				return Err(syn::Error::new(flag.span(), "expected `wrapper_only`"));
+			}
+			true
+		};
+		if !input.is_empty() {
+			return Err(input.error("unexpected tokens after the arguments"));
+		}
+
+		Ok(OverlordArgs { message_wrapper, wrapper_only })
+	}
+}
+
+/// A single subsystem declared in the struct.
+struct SubsystemField {
+	/// Doc comments of the field.
+	docs: Vec<Attribute>,
+	/// Name of the field.
+	name: Ident,
+	/// Name of the generic parameter of `AllSubsystems` standing for this subsystem.
+	generic: Ident,
+	/// The message type the subsystem handles.
+	message: Type,
+	/// The variant of the message wrapper carrying the message.
+	variant: Ident,
+}
+
+/// Name of the message wrapper variant carrying the given message type.
+fn variant_name(message: &Type) -> syn::Result<Ident> {
+	let segment = match message {
+		Type::Path(path) if path.qself.is_none() => path.path.segments.last(),
+		_ => None,
+	};
+	let segment = segment.ok_or_else(|| syn::Error::new_spanned(message, "expected a message type path"))?;
+	let ident = segment.ident.to_string();
+
+	match ident.strip_suffix("Message") {
+		Some(variant) if !variant.is_empty() => Ok(Ident::new(variant, segment.ident.span())),
+		_ => Err(syn::Error::new_spanned(
+			message,
+			"the name of a message type is expected to end in `Message`",
+		)),
+	}
+}
+
+/// `candidate_validation` -> `CandidateValidation`.
+fn generic_name(field: &Ident) -> Ident {
+	let camel_case: String = field.to_string()
+		.split('_')
+		.filter(|part| !part.is_empty())
+		.map(|part| {
+			let mut chars = part.chars();
+			chars.next()
+				.map(|first| first.to_uppercase().chain(chars).collect::<String>())
+				.unwrap_or_default()
+		})
+		.collect();
+
+	Ident::new(&camel_case, field.span())
+}
+
+fn parse_fields(item: &ItemStruct) -> syn::Result<Vec<SubsystemField>> {
+	if !item.generics.params.is_empty() || item.generics.where_clause.is_some() {
+		return Err(syn::Error::new_spanned(&item.generics, "the subsystems struct can not be generic"));
+	}
+
+	let fields = match &item.fields {
+		Fields::Named(fields) if !fields.named.is_empty() => &fields.named,
+		_ => return Err(syn::Error::new_spanned(item, "expected a struct with named fields, one per subsystem")),
+	};
+
+	fields.iter().map(|field| {
+		let name = field.ident.clone().expect("named fields have an ident; qed");
+
+		if let Some(attr) = field.attrs.iter().find(|attr| !attr.path.is_ident("doc")) {
+			return Err(syn::Error::new_spanned(attr, "only doc comments are allowed on subsystem fields"));
+		}
+
+		Ok(SubsystemField {
+			docs: field.attrs.clone(),
+			generic: generic_name(&name),
+			message: field.ty.clone(),
+			variant: variant_name(&field.ty)?,
+			name,
+		})
+	}).collect()
+}
+
+fn impl_overlord(attr: TokenStream2, item: TokenStream2) -> syn::Result<TokenStream2> {
+	let args: OverlordArgs = syn::parse2(attr)?;
+	let item: ItemStruct = syn::parse2(item)?;
+	let subsystems = parse_fields(&item)?;
+
+	if args.wrapper_only {
+		return impl_message_wrapper(&item, &args, &subsystems);
+	}
+
+	let mut tokens = impl_overseen_subsystems(&item, &args, &subsystems);
+	tokens.extend(impl_all_subsystems(&subsystems));
+	tokens.extend(impl_channel_capacities(&subsystems));
+	tokens.extend(impl_spawn_all_subsystems(&item.ident, &subsystems));
+
+	Ok(tokens)
+}
+
+/// The enum wrapping the messages of all subsystems, in place of the declaring struct.
+fn impl_message_wrapper(
+	item: &ItemStruct,
+	args: &OverlordArgs,
+	subsystems: &[SubsystemField],
+) -> syn::Result<TokenStream2> {
+	let attrs = &item.attrs;
+	let vis = &item.vis;
+	let wrapper = args.message_wrapper.get_ident().ok_or_else(|| syn::Error::new_spanned(
+		&args.message_wrapper,
+		"the generated message wrapper must be named by a plain identifier",
+	))?;
+
+	let docs = subsystems.iter().map(|s| &s.docs);
+	let messages = subsystems.iter().map(|s| &s.message);
+	let variants = subsystems.iter().map(|s| &s.variant);
+
+	Ok(quote! {
+		#( #attrs )*
+		#vis enum #wrapper {
+			#(
+				#( #docs )*
+				#variants(#messages),
+			)*
+		}
+	})
+}
+
+/// The struct holding the running subsystems and the methods to talk to all of them.
+fn impl_overseen_subsystems(
+	item: &ItemStruct,
+	args: &OverlordArgs,
+	subsystems: &[SubsystemField],
+) -> TokenStream2 {
+	let attrs = &item.attrs;
+	let vis = &item.vis;
+	let ident = &item.ident;
+	let wrapper = &args.message_wrapper;
+
+	let docs = subsystems.iter().map(|s| &s.docs);
+	let names = subsystems.iter().map(|s| &s.name).collect::<Vec<_>>();
+	let messages = subsystems.iter().map(|s| &s.message);
+	let variants = subsystems.iter().map(|s| &s.variant).collect::<Vec<_>>();
+	let labels = subsystems.iter().map(|s| s.name.to_string()).collect::<Vec<_>>();
+
+	quote! {
+		#( #attrs )*
+		#vis struct #ident {
+			#(
+				#( #docs )*
+				#names: OverseenSubsystem<#messages>,
+			)*
+		}
+
+		impl #ident {
+			/// Send a signal to every subsystem, even if sending it to one of them fails.
+			///
+			/// Returns the first error, if any.
+			async fn broadcast_signal(&mut self, signal: OverseerSignal) -> SubsystemResult<()> {
+				let mut result = Ok(());
+				#(
+					if let Err(e) = self.#names.send_signal(signal.clone()).await {
+						result = result.and(Err(e));
+					}
+				)*
+
+				result
+			}
+
+			/// Ask every subsystem to conclude, ignoring the ones which are already gone.
+			async fn conclude(&mut self) {
+				#( let _ = self.#names.send_signal(OverseerSignal::Conclude).await; )*
+			}
+
+			/// Route a message to the subsystem handling it.
+			async fn route_message(&mut self, msg: #wrapper) {
+				match msg {
+					#(
+						#wrapper::#variants(msg) => {
+							let _ = self.#names.send_message(msg).await;
+						},
+					)*
+				}
+			}
+
+			/// The label of the subsystem handling `msg`.
+			fn destination(msg: &#wrapper) -> &'static str {
+				match msg {
+					#( #wrapper::#variants(_) => #labels, )*
+				}
+			}
+
+			/// Note that the subsystem labelled `label` exited and decide what to do about it.
+			fn on_exit(&mut self, label: &str) -> SubsystemExit {
+				match label {
+					#( #labels => self.#names.on_exit(), )*
+					_ => SubsystemExit::Ignore,
+				}
+			}
+
+			/// Start the subsystem labelled `label` again, sending it `signal` before anything else.
+			fn restart<S: SpawnNamed>(
+				&mut self,
+				label: &str,
+				spawner: &mut SubsystemSpawner<'_, S>,
+				signal: OverseerSignal,
+			) -> SubsystemResult<()> {
+				match label {
+					#( #labels => spawner.restart(&mut self.#names, signal), )*
+					_ => Ok(()),
+				}
+			}
+		}
+	}
+}
+
+/// `AllSubsystems` together with its constructor and the `replace_*` methods.
+fn impl_all_subsystems(subsystems: &[SubsystemField]) -> TokenStream2 {
+	let docs = subsystems.iter().map(|s| &s.docs).collect::<Vec<_>>();
+	let names = subsystems.iter().map(|s| &s.name).collect::<Vec<_>>();
+	let generics = subsystems.iter().map(|s| &s.generic).collect::<Vec<_>>();
+	let dummies = subsystems.iter().map(|_| quote!(DummySubsystem)).collect::<Vec<_>>();
+
+	let replace_methods = subsystems.iter().enumerate().map(|(i, subsystem)| {
+		let name = &subsystem.name;
+		let method = format_ident!("replace_{}", name);
+		let method_doc = format!("Replace the `{}` instance in `self`.", name);
+		let new = Ident::new("NEW", Span::call_site());
+
+		let new_generics = generics.iter().enumerate().map(|(j, generic)| {
+			if i == j { &new } else { *generic }
+		});
+		let others = names.iter()
+			.enumerate()
+			.filter(|(j, _)| i != *j)
+			.map(|(_, name)| *name)
+			.collect::<Vec<_>>();
+
+		quote! {
+			#[doc = #method_doc]
+			pub fn #method<#new>(self, #name: #new) -> AllSubsystems<#( #new_generics ),*> {
+				AllSubsystems {
+					#name,
+					#( #others: self.#others, )*
+				}
+			}
+		}
+	});
+
+	quote! {
+		/// This struct is passed as an argument to create a new instance of an [`Overseer`].
+		///
+		/// As any entity that satisfies the interface may act as a [`Subsystem`] this allows
+		/// mocking in the test code:
+		///
+		/// Each [`Subsystem`] is supposed to implement some interface that is generic over
+		/// message type that is specific to this [`Subsystem`]. At the moment not all
+		/// subsystems are implemented and the rest can be mocked with the [`DummySubsystem`].
+		pub struct AllSubsystems<#( #generics = () ),*> {
+			#(
+				#( #docs )*
+				pub #names: #generics,
+			)*
+		}
+
+		impl<#( #generics ),*> AllSubsystems<#( #generics ),*> {
+			/// Create a new instance of [`AllSubsystems`].
+			///
+			/// Each subsystem is set to [`DummySystem`].
+			///
+			///# Note
+			///
+			/// Because of a bug in rustc it is required that when calling this function,
+			/// you provide a "random" type for the first generic parameter:
+			///
+			/// ```
+			/// polkadot_overseer::AllSubsystems::<()>::dummy();
+			/// ```
+			pub fn dummy() -> AllSubsystems<#( #dummies ),*> {
+				AllSubsystems {
+					#( #names: DummySubsystem, )*
+				}
+			}
+
+			#( #replace_methods )*
+		}
+	}
+}
+
+/// `ChannelCapacities` and its default.
+fn impl_channel_capacities(subsystems: &[SubsystemField]) -> TokenStream2 {
+	let names = subsystems.iter().map(|s| &s.name).collect::<Vec<_>>();
+	let docs = subsystems.iter()
+		.map(|s| format!("Capacity of the message channel of the `{}` subsystem.", s.name));
+
+	quote! {
+		/// The capacities of the channels messages are sent to each subsystem on.
+		#[derive(Debug, Clone)]
+		pub struct ChannelCapacities {
+			#(
+				#[doc = #docs]
+				pub #names: usize,
+			)*
+		}
+
+		impl Default for ChannelCapacities {
+			fn default() -> Self {
+				ChannelCapacities {
+					#( #names: CHANNEL_CAPACITY, )*
+				}
+			}
+		}
+	}
+}
+
+/// The `SpawnAllSubsystems` trait and its implementation for `AllSubsystems`.
+fn impl_spawn_all_subsystems(overseen: &Ident, subsystems: &[SubsystemField]) -> TokenStream2 {
+	let names = subsystems.iter().map(|s| &s.name).collect::<Vec<_>>();
+	let labels = subsystems.iter().map(|s| s.name.to_string());
+	let generics = subsystems.iter().map(|s| &s.generic).collect::<Vec<_>>();
+	let messages = subsystems.iter().map(|s| &s.message);
+
+	quote! {
+		/// A set of subsystems which can be spawned by the [`Overseer`].
+		///
+		/// Implemented for [`AllSubsystems`] whenever each of its subsystems handles the
+		/// messages it is meant to.
+		pub trait SpawnAllSubsystems {
+			/// Spawn all subsystems, returning the handles to the running ones.
+			fn spawn_all<S: SpawnNamed>(
+				self,
+				spawner: &mut SubsystemSpawner<'_, S>,
+				capacities: &ChannelCapacities,
+			) -> SubsystemResult<#overseen>;
+		}
+
+		impl<#( #generics ),*> SpawnAllSubsystems for AllSubsystems<#( #generics ),*>
+		where
+			#( #generics: SupervisedSubsystem<#messages>, )*
+		{
+			fn spawn_all<S: SpawnNamed>(
+				self,
+				spawner: &mut SubsystemSpawner<'_, S>,
+				capacities: &ChannelCapacities,
+			) -> SubsystemResult<#overseen> {
+				Ok(#overseen {
+					#( #names: spawner.spawn(self.#names, capacities.#names, #labels)?, )*
+				})
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use syn::parse_quote;
+
+	#[test]
+	fn names_are_derived_from_fields_and_messages() {
+		let message: Type = parse_quote!(messages::PoVDistributionMessage);
+		assert_eq!(variant_name(&message).unwrap(), "PoVDistribution");
+
+		let message: Type = parse_quote!(Message);
+		assert!(variant_name(&message).is_err());
+
+		let message: Type = parse_quote!(CandidateValidation);
+		assert!(variant_name(&message).is_err());
+
+		let field: Ident = parse_quote!(candidate_validation);
+		assert_eq!(generic_name(&field), "CandidateValidation");
+	}
+
+	#[test]
+	fn expands_subsystems_struct() {
+		let attr = quote!(message_wrapper = AllMessages);
+		let item = quote! {
+			pub struct OverseenSubsystems {
+				/// A candidate validation subsystem.
+				candidate_validation: CandidateValidationMessage,
+				/// A candidate backing subsystem.
+				candidate_backing: CandidateBackingMessage,
+			}
+		};
+
+		let expanded = impl_overlord(attr, item).unwrap();
+		let file: syn::File = syn::parse2(expanded).unwrap();
+
+		// The subsystems struct and its methods, `AllSubsystems` and its methods,
+		// `ChannelCapacities` and its default, the spawning trait and its implementation.
+		assert_eq!(file.items.len(), 8);
+
+		let expanded = quote!(#file).to_string().replace(' ', "");
+		assert!(expanded.contains("AllMessages::CandidateBacking(msg)"));
+		assert!(expanded.contains("AllMessages::CandidateBacking(_)=>\"candidate_backing\","));
+		assert!(expanded.contains("fnreplace_candidate_validation<NEW>"));
+		assert!(expanded.contains("AllSubsystems<CandidateValidation,NEW>"));
+		assert!(expanded.contains("CandidateBacking:SupervisedSubsystem<CandidateBackingMessage>,"));
+		assert!(expanded.contains("\"candidate_backing\"=>self.candidate_backing.on_exit(),"));
+		assert!(expanded.contains("spawner.spawn(self.candidate_backing,capacities.candidate_backing,\"candidate_backing\")?"));
+		assert!(expanded.contains("candidate_validation:CHANNEL_CAPACITY,"));
+	}
+
+	#[test]
+	fn expands_message_wrapper() {
+		let attr = quote!(message_wrapper = AllMessages, wrapper_only);
+		let item = quote! {
+			/// All messages.
+			#[derive(Debug)]
+			pub struct OverseenSubsystems {
+				/// Message for the validation subsystem.
+				candidate_validation: CandidateValidationMessage,
+				/// Message for the PoV distribution subsystem.
+				pov_distribution: messages::PoVDistributionMessage,
+			}
+		};
+
+		let expanded = impl_overlord(attr, item).unwrap();
+		let file: syn::File = syn::parse2(expanded).unwrap();
+		assert_eq!(file.items.len(), 1);
+
+		let expected: syn::File = parse_quote! {
+			/// All messages.
+			#[derive(Debug)]
+			pub enum AllMessages {
+				/// Message for the validation subsystem.
+				CandidateValidation(CandidateValidationMessage),
+				/// Message for the PoV distribution subsystem.
+				PoVDistribution(messages::PoVDistributionMessage),
+			}
+		};
+		assert_eq!(quote!(#file).to_string(), quote!(#expected).to_string());
+	}
+
+	#[test]
+	fn rejects_malformed_input() {
+		let item = quote! {
+			pub struct OverseenSubsystems {
+				candidate_validation: CandidateValidationMessage,
+			}
+		};
+		assert!(impl_overlord(quote!(wrapper = AllMessages), item.clone()).is_err());
+		assert!(impl_overlord(quote!(message_wrapper = AllMessages, extra), item.clone()).is_err());
+		assert!(impl_overlord(
+			quote!(message_wrapper = AllMessages, wrapper_only, extra),
+			item.clone(),
+		).is_err());
+		assert!(impl_overlord(quote!(message_wrapper = messages::AllMessages, wrapper_only), item).is_err());
+
+		let generic = quote! {
+			pub struct OverseenSubsystems<T> {
+				candidate_validation: T,
+			}
+		};
+		assert!(impl_overlord(quote!(message_wrapper = AllMessages), generic).is_err());
+
+		let unnamed = quote! {
+			pub struct OverseenSubsystems(CandidateValidationMessage);
+		};
+		assert!(impl_overlord(quote!(message_wrapper = AllMessages), unnamed).is_err());
+
+		let attributed = quote! {
+			pub struct OverseenSubsystems {
+				#[allow(dead_code)]
+				candidate_validation: CandidateValidationMessage,
+			}
+		};
+		assert!(impl_overlord(quote!(message_wrapper = AllMessages), attributed).is_err());
+	}
+}
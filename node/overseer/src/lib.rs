@@ -31,6 +31,12 @@
 //! The `Overseer` is instantiated with a pre-defined set of `Subsystems` that
 //! share the same behavior from `Overseer`'s point of view.
 //!
+//! The set of subsystems is declared in [`OverseenSubsystems`]. The per-subsystem
+//! plumbing - [`AllSubsystems`], spawning, broadcasting signals and routing messages - is
+//! generated from that declaration by the `overlord` macro of `polkadot-overseer-gen`.
+//! [`AllMessages`] is generated by the same macro from the matching declaration in
+//! `polkadot-node-subsystem`, which every subsystem depends on.
+//!
 //! ```text
 //!                              +-----------------------------+
 //!                              |         Overseer            |
@@ -62,22 +68,25 @@
 use std::fmt::Debug;
 use std::pin::Pin;
 use std::sync::Arc;
-use std::task::Poll;
-use std::time::Duration;
-use std::collections::{hash_map, HashMap};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use std::collections::HashMap;
 
 use futures::channel::{mpsc, oneshot};
 use futures::{
 	poll, select,
-	future::BoxFuture,
+	future::{self, BoxFuture},
 	stream::{self, FuturesUnordered},
 	Future, FutureExt, SinkExt, StreamExt,
 };
 use futures_timer::Delay;
 use streamunordered::{StreamYield, StreamUnordered};
 
-use polkadot_primitives::v1::{Block, BlockNumber, Hash};
-use client::{BlockImportNotification, BlockchainEvents, FinalityNotification};
+use polkadot_primitives::v1::{Block, BlockId, BlockNumber, Hash, ParachainHost, SessionIndex};
+use client::{BlockImportNotification, BlockchainEvents};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
 
 use polkadot_subsystem::messages::{
 	CandidateValidationMessage, CandidateBackingMessage,
@@ -88,18 +97,27 @@ use polkadot_subsystem::messages::{
 };
 pub use polkadot_subsystem::{
 	Subsystem, SubsystemContext, OverseerSignal, FromOverseer, SubsystemError, SubsystemResult,
-	SpawnedSubsystem, ActiveLeavesUpdate, DummySubsystem,
+	SpawnedSubsystem, ActiveLeavesUpdate, ActivatedLeaf, DummySubsystem,
 };
 use polkadot_node_subsystem_util::metrics::{self, prometheus};
 use polkadot_node_primitives::SpawnNamed;
+use polkadot_overseer_gen::overlord;
+
+pub mod recorder;
+
+pub use recorder::Recorder;
 
 
 // A capacity of bounded channels inside the overseer.
 const CHANNEL_CAPACITY: usize = 1024;
+// A capacity of the channels signals are sent to subsystems on.
+const SIGNAL_CHANNEL_CAPACITY: usize = 64;
 // A graceful `Overseer` teardown time delay.
 const STOP_DELAY: u64 = 1;
 // Target for logs.
 const LOG_TARGET: &'static str = "overseer";
+// The maximum number of unfinalized ancestors looked up for an imported block.
+const MAX_ANCESTRY: usize = 64;
 
 /// A type of messages that are sent from [`Subsystem`] to [`Overseer`].
 ///
@@ -111,7 +129,11 @@ const LOG_TARGET: &'static str = "overseer";
 /// [`Overseer`]: struct.Overseer.html
 enum ToOverseer {
 	/// This is a message sent by a `Subsystem`.
-	SubsystemMessage(AllMessages),
+	SubsystemMessage {
+		/// The label of the sending `Subsystem`.
+		source: &'static str,
+		msg: AllMessages,
+	},
 
 	/// A message that wraps something the `Subsystem` is desiring to
 	/// spawn on the overseer and a `oneshot::Sender` to signal the result
@@ -135,7 +157,7 @@ enum ToOverseer {
 /// This structure exists solely for the purposes of decoupling
 /// `Overseer` code from the client code and the necessity to call
 /// `HeaderBackend::block_number_from_id()`.
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct BlockInfo {
 	/// hash of the block.
 	pub hash: Hash,
@@ -143,26 +165,12 @@ pub struct BlockInfo {
 	pub parent_hash: Hash,
 	/// block's number.
 	pub number: BlockNumber,
-}
-
-impl From<BlockImportNotification<Block>> for BlockInfo {
-	fn from(n: BlockImportNotification<Block>) -> Self {
-		BlockInfo {
-			hash: n.hash,
-			parent_hash: n.header.parent_hash,
-			number: n.header.number,
-		}
-	}
-}
-
-impl From<FinalityNotification<Block>> for BlockInfo {
-	fn from(n: FinalityNotification<Block>) -> Self {
-		BlockInfo {
-			hash: n.hash,
-			parent_hash: n.header.parent_hash,
-			number: n.header.number,
-		}
-	}
+	/// session of the block's children, if known. Only used for imported blocks.
+	pub session_index: Option<SessionIndex>,
+	/// unfinalized ancestors of the block, most recent first. Only used for imported blocks.
+	///
+	/// The `Overseer` passes the ones it didn't see before on to subsystems.
+	pub ancestry: Vec<Hash>,
 }
 
 /// Some event from the outer world.
@@ -240,12 +248,19 @@ impl OverseerHandler {
 /// Glues together the [`Overseer`] and `BlockchainEvents` by forwarding
 /// import and finality notifications into the [`OverseerHandler`].
 ///
+/// Describing an imported block takes blocking client calls, so this should be spawned as a
+/// blocking task of its own rather than polled alongside the overseer.
+///
 /// [`Overseer`]: struct.Overseer.html
 /// [`OverseerHandler`]: struct.OverseerHandler.html
-pub async fn forward_events<P: BlockchainEvents<Block>>(
+pub async fn forward_events<P>(
 	client: Arc<P>,
 	mut handler: OverseerHandler,
-) {
+)
+where
+	P: BlockchainEvents<Block> + HeaderBackend<Block> + ProvideRuntimeApi<Block>,
+	P::Api: ParachainHost<Block>,
+{
 	let mut finality = client.finality_notification_stream();
 	let mut imports = client.import_notification_stream();
 
@@ -254,7 +269,12 @@ pub async fn forward_events<P: BlockchainEvents<Block>>(
 			f = finality.next() => {
 				match f {
 					Some(block) => {
-						handler.block_finalized(block.into()).await;
+						handler.block_finalized(BlockInfo {
+							hash: block.hash,
+							parent_hash: block.header.parent_hash,
+							number: block.header.number,
+							..Default::default()
+						}).await;
 					}
 					None => break,
 				}
@@ -262,7 +282,8 @@ pub async fn forward_events<P: BlockchainEvents<Block>>(
 			i = imports.next() => {
 				match i {
 					Some(block) => {
-						handler.block_imported(block.into()).await;
+						let block = imported_block_info(&*client, block);
+						handler.block_imported(block).await;
 					}
 					None => break,
 				}
@@ -272,11 +293,70 @@ pub async fn forward_events<P: BlockchainEvents<Block>>(
 	}
 }
 
+/// Look up the session a child of the given block is in.
+///
+/// If it can't be determined, which is the case for blocks of runtimes without parachains, the
+/// failure is logged and `None` is returned, so that the block is never dropped.
+pub fn session_index_for_child<P>(client: &P, hash: Hash) -> Option<SessionIndex>
+where
+	P: ProvideRuntimeApi<Block>,
+	P::Api: ParachainHost<Block>,
+{
+	match client.runtime_api().session_index_for_child(&BlockId::Hash(hash)) {
+		Ok(session_index) => Some(session_index),
+		Err(e) => {
+			tracing::warn!(
+				target: LOG_TARGET,
+				err = ?e,
+				%hash,
+				"Failed to fetch the session of a block",
+			);
+			None
+		}
+	}
+}
+
+/// Describe an imported block, looking up its session and its unfinalized ancestry.
+fn imported_block_info<P>(
+	client: &P,
+	block: BlockImportNotification<Block>,
+) -> BlockInfo
+where
+	P: HeaderBackend<Block> + ProvideRuntimeApi<Block>,
+	P::Api: ParachainHost<Block>,
+{
+	let session_index = session_index_for_child(client, block.hash);
+
+	let finalized_number = client.info().finalized_number;
+	let mut ancestry = Vec::new();
+	let mut ancestor = block.header.parent_hash;
+	let mut ancestor_number = block.header.number.saturating_sub(1);
+
+	while ancestor_number > finalized_number && ancestry.len() < MAX_ANCESTRY {
+		ancestry.push(ancestor);
+		match client.header(BlockId::Hash(ancestor)) {
+			Ok(Some(header)) => {
+				ancestor = header.parent_hash;
+				ancestor_number -= 1;
+			}
+			_ => break,
+		}
+	}
+
+	BlockInfo {
+		hash: block.hash,
+		parent_hash: block.header.parent_hash,
+		number: block.header.number,
+		session_index,
+		ancestry,
+	}
+}
+
 impl Debug for ToOverseer {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 		match self {
-			ToOverseer::SubsystemMessage(msg) => {
-				write!(f, "OverseerMessage::SubsystemMessage({:?})", msg)
+			ToOverseer::SubsystemMessage { source, msg } => {
+				write!(f, "OverseerMessage::SubsystemMessage({}, {:?})", source, msg)
 			}
 			ToOverseer::SpawnJob { .. } => write!(f, "OverseerMessage::Spawn(..)"),
 			ToOverseer::SpawnBlockingJob { .. } => write!(f, "OverseerMessage::SpawnBlocking(..)")
@@ -288,7 +368,45 @@ impl Debug for ToOverseer {
 ///
 /// [`Subsystem`]: trait.Subsystem.html
 struct SubsystemInstance<M> {
-	tx: mpsc::Sender<FromOverseer<M>>,
+	/// Signals are sent on their own channel, which the subsystem always drains first.
+	tx_signal: mpsc::Sender<OverseerSignal>,
+	tx_bounded: mpsc::Sender<M>,
+	meter: QueueMeter,
+	metrics: Metrics,
+	name: &'static str,
+}
+
+/// Keeps count of the messages sent to a subsystem which it has not received yet.
+///
+/// Shared between the [`Overseer`] and the context of the subsystem.
+#[derive(Clone)]
+struct QueueMeter {
+	queued: Arc<AtomicUsize>,
+	gauge: Option<prometheus::Gauge<prometheus::U64>>,
+}
+
+impl QueueMeter {
+	fn on_queued(&self) {
+		let queued = self.queued.fetch_add(1, Ordering::Relaxed) + 1;
+		self.update_gauge(queued);
+	}
+
+	fn on_dequeued(&self) {
+		let queued = self.queued.fetch_sub(1, Ordering::Relaxed).saturating_sub(1);
+		self.update_gauge(queued);
+	}
+
+	fn update_gauge(&self, queued: usize) {
+		if let Some(gauge) = &self.gauge {
+			gauge.set(queued as u64);
+		}
+	}
+}
+
+impl Debug for QueueMeter {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("QueueMeter").field("queued", &self.queued).finish()
+	}
 }
 
 /// A context type that is given to the [`Subsystem`] upon spawning.
@@ -300,7 +418,10 @@ struct SubsystemInstance<M> {
 /// [`SubsystemJob`]: trait.SubsystemJob.html
 #[derive(Debug)]
 pub struct OverseerSubsystemContext<M>{
-	rx: mpsc::Receiver<FromOverseer<M>>,
+	label: &'static str,
+	signals: mpsc::Receiver<OverseerSignal>,
+	messages: mpsc::Receiver<M>,
+	meter: QueueMeter,
 	tx: mpsc::Sender<ToOverseer>,
 }
 
@@ -309,7 +430,7 @@ impl<M: Send + 'static> SubsystemContext for OverseerSubsystemContext<M> {
 	type Message = M;
 
 	async fn try_recv(&mut self) -> Result<Option<FromOverseer<M>>, ()> {
-		match poll!(self.rx.next()) {
+		match poll!(future::poll_fn(|cx| self.poll_from_overseer(cx))) {
 			Poll::Ready(Some(msg)) => Ok(Some(msg)),
 			Poll::Ready(None) => Err(()),
 			Poll::Pending => Ok(None),
@@ -317,7 +438,7 @@ impl<M: Send + 'static> SubsystemContext for OverseerSubsystemContext<M> {
 	}
 
 	async fn recv(&mut self) -> SubsystemResult<FromOverseer<M>> {
-		self.rx.next().await
+		future::poll_fn(|cx| self.poll_from_overseer(cx)).await
 			.ok_or(SubsystemError::Context(
 				"No more messages in rx queue to process"
 				.to_owned()
@@ -343,13 +464,17 @@ impl<M: Send + 'static> SubsystemContext for OverseerSubsystemContext<M> {
 	}
 
 	async fn send_message(&mut self, msg: AllMessages) {
-		self.send_and_log_error(ToOverseer::SubsystemMessage(msg)).await
+		let source = self.label;
+		self.send_and_log_error(ToOverseer::SubsystemMessage { source, msg }).await
 	}
 
 	async fn send_messages<T>(&mut self, msgs: T)
 		where T: IntoIterator<Item = AllMessages> + Send, T::IntoIter: Send
 	{
-		let mut msgs = stream::iter(msgs.into_iter().map(ToOverseer::SubsystemMessage).map(Ok));
+		let source = self.label;
+		let mut msgs = stream::iter(
+			msgs.into_iter().map(|msg| Ok(ToOverseer::SubsystemMessage { source, msg }))
+		);
 		if self.tx.send_all(&mut msgs).await.is_err() {
 			tracing::debug!(
 				target: LOG_TARGET,
@@ -362,6 +487,24 @@ impl<M: Send + 'static> SubsystemContext for OverseerSubsystemContext<M> {
 }
 
 impl<M> OverseerSubsystemContext<M> {
+	/// Poll for the next signal or message, signals taking priority over messages.
+	fn poll_from_overseer(&mut self, cx: &mut Context<'_>) -> Poll<Option<FromOverseer<M>>> {
+		match self.signals.poll_next_unpin(cx) {
+			Poll::Ready(Some(signal)) => return Poll::Ready(Some(FromOverseer::Signal(signal))),
+			Poll::Ready(None) => return Poll::Ready(None),
+			Poll::Pending => {}
+		}
+
+		match self.messages.poll_next_unpin(cx) {
+			Poll::Ready(Some(msg)) => {
+				self.meter.on_dequeued();
+				Poll::Ready(Some(FromOverseer::Communication { msg }))
+			}
+			Poll::Ready(None) => Poll::Ready(None),
+			Poll::Pending => Poll::Pending,
+		}
+	}
+
 	async fn send_and_log_error(&mut self, msg: ToOverseer) {
 		if self.tx.send(msg).await.is_err() {
 			tracing::debug!(
@@ -373,6 +516,117 @@ impl<M> OverseerSubsystemContext<M> {
 	}
 }
 
+/// What the [`Overseer`] does when a [`Subsystem`] exits, with or without an error.
+///
+/// [`Subsystem`]: trait.Subsystem.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SupervisionPolicy {
+	/// Stop the `Overseer`, bringing the node down.
+	FailNode,
+	/// Carry on without the subsystem, dropping any messages sent to it.
+	Ignore,
+	/// Start the subsystem again once `initial_backoff` elapsed, doubling the
+	/// backoff on every consecutive restart, up to `max_backoff`.
+	///
+	/// Restarts stop being consecutive once the subsystem ran for `max_backoff`.
+	Restart {
+		/// The backoff before the first restart.
+		initial_backoff: Duration,
+		/// The longest backoff between restarts.
+		max_backoff: Duration,
+	},
+}
+
+impl Default for SupervisionPolicy {
+	fn default() -> Self {
+		SupervisionPolicy::FailNode
+	}
+}
+
+/// A [`Subsystem`] supervised according to a [`SupervisionPolicy`].
+///
+/// Any [`Subsystem`] can be passed to the [`Overseer`] as is, in which case its exit
+/// brings the node down. Wrapping the [`Subsystem`] lets the `Overseer` build it again
+/// with `factory` whenever the policy calls for a restart.
+///
+/// [`Subsystem`]: trait.Subsystem.html
+pub struct Supervised<F> {
+	factory: F,
+	policy: SupervisionPolicy,
+}
+
+impl<F> Supervised<F> {
+	/// Supervise the subsystems built by `factory` according to `policy`.
+	///
+	/// `factory` is called once on startup and once more on every restart.
+	pub fn new(factory: F, policy: SupervisionPolicy) -> Self {
+		Supervised { factory, policy }
+	}
+}
+
+/// Starts a new instance of a subsystem in the given context.
+pub type SubsystemStarter<M> = Box<dyn FnMut(OverseerSubsystemContext<M>) -> SpawnedSubsystem + Send>;
+
+/// Something which can be run by the [`Overseer`] as a subsystem handling messages of type `M`.
+///
+/// Implemented for every [`Subsystem`] and for [`Supervised`] subsystems.
+///
+/// [`Subsystem`]: trait.Subsystem.html
+pub trait SupervisedSubsystem<M> {
+	/// The policy of the subsystem and a way to start it as often as the policy requires.
+	fn into_starter(self) -> (SupervisionPolicy, SubsystemStarter<M>);
+}
+
+impl<M, S> SupervisedSubsystem<M> for S
+where
+	M: Send + 'static,
+	S: Subsystem<OverseerSubsystemContext<M>> + Send + 'static,
+{
+	fn into_starter(self) -> (SupervisionPolicy, SubsystemStarter<M>) {
+		let mut subsystem = Some(self);
+		let starter = move |ctx| {
+			subsystem.take()
+				.expect("subsystems failing the node are never restarted; qed")
+				.start(ctx)
+		};
+
+		(SupervisionPolicy::FailNode, Box::new(starter))
+	}
+}
+
+impl<M, F, S> SupervisedSubsystem<M> for Supervised<F>
+where
+	M: Send + 'static,
+	F: FnMut() -> S + Send + 'static,
+	S: Subsystem<OverseerSubsystemContext<M>>,
+{
+	fn into_starter(self) -> (SupervisionPolicy, SubsystemStarter<M>) {
+		let mut factory = self.factory;
+		(self.policy, Box::new(move |ctx| factory().start(ctx)))
+	}
+}
+
+/// What to do about a subsystem which exited.
+enum SubsystemExit {
+	FailNode,
+	Ignore,
+	Restart {
+		backoff: Duration,
+		/// Number of consecutive restarts, including this one.
+		restarts: u32,
+	},
+}
+
+/// Keeps what is needed to start a subsystem again.
+struct Supervisor<M> {
+	label: &'static str,
+	capacity: usize,
+	policy: SupervisionPolicy,
+	starter: SubsystemStarter<M>,
+	started: Instant,
+	restarts: u32,
+}
+
 /// A subsystem that we oversee.
 ///
 /// Ties together the [`Subsystem`] itself and it's running instance
@@ -382,15 +636,47 @@ impl<M> OverseerSubsystemContext<M> {
 /// [`Subsystem`]: trait.Subsystem.html
 struct OverseenSubsystem<M> {
 	instance: Option<SubsystemInstance<M>>,
+	supervisor: Supervisor<M>,
 }
 
 impl<M> OverseenSubsystem<M> {
+	/// Note that the running instance exited and decide what to do about it.
+	fn on_exit(&mut self) -> SubsystemExit {
+		self.instance = None;
+
+		let supervisor = &mut self.supervisor;
+		match supervisor.policy {
+			SupervisionPolicy::FailNode => SubsystemExit::FailNode,
+			SupervisionPolicy::Ignore => SubsystemExit::Ignore,
+			SupervisionPolicy::Restart { initial_backoff, max_backoff } => {
+				if supervisor.started.elapsed() >= max_backoff {
+					supervisor.restarts = 0;
+				}
+
+				let backoff = initial_backoff
+					.checked_mul(2u32.saturating_pow(supervisor.restarts))
+					.map_or(max_backoff, |backoff| backoff.min(max_backoff));
+				supervisor.restarts = supervisor.restarts.saturating_add(1);
+
+				SubsystemExit::Restart { backoff, restarts: supervisor.restarts }
+			}
+		}
+	}
+
 	/// Send a message to the wrapped subsystem.
 	///
 	/// If the inner `instance` is `None`, nothing is happening.
 	async fn send_message(&mut self, msg: M) -> SubsystemResult<()> {
 		if let Some(ref mut instance) = self.instance {
-			instance.tx.send(FromOverseer::Communication { msg }).await?;
+			let _timer = instance.metrics.time_send_blocked(instance.name);
+
+			// Counted before sending, so the subsystem can never dequeue a message
+			// which has not been counted yet.
+			instance.meter.on_queued();
+			if let Err(e) = instance.tx_bounded.send(msg).await {
+				instance.meter.on_dequeued();
+				return Err(e.into());
+			}
 		}
 
 		Ok(())
@@ -398,10 +684,23 @@ impl<M> OverseenSubsystem<M> {
 
 	/// Send a signal to the wrapped subsystem.
 	///
-	/// If the inner `instance` is `None`, nothing is happening.
+	/// If the inner `instance` is `None`, nothing is happening. A subsystem which is not supervised
+	/// with `FailNode` may have exited before we noticed, so failing to send to it is only logged:
+	/// its exit is handled once its handle resolves.
 	async fn send_signal(&mut self, signal: OverseerSignal) -> SubsystemResult<()> {
 		if let Some(ref mut instance) = self.instance {
-			instance.tx.send(FromOverseer::Signal(signal)).await?;
+			if let Err(e) = instance.tx_signal.send(signal).await {
+				if self.supervisor.policy == SupervisionPolicy::FailNode {
+					return Err(e.into());
+				}
+
+				tracing::warn!(
+					target: LOG_TARGET,
+					subsystem = self.supervisor.label,
+					err = ?e,
+					"failed to send a signal to the subsystem",
+				);
+			}
 		}
 
 		Ok(())
@@ -410,56 +709,19 @@ impl<M> OverseenSubsystem<M> {
 
 /// The `Overseer` itself.
 pub struct Overseer<S> {
-	/// A candidate validation subsystem.
-	candidate_validation_subsystem: OverseenSubsystem<CandidateValidationMessage>,
-
-	/// A candidate backing subsystem.
-	candidate_backing_subsystem: OverseenSubsystem<CandidateBackingMessage>,
-
-	/// A candidate selection subsystem.
-	candidate_selection_subsystem: OverseenSubsystem<CandidateSelectionMessage>,
-
-	/// A statement distribution subsystem.
-	statement_distribution_subsystem: OverseenSubsystem<StatementDistributionMessage>,
-
-	/// An availability distribution subsystem.
-	availability_distribution_subsystem: OverseenSubsystem<AvailabilityDistributionMessage>,
-
-	/// A bitfield signing subsystem.
-	bitfield_signing_subsystem: OverseenSubsystem<BitfieldSigningMessage>,
-
-	/// A bitfield distribution subsystem.
-	bitfield_distribution_subsystem: OverseenSubsystem<BitfieldDistributionMessage>,
-
-	/// A provisioner subsystem.
-	provisioner_subsystem: OverseenSubsystem<ProvisionerMessage>,
+	/// The running subsystems.
+	subsystems: OverseenSubsystems,
 
-	/// A PoV distribution subsystem.
-	pov_distribution_subsystem: OverseenSubsystem<PoVDistributionMessage>,
-
-	/// A runtime API subsystem.
-	runtime_api_subsystem: OverseenSubsystem<RuntimeApiMessage>,
-
-	/// An availability store subsystem.
-	availability_store_subsystem: OverseenSubsystem<AvailabilityStoreMessage>,
-
-	/// A network bridge subsystem.
-	network_bridge_subsystem: OverseenSubsystem<NetworkBridgeMessage>,
-
-	/// A Chain API subsystem.
-	chain_api_subsystem: OverseenSubsystem<ChainApiMessage>,
-
-	/// A Collation Generation subsystem.
-	collation_generation_subsystem: OverseenSubsystem<CollationGenerationMessage>,
-
-	/// A Collator Protocol subsystem.
-	collator_protocol_subsystem: OverseenSubsystem<CollatorProtocolMessage>,
+	/// Labels of the subsystems to restart, yielded once their backoff elapsed.
+	pending_restarts: FuturesUnordered<BoxFuture<'static, &'static str>>,
 
 	/// Spawner to spawn tasks to.
 	s: S,
 
 	/// Here we keep handles to spawned subsystems to be notified when they terminate.
-	running_subsystems: FuturesUnordered<BoxFuture<'static, SubsystemResult<()>>>,
+	///
+	/// Each handle yields the label of its subsystem.
+	running_subsystems: FuturesUnordered<BoxFuture<'static, &'static str>>,
 
 	/// Gather running subsystms' outbound streams into one.
 	running_subsystems_rx: StreamUnordered<mpsc::Receiver<ToOverseer>>,
@@ -473,468 +735,156 @@ pub struct Overseer<S> {
 	/// A set of leaves that `Overseer` starts working with.
 	///
 	/// Drained at the beginning of `run` and never used again.
-	leaves: Vec<(Hash, BlockNumber)>,
+	leaves: Vec<BlockInfo>,
 
 	/// The set of the "active leaves".
-	active_leaves: HashMap<Hash, BlockNumber>,
+	active_leaves: HashMap<Hash, ActivatedLeaf>,
+
+	/// The unfinalized blocks the `Overseer` has seen, as active leaves or their ancestry.
+	known_blocks: HashMap<Hash, BlockNumber>,
 
 	/// Various Prometheus metrics.
 	metrics: Metrics,
+
+	/// Records the signals and messages routed, if enabled.
+	recorder: Option<recorder::ActiveRecorder>,
 }
 
-/// This struct is passed as an argument to create a new instance of an [`Overseer`].
+/// The subsystems run by the [`Overseer`], each declared with the type of messages it handles.
 ///
-/// As any entity that satisfies the interface may act as a [`Subsystem`] this allows
-/// mocking in the test code:
-///
-/// Each [`Subsystem`] is supposed to implement some interface that is generic over
-/// message type that is specific to this [`Subsystem`]. At the moment not all
-/// subsystems are implemented and the rest can be mocked with the [`DummySubsystem`].
-pub struct AllSubsystems<
-	CV = (), CB = (), CS = (), SD = (), AD = (), BS = (), BD = (), P = (),
-	PoVD = (), RA = (), AS = (), NB = (), CA = (), CG = (), CP = ()
-> {
+/// Generates [`AllSubsystems`] and [`SpawnAllSubsystems`] as well, see `polkadot_overseer_gen`.
+/// Must declare the same subsystems as the declaration [`AllMessages`] is generated from.
+#[overlord(message_wrapper = AllMessages)]
+pub struct OverseenSubsystems {
 	/// A candidate validation subsystem.
-	pub candidate_validation: CV,
+	candidate_validation: CandidateValidationMessage,
 	/// A candidate backing subsystem.
-	pub candidate_backing: CB,
+	candidate_backing: CandidateBackingMessage,
 	/// A candidate selection subsystem.
-	pub candidate_selection: CS,
+	candidate_selection: CandidateSelectionMessage,
 	/// A statement distribution subsystem.
-	pub statement_distribution: SD,
+	statement_distribution: StatementDistributionMessage,
 	/// An availability distribution subsystem.
-	pub availability_distribution: AD,
+	availability_distribution: AvailabilityDistributionMessage,
 	/// A bitfield signing subsystem.
-	pub bitfield_signing: BS,
+	bitfield_signing: BitfieldSigningMessage,
 	/// A bitfield distribution subsystem.
-	pub bitfield_distribution: BD,
+	bitfield_distribution: BitfieldDistributionMessage,
 	/// A provisioner subsystem.
-	pub provisioner: P,
+	provisioner: ProvisionerMessage,
 	/// A PoV distribution subsystem.
-	pub pov_distribution: PoVD,
+	pov_distribution: PoVDistributionMessage,
 	/// A runtime API subsystem.
-	pub runtime_api: RA,
+	runtime_api: RuntimeApiMessage,
 	/// An availability store subsystem.
-	pub availability_store: AS,
+	availability_store: AvailabilityStoreMessage,
 	/// A network bridge subsystem.
-	pub network_bridge: NB,
+	network_bridge: NetworkBridgeMessage,
 	/// A Chain API subsystem.
-	pub chain_api: CA,
+	chain_api: ChainApiMessage,
 	/// A Collation Generation subsystem.
-	pub collation_generation: CG,
+	collation_generation: CollationGenerationMessage,
 	/// A Collator Protocol subsystem.
-	pub collator_protocol: CP,
+	collator_protocol: CollatorProtocolMessage,
 }
 
-impl<CV, CB, CS, SD, AD, BS, BD, P, PoVD, RA, AS, NB, CA, CG, CP>
-	AllSubsystems<CV, CB, CS, SD, AD, BS, BD, P, PoVD, RA, AS, NB, CA, CG, CP>
-{
-	/// Create a new instance of [`AllSubsystems`].
-	///
-	/// Each subsystem is set to [`DummySystem`].
-	///
-	///# Note
-	///
-	/// Because of a bug in rustc it is required that when calling this function,
-	/// you provide a "random" type for the first generic parameter:
-	///
-	/// ```
-	/// polkadot_overseer::AllSubsystems::<()>::dummy();
-	/// ```
-	pub fn dummy() -> AllSubsystems<
-		DummySubsystem,
-		DummySubsystem,
-		DummySubsystem,
-		DummySubsystem,
-		DummySubsystem,
-		DummySubsystem,
-		DummySubsystem,
-		DummySubsystem,
-		DummySubsystem,
-		DummySubsystem,
-		DummySubsystem,
-		DummySubsystem,
-		DummySubsystem,
-		DummySubsystem,
-		DummySubsystem
-	> {
-		AllSubsystems {
-			candidate_validation: DummySubsystem,
-			candidate_backing: DummySubsystem,
-			candidate_selection: DummySubsystem,
-			statement_distribution: DummySubsystem,
-			availability_distribution: DummySubsystem,
-			bitfield_signing: DummySubsystem,
-			bitfield_distribution: DummySubsystem,
-			provisioner: DummySubsystem,
-			pov_distribution: DummySubsystem,
-			runtime_api: DummySubsystem,
-			availability_store: DummySubsystem,
-			network_bridge: DummySubsystem,
-			chain_api: DummySubsystem,
-			collation_generation: DummySubsystem,
-			collator_protocol: DummySubsystem,
-		}
-	}
-
-	/// Replace the `candidate_validation` instance in `self`.
-	pub fn replace_candidate_validation<NEW>(
-		self,
-		candidate_validation: NEW,
-	) -> AllSubsystems<NEW, CB, CS, SD, AD, BS, BD, P, PoVD, RA, AS, NB, CA, CG, CP> {
-		AllSubsystems {
-			candidate_validation,
-			candidate_backing: self.candidate_backing,
-			candidate_selection: self.candidate_selection,
-			statement_distribution: self.statement_distribution,
-			availability_distribution: self.availability_distribution,
-			bitfield_signing: self.bitfield_signing,
-			bitfield_distribution: self.bitfield_distribution,
-			provisioner: self.provisioner,
-			pov_distribution: self.pov_distribution,
-			runtime_api: self.runtime_api,
-			availability_store: self.availability_store,
-			network_bridge: self.network_bridge,
-			chain_api: self.chain_api,
-			collation_generation: self.collation_generation,
-			collator_protocol: self.collator_protocol,
-		}
-	}
-
-	/// Replace the `candidate_backing` instance in `self`.
-	pub fn replace_candidate_backing<NEW>(
-		self,
-		candidate_backing: NEW,
-	) -> AllSubsystems<CV, NEW, CS, SD, AD, BS, BD, P, PoVD, RA, AS, NB, CA, CG, CP> {
-		AllSubsystems {
-			candidate_validation: self.candidate_validation,
-			candidate_backing,
-			candidate_selection: self.candidate_selection,
-			statement_distribution: self.statement_distribution,
-			availability_distribution: self.availability_distribution,
-			bitfield_signing: self.bitfield_signing,
-			bitfield_distribution: self.bitfield_distribution,
-			provisioner: self.provisioner,
-			pov_distribution: self.pov_distribution,
-			runtime_api: self.runtime_api,
-			availability_store: self.availability_store,
-			network_bridge: self.network_bridge,
-			chain_api: self.chain_api,
-			collation_generation: self.collation_generation,
-			collator_protocol: self.collator_protocol,
-		}
-	}
-
-	/// Replace the `candidate_selection` instance in `self`.
-	pub fn replace_candidate_selection<NEW>(
-		self,
-		candidate_selection: NEW,
-	) -> AllSubsystems<CV, CB, NEW, SD, AD, BS, BD, P, PoVD, RA, AS, NB, CA, CG, CP> {
-		AllSubsystems {
-			candidate_validation: self.candidate_validation,
-			candidate_backing: self.candidate_backing,
-			candidate_selection,
-			statement_distribution: self.statement_distribution,
-			availability_distribution: self.availability_distribution,
-			bitfield_signing: self.bitfield_signing,
-			bitfield_distribution: self.bitfield_distribution,
-			provisioner: self.provisioner,
-			pov_distribution: self.pov_distribution,
-			runtime_api: self.runtime_api,
-			availability_store: self.availability_store,
-			network_bridge: self.network_bridge,
-			chain_api: self.chain_api,
-			collation_generation: self.collation_generation,
-			collator_protocol: self.collator_protocol,
-		}
-	}
+/// Spawns subsystems on behalf of the [`Overseer`] and keeps track of the running ones.
+///
+/// Handed to [`SpawnAllSubsystems::spawn_all`] when the [`Overseer`] is created.
+pub struct SubsystemSpawner<'a, S> {
+	spawner: &'a mut S,
+	metrics: &'a Metrics,
+	running_subsystems: &'a mut FuturesUnordered<BoxFuture<'static, &'static str>>,
+	running_subsystems_rx: &'a mut StreamUnordered<mpsc::Receiver<ToOverseer>>,
+}
 
-	/// Replace the `statement_distribution` instance in `self`.
-	pub fn replace_statement_distribution<NEW>(
-		self,
-		statement_distribution: NEW,
-	) -> AllSubsystems<CV, CB, CS, NEW, AD, BS, BD, P, PoVD, RA, AS, NB, CA, CG, CP> {
-		AllSubsystems {
-			candidate_validation: self.candidate_validation,
-			candidate_backing: self.candidate_backing,
-			candidate_selection: self.candidate_selection,
-			statement_distribution,
-			availability_distribution: self.availability_distribution,
-			bitfield_signing: self.bitfield_signing,
-			bitfield_distribution: self.bitfield_distribution,
-			provisioner: self.provisioner,
-			pov_distribution: self.pov_distribution,
-			runtime_api: self.runtime_api,
-			availability_store: self.availability_store,
-			network_bridge: self.network_bridge,
-			chain_api: self.chain_api,
-			collation_generation: self.collation_generation,
-			collator_protocol: self.collator_protocol,
-		}
-	}
+impl<'a, S: SpawnNamed> SubsystemSpawner<'a, S> {
+	/// Spawn a subsystem, labelling its metrics with `label`.
+	fn spawn<M: Send + 'static>(
+		&mut self,
+		s: impl SupervisedSubsystem<M>,
+		capacity: usize,
+		label: &'static str,
+	) -> SubsystemResult<OverseenSubsystem<M>> {
+		let (policy, starter) = s.into_starter();
+		let mut subsystem = OverseenSubsystem {
+			instance: None,
+			supervisor: Supervisor {
+				label,
+				capacity,
+				policy,
+				starter,
+				started: Instant::now(),
+				restarts: 0,
+			},
+		};
 
-	/// Replace the `availability_distribution` instance in `self`.
-	pub fn replace_availability_distribution<NEW>(
-		self,
-		availability_distribution: NEW,
-	) -> AllSubsystems<CV, CB, CS, SD, NEW, BS, BD, P, PoVD, RA, AS, NB, CA, CG, CP> {
-		AllSubsystems {
-			candidate_validation: self.candidate_validation,
-			candidate_backing: self.candidate_backing,
-			candidate_selection: self.candidate_selection,
-			statement_distribution: self.statement_distribution,
-			availability_distribution,
-			bitfield_signing: self.bitfield_signing,
-			bitfield_distribution: self.bitfield_distribution,
-			provisioner: self.provisioner,
-			pov_distribution: self.pov_distribution,
-			runtime_api: self.runtime_api,
-			availability_store: self.availability_store,
-			network_bridge: self.network_bridge,
-			chain_api: self.chain_api,
-			collation_generation: self.collation_generation,
-			collator_protocol: self.collator_protocol,
-		}
-	}
+		self.start(&mut subsystem);
 
-	/// Replace the `bitfield_signing` instance in `self`.
-	pub fn replace_bitfield_signing<NEW>(
-		self,
-		bitfield_signing: NEW,
-	) -> AllSubsystems<CV, CB, CS, SD, AD, NEW, BD, P, PoVD, RA, AS, NB, CA, CG, CP> {
-		AllSubsystems {
-			candidate_validation: self.candidate_validation,
-			candidate_backing: self.candidate_backing,
-			candidate_selection: self.candidate_selection,
-			statement_distribution: self.statement_distribution,
-			availability_distribution: self.availability_distribution,
-			bitfield_signing,
-			bitfield_distribution: self.bitfield_distribution,
-			provisioner: self.provisioner,
-			pov_distribution: self.pov_distribution,
-			runtime_api: self.runtime_api,
-			availability_store: self.availability_store,
-			network_bridge: self.network_bridge,
-			chain_api: self.chain_api,
-			collation_generation: self.collation_generation,
-			collator_protocol: self.collator_protocol,
-		}
+		Ok(subsystem)
 	}
 
-	/// Replace the `bitfield_distribution` instance in `self`.
-	pub fn replace_bitfield_distribution<NEW>(
-		self,
-		bitfield_distribution: NEW,
-	) -> AllSubsystems<CV, CB, CS, SD, AD, BS, NEW, P, PoVD, RA, AS, NB, CA, CG, CP> {
-		AllSubsystems {
-			candidate_validation: self.candidate_validation,
-			candidate_backing: self.candidate_backing,
-			candidate_selection: self.candidate_selection,
-			statement_distribution: self.statement_distribution,
-			availability_distribution: self.availability_distribution,
-			bitfield_signing: self.bitfield_signing,
-			bitfield_distribution,
-			provisioner: self.provisioner,
-			pov_distribution: self.pov_distribution,
-			runtime_api: self.runtime_api,
-			availability_store: self.availability_store,
-			network_bridge: self.network_bridge,
-			chain_api: self.chain_api,
-			collation_generation: self.collation_generation,
-			collator_protocol: self.collator_protocol,
-		}
-	}
+	/// Start a subsystem which exited again, sending it `signal` before anything else.
+	fn restart<M: Send + 'static>(
+		&mut self,
+		subsystem: &mut OverseenSubsystem<M>,
+		signal: OverseerSignal,
+	) -> SubsystemResult<()> {
+		self.metrics.on_subsystem_restarted(subsystem.supervisor.label);
+		self.start(subsystem);
 
-	/// Replace the `provisioner` instance in `self`.
-	pub fn replace_provisioner<NEW>(
-		self,
-		provisioner: NEW,
-	) -> AllSubsystems<CV, CB, CS, SD, AD, BS, BD, NEW, PoVD, RA, AS, NB, CA, CG, CP> {
-		AllSubsystems {
-			candidate_validation: self.candidate_validation,
-			candidate_backing: self.candidate_backing,
-			candidate_selection: self.candidate_selection,
-			statement_distribution: self.statement_distribution,
-			availability_distribution: self.availability_distribution,
-			bitfield_signing: self.bitfield_signing,
-			bitfield_distribution: self.bitfield_distribution,
-			provisioner,
-			pov_distribution: self.pov_distribution,
-			runtime_api: self.runtime_api,
-			availability_store: self.availability_store,
-			network_bridge: self.network_bridge,
-			chain_api: self.chain_api,
-			collation_generation: self.collation_generation,
-			collator_protocol: self.collator_protocol,
+		if let Some(ref mut instance) = subsystem.instance {
+			instance.tx_signal.try_send(signal).map_err(|e| e.into_send_error())?;
 		}
-	}
 
-	/// Replace the `pov_distribution` instance in `self`.
-	pub fn replace_pov_distribution<NEW>(
-		self,
-		pov_distribution: NEW,
-	) -> AllSubsystems<CV, CB, CS, SD, AD, BS, BD, P, NEW, RA, AS, NB, CA, CG, CP> {
-		AllSubsystems {
-			candidate_validation: self.candidate_validation,
-			candidate_backing: self.candidate_backing,
-			candidate_selection: self.candidate_selection,
-			statement_distribution: self.statement_distribution,
-			availability_distribution: self.availability_distribution,
-			bitfield_signing: self.bitfield_signing,
-			bitfield_distribution: self.bitfield_distribution,
-			provisioner: self.provisioner,
-			pov_distribution,
-			runtime_api: self.runtime_api,
-			availability_store: self.availability_store,
-			network_bridge: self.network_bridge,
-			chain_api: self.chain_api,
-			collation_generation: self.collation_generation,
-			collator_protocol: self.collator_protocol,
-		}
+		Ok(())
 	}
 
-	/// Replace the `runtime_api` instance in `self`.
-	pub fn replace_runtime_api<NEW>(
-		self,
-		runtime_api: NEW,
-	) -> AllSubsystems<CV, CB, CS, SD, AD, BS, BD, P, PoVD, NEW, AS, NB, CA, CG, CP> {
-		AllSubsystems {
-			candidate_validation: self.candidate_validation,
-			candidate_backing: self.candidate_backing,
-			candidate_selection: self.candidate_selection,
-			statement_distribution: self.statement_distribution,
-			availability_distribution: self.availability_distribution,
-			bitfield_signing: self.bitfield_signing,
-			bitfield_distribution: self.bitfield_distribution,
-			provisioner: self.provisioner,
-			pov_distribution: self.pov_distribution,
-			runtime_api,
-			availability_store: self.availability_store,
-			network_bridge: self.network_bridge,
-			chain_api: self.chain_api,
-			collation_generation: self.collation_generation,
-			collator_protocol: self.collator_protocol,
-		}
-	}
+	fn start<M: Send + 'static>(&mut self, subsystem: &mut OverseenSubsystem<M>) {
+		let label = subsystem.supervisor.label;
 
-	/// Replace the `availability_store` instance in `self`.
-	pub fn replace_availability_store<NEW>(
-		self,
-		availability_store: NEW,
-	) -> AllSubsystems<CV, CB, CS, SD, AD, BS, BD, P, PoVD, RA, NEW, NB, CA, CG, CP> {
-		AllSubsystems {
-			candidate_validation: self.candidate_validation,
-			candidate_backing: self.candidate_backing,
-			candidate_selection: self.candidate_selection,
-			statement_distribution: self.statement_distribution,
-			availability_distribution: self.availability_distribution,
-			bitfield_signing: self.bitfield_signing,
-			bitfield_distribution: self.bitfield_distribution,
-			provisioner: self.provisioner,
-			pov_distribution: self.pov_distribution,
-			runtime_api: self.runtime_api,
-			availability_store,
-			network_bridge: self.network_bridge,
-			chain_api: self.chain_api,
-			collation_generation: self.collation_generation,
-			collator_protocol: self.collator_protocol,
-		}
-	}
+		let (signal_tx, signal_rx) = mpsc::channel(SIGNAL_CHANNEL_CAPACITY);
+		let (to_tx, to_rx) = mpsc::channel(subsystem.supervisor.capacity);
+		let (from_tx, from_rx) = mpsc::channel(CHANNEL_CAPACITY);
+		let meter = self.metrics.queue_meter(label);
+		let ctx = OverseerSubsystemContext {
+			label,
+			signals: signal_rx,
+			messages: to_rx,
+			meter: meter.clone(),
+			tx: from_tx,
+		};
+		let SpawnedSubsystem { future, name } = (subsystem.supervisor.starter)(ctx);
 
-	/// Replace the `network_bridge` instance in `self`.
-	pub fn replace_network_bridge<NEW>(
-		self,
-		network_bridge: NEW,
-	) -> AllSubsystems<CV, CB, CS, SD, AD, BS, BD, P, PoVD, RA, AS, NEW, CA, CG, CP> {
-		AllSubsystems {
-			candidate_validation: self.candidate_validation,
-			candidate_backing: self.candidate_backing,
-			candidate_selection: self.candidate_selection,
-			statement_distribution: self.statement_distribution,
-			availability_distribution: self.availability_distribution,
-			bitfield_signing: self.bitfield_signing,
-			bitfield_distribution: self.bitfield_distribution,
-			provisioner: self.provisioner,
-			pov_distribution: self.pov_distribution,
-			runtime_api: self.runtime_api,
-			availability_store: self.availability_store,
-			network_bridge,
-			chain_api: self.chain_api,
-			collation_generation: self.collation_generation,
-			collator_protocol: self.collator_protocol,
-		}
-	}
+		let (tx, rx) = oneshot::channel();
 
-	/// Replace the `chain_api` instance in `self`.
-	pub fn replace_chain_api<NEW>(
-		self,
-		chain_api: NEW,
-	) -> AllSubsystems<CV, CB, CS, SD, AD, BS, BD, P, PoVD, RA, AS, NB, NEW, CG, CP> {
-		AllSubsystems {
-			candidate_validation: self.candidate_validation,
-			candidate_backing: self.candidate_backing,
-			candidate_selection: self.candidate_selection,
-			statement_distribution: self.statement_distribution,
-			availability_distribution: self.availability_distribution,
-			bitfield_signing: self.bitfield_signing,
-			bitfield_distribution: self.bitfield_distribution,
-			provisioner: self.provisioner,
-			pov_distribution: self.pov_distribution,
-			runtime_api: self.runtime_api,
-			availability_store: self.availability_store,
-			network_bridge: self.network_bridge,
-			chain_api,
-			collation_generation: self.collation_generation,
-			collator_protocol: self.collator_protocol,
-		}
-	}
+		let fut = Box::pin(async move {
+			if let Err(e) = future.await {
+				tracing::error!(subsystem=name, err = ?e, "subsystem exited with error");
+			} else {
+				tracing::debug!(subsystem=name, "subsystem exited without an error");
+			}
+			let _ = tx.send(());
+		});
 
-	/// Replace the `collation_generation` instance in `self`.
-	pub fn replace_collation_generation<NEW>(
-		self,
-		collation_generation: NEW,
-	) -> AllSubsystems<CV, CB, CS, SD, AD, BS, BD, P, PoVD, RA, AS, NB, CA, NEW, CP> {
-		AllSubsystems {
-			candidate_validation: self.candidate_validation,
-			candidate_backing: self.candidate_backing,
-			candidate_selection: self.candidate_selection,
-			statement_distribution: self.statement_distribution,
-			availability_distribution: self.availability_distribution,
-			bitfield_signing: self.bitfield_signing,
-			bitfield_distribution: self.bitfield_distribution,
-			provisioner: self.provisioner,
-			pov_distribution: self.pov_distribution,
-			runtime_api: self.runtime_api,
-			availability_store: self.availability_store,
-			network_bridge: self.network_bridge,
-			chain_api: self.chain_api,
-			collation_generation,
-			collator_protocol: self.collator_protocol,
-		}
-	}
+		self.spawner.spawn(name, fut);
 
-	/// Replace the `collator_protocol` instance in `self`.
-	pub fn replace_collator_protocol<NEW>(
-		self,
-		collator_protocol: NEW,
-	) -> AllSubsystems<CV, CB, CS, SD, AD, BS, BD, P, PoVD, RA, AS, NB, CA, CG, NEW> {
-		AllSubsystems {
-			candidate_validation: self.candidate_validation,
-			candidate_backing: self.candidate_backing,
-			candidate_selection: self.candidate_selection,
-			statement_distribution: self.statement_distribution,
-			availability_distribution: self.availability_distribution,
-			bitfield_signing: self.bitfield_signing,
-			bitfield_distribution: self.bitfield_distribution,
-			provisioner: self.provisioner,
-			pov_distribution: self.pov_distribution,
-			runtime_api: self.runtime_api,
-			availability_store: self.availability_store,
-			network_bridge: self.network_bridge,
-			chain_api: self.chain_api,
-			collation_generation: self.collation_generation,
-			collator_protocol,
-		}
+		let _ = self.running_subsystems_rx.push(from_rx);
+		self.running_subsystems.push(Box::pin(rx.map(move |res| {
+			if res.is_err() {
+				tracing::error!(target: LOG_TARGET, subsystem = label, "subsystem panicked or was dropped");
+			}
+			label
+		})));
+
+		subsystem.supervisor.started = Instant::now();
+		subsystem.instance = Some(SubsystemInstance {
+			tx_signal: signal_tx,
+			tx_bounded: to_tx,
+			meter,
+			metrics: self.metrics.clone(),
+			name: label,
+		});
 	}
 }
 
@@ -944,6 +894,9 @@ struct MetricsInner {
 	activated_heads_total: prometheus::Counter<prometheus::U64>,
 	deactivated_heads_total: prometheus::Counter<prometheus::U64>,
 	messages_relayed_total: prometheus::Counter<prometheus::U64>,
+	queued_messages: prometheus::GaugeVec<prometheus::U64>,
+	send_blocked: prometheus::HistogramVec,
+	subsystem_restarts_total: prometheus::CounterVec<prometheus::U64>,
 }
 
 #[derive(Default, Clone)]
@@ -967,6 +920,25 @@ impl Metrics {
 			metrics.messages_relayed_total.inc();
 		}
 	}
+
+	/// Provide a meter of the messages queued for the given subsystem.
+	fn queue_meter(&self, subsystem: &'static str) -> QueueMeter {
+		QueueMeter {
+			queued: Arc::new(AtomicUsize::new(0)),
+			gauge: self.0.as_ref().map(|metrics| metrics.queued_messages.with_label_values(&[subsystem])),
+		}
+	}
+
+	fn on_subsystem_restarted(&self, subsystem: &'static str) {
+		if let Some(metrics) = &self.0 {
+			metrics.subsystem_restarts_total.with_label_values(&[subsystem]).inc();
+		}
+	}
+
+	/// Provide a timer for sending a message to the given subsystem which updates on drop.
+	fn time_send_blocked(&self, subsystem: &'static str) -> Option<metrics::prometheus::prometheus::HistogramTimer> {
+		self.0.as_ref().map(|metrics| metrics.send_blocked.with_label_values(&[subsystem]).start_timer())
+	}
 }
 
 impl metrics::Metrics for Metrics {
@@ -993,6 +965,36 @@ impl metrics::Metrics for Metrics {
 				)?,
 				registry,
 			)?,
+			queued_messages: prometheus::register(
+				prometheus::GaugeVec::new(
+					prometheus::Opts::new(
+						"parachain_subsystem_queued_messages",
+						"Number of messages sent to a subsystem and not yet received by it.",
+					),
+					&["subsystem_name"],
+				)?,
+				registry,
+			)?,
+			send_blocked: prometheus::register(
+				prometheus::HistogramVec::new(
+					prometheus::HistogramOpts::new(
+						"parachain_subsystem_send_blocked",
+						"Time the Overseer spent waiting to send a message to a subsystem.",
+					),
+					&["subsystem_name"],
+				)?,
+				registry,
+			)?,
+			subsystem_restarts_total: prometheus::register(
+				prometheus::CounterVec::new(
+					prometheus::Opts::new(
+						"parachain_subsystem_restarts_total",
+						"Number of times a subsystem was restarted after exiting.",
+					),
+					&["subsystem_name"],
+				)?,
+				registry,
+			)?,
 		};
 		Ok(Metrics(Some(metrics)))
 	}
@@ -1085,170 +1087,65 @@ where
 	/// #
 	/// # }); }
 	/// ```
-	pub fn new<CV, CB, CS, SD, AD, BS, BD, P, PoVD, RA, AS, NB, CA, CG, CP>(
+	pub fn new(
 		leaves: impl IntoIterator<Item = BlockInfo>,
-		all_subsystems: AllSubsystems<CV, CB, CS, SD, AD, BS, BD, P, PoVD, RA, AS, NB, CA, CG, CP>,
+		all_subsystems: impl SpawnAllSubsystems,
+		prometheus_registry: Option<&prometheus::Registry>,
+		s: S,
+	) -> SubsystemResult<(Self, OverseerHandler)> {
+		Self::with_channel_capacities(
+			leaves,
+			all_subsystems,
+			ChannelCapacities::default(),
+			prometheus_registry,
+			s,
+		)
+	}
+
+	/// Create a new instance of the `Overseer`, sizing the message channel of each
+	/// [`Subsystem`] according to `capacities`.
+	///
+	/// Signals are sent to every [`Subsystem`] on a separate channel, which is drained
+	/// before any messages, so a full message channel never delays them.
+	///
+	/// [`Subsystem`]: trait.Subsystem.html
+	pub fn with_channel_capacities(
+		leaves: impl IntoIterator<Item = BlockInfo>,
+		all_subsystems: impl SpawnAllSubsystems,
+		capacities: ChannelCapacities,
 		prometheus_registry: Option<&prometheus::Registry>,
 		mut s: S,
-	) -> SubsystemResult<(Self, OverseerHandler)>
-	where
-		CV: Subsystem<OverseerSubsystemContext<CandidateValidationMessage>> + Send,
-		CB: Subsystem<OverseerSubsystemContext<CandidateBackingMessage>> + Send,
-		CS: Subsystem<OverseerSubsystemContext<CandidateSelectionMessage>> + Send,
-		SD: Subsystem<OverseerSubsystemContext<StatementDistributionMessage>> + Send,
-		AD: Subsystem<OverseerSubsystemContext<AvailabilityDistributionMessage>> + Send,
-		BS: Subsystem<OverseerSubsystemContext<BitfieldSigningMessage>> + Send,
-		BD: Subsystem<OverseerSubsystemContext<BitfieldDistributionMessage>> + Send,
-		P: Subsystem<OverseerSubsystemContext<ProvisionerMessage>> + Send,
-		PoVD: Subsystem<OverseerSubsystemContext<PoVDistributionMessage>> + Send,
-		RA: Subsystem<OverseerSubsystemContext<RuntimeApiMessage>> + Send,
-		AS: Subsystem<OverseerSubsystemContext<AvailabilityStoreMessage>> + Send,
-		NB: Subsystem<OverseerSubsystemContext<NetworkBridgeMessage>> + Send,
-		CA: Subsystem<OverseerSubsystemContext<ChainApiMessage>> + Send,
-		CG: Subsystem<OverseerSubsystemContext<CollationGenerationMessage>> + Send,
-		CP: Subsystem<OverseerSubsystemContext<CollatorProtocolMessage>> + Send,
-	{
+	) -> SubsystemResult<(Self, OverseerHandler)> {
 		let (events_tx, events_rx) = mpsc::channel(CHANNEL_CAPACITY);
 
 		let handler = OverseerHandler {
 			events_tx: events_tx.clone(),
 		};
 
+		let metrics = <Metrics as metrics::Metrics>::register(prometheus_registry)?;
+
 		let mut running_subsystems_rx = StreamUnordered::new();
 		let mut running_subsystems = FuturesUnordered::new();
 
-		let candidate_validation_subsystem = spawn(
-			&mut s,
-			&mut running_subsystems,
-			&mut running_subsystems_rx,
-			all_subsystems.candidate_validation,
-		)?;
-
-		let candidate_backing_subsystem = spawn(
-			&mut s,
-			&mut running_subsystems,
-			&mut running_subsystems_rx,
-			all_subsystems.candidate_backing,
-		)?;
-
-		let candidate_selection_subsystem = spawn(
-			&mut s,
-			&mut running_subsystems,
-			&mut running_subsystems_rx,
-			all_subsystems.candidate_selection,
-		)?;
-
-		let statement_distribution_subsystem = spawn(
-			&mut s,
-			&mut running_subsystems,
-			&mut running_subsystems_rx,
-			all_subsystems.statement_distribution,
-		)?;
-
-		let availability_distribution_subsystem = spawn(
-			&mut s,
-			&mut running_subsystems,
-			&mut running_subsystems_rx,
-			all_subsystems.availability_distribution,
-		)?;
-
-		let bitfield_signing_subsystem = spawn(
-			&mut s,
-			&mut running_subsystems,
-			&mut running_subsystems_rx,
-			all_subsystems.bitfield_signing,
-		)?;
-
-		let bitfield_distribution_subsystem = spawn(
-			&mut s,
-			&mut running_subsystems,
-			&mut running_subsystems_rx,
-			all_subsystems.bitfield_distribution,
-		)?;
-
-		let provisioner_subsystem = spawn(
-			&mut s,
-			&mut running_subsystems,
-			&mut running_subsystems_rx,
-			all_subsystems.provisioner,
-		)?;
-
-		let pov_distribution_subsystem = spawn(
-			&mut s,
-			&mut running_subsystems,
-			&mut running_subsystems_rx,
-			all_subsystems.pov_distribution,
-		)?;
-
-		let runtime_api_subsystem = spawn(
-			&mut s,
-			&mut running_subsystems,
-			&mut running_subsystems_rx,
-			all_subsystems.runtime_api,
-		)?;
-
-		let availability_store_subsystem = spawn(
-			&mut s,
-			&mut running_subsystems,
-			&mut running_subsystems_rx,
-			all_subsystems.availability_store,
-		)?;
-
-		let network_bridge_subsystem = spawn(
-			&mut s,
-			&mut running_subsystems,
-			&mut running_subsystems_rx,
-			all_subsystems.network_bridge,
-		)?;
-
-		let chain_api_subsystem = spawn(
-			&mut s,
-			&mut running_subsystems,
-			&mut running_subsystems_rx,
-			all_subsystems.chain_api,
-		)?;
-
-		let collation_generation_subsystem = spawn(
-			&mut s,
-			&mut running_subsystems,
-			&mut running_subsystems_rx,
-			all_subsystems.collation_generation,
+		let subsystems = all_subsystems.spawn_all(
+			&mut SubsystemSpawner {
+				spawner: &mut s,
+				metrics: &metrics,
+				running_subsystems: &mut running_subsystems,
+				running_subsystems_rx: &mut running_subsystems_rx,
+			},
+			&capacities,
 		)?;
 
-
-		let collator_protocol_subsystem = spawn(
-			&mut s,
-			&mut running_subsystems,
-			&mut running_subsystems_rx,
-			all_subsystems.collator_protocol,
-		)?;
-
-		let leaves = leaves
-			.into_iter()
-			.map(|BlockInfo { hash, parent_hash: _, number }| (hash, number))
-			.collect();
+		let leaves = leaves.into_iter().collect();
 
 		let active_leaves = HashMap::new();
 
-		let metrics = <Metrics as metrics::Metrics>::register(prometheus_registry)?;
 		let activation_external_listeners = HashMap::new();
 
 		let this = Self {
-			candidate_validation_subsystem,
-			candidate_backing_subsystem,
-			candidate_selection_subsystem,
-			statement_distribution_subsystem,
-			availability_distribution_subsystem,
-			bitfield_signing_subsystem,
-			bitfield_distribution_subsystem,
-			provisioner_subsystem,
-			pov_distribution_subsystem,
-			runtime_api_subsystem,
-			availability_store_subsystem,
-			network_bridge_subsystem,
-			chain_api_subsystem,
-			collation_generation_subsystem,
-			collator_protocol_subsystem,
+			subsystems,
+			pending_restarts: FuturesUnordered::new(),
 			s,
 			running_subsystems,
 			running_subsystems_rx,
@@ -1256,29 +1153,32 @@ where
 			activation_external_listeners,
 			leaves,
 			active_leaves,
+			known_blocks: HashMap::new(),
 			metrics,
+			recorder: None,
 		};
 
 		Ok((this, handler))
 	}
 
+	/// Record every signal and message routed by the `Overseer` with `recorder`.
+	///
+	/// See the [`recorder`] module for what is recorded.
+	///
+	/// [`recorder`]: recorder/index.html
+	pub fn with_recorder(mut self, recorder: Recorder) -> Self {
+		self.recorder = Some(recorder.start(&self.s));
+		self
+	}
+
 	// Stop the overseer.
 	async fn stop(mut self) {
-		let _ = self.candidate_validation_subsystem.send_signal(OverseerSignal::Conclude).await;
-		let _ = self.candidate_backing_subsystem.send_signal(OverseerSignal::Conclude).await;
-		let _ = self.candidate_selection_subsystem.send_signal(OverseerSignal::Conclude).await;
-		let _ = self.statement_distribution_subsystem.send_signal(OverseerSignal::Conclude).await;
-		let _ = self.availability_distribution_subsystem.send_signal(OverseerSignal::Conclude).await;
-		let _ = self.bitfield_signing_subsystem.send_signal(OverseerSignal::Conclude).await;
-		let _ = self.bitfield_distribution_subsystem.send_signal(OverseerSignal::Conclude).await;
-		let _ = self.provisioner_subsystem.send_signal(OverseerSignal::Conclude).await;
-		let _ = self.pov_distribution_subsystem.send_signal(OverseerSignal::Conclude).await;
-		let _ = self.runtime_api_subsystem.send_signal(OverseerSignal::Conclude).await;
-		let _ = self.availability_store_subsystem.send_signal(OverseerSignal::Conclude).await;
-		let _ = self.network_bridge_subsystem.send_signal(OverseerSignal::Conclude).await;
-		let _ = self.chain_api_subsystem.send_signal(OverseerSignal::Conclude).await;
-		let _ = self.collator_protocol_subsystem.send_signal(OverseerSignal::Conclude).await;
-		let _ = self.collation_generation_subsystem.send_signal(OverseerSignal::Conclude).await;
+		if let Some(mut recorder) = self.recorder.take() {
+			recorder.record_signal(recorder::ALL_SUBSYSTEMS, &OverseerSignal::Conclude);
+			recorder.finish().await;
+		}
+
+		self.subsystems.conclude().await;
 
 		let mut stop_delay = Delay::new(Duration::from_secs(STOP_DELAY)).fuse();
 
@@ -1300,9 +1200,11 @@ where
 	pub async fn run(mut self) -> SubsystemResult<()> {
 		let mut update = ActiveLeavesUpdate::default();
 
-		for (hash, number) in std::mem::take(&mut self.leaves) {
-			update.activated.push(hash);
-			let _ = self.active_leaves.insert(hash, number);
+		for block in std::mem::take(&mut self.leaves) {
+			let leaf = self.note_activated(block);
+			let hash = leaf.hash;
+			update.activated.push(leaf.clone());
+			let _ = self.active_leaves.insert(hash, leaf);
 			self.on_head_activated(&hash);
 		}
 
@@ -1319,7 +1221,7 @@ where
 
 					match msg {
 						Event::MsgToSubsystem(msg) => {
-							self.route_message(msg).await;
+							self.route_message(msg, recorder::EXTERNAL).await;
 						}
 						Event::Stop => {
 							self.stop().await;
@@ -1344,7 +1246,7 @@ where
 					};
 
 					match msg {
-						ToOverseer::SubsystemMessage(msg) => self.route_message(msg).await,
+						ToOverseer::SubsystemMessage { source, msg } => self.route_message(msg, source).await,
 						ToOverseer::SpawnJob { name, s } => {
 							self.spawn_job(name, s);
 						}
@@ -1354,41 +1256,82 @@ where
 					}
 				},
 				res = self.running_subsystems.next().fuse() => {
-					let finished = if let Some(finished) = res {
-						finished
+					let label = if let Some(label) = res {
+						label
 					} else {
 						continue
 					};
 
-					tracing::error!(target: LOG_TARGET, subsystem = ?finished, "subsystem finished unexpectedly");
-					self.stop().await;
-					return finished;
+					match self.subsystems.on_exit(label) {
+						SubsystemExit::FailNode => {
+							tracing::error!(target: LOG_TARGET, subsystem = label, "subsystem finished unexpectedly");
+							self.stop().await;
+							return Ok(());
+						}
+						SubsystemExit::Ignore => {
+							tracing::warn!(target: LOG_TARGET, subsystem = label, "subsystem finished, carrying on without it");
+						}
+						SubsystemExit::Restart { backoff, restarts } => {
+							tracing::warn!(
+								target: LOG_TARGET,
+								subsystem = label,
+								?backoff,
+								consecutive_restarts = restarts,
+								"subsystem finished, restarting it",
+							);
+							self.pending_restarts.push(Box::pin(Delay::new(backoff).map(move |_| label)));
+						}
+					}
+				},
+				label = self.pending_restarts.select_next_some() => {
+					self.restart_subsystem(label)?;
 				},
 			}
 		}
 	}
 
+	/// Start a subsystem which exited again, replaying the current active leaves to it.
+	fn restart_subsystem(&mut self, label: &'static str) -> SubsystemResult<()> {
+		let update = ActiveLeavesUpdate {
+			activated: self.active_leaves.values().cloned().collect(),
+			..Default::default()
+		};
+
+		let mut spawner = SubsystemSpawner {
+			spawner: &mut self.s,
+			metrics: &self.metrics,
+			running_subsystems: &mut self.running_subsystems,
+			running_subsystems_rx: &mut self.running_subsystems_rx,
+		};
+
+		let signal = OverseerSignal::ActiveLeaves(update);
+		if let Some(recorder) = &mut self.recorder {
+			recorder.record_signal(label, &signal);
+		}
+
+		self.subsystems.restart(label, &mut spawner, signal)
+	}
+
 	#[tracing::instrument(level = "trace", skip(self), fields(subsystem = LOG_TARGET))]
 	async fn block_imported(&mut self, block: BlockInfo) -> SubsystemResult<()> {
 		let mut update = ActiveLeavesUpdate::default();
 
-		if let Some(number) = self.active_leaves.remove(&block.parent_hash) {
+		if let Some(parent) = self.active_leaves.remove(&block.parent_hash) {
 			if let Some(expected_parent_number) = block.number.checked_sub(1) {
-				debug_assert_eq!(expected_parent_number, number);
+				debug_assert_eq!(expected_parent_number, parent.number);
 			}
 			update.deactivated.push(block.parent_hash);
 			self.on_head_deactivated(&block.parent_hash);
 		}
 
-		match self.active_leaves.entry(block.hash) {
-			hash_map::Entry::Vacant(entry) => {
-				update.activated.push(block.hash);
-				let _ = entry.insert(block.number);
-				self.on_head_activated(&block.hash);
-			},
-			hash_map::Entry::Occupied(entry) => {
-				debug_assert_eq!(*entry.get(), block.number);
-			}
+		if let Some(leaf) = self.active_leaves.get(&block.hash) {
+			debug_assert_eq!(leaf.number, block.number);
+		} else {
+			let leaf = self.note_activated(block);
+			let hash = leaf.hash;
+			update.activated.push(leaf.clone());
+			let _ = self.active_leaves.insert(hash, leaf);
+			self.on_head_activated(&hash);
 		}
 
 		self.clean_up_external_listeners();
@@ -1402,14 +1345,15 @@ where
 	async fn block_finalized(&mut self, block: BlockInfo) -> SubsystemResult<()> {
 		let mut update = ActiveLeavesUpdate::default();
 
-		self.active_leaves.retain(|h, n| {
-			if *n <= block.number {
+		self.active_leaves.retain(|h, leaf| {
+			if leaf.number <= block.number {
 				update.deactivated.push(*h);
 				false
 			} else {
 				true
 			}
 		});
+		self.known_blocks.retain(|_, number| *number > block.number);
 
 		for deactivated in &update.deactivated {
 			self.on_head_deactivated(deactivated)
@@ -1421,81 +1365,51 @@ where
 			self.broadcast_signal(OverseerSignal::ActiveLeaves(update)).await?;
 		}
 
-		self.broadcast_signal(OverseerSignal::BlockFinalized(block.hash)).await?;
+		self.broadcast_signal(OverseerSignal::BlockFinalized(block.hash, block.number)).await?;
 
 		Ok(())
 	}
 
 	#[tracing::instrument(level = "trace", skip(self), fields(subsystem = LOG_TARGET))]
 	async fn broadcast_signal(&mut self, signal: OverseerSignal) -> SubsystemResult<()> {
-		self.candidate_validation_subsystem.send_signal(signal.clone()).await?;
-		self.candidate_backing_subsystem.send_signal(signal.clone()).await?;
-		self.candidate_selection_subsystem.send_signal(signal.clone()).await?;
-		self.statement_distribution_subsystem.send_signal(signal.clone()).await?;
-		self.availability_distribution_subsystem.send_signal(signal.clone()).await?;
-		self.bitfield_signing_subsystem.send_signal(signal.clone()).await?;
-		self.bitfield_distribution_subsystem.send_signal(signal.clone()).await?;
-		self.provisioner_subsystem.send_signal(signal.clone()).await?;
-		self.pov_distribution_subsystem.send_signal(signal.clone()).await?;
-		self.runtime_api_subsystem.send_signal(signal.clone()).await?;
-		self.availability_store_subsystem.send_signal(signal.clone()).await?;
-		self.network_bridge_subsystem.send_signal(signal.clone()).await?;
-		self.chain_api_subsystem.send_signal(signal.clone()).await?;
-		self.collator_protocol_subsystem.send_signal(signal.clone()).await?;
-		self.collation_generation_subsystem.send_signal(signal).await?;
+		if let Some(recorder) = &mut self.recorder {
+			recorder.record_signal(recorder::ALL_SUBSYSTEMS, &signal);
+		}
+
+		self.subsystems.broadcast_signal(signal).await
+	}
+
+	#[tracing::instrument(level = "trace", skip(self), fields(subsystem = LOG_TARGET))]
+	async fn route_message(&mut self, msg: AllMessages, source: &'static str) {
+		self.metrics.on_message_relayed();
+		if let Some(recorder) = &mut self.recorder {
+			recorder.record_message(source, OverseenSubsystems::destination(&msg), &msg);
+		}
 
-		Ok(())
+		self.subsystems.route_message(msg).await;
 	}
 
-	#[tracing::instrument(level = "trace", skip(self), fields(subsystem = LOG_TARGET))]
-	async fn route_message(&mut self, msg: AllMessages) {
-		self.metrics.on_message_relayed();
-		match msg {
-			AllMessages::CandidateValidation(msg) => {
-				let _ = self.candidate_validation_subsystem.send_message(msg).await;
-			},
-			AllMessages::CandidateBacking(msg) => {
-				let _ = self.candidate_backing_subsystem.send_message(msg).await;
-			},
-			AllMessages::CandidateSelection(msg) => {
-				let _ = self.candidate_selection_subsystem.send_message(msg).await;
-			},
-			AllMessages::StatementDistribution(msg) => {
-				let _ = self.statement_distribution_subsystem.send_message(msg).await;
-			},
-			AllMessages::AvailabilityDistribution(msg) => {
-				let _ = self.availability_distribution_subsystem.send_message(msg).await;
-			},
-			AllMessages::BitfieldDistribution(msg) => {
-				let _ = self.bitfield_distribution_subsystem.send_message(msg).await;
-			},
-			AllMessages::BitfieldSigning(msg) => {
-				let _ = self.bitfield_signing_subsystem.send_message(msg).await;
-			},
-			AllMessages::Provisioner(msg) => {
-				let _ = self.provisioner_subsystem.send_message(msg).await;
-			},
-			AllMessages::PoVDistribution(msg) => {
-				let _ = self.pov_distribution_subsystem.send_message(msg).await;
-			},
-			AllMessages::RuntimeApi(msg) => {
-				let _ = self.runtime_api_subsystem.send_message(msg).await;
-			},
-			AllMessages::AvailabilityStore(msg) => {
-				let _ = self.availability_store_subsystem.send_message(msg).await;
-			},
-			AllMessages::NetworkBridge(msg) => {
-				let _ = self.network_bridge_subsystem.send_message(msg).await;
-			},
-			AllMessages::ChainApi(msg) => {
-				let _ = self.chain_api_subsystem.send_message(msg).await;
-			},
-			AllMessages::CollationGeneration(msg) => {
-				let _ = self.collation_generation_subsystem.send_message(msg).await;
-			},
-			AllMessages::CollatorProtocol(msg) => {
-				let _ = self.collator_protocol_subsystem.send_message(msg).await;
-			},
+	/// Describe a block which becomes an active leaf, keeping only the part of its ancestry
+	/// which wasn't seen before, and note the block and its ancestry as seen.
+	fn note_activated(&mut self, block: BlockInfo) -> ActivatedLeaf {
+		let known_blocks = &mut self.known_blocks;
+		let new_ancestry: Vec<Hash> = block.ancestry
+			.into_iter()
+			.take_while(|hash| !known_blocks.contains_key(hash))
+			.collect();
+
+		for (depth, hash) in new_ancestry.iter().enumerate() {
+			let number = block.number.saturating_sub(depth as BlockNumber + 1);
+			known_blocks.insert(*hash, number);
+		}
+		known_blocks.insert(block.hash, block.number);
+
+		ActivatedLeaf {
+			hash: block.hash,
+			number: block.number,
+			parent_hash: block.parent_hash,
+			session_index: block.session_index,
+			new_ancestry,
 		}
 	}
 
@@ -1551,43 +1465,6 @@ where
 	}
 }
 
-fn spawn<S: SpawnNamed, M: Send + 'static>(
-	spawner: &mut S,
-	futures: &mut FuturesUnordered<BoxFuture<'static, SubsystemResult<()>>>,
-	streams: &mut StreamUnordered<mpsc::Receiver<ToOverseer>>,
-	s: impl Subsystem<OverseerSubsystemContext<M>>,
-) -> SubsystemResult<OverseenSubsystem<M>> {
-	let (to_tx, to_rx) = mpsc::channel(CHANNEL_CAPACITY);
-	let (from_tx, from_rx) = mpsc::channel(CHANNEL_CAPACITY);
-	let ctx = OverseerSubsystemContext { rx: to_rx, tx: from_tx };
-	let SpawnedSubsystem { future, name } = s.start(ctx);
-
-	let (tx, rx) = oneshot::channel();
-
-	let fut = Box::pin(async move {
-		if let Err(e) = future.await {
-			tracing::error!(subsystem=name, err = ?e, "subsystem exited with error");
-		} else {
-			tracing::debug!(subsystem=name, "subsystem exited without an error");
-		}
-		let _ = tx.send(());
-	});
-
-	spawner.spawn(name, fut);
-
-	let _ = streams.push(from_rx);
-	futures.push(Box::pin(rx.map(|e| { tracing::warn!(err = ?e, "dropping error"); Ok(()) })));
-
-	let instance = Some(SubsystemInstance {
-		tx: to_tx,
-	});
-
-	Ok(OverseenSubsystem {
-		instance,
-	})
-}
-
-
 #[cfg(test)]
 mod tests {
 	use std::sync::atomic;
@@ -1764,16 +1641,19 @@ mod tests {
 				hash: first_block_hash,
 				parent_hash: [0; 32].into(),
 				number: 1,
+				..Default::default()
 			};
 			let second_block = BlockInfo {
 				hash: second_block_hash,
 				parent_hash: first_block_hash,
 				number: 2,
+				..Default::default()
 			};
 			let third_block = BlockInfo {
 				hash: third_block_hash,
 				parent_hash: second_block_hash,
 				number: 3,
+				..Default::default()
 			};
 
 			let all_subsystems = AllSubsystems::<()>::dummy();
@@ -1926,16 +1806,22 @@ mod tests {
 				hash: first_block_hash,
 				parent_hash: [0; 32].into(),
 				number: 1,
+				session_index: Some(0),
+				..Default::default()
 			};
 			let second_block = BlockInfo {
 				hash: second_block_hash,
 				parent_hash: first_block_hash,
 				number: 2,
+				session_index: Some(0),
+				..Default::default()
 			};
 			let third_block = BlockInfo {
 				hash: third_block_hash,
 				parent_hash: second_block_hash,
 				number: 3,
+				session_index: Some(0),
+				..Default::default()
 			};
 
 			let (tx_5, mut rx_5) = mpsc::channel(64);
@@ -1960,13 +1846,15 @@ mod tests {
 			handler.block_imported(third_block).await;
 
 			let expected_heartbeats = vec![
-				OverseerSignal::ActiveLeaves(ActiveLeavesUpdate::start_work(first_block_hash)),
+				OverseerSignal::ActiveLeaves(ActiveLeavesUpdate::start_work(
+					ActivatedLeaf::new(first_block_hash, 1, [0; 32].into(), 0),
+				)),
 				OverseerSignal::ActiveLeaves(ActiveLeavesUpdate {
-					activated: [second_block_hash].as_ref().into(),
+					activated: [ActivatedLeaf::new(second_block_hash, 2, first_block_hash, 0)].as_ref().into(),
 					deactivated: [first_block_hash].as_ref().into(),
 				}),
 				OverseerSignal::ActiveLeaves(ActiveLeavesUpdate {
-					activated: [third_block_hash].as_ref().into(),
+					activated: [ActivatedLeaf::new(third_block_hash, 3, second_block_hash, 0)].as_ref().into(),
 					deactivated: [second_block_hash].as_ref().into(),
 				}),
 			];
@@ -2001,6 +1889,67 @@ mod tests {
 		});
 	}
 
+	// Tests that activated leaves carry the part of their ancestry the overseer didn't see before.
+	#[test]
+	fn activated_leaves_carry_new_ancestry() {
+		let spawner = sp_core::testing::TaskExecutor::new();
+
+		executor::block_on(async move {
+			let hashes: Vec<Hash> = (0..6u8).map(Hash::repeat_byte).collect();
+			let block = |number: usize, ancestry: Vec<Hash>| BlockInfo {
+				hash: hashes[number],
+				parent_hash: hashes[number - 1],
+				number: number as BlockNumber,
+				session_index: Some(7),
+				ancestry,
+			};
+
+			let (tx_5, mut rx_5) = mpsc::channel(64);
+			let all_subsystems = AllSubsystems::<()>::dummy()
+				.replace_candidate_validation(TestSubsystem5(tx_5));
+			let (overseer, mut handler) = Overseer::new(
+				vec![block(1, vec![])],
+				all_subsystems,
+				None,
+				spawner,
+			).unwrap();
+
+			let overseer_fut = overseer.run().fuse();
+			pin_mut!(overseer_fut);
+
+			// blocks 2 and 3 were never announced, block 1 is a leaf already.
+			handler.block_imported(block(4, vec![hashes[3], hashes[2], hashes[1]])).await;
+			handler.block_imported(block(5, vec![hashes[4], hashes[3], hashes[2], hashes[1]])).await;
+
+			let mut leaf_4 = ActivatedLeaf::new(hashes[4], 4, hashes[3], 7);
+			leaf_4.new_ancestry = vec![hashes[3], hashes[2]];
+
+			let expected_heartbeats = vec![
+				OverseerSignal::ActiveLeaves(ActiveLeavesUpdate::start_work(
+					ActivatedLeaf::new(hashes[1], 1, hashes[0], 7),
+				)),
+				OverseerSignal::ActiveLeaves(ActiveLeavesUpdate::start_work(leaf_4)),
+				OverseerSignal::ActiveLeaves(ActiveLeavesUpdate {
+					activated: [ActivatedLeaf::new(hashes[5], 5, hashes[4], 7)].as_ref().into(),
+					deactivated: [hashes[4]].as_ref().into(),
+				}),
+			];
+
+			let mut ss5_results = Vec::new();
+			while ss5_results.len() < expected_heartbeats.len() {
+				select! {
+					_ = overseer_fut => panic!("overseer exited before stopping"),
+					res = rx_5.next() => ss5_results.push(res.unwrap()),
+				}
+			}
+
+			assert_eq!(ss5_results, expected_heartbeats);
+
+			handler.stop().await;
+			overseer_fut.await.unwrap();
+		});
+	}
+
 	// Tests that starting with a defined set of leaves and receiving
 	// notifications on imported blocks triggers expected `StartWork` and `StopWork` heartbeats.
 	#[test]
@@ -2016,16 +1965,21 @@ mod tests {
 				hash: first_block_hash,
 				parent_hash: [0; 32].into(),
 				number: 1,
+				session_index: Some(0),
+				..Default::default()
 			};
 			let second_block = BlockInfo {
 				hash: second_block_hash,
 				parent_hash: [42; 32].into(),
 				number: 2,
+				session_index: Some(0),
+				..Default::default()
 			};
 			let third_block = BlockInfo {
 				hash: third_block_hash,
 				parent_hash: second_block_hash,
 				number: 3,
+				..Default::default()
 			};
 
 			let (tx_5, mut rx_5) = mpsc::channel(64);
@@ -2054,14 +2008,17 @@ mod tests {
 
 			let expected_heartbeats = vec![
 				OverseerSignal::ActiveLeaves(ActiveLeavesUpdate {
-					activated: [first_block_hash, second_block_hash].as_ref().into(),
+					activated: [
+						ActivatedLeaf::new(first_block_hash, 1, [0; 32].into(), 0),
+						ActivatedLeaf::new(second_block_hash, 2, [42; 32].into(), 0),
+					].as_ref().into(),
 					..Default::default()
 				}),
 				OverseerSignal::ActiveLeaves(ActiveLeavesUpdate {
 					deactivated: [first_block_hash, second_block_hash].as_ref().into(),
 					..Default::default()
 				}),
-				OverseerSignal::BlockFinalized(third_block_hash),
+				OverseerSignal::BlockFinalized(third_block_hash, 3),
 			];
 
 			loop {
@@ -2283,11 +2240,7 @@ mod tests {
 			pin_mut!(overseer_fut);
 
 			// send a signal to each subsystem
-			handler.block_imported(BlockInfo {
-				hash: Default::default(),
-				parent_hash: Default::default(),
-				number: Default::default(),
-			}).await;
+			handler.block_imported(BlockInfo::default()).await;
 
 			// send a msg to each subsystem
 			// except for BitfieldSigning as the message is not instantiable
@@ -2307,13 +2260,22 @@ mod tests {
 			handler.send_msg(AllMessages::NetworkBridge(test_network_bridge_msg())).await;
 			handler.send_msg(AllMessages::ChainApi(test_chain_api_msg())).await;
 
+			const NUM_SUBSYSTEMS: usize = 15;
+
+			// signals overtake messages, so wait for all the messages to be received
+			// before asking the subsystems to conclude.
+			while msgs_received.load(atomic::Ordering::SeqCst) < NUM_SUBSYSTEMS - 1 {
+				select! {
+					_ = overseer_fut => panic!("overseer exited before stopping"),
+					_ = Delay::new(Duration::from_millis(10)).fuse() => (),
+				}
+			}
+
 			// send a stop signal to each subsystems
 			handler.stop().await;
 
 			select! {
 				res = overseer_fut => {
-					const NUM_SUBSYSTEMS: usize = 15;
-
 					assert_eq!(stop_signals_received.load(atomic::Ordering::SeqCst), NUM_SUBSYSTEMS);
 					// x2 because of broadcast_signal on startup
 					assert_eq!(signals_received.load(atomic::Ordering::SeqCst), 2 * NUM_SUBSYSTEMS);
@@ -2326,4 +2288,358 @@ mod tests {
 			}
 		});
 	}
+
+	#[test]
+	fn signals_are_received_before_messages() {
+		let (mut signal_tx, signals) = mpsc::channel(SIGNAL_CHANNEL_CAPACITY);
+		let (mut message_tx, messages) = mpsc::channel(CHANNEL_CAPACITY);
+		let (tx, _rx) = mpsc::channel(CHANNEL_CAPACITY);
+		let meter = Metrics::default().queue_meter("candidate_validation");
+
+		let mut ctx = OverseerSubsystemContext::<CandidateValidationMessage> {
+			label: "candidate_validation",
+			signals,
+			messages,
+			meter: meter.clone(),
+			tx,
+		};
+
+		executor::block_on(async move {
+			meter.on_queued();
+			message_tx.send(test_candidate_validation_msg()).await.unwrap();
+			signal_tx.send(OverseerSignal::Conclude).await.unwrap();
+
+			match ctx.recv().await {
+				Ok(FromOverseer::Signal(OverseerSignal::Conclude)) => (),
+				other => panic!("expected the signal first, got {:?}", other.map(|_| ())),
+			}
+			assert_eq!(meter.queued.load(Ordering::Relaxed), 1);
+
+			match ctx.recv().await {
+				Ok(FromOverseer::Communication { .. }) => (),
+				other => panic!("expected the message, got {:?}", other.map(|_| ())),
+			}
+			assert_eq!(meter.queued.load(Ordering::Relaxed), 0);
+
+			assert!(matches!(ctx.try_recv().await, Ok(None)));
+
+			drop(signal_tx);
+			assert!(ctx.try_recv().await.is_err());
+		});
+	}
+
+	#[test]
+	fn channel_capacities_are_configurable() {
+		let spawner = sp_core::testing::TaskExecutor::new();
+
+		executor::block_on(async move {
+			let (s1_tx, mut s1_rx) = mpsc::channel::<usize>(64);
+			let all_subsystems = AllSubsystems::<()>::dummy()
+				.replace_candidate_validation(TestSubsystem1(s1_tx));
+			let capacities = ChannelCapacities {
+				candidate_validation: 1,
+				..Default::default()
+			};
+
+			let registry = prometheus::Registry::new();
+			let (overseer, mut handler) = Overseer::with_channel_capacities(
+				vec![],
+				all_subsystems,
+				capacities,
+				Some(&registry),
+				spawner,
+			).unwrap();
+			let overseer_fut = overseer.run().fuse();
+			pin_mut!(overseer_fut);
+
+			for _ in 0..3 {
+				handler.send_msg(AllMessages::CandidateValidation(test_candidate_validation_msg())).await;
+			}
+
+			let mut received = Vec::new();
+			while received.len() < 3 {
+				select! {
+					_ = overseer_fut => panic!("overseer exited before stopping"),
+					i = s1_rx.next() => received.push(i.unwrap()),
+				}
+			}
+			assert_eq!(received, vec![0, 1, 2]);
+
+			let families = registry.gather();
+			assert!(families.iter().any(|f| f.get_name() == "parachain_subsystem_queued_messages"));
+			assert!(families.iter().any(|f| f.get_name() == "parachain_subsystem_send_blocked"));
+
+			handler.stop().await;
+			overseer_fut.await.unwrap();
+		});
+	}
+
+	#[test]
+	fn restart_backoff_doubles_up_to_max() {
+		let mut subsystem = OverseenSubsystem::<CandidateValidationMessage> {
+			instance: None,
+			supervisor: Supervisor {
+				label: "candidate_validation",
+				capacity: CHANNEL_CAPACITY,
+				policy: SupervisionPolicy::Restart {
+					initial_backoff: Duration::from_secs(1),
+					max_backoff: Duration::from_secs(5),
+				},
+				starter: Box::new(|_| unreachable!()),
+				started: Instant::now(),
+				restarts: 0,
+			},
+		};
+
+		let backoffs: Vec<_> = (0..5).map(|_| match subsystem.on_exit() {
+			SubsystemExit::Restart { backoff, .. } => backoff.as_secs(),
+			_ => panic!("the policy is to restart"),
+		}).collect();
+		assert_eq!(backoffs, vec![1, 2, 4, 5, 5]);
+		assert_eq!(subsystem.supervisor.restarts, 5);
+	}
+
+	#[derive(Clone)]
+	struct ExitOnceSubsystem {
+		starts: Arc<atomic::AtomicUsize>,
+		signals_tx: mpsc::UnboundedSender<(usize, OverseerSignal)>,
+	}
+
+	impl<C> Subsystem<C> for ExitOnceSubsystem
+		where C: SubsystemContext<Message=CandidateValidationMessage>
+	{
+		fn start(self, mut ctx: C) -> SpawnedSubsystem {
+			let start = self.starts.fetch_add(1, atomic::Ordering::SeqCst);
+
+			SpawnedSubsystem {
+				name: "exit-once-subsystem",
+				future: Box::pin(async move {
+					loop {
+						match ctx.recv().await {
+							Ok(FromOverseer::Signal(OverseerSignal::Conclude)) | Err(_) => return Ok(()),
+							Ok(FromOverseer::Signal(signal)) => {
+								let _ = self.signals_tx.unbounded_send((start, signal));
+
+								// the first instance exits on its first signal.
+								if start == 0 {
+									return Ok(());
+								}
+							}
+							Ok(FromOverseer::Communication { .. }) => (),
+						}
+					}
+				}),
+			}
+		}
+	}
+
+	#[test]
+	fn restarted_subsystems_receive_active_leaves() {
+		let spawner = sp_core::testing::TaskExecutor::new();
+
+		executor::block_on(async move {
+			let starts = Arc::new(atomic::AtomicUsize::new(0));
+			let (signals_tx, mut signals_rx) = mpsc::unbounded();
+			let subsystem = ExitOnceSubsystem { starts: starts.clone(), signals_tx };
+
+			let all_subsystems = AllSubsystems::<()>::dummy()
+				.replace_candidate_validation(Supervised::new(
+					move || subsystem.clone(),
+					SupervisionPolicy::Restart {
+						initial_backoff: Duration::from_millis(10),
+						max_backoff: Duration::from_secs(1),
+					},
+				));
+
+			let leaf = BlockInfo {
+				hash: Hash::repeat_byte(1),
+				parent_hash: Hash::repeat_byte(0),
+				number: 1,
+				session_index: Some(0),
+				..Default::default()
+			};
+			let (overseer, mut handler) = Overseer::new(
+				vec![leaf],
+				all_subsystems,
+				None,
+				spawner,
+			).unwrap();
+			let overseer_fut = overseer.run().fuse();
+			pin_mut!(overseer_fut);
+
+			let mut signals = Vec::new();
+			while signals.len() < 2 {
+				select! {
+					_ = overseer_fut => panic!("overseer exited before stopping"),
+					signal = signals_rx.next() => signals.push(signal.unwrap()),
+				}
+			}
+
+			let active_leaves = OverseerSignal::ActiveLeaves(ActiveLeavesUpdate::start_work(
+				ActivatedLeaf::new(Hash::repeat_byte(1), 1, Hash::repeat_byte(0), 0),
+			));
+			assert_eq!(signals, vec![(0, active_leaves.clone()), (1, active_leaves)]);
+			assert_eq!(starts.load(atomic::Ordering::SeqCst), 2);
+
+			handler.stop().await;
+			overseer_fut.await.unwrap();
+		});
+	}
+
+	struct SignalsDroppedSubsystem {
+		dropped_tx: oneshot::Sender<()>,
+		release_rx: oneshot::Receiver<()>,
+	}
+
+	impl<C> Subsystem<C> for SignalsDroppedSubsystem
+		where C: SubsystemContext<Message=CandidateValidationMessage>
+	{
+		fn start(self, mut ctx: C) -> SpawnedSubsystem {
+			SpawnedSubsystem {
+				name: "signals-dropped-subsystem",
+				future: Box::pin(async move {
+					let _ = ctx.recv().await;
+
+					// stop receiving signals, but only finish once released, so the overseer
+					// can't have noticed the exit when it broadcasts the next signal.
+					drop(ctx);
+					let _ = self.dropped_tx.send(());
+					let _ = self.release_rx.await;
+
+					Ok(())
+				}),
+			}
+		}
+	}
+
+	#[test]
+	fn signals_are_broadcast_past_exited_ignored_subsystems() {
+		let spawner = sp_core::testing::TaskExecutor::new();
+
+		executor::block_on(async move {
+			let first_block_hash = [1; 32].into();
+			let second_block_hash = [2; 32].into();
+
+			let first_block = BlockInfo {
+				hash: first_block_hash,
+				parent_hash: [0; 32].into(),
+				number: 1,
+				session_index: Some(0),
+				..Default::default()
+			};
+			let second_block = BlockInfo {
+				hash: second_block_hash,
+				parent_hash: first_block_hash,
+				number: 2,
+				session_index: Some(0),
+				..Default::default()
+			};
+
+			let (dropped_tx, dropped_rx) = oneshot::channel();
+			let (release_tx, release_rx) = oneshot::channel();
+			let mut subsystem = Some(SignalsDroppedSubsystem { dropped_tx, release_rx });
+			let (tx_6, mut rx_6) = mpsc::channel(64);
+
+			let all_subsystems = AllSubsystems::<()>::dummy()
+				.replace_candidate_validation(Supervised::new(
+					move || subsystem.take().expect("the subsystem is never restarted"),
+					SupervisionPolicy::Ignore,
+				))
+				.replace_candidate_backing(TestSubsystem6(tx_6));
+			let (overseer, mut handler) = Overseer::new(
+				vec![first_block],
+				all_subsystems,
+				None,
+				spawner,
+			).unwrap();
+			let overseer_fut = overseer.run().fuse();
+			pin_mut!(overseer_fut);
+
+			select! {
+				_ = overseer_fut => panic!("overseer exited before stopping"),
+				dropped = dropped_rx.fuse() => dropped.unwrap(),
+			}
+
+			handler.block_imported(second_block).await;
+
+			let mut signals = Vec::new();
+			while signals.len() < 2 {
+				select! {
+					_ = overseer_fut => panic!("overseer exited before stopping"),
+					signal = rx_6.next() => signals.push(signal.unwrap()),
+				}
+			}
+
+			assert_eq!(signals, vec![
+				OverseerSignal::ActiveLeaves(ActiveLeavesUpdate::start_work(
+					ActivatedLeaf::new(first_block_hash, 1, [0; 32].into(), 0),
+				)),
+				OverseerSignal::ActiveLeaves(ActiveLeavesUpdate {
+					activated: [ActivatedLeaf::new(second_block_hash, 2, first_block_hash, 0)].as_ref().into(),
+					deactivated: [first_block_hash].as_ref().into(),
+				}),
+			]);
+
+			release_tx.send(()).unwrap();
+			handler.stop().await;
+			overseer_fut.await.unwrap();
+		});
+	}
+
+	#[test]
+	fn overseer_records_routed_traffic() {
+		let spawner = sp_core::testing::TaskExecutor::new();
+		let path = std::env::temp_dir().join(format!("overseer-recording-{}.jsonl", std::process::id()));
+
+		executor::block_on(async {
+			let (s1_tx, mut s1_rx) = mpsc::channel::<usize>(64);
+			let all_subsystems = AllSubsystems::<()>::dummy()
+				.replace_candidate_validation(TestSubsystem1(s1_tx));
+			let (overseer, mut handler) = Overseer::new(
+				vec![],
+				all_subsystems,
+				None,
+				spawner,
+			).unwrap();
+			let overseer = overseer.with_recorder(Recorder::create(&path).unwrap());
+			let overseer_fut = overseer.run().fuse();
+			pin_mut!(overseer_fut);
+
+			handler.send_msg(AllMessages::CandidateValidation(test_candidate_validation_msg())).await;
+			select! {
+				_ = overseer_fut => panic!("overseer exited before stopping"),
+				i = s1_rx.next() => assert_eq!(i, Some(0)),
+			}
+
+			handler.stop().await;
+			overseer_fut.await.unwrap();
+		});
+
+		let recording = std::io::BufReader::new(std::fs::File::open(&path).unwrap());
+		let events = recorder::read_recording(recording).collect::<std::io::Result<Vec<_>>>().unwrap();
+		let _ = std::fs::remove_file(&path);
+
+		let routes: Vec<_> = events.iter()
+			.map(|event| (event.source.as_str(), event.destination.as_str()))
+			.collect();
+		assert_eq!(routes, vec![
+			(recorder::OVERSEER, recorder::ALL_SUBSYSTEMS),
+			(recorder::EXTERNAL, "candidate_validation"),
+			(recorder::OVERSEER, recorder::ALL_SUBSYSTEMS),
+		]);
+
+		assert!(matches!(
+			&events[0].payload,
+			recorder::RecordedPayload::Signal(recorder::RecordedSignal::ActiveLeaves { .. })
+		));
+		assert!(matches!(
+			&events[1].payload,
+			recorder::RecordedPayload::Message(msg)
+				if msg.debug.starts_with("CandidateValidation(") && msg.encoded.is_none()
+		));
+		assert_eq!(
+			events[2].payload,
+			recorder::RecordedPayload::Signal(recorder::RecordedSignal::Conclude),
+		);
+	}
 }
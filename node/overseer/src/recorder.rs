@@ -0,0 +1,585 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Recording of the signals and messages routed by the [`Overseer`].
+//!
+//! A [`Recorder`] writes one JSON object per line, each describing a [`RecordedEvent`]. Events are
+//! handed to a background task which does the writing, so recording never blocks the overseer; if
+//! the task falls behind by more than a few thousand events, further events are dropped with a
+//! warning.
+//!
+//! Signals are recorded in full and can be replayed as they were sent. Messages are recorded in
+//! their debug representation, together with their SCALE encoding if they only carry data.
+//! Messages carrying channels to respond on, incoming requests or network events can't be
+//! reconstructed, so they are only recorded for inspection.
+//!
+//! [`Overseer`]: ../struct.Overseer.html
+
+use std::fs::File;
+use std::io::{self, BufRead, BufWriter, Write};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Instant;
+
+use futures::channel::{mpsc, oneshot};
+use futures::StreamExt;
+use parity_scale_codec::{Decode, Encode};
+use serde::{Deserialize, Serialize};
+
+use polkadot_node_primitives::{SignedFullStatement, SpawnNamed};
+use polkadot_primitives::v1::{
+	BackedCandidate, BlockNumber, CandidateDescriptor, CandidateReceipt, CollatorId, Hash,
+	Id as ParaId, PoV, SessionIndex, SignedAvailabilityBitfield, ValidatorSignature,
+};
+use polkadot_subsystem::{ActiveLeavesUpdate, ActivatedLeaf, OverseerSignal};
+use polkadot_subsystem::messages::{
+	AllMessages, CandidateBackingMessage, CandidateSelectionMessage, CollatorProtocolMessage,
+	BitfieldDistributionMessage, PoVDistributionMessage, ProvisionableData, ProvisionerMessage,
+	StatementDistributionMessage,
+};
+
+use super::LOG_TARGET;
+
+/// The source of signals, which are always sent by the overseer itself.
+pub const OVERSEER: &str = "overseer";
+
+/// The source of messages sent through an [`OverseerHandler`].
+///
+/// [`OverseerHandler`]: ../struct.OverseerHandler.html
+pub const EXTERNAL: &str = "external";
+
+/// The destination of signals broadcast to every subsystem.
+pub const ALL_SUBSYSTEMS: &str = "all";
+
+/// The number of events which may wait to be written before further events are dropped.
+const CHANNEL_CAPACITY: usize = 4096;
+
+/// A signal or message routed by the overseer.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedEvent {
+	/// Microseconds elapsed between the start of the recording and the event.
+	pub timestamp: u64,
+	/// The label of the subsystem which sent the event, [`OVERSEER`] or [`EXTERNAL`].
+	pub source: String,
+	/// The label of the subsystem the event was sent to, or [`ALL_SUBSYSTEMS`].
+	pub destination: String,
+	/// The event itself.
+	pub payload: RecordedPayload,
+}
+
+/// The content of a [`RecordedEvent`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RecordedPayload {
+	/// A signal.
+	Signal(RecordedSignal),
+	/// A message.
+	Message(RecordedMessage),
+}
+
+/// A recorded [`OverseerSignal`], with block hashes hex encoded.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RecordedSignal {
+	/// See [`OverseerSignal::ActiveLeaves`].
+	ActiveLeaves {
+		/// The activated leaves.
+		activated: Vec<RecordedLeaf>,
+		/// The deactivated leaves.
+		deactivated: Vec<String>,
+	},
+	/// See [`OverseerSignal::BlockFinalized`].
+	BlockFinalized(String, BlockNumber),
+	/// See [`OverseerSignal::Conclude`].
+	Conclude,
+}
+
+/// A recorded [`ActivatedLeaf`], with block hashes hex encoded.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedLeaf {
+	/// See [`ActivatedLeaf::hash`].
+	pub hash: String,
+	/// See [`ActivatedLeaf::number`].
+	pub number: BlockNumber,
+	/// See [`ActivatedLeaf::parent_hash`].
+	pub parent_hash: String,
+	/// See [`ActivatedLeaf::session_index`].
+	pub session_index: Option<SessionIndex>,
+	/// See [`ActivatedLeaf::new_ancestry`].
+	pub new_ancestry: Vec<String>,
+}
+
+/// A recorded message.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedMessage {
+	/// The debug representation of the message.
+	pub debug: String,
+	/// The hex encoded SCALE encoding of the message, if it can be replayed.
+	pub encoded: Option<String>,
+}
+
+impl From<&ActivatedLeaf> for RecordedLeaf {
+	fn from(leaf: &ActivatedLeaf) -> Self {
+		RecordedLeaf {
+			hash: encode_hex(leaf.hash.as_bytes()),
+			number: leaf.number,
+			parent_hash: encode_hex(leaf.parent_hash.as_bytes()),
+			session_index: leaf.session_index,
+			new_ancestry: leaf.new_ancestry.iter().map(|h| encode_hex(h.as_bytes())).collect(),
+		}
+	}
+}
+
+impl RecordedLeaf {
+	/// Turn the recorded leaf back into the leaf activated by the overseer.
+	pub fn into_leaf(self) -> io::Result<ActivatedLeaf> {
+		Ok(ActivatedLeaf {
+			hash: decode_hash(&self.hash)?,
+			number: self.number,
+			parent_hash: decode_hash(&self.parent_hash)?,
+			session_index: self.session_index,
+			new_ancestry: self.new_ancestry.iter().map(|h| decode_hash(h)).collect::<io::Result<_>>()?,
+		})
+	}
+}
+
+impl From<&OverseerSignal> for RecordedSignal {
+	fn from(signal: &OverseerSignal) -> Self {
+		match signal {
+			OverseerSignal::ActiveLeaves(update) => RecordedSignal::ActiveLeaves {
+				activated: update.activated.iter().map(Into::into).collect(),
+				deactivated: update.deactivated.iter().map(|h| encode_hex(h.as_bytes())).collect(),
+			},
+			OverseerSignal::BlockFinalized(hash, number) => {
+				RecordedSignal::BlockFinalized(encode_hex(hash.as_bytes()), *number)
+			}
+			OverseerSignal::Conclude => RecordedSignal::Conclude,
+		}
+	}
+}
+
+impl RecordedSignal {
+	/// Turn the recorded signal back into the signal sent by the overseer.
+	pub fn into_signal(self) -> io::Result<OverseerSignal> {
+		Ok(match self {
+			RecordedSignal::ActiveLeaves { activated, deactivated } => {
+				OverseerSignal::ActiveLeaves(ActiveLeavesUpdate {
+					activated: activated.into_iter().map(RecordedLeaf::into_leaf).collect::<io::Result<_>>()?,
+					deactivated: deactivated.iter().map(|h| decode_hash(h)).collect::<io::Result<_>>()?,
+				})
+			}
+			RecordedSignal::BlockFinalized(hash, number) => {
+				OverseerSignal::BlockFinalized(decode_hash(&hash)?, number)
+			}
+			RecordedSignal::Conclude => OverseerSignal::Conclude,
+		})
+	}
+}
+
+impl From<&AllMessages> for RecordedMessage {
+	fn from(msg: &AllMessages) -> Self {
+		RecordedMessage {
+			debug: format!("{:?}", msg),
+			encoded: ReplayableMessage::from_message(msg).map(|msg| encode_hex(&msg.encode())),
+		}
+	}
+}
+
+impl RecordedMessage {
+	/// Turn the recorded message back into the message routed by the overseer.
+	///
+	/// Returns `None` for messages which were only recorded in their debug representation.
+	pub fn into_message(self) -> io::Result<Option<AllMessages>> {
+		let encoded = match self.encoded {
+			Some(encoded) => decode_hex(&encoded)?,
+			None => return Ok(None),
+		};
+
+		ReplayableMessage::decode(&mut &encoded[..])
+			.map(|msg| Some(msg.into()))
+			.map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("invalid message: {}", e)))
+	}
+}
+
+/// The messages which only carry data, and so can be recorded in full and replayed.
+///
+/// Variants must only ever be appended, so that older recordings keep decoding.
+#[derive(Encode, Decode)]
+enum ReplayableMessage {
+	CandidateSelectionCollation(Hash, ParaId, CollatorId),
+	CandidateSelectionInvalid(Hash, CandidateReceipt),
+	CandidateBackingSecond(Hash, CandidateReceipt, PoV),
+	CandidateBackingStatement(Hash, SignedFullStatement),
+	CollatorProtocolCollateOn(ParaId),
+	CollatorProtocolDistributeCollation(CandidateReceipt, PoV),
+	CollatorProtocolReportCollator(CollatorId),
+	CollatorProtocolNoteGoodCollation(CollatorId),
+	StatementDistributionShare(Hash, SignedFullStatement),
+	BitfieldDistributionDistributeBitfield(Hash, SignedAvailabilityBitfield),
+	ProvisionerBitfield(Hash, Hash, SignedAvailabilityBitfield),
+	ProvisionerBackedCandidate(Hash, BackedCandidate),
+	ProvisionerDispute(Hash, Hash, ValidatorSignature),
+	PoVDistributionDistributePoV(Hash, CandidateDescriptor, PoV),
+}
+
+impl ReplayableMessage {
+	fn from_message(msg: &AllMessages) -> Option<Self> {
+		Some(match msg {
+			AllMessages::CandidateSelection(msg) => match msg {
+				CandidateSelectionMessage::Collation(relay_parent, para_id, collator) => {
+					Self::CandidateSelectionCollation(*relay_parent, *para_id, collator.clone())
+				}
+				CandidateSelectionMessage::Invalid(relay_parent, receipt) => {
+					Self::CandidateSelectionInvalid(*relay_parent, receipt.clone())
+				}
+			},
+			AllMessages::CandidateBacking(msg) => match msg {
+				CandidateBackingMessage::Second(relay_parent, receipt, pov) => {
+					Self::CandidateBackingSecond(*relay_parent, receipt.clone(), pov.clone())
+				}
+				CandidateBackingMessage::Statement(relay_parent, statement) => {
+					Self::CandidateBackingStatement(*relay_parent, statement.clone())
+				}
+				CandidateBackingMessage::GetBackedCandidates(..) => return None,
+			},
+			AllMessages::CollatorProtocol(msg) => match msg {
+				CollatorProtocolMessage::CollateOn(para_id) => Self::CollatorProtocolCollateOn(*para_id),
+				CollatorProtocolMessage::DistributeCollation(receipt, pov) => {
+					Self::CollatorProtocolDistributeCollation(receipt.clone(), pov.clone())
+				}
+				CollatorProtocolMessage::ReportCollator(collator) => {
+					Self::CollatorProtocolReportCollator(collator.clone())
+				}
+				CollatorProtocolMessage::NoteGoodCollation(collator) => {
+					Self::CollatorProtocolNoteGoodCollation(collator.clone())
+				}
+				_ => return None,
+			},
+			AllMessages::StatementDistribution(StatementDistributionMessage::Share(relay_parent, statement)) => {
+				Self::StatementDistributionShare(*relay_parent, statement.clone())
+			}
+			AllMessages::BitfieldDistribution(
+				BitfieldDistributionMessage::DistributeBitfield(relay_parent, bitfield),
+			) => Self::BitfieldDistributionDistributeBitfield(*relay_parent, bitfield.clone()),
+			AllMessages::Provisioner(ProvisionerMessage::ProvisionableData(relay_parent, data)) => {
+				match data {
+					ProvisionableData::Bitfield(hash, bitfield) => {
+						Self::ProvisionerBitfield(*relay_parent, *hash, bitfield.clone())
+					}
+					ProvisionableData::BackedCandidate(candidate) => {
+						Self::ProvisionerBackedCandidate(*relay_parent, candidate.clone())
+					}
+					ProvisionableData::Dispute(hash, signature) => {
+						Self::ProvisionerDispute(*relay_parent, *hash, signature.clone())
+					}
+					ProvisionableData::MisbehaviorReport(..) => return None,
+				}
+			}
+			AllMessages::PoVDistribution(PoVDistributionMessage::DistributePoV(relay_parent, descriptor, pov)) => {
+				Self::PoVDistributionDistributePoV(*relay_parent, descriptor.clone(), (**pov).clone())
+			}
+			_ => return None,
+		})
+	}
+}
+
+impl From<ReplayableMessage> for AllMessages {
+	fn from(msg: ReplayableMessage) -> Self {
+		use ReplayableMessage::*;
+
+		match msg {
+			CandidateSelectionCollation(relay_parent, para_id, collator) => {
+				CandidateSelectionMessage::Collation(relay_parent, para_id, collator).into()
+			}
+			CandidateSelectionInvalid(relay_parent, receipt) => {
+				CandidateSelectionMessage::Invalid(relay_parent, receipt).into()
+			}
+			CandidateBackingSecond(relay_parent, receipt, pov) => {
+				CandidateBackingMessage::Second(relay_parent, receipt, pov).into()
+			}
+			CandidateBackingStatement(relay_parent, statement) => {
+				CandidateBackingMessage::Statement(relay_parent, statement).into()
+			}
+			CollatorProtocolCollateOn(para_id) => CollatorProtocolMessage::CollateOn(para_id).into(),
+			CollatorProtocolDistributeCollation(receipt, pov) => {
+				CollatorProtocolMessage::DistributeCollation(receipt, pov).into()
+			}
+			CollatorProtocolReportCollator(collator) => CollatorProtocolMessage::ReportCollator(collator).into(),
+			CollatorProtocolNoteGoodCollation(collator) => {
+				CollatorProtocolMessage::NoteGoodCollation(collator).into()
+			}
+			StatementDistributionShare(relay_parent, statement) => {
+				StatementDistributionMessage::Share(relay_parent, statement).into()
+			}
+			BitfieldDistributionDistributeBitfield(relay_parent, bitfield) => {
+				BitfieldDistributionMessage::DistributeBitfield(relay_parent, bitfield).into()
+			}
+			ProvisionerBitfield(relay_parent, hash, bitfield) => ProvisionerMessage::ProvisionableData(
+				relay_parent,
+				ProvisionableData::Bitfield(hash, bitfield),
+			).into(),
+			ProvisionerBackedCandidate(relay_parent, candidate) => ProvisionerMessage::ProvisionableData(
+				relay_parent,
+				ProvisionableData::BackedCandidate(candidate),
+			).into(),
+			ProvisionerDispute(relay_parent, hash, signature) => ProvisionerMessage::ProvisionableData(
+				relay_parent,
+				ProvisionableData::Dispute(hash, signature),
+			).into(),
+			PoVDistributionDistributePoV(relay_parent, descriptor, pov) => {
+				PoVDistributionMessage::DistributePoV(relay_parent, descriptor, Arc::new(pov)).into()
+			}
+		}
+	}
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+	bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn decode_hex(hex: &str) -> io::Result<Vec<u8>> {
+	let invalid = || io::Error::new(io::ErrorKind::InvalidData, format!("invalid hex: {}", hex));
+
+	if hex.len() % 2 != 0 || !hex.is_ascii() {
+		return Err(invalid());
+	}
+
+	hex.as_bytes()
+		.chunks(2)
+		.map(|chunk| {
+			let chunk = std::str::from_utf8(chunk).map_err(|_| invalid())?;
+			u8::from_str_radix(chunk, 16).map_err(|_| invalid())
+		})
+		.collect()
+}
+
+fn decode_hash(hex: &str) -> io::Result<Hash> {
+	let bytes = decode_hex(hex)?;
+	if bytes.len() != Hash::len_bytes() {
+		return Err(io::Error::new(io::ErrorKind::InvalidData, format!("invalid block hash: {}", hex)));
+	}
+
+	Ok(Hash::from_slice(&bytes))
+}
+
+/// Where the signals and messages routed by the overseer are recorded, one JSON object per line.
+///
+/// Failing to record an event is logged, but doesn't affect the overseer otherwise.
+pub struct Recorder {
+	writer: Box<dyn Write + Send>,
+}
+
+impl Recorder {
+	/// Record to the file at `path`, truncating it if it exists.
+	pub fn create(path: impl AsRef<Path>) -> io::Result<Self> {
+		Ok(Self::new(BufWriter::new(File::create(path)?)))
+	}
+
+	/// Record to `writer`.
+	pub fn new(writer: impl Write + Send + 'static) -> Self {
+		Recorder { writer: Box::new(writer) }
+	}
+
+	/// Start writing the recorded events on a blocking task spawned with `spawner`.
+	pub(crate) fn start(self, spawner: &impl SpawnNamed) -> ActiveRecorder {
+		let (events_tx, events_rx) = mpsc::channel(CHANNEL_CAPACITY);
+		let (written_tx, written_rx) = oneshot::channel();
+
+		spawner.spawn_blocking("overseer-recorder", Box::pin(write_events(self.writer, events_rx, written_tx)));
+
+		ActiveRecorder {
+			events: events_tx,
+			written: written_rx,
+			started: Instant::now(),
+			dropped: 0,
+		}
+	}
+}
+
+async fn write_events(
+	mut writer: Box<dyn Write + Send>,
+	mut events: mpsc::Receiver<RecordedEvent>,
+	written: oneshot::Sender<()>,
+) {
+	while let Some(event) = events.next().await {
+		let res = serde_json::to_writer(&mut writer, &event)
+			.map_err(io::Error::from)
+			.and_then(|_| writer.write_all(b"\n"));
+
+		if let Err(e) = res {
+			tracing::warn!(target: LOG_TARGET, err = ?e, "Failed to record an event");
+		}
+	}
+
+	if let Err(e) = writer.flush() {
+		tracing::warn!(target: LOG_TARGET, err = ?e, "Failed to flush the recording");
+	}
+
+	let _ = written.send(());
+}
+
+/// The overseer's end of a started [`Recorder`].
+pub(crate) struct ActiveRecorder {
+	events: mpsc::Sender<RecordedEvent>,
+	written: oneshot::Receiver<()>,
+	started: Instant,
+	dropped: u64,
+}
+
+impl ActiveRecorder {
+	pub(crate) fn record_signal(&mut self, destination: &str, signal: &OverseerSignal) {
+		self.record(OVERSEER, destination, RecordedPayload::Signal(signal.into()));
+	}
+
+	pub(crate) fn record_message(&mut self, source: &str, destination: &str, msg: &AllMessages) {
+		self.record(source, destination, RecordedPayload::Message(msg.into()));
+	}
+
+	/// Wait for all recorded events to be written.
+	pub(crate) async fn finish(self) {
+		drop(self.events);
+		let _ = self.written.await;
+	}
+
+	fn record(&mut self, source: &str, destination: &str, payload: RecordedPayload) {
+		let event = RecordedEvent {
+			timestamp: self.started.elapsed().as_micros() as u64,
+			source: source.to_owned(),
+			destination: destination.to_owned(),
+			payload,
+		};
+
+		if self.events.try_send(event).is_err() {
+			self.dropped += 1;
+			tracing::warn!(
+				target: LOG_TARGET,
+				dropped = self.dropped,
+				"The recording fell behind, dropping an event",
+			);
+		}
+	}
+}
+
+/// Read back the events written by a [`Recorder`], in the order they were routed.
+pub fn read_recording(recording: impl BufRead) -> impl Iterator<Item = io::Result<RecordedEvent>> {
+	recording.lines().map(|line| line.and_then(|line| serde_json::from_str(&line).map_err(Into::into)))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::sync::{Arc, Mutex};
+
+	#[derive(Clone, Default)]
+	struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+	impl Write for SharedBuffer {
+		fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+			self.0.lock().unwrap().write(buf)
+		}
+
+		fn flush(&mut self) -> io::Result<()> {
+			Ok(())
+		}
+	}
+
+	#[test]
+	fn recorded_signals_round_trip() {
+		let buffer = SharedBuffer::default();
+		let mut recorder = Recorder::new(buffer.clone()).start(&sp_core::testing::TaskExecutor::new());
+
+		let signals = vec![
+			OverseerSignal::ActiveLeaves(ActiveLeavesUpdate {
+				activated: [
+					ActivatedLeaf::new(Hash::repeat_byte(1), 1, Hash::repeat_byte(0), 1),
+					ActivatedLeaf {
+						hash: Hash::repeat_byte(0xab),
+						number: 5,
+						parent_hash: Hash::repeat_byte(0xaa),
+						session_index: Some(2),
+						new_ancestry: vec![Hash::repeat_byte(0xaa), Hash::repeat_byte(0xa9)],
+					},
+				][..].into(),
+				deactivated: [Hash::repeat_byte(2)][..].into(),
+			}),
+			OverseerSignal::BlockFinalized(Hash::repeat_byte(3), 3),
+			OverseerSignal::Conclude,
+		];
+		for signal in &signals {
+			recorder.record_signal(ALL_SUBSYSTEMS, signal);
+		}
+		futures::executor::block_on(recorder.finish());
+
+		let recording = buffer.0.lock().unwrap().clone();
+		let events = read_recording(&recording[..]).collect::<io::Result<Vec<_>>>().unwrap();
+		assert_eq!(events.len(), signals.len());
+
+		for (event, signal) in events.into_iter().zip(signals) {
+			assert_eq!(event.source, OVERSEER);
+			assert_eq!(event.destination, ALL_SUBSYSTEMS);
+			match event.payload {
+				RecordedPayload::Signal(recorded) => assert_eq!(recorded.into_signal().unwrap(), signal),
+				RecordedPayload::Message(_) => panic!("only signals were recorded"),
+			}
+		}
+	}
+
+	#[test]
+	fn data_only_messages_round_trip() {
+		let buffer = SharedBuffer::default();
+		let mut recorder = Recorder::new(buffer.clone()).start(&sp_core::testing::TaskExecutor::new());
+
+		let (tx, _rx) = oneshot::channel();
+		let messages: Vec<AllMessages> = vec![
+			CandidateSelectionMessage::Invalid(Hash::repeat_byte(1), Default::default()).into(),
+			CandidateBackingMessage::GetBackedCandidates(Hash::repeat_byte(2), tx).into(),
+			CollatorProtocolMessage::CollateOn(ParaId::from(5u32)).into(),
+		];
+		for msg in &messages {
+			recorder.record_message(EXTERNAL, "candidate_selection", msg);
+		}
+		futures::executor::block_on(recorder.finish());
+
+		let recording = buffer.0.lock().unwrap().clone();
+		let replayed = read_recording(&recording[..])
+			.map(|event| match event.unwrap().payload {
+				RecordedPayload::Message(msg) => msg.into_message().unwrap(),
+				RecordedPayload::Signal(_) => panic!("only messages were recorded"),
+			})
+			.collect::<Vec<_>>();
+		assert_eq!(replayed.len(), messages.len());
+
+		assert!(matches!(
+			&replayed[0],
+			Some(AllMessages::CandidateSelection(CandidateSelectionMessage::Invalid(h, _)))
+				if *h == Hash::repeat_byte(1)
+		));
+		// Messages carrying a channel to respond on can't be replayed.
+		assert!(replayed[1].is_none());
+		assert!(matches!(
+			&replayed[2],
+			Some(AllMessages::CollatorProtocol(CollatorProtocolMessage::CollateOn(id))) if *id == ParaId::from(5u32)
+		));
+	}
+
+	#[test]
+	fn invalid_hashes_are_rejected() {
+		assert!(RecordedSignal::BlockFinalized("00".into(), 1).into_signal().is_err());
+		assert!(RecordedSignal::BlockFinalized("zz".repeat(32), 1).into_signal().is_err());
+		assert_eq!(
+			RecordedSignal::BlockFinalized("0a".repeat(32), 10).into_signal().unwrap(),
+			OverseerSignal::BlockFinalized(Hash::repeat_byte(10), 10),
+		);
+	}
+}
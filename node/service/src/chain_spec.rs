@@ -780,9 +780,15 @@ fn rococo_staging_testnet_config_genesis(wasm_binary: &[u8]) -> rococo_runtime::
 				max_pov_size: 50 * 1024 * 1024,
 				max_head_data_size: 32 * 1024,
 				group_rotation_frequency: 10,
+				max_downward_message_size: 1024,
+				max_downward_queue_count: 64,
+				max_downward_queue_size: 64 * 1024,
+				// approximately 20ms.
+				ump_max_individual_weight: 20 * 1_000_000_000,
 				..Default::default()
 			},
 		}),
+		parachains_hrmp: Some(Default::default()),
 	}
 }
 
@@ -1236,12 +1242,16 @@ pub fn rococo_testnet_genesis(
 				max_upward_queue_count: 8,
 				max_upward_queue_size: 8 * 1024,
 				max_downward_message_size: 1024,
+				max_downward_queue_count: 64,
+				max_downward_queue_size: 64 * 1024,
 				// this is approximatelly 4ms.
 				//
 				// Same as `4 * frame_support::weights::WEIGHT_PER_MILLIS`. We don't bother with
 				// an import since that's a made up number and should be replaced with a constant
 				// obtained by benchmarking anyway.
 				preferred_dispatchable_upward_messages_step_weight: 4 * 1_000_000_000,
+				// approximately 20ms, same caveat as above.
+				ump_max_individual_weight: 20 * 1_000_000_000,
 				max_upward_message_size: 1024,
 				max_upward_message_num_per_candidate: 5,
 				hrmp_open_request_ttl: 5,
@@ -1258,6 +1268,7 @@ pub fn rococo_testnet_genesis(
 				..Default::default()
 			},
 		}),
+		parachains_hrmp: Some(Default::default()),
 	}
 }
 
@@ -26,12 +26,13 @@ use grandpa::{self, FinalityProofProvider as GrandpaFinalityProofProvider};
 #[cfg(feature = "full-node")]
 use {
 	std::convert::TryInto,
+	std::path::PathBuf,
 	std::time::Duration,
 
 	tracing::info,
 	polkadot_node_core_av_store::Config as AvailabilityConfig,
 	polkadot_node_core_proposer::ProposerFactory,
-	polkadot_overseer::{AllSubsystems, BlockInfo, Overseer, OverseerHandler},
+	polkadot_overseer::{AllSubsystems, BlockInfo, Overseer, OverseerHandler, Recorder},
 	polkadot_primitives::v1::ParachainHost,
 	sc_authority_discovery::Service as AuthorityDiscoveryService,
 	sp_blockchain::HeaderBackend,
@@ -50,6 +51,7 @@ use service::RpcHandlers;
 pub use self::client::{AbstractClient, Client, ClientHandle, ExecuteWithClient, RuntimeApiCollection};
 pub use chain_spec::{PolkadotChainSpec, KusamaChainSpec, WestendChainSpec, RococoChainSpec};
 pub use consensus_common::{Proposal, SelectChain, BlockImport, RecordProof, block_validation::Chain};
+pub use polkadot_node_network_protocol::peer_set::{PeerSetConfig, PeerSetsConfig};
 pub use polkadot_parachain::wasm_executor::IsolationStrategy;
 pub use polkadot_primitives::v1::{Block, BlockId, CollatorId, Hash, Id as ParaId};
 pub use sc_client_api::{Backend, ExecutionStrategy, CallExecutor};
@@ -289,10 +291,12 @@ fn real_overseer<Spawner, RuntimeClient>(
 	_: AvailabilityConfig,
 	_: Arc<sc_network::NetworkService<Block, Hash>>,
 	_: AuthorityDiscoveryService,
+	_: (),
 	registry: Option<&Registry>,
 	spawner: Spawner,
 	_: IsCollator,
 	_: IsolationStrategy,
+	_: PeerSetsConfig,
 ) -> Result<(Overseer<Spawner>, OverseerHandler), Error>
 where
 	RuntimeClient: 'static + ProvideRuntimeApi<Block> + HeaderBackend<Block>,
@@ -315,10 +319,12 @@ fn real_overseer<Spawner, RuntimeClient>(
 	availability_config: AvailabilityConfig,
 	network_service: Arc<sc_network::NetworkService<Block, Hash>>,
 	authority_discovery: AuthorityDiscoveryService,
+	request_multiplexer: polkadot_network_bridge::RequestMultiplexer,
 	registry: Option<&Registry>,
 	spawner: Spawner,
 	is_collator: IsCollator,
 	isolation_strategy: IsolationStrategy,
+	peer_sets: PeerSetsConfig,
 ) -> Result<(Overseer<Spawner>, OverseerHandler), Error>
 where
 	RuntimeClient: 'static + ProvideRuntimeApi<Block> + HeaderBackend<Block>,
@@ -326,6 +332,8 @@ where
 	Spawner: 'static + SpawnNamed + Clone + Unpin,
 {
 	use polkadot_node_subsystem_util::metrics::Metrics;
+	use polkadot_overseer::{Supervised, SupervisionPolicy};
+	use std::time::Duration;
 
 	use polkadot_availability_distribution::AvailabilityDistributionSubsystem;
 	use polkadot_node_core_av_store::AvailabilityStoreSubsystem;
@@ -352,9 +360,17 @@ where
 			availability_config,
 			Metrics::register(registry)?,
 		)?,
-		bitfield_distribution: BitfieldDistributionSubsystem::new(
-			Metrics::register(registry)?,
-		),
+		bitfield_distribution: {
+			let metrics: polkadot_availability_bitfield_distribution::Metrics = Metrics::register(registry)?;
+			// Gossiping bitfields is not worth taking the node down for.
+			Supervised::new(
+				move || BitfieldDistributionSubsystem::new(metrics.clone()),
+				SupervisionPolicy::Restart {
+					initial_backoff: Duration::from_secs(1),
+					max_backoff: Duration::from_secs(60),
+				},
+			)
+		},
 		bitfield_signing: BitfieldSigningSubsystem::new(
 			spawner.clone(),
 			keystore.clone(),
@@ -394,6 +410,10 @@ where
 		network_bridge: NetworkBridgeSubsystem::new(
 			network_service,
 			authority_discovery,
+			request_multiplexer,
+			keystore.clone(),
+			Metrics::register(registry)?,
+			peer_sets,
 		),
 		pov_distribution: PoVDistributionSubsystem::new(
 			Metrics::register(registry)?,
@@ -475,6 +495,8 @@ pub fn new_full<RuntimeApi, Executor>(
 	is_collator: IsCollator,
 	grandpa_pause: Option<(u32, u32)>,
 	isolation_strategy: IsolationStrategy,
+	peer_sets: PeerSetsConfig,
+	overseer_recording: Option<PathBuf>,
 ) -> Result<NewFull<Arc<FullClient<RuntimeApi, Executor>>>, Error>
 	where
 		RuntimeApi: ConstructRuntimeApi<Block, FullClient<RuntimeApi, Executor>> + Send + Sync + 'static,
@@ -508,6 +530,25 @@ pub fn new_full<RuntimeApi, Executor>(
 	#[cfg(feature = "real-overseer")]
 	config.network.notifications_protocols.extend(polkadot_network_bridge::notifications_protocol_info());
 
+	// The network bridge enforces the slots of each peer-set, but the connections are made by the
+	// network, which has to allow as many of them in each direction as any peer-set does.
+	#[cfg(feature = "real-overseer")]
+	{
+		config.network.in_peers = config.network.in_peers.max(peer_sets.max_in_peers());
+		config.network.out_peers = config.network.out_peers.max(peer_sets.max_out_peers());
+	}
+
+	// The request/response protocols have to be registered with the network before it is built,
+	// while the multiplexer of their incoming requests is handed to the network bridge.
+	#[cfg(feature = "real-overseer")]
+	let request_multiplexer = {
+		let (multiplexer, configs) = polkadot_network_bridge::RequestMultiplexer::new();
+		config.network.request_response_protocols.extend(configs);
+		multiplexer
+	};
+	#[cfg(not(feature = "real-overseer"))]
+	let request_multiplexer = ();
+
 	let (network, network_status_sinks, system_rpc_tx, network_starter) =
 		service::build_network(service::BuildNetworkParams {
 			config: &config,
@@ -554,13 +595,21 @@ pub fn new_full<RuntimeApi, Executor>(
 		.unwrap_or_else(|_| vec![])
 		.into_iter()
 		.filter_map(|hash| {
-			let number = client.number(hash).ok()??;
-			let parent_hash = client.header(&BlockId::Hash(hash)).ok()??.parent_hash;
+			let header = match client.header(&BlockId::Hash(hash)) {
+				Ok(Some(header)) => header,
+				res => {
+					tracing::warn!(?hash, ?res, "Failed to fetch the header of a leaf, not activating it");
+					return None;
+				}
+			};
+			let session_index = polkadot_overseer::session_index_for_child(&*client, hash);
 
 			Some(BlockInfo {
 				hash,
-				parent_hash,
-				number,
+				parent_hash: header.parent_hash,
+				number: header.number,
+				session_index,
+				ancestry: Vec::new(),
 			})
 		})
 		.collect();
@@ -606,28 +655,30 @@ pub fn new_full<RuntimeApi, Executor>(
 			availability_config?,
 			network.clone(),
 			authority_discovery_service,
+			request_multiplexer,
 			prometheus_registry.as_ref(),
 			spawner,
 			is_collator,
 			isolation_strategy,
+			peer_sets,
 		)?;
+		let overseer = match overseer_recording {
+			Some(path) => overseer.with_recorder(Recorder::create(&path).map_err(|e| {
+				Error::Other(format!("Failed to create the overseer recording {}: {}", path.display(), e))
+			})?),
+			None => overseer,
+		};
 		let overseer_handler_clone = overseer_handler.clone();
 
+		// Describing imported blocks takes blocking client calls, so the events are forwarded from
+		// a task of their own to keep them off the overseer's loop.
+		task_manager.spawn_essential_handle().spawn_blocking(
+			"overseer-forward-events",
+			Box::pin(polkadot_overseer::forward_events(overseer_client, overseer_handler_clone)),
+		);
 		task_manager.spawn_essential_handle().spawn_blocking("overseer", Box::pin(async move {
-			use futures::{pin_mut, select, FutureExt};
-
-			let forward = polkadot_overseer::forward_events(overseer_client, overseer_handler_clone);
-
-			let forward = forward.fuse();
-			let overseer_fut = overseer.run().fuse();
-
-			pin_mut!(overseer_fut);
-			pin_mut!(forward);
-
-			select! {
-				_ = forward => (),
-				_ = overseer_fut => (),
-				complete => (),
+			if let Err(e) = overseer.run().await {
+				tracing::error!(err = ?e, "Overseer exited with an error");
 			}
 		}));
 
@@ -895,6 +946,8 @@ pub fn build_full(
 	config: Configuration,
 	is_collator: IsCollator,
 	grandpa_pause: Option<(u32, u32)>,
+	peer_sets: PeerSetsConfig,
+	overseer_recording: Option<PathBuf>,
 ) -> Result<NewFull<Client>, Error> {
 	if config.chain_spec.is_rococo() {
 		new_full::<rococo_runtime::RuntimeApi, RococoExecutor>(
@@ -902,6 +955,8 @@ pub fn build_full(
 			is_collator,
 			grandpa_pause,
 			Default::default(),
+			peer_sets,
+			overseer_recording,
 		).map(|full| full.with_client(Client::Rococo))
 	} else if config.chain_spec.is_kusama() {
 		new_full::<kusama_runtime::RuntimeApi, KusamaExecutor>(
@@ -909,6 +964,8 @@ pub fn build_full(
 			is_collator,
 			grandpa_pause,
 			Default::default(),
+			peer_sets,
+			overseer_recording,
 		).map(|full| full.with_client(Client::Kusama))
 	} else if config.chain_spec.is_westend() {
 		new_full::<westend_runtime::RuntimeApi, WestendExecutor>(
@@ -916,6 +973,8 @@ pub fn build_full(
 			is_collator,
 			grandpa_pause,
 			Default::default(),
+			peer_sets,
+			overseer_recording,
 		).map(|full| full.with_client(Client::Westend))
 	} else {
 		new_full::<polkadot_runtime::RuntimeApi, PolkadotExecutor>(
@@ -923,6 +982,8 @@ pub fn build_full(
 			is_collator,
 			grandpa_pause,
 			Default::default(),
+			peer_sets,
+			overseer_recording,
 		).map(|full| full.with_client(Client::Polkadot))
 	}
 }
@@ -21,9 +21,11 @@
 use polkadot_node_subsystem::messages::AllMessages;
 use polkadot_node_subsystem::{
 	FromOverseer, SubsystemContext, SubsystemError, SubsystemResult, Subsystem,
-	SpawnedSubsystem, OverseerSignal,
+	SpawnedSubsystem, OverseerSignal, ActivatedLeaf,
 };
 use polkadot_node_subsystem_util::TimeoutExt;
+use polkadot_overseer::recorder::{self, RecordedPayload};
+use polkadot_primitives::v1::Hash;
 
 use futures::channel::mpsc;
 use futures::poll;
@@ -31,7 +33,8 @@ use futures::prelude::*;
 use parking_lot::Mutex;
 use sp_core::{testing::TaskExecutor, traits::SpawnNamed};
 
-use std::convert::Infallible;
+use std::convert::{Infallible, TryFrom};
+use std::io::{self, BufRead};
 use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll, Waker};
@@ -258,6 +261,13 @@ pub fn make_subsystem_context<M, S>(
 	)
 }
 
+/// An activated leaf with the given hash, for tests which don't care about the rest of it.
+///
+/// The leaf is block number 1 in session 1, has a zero parent hash and no new ancestry.
+pub fn activated_leaf(hash: Hash) -> ActivatedLeaf {
+	ActivatedLeaf::new(hash, 1, Hash::zero(), 1)
+}
+
 /// Test a subsystem, mocking the overseer
 ///
 /// Pass in two async closures: one mocks the overseer, the other runs the test from the perspective of a subsystem.
@@ -287,6 +297,42 @@ pub fn subsystem_test_harness<M, OverseerFactory, Overseer, TestFactory, Test>(
 	});
 }
 
+/// Replay the traffic a subsystem received in a recording made by the overseer.
+///
+/// Only events sent to `subsystem`, by its label, or broadcast to all subsystems are replayed,
+/// in the order they were recorded. Signals are replayed as they were sent. Messages which were
+/// only recorded in their debug representation, such as those carrying channels to respond on,
+/// are skipped.
+///
+/// Returns the number of replayed events.
+pub async fn replay_recording<M: TryFrom<AllMessages>>(
+	recording: impl BufRead,
+	subsystem: &str,
+	handle: &mut TestSubsystemContextHandle<M>,
+) -> io::Result<usize> {
+	let mut replayed = 0;
+
+	for event in recorder::read_recording(recording) {
+		let event = event?;
+		if event.destination != subsystem && event.destination != recorder::ALL_SUBSYSTEMS {
+			continue;
+		}
+
+		let from_overseer = match event.payload {
+			RecordedPayload::Signal(signal) => FromOverseer::Signal(signal.into_signal()?),
+			RecordedPayload::Message(msg) => match msg.into_message()?.map(M::try_from) {
+				Some(Ok(msg)) => FromOverseer::Communication { msg },
+				_ => continue,
+			},
+		};
+
+		handle.send(from_overseer).await;
+		replayed += 1;
+	}
+
+	Ok(replayed)
+}
+
 /// A forward subsystem that implements [`Subsystem`].
 ///
 /// It forwards all communication from the overseer to the internal message
@@ -322,7 +368,7 @@ mod tests {
 	use super::*;
 	use polkadot_overseer::{Overseer, AllSubsystems};
 	use futures::executor::block_on;
-	use polkadot_node_subsystem::messages::CandidateSelectionMessage;
+	use polkadot_node_subsystem::messages::{CandidateBackingMessage, CandidateSelectionMessage};
 
 	#[test]
 	fn forward_subsystem_works() {
@@ -341,4 +387,62 @@ mod tests {
 		block_on(handler.send_msg(CandidateSelectionMessage::Invalid(Default::default(), Default::default())));
 		assert!(matches!(block_on(rx.into_future()).0.unwrap(), CandidateSelectionMessage::Invalid(_, _)));
 	}
+
+	#[test]
+	fn recordings_are_replayed_in_order() {
+		let event = |destination: &str, payload| recorder::RecordedEvent {
+			timestamp: 0,
+			source: recorder::OVERSEER.into(),
+			destination: destination.into(),
+			payload,
+		};
+		let message = |msg: AllMessages| RecordedPayload::Message((&msg).into());
+		let invalid = |byte| CandidateSelectionMessage::Invalid(Hash::repeat_byte(byte), Default::default());
+
+		let (tx, _rx) = futures::channel::oneshot::channel();
+		let events = vec![
+			event(recorder::ALL_SUBSYSTEMS, RecordedPayload::Signal(recorder::RecordedSignal::ActiveLeaves {
+				activated: vec![(&activated_leaf(Hash::repeat_byte(1))).into()],
+				deactivated: vec![],
+			})),
+			event("candidate_selection", message(invalid(1).into())),
+			event("candidate_backing", message(CandidateBackingMessage::GetBackedCandidates(Hash::zero(), tx).into())),
+			event("candidate_selection", message(invalid(2).into())),
+			event("candidate_selection", RecordedPayload::Message(recorder::RecordedMessage {
+				debug: "not replayable".into(),
+				encoded: None,
+			})),
+			event(recorder::ALL_SUBSYSTEMS, RecordedPayload::Signal(recorder::RecordedSignal::Conclude)),
+		];
+		let recording = events.iter()
+			.map(|event| serde_json::to_string(event).unwrap() + "\n")
+			.collect::<String>();
+
+		subsystem_test_harness(
+			|mut handle: TestSubsystemContextHandle<CandidateSelectionMessage>| async move {
+				let replayed = replay_recording(recording.as_bytes(), "candidate_selection", &mut handle)
+					.await
+					.unwrap();
+				assert_eq!(replayed, 4);
+			},
+			|mut ctx| async move {
+				assert!(matches!(
+					ctx.recv().await.unwrap(),
+					FromOverseer::Signal(OverseerSignal::ActiveLeaves(update))
+						if update.activated[0] == activated_leaf(Hash::repeat_byte(1))
+				));
+				assert!(matches!(
+					ctx.recv().await.unwrap(),
+					FromOverseer::Communication { msg: CandidateSelectionMessage::Invalid(h, _) }
+						if h == Hash::repeat_byte(1)
+				));
+				assert!(matches!(
+					ctx.recv().await.unwrap(),
+					FromOverseer::Communication { msg: CandidateSelectionMessage::Invalid(h, _) }
+						if h == Hash::repeat_byte(2)
+				));
+				assert!(matches!(ctx.recv().await.unwrap(), FromOverseer::Signal(OverseerSignal::Conclude)));
+			},
+		);
+	}
 }
@@ -779,7 +779,7 @@ where
 		metrics: &Job::Metrics,
 		err_tx: &mut Option<mpsc::Sender<(Option<Hash>, JobsError<Job::Error>)>>,
 	) -> bool {
-		use polkadot_node_subsystem::ActiveLeavesUpdate;
+		use polkadot_node_subsystem::{ActiveLeavesUpdate, ActivatedLeaf};
 		use polkadot_node_subsystem::FromOverseer::{Communication, Signal};
 		use polkadot_node_subsystem::OverseerSignal::{ActiveLeaves, BlockFinalized, Conclude};
 
@@ -788,7 +788,7 @@ where
 				activated,
 				deactivated,
 			}))) => {
-				for hash in activated {
+				for ActivatedLeaf { hash, .. } in activated {
 					let metrics = metrics.clone();
 					if let Err(e) = jobs.spawn_job(hash, run_args.clone(), metrics) {
 						tracing::error!(
@@ -846,7 +846,7 @@ where
 					}
 				}
 			}
-			Ok(Signal(BlockFinalized(_))) => {}
+			Ok(Signal(BlockFinalized(..))) => {}
 			Err(err) => {
 				tracing::error!(
 					job = Job::NAME,
@@ -1233,7 +1233,7 @@ mod tests {
 		test_harness(run_args, |mut overseer_handle, err_rx| async move {
 			overseer_handle
 				.send(FromOverseer::Signal(OverseerSignal::ActiveLeaves(
-					ActiveLeavesUpdate::start_work(relay_parent),
+					ActiveLeavesUpdate::start_work(test_helpers::activated_leaf(relay_parent)),
 				)))
 				.await;
 			assert_matches!(
@@ -1267,7 +1267,7 @@ mod tests {
 		test_harness(run_args, |mut overseer_handle, err_rx| async move {
 			overseer_handle
 				.send(FromOverseer::Signal(OverseerSignal::ActiveLeaves(
-					ActiveLeavesUpdate::start_work(relay_parent),
+					ActiveLeavesUpdate::start_work(test_helpers::activated_leaf(relay_parent)),
 				)))
 				.await;
 
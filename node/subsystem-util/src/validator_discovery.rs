@@ -32,6 +32,7 @@ use polkadot_node_subsystem::{
 	messages::{AllMessages, RuntimeApiMessage, RuntimeApiRequest, NetworkBridgeMessage},
 	SubsystemContext,
 };
+use polkadot_node_network_protocol::PeerSet;
 use polkadot_primitives::v1::{Hash, ValidatorId, AuthorityDiscoveryId};
 use sc_network::PeerId;
 
@@ -49,11 +50,12 @@ pub enum Error {
 	RuntimeApi(#[from] RuntimeApiError),
 }
 
-/// Utility function to make it easier to connect to validators.
+/// Utility function to make it easier to connect to validators on the given peer-set.
 pub async fn connect_to_validators<Context: SubsystemContext>(
 	ctx: &mut Context,
 	relay_parent: Hash,
 	validators: Vec<ValidatorId>,
+	peer_set: PeerSet,
 ) -> Result<ConnectionRequest, Error> {
 	// ValidatorId -> AuthorityDiscoveryId
 	let (tx, rx) = oneshot::channel();
@@ -76,7 +78,7 @@ pub async fn connect_to_validators<Context: SubsystemContext>(
 		.filter_map(|(k, v)| v.map(|v| (v, k)))
 		.collect::<HashMap<AuthorityDiscoveryId, ValidatorId>>();
 
-	let connections = connect_to_authorities(ctx, authorities).await?;
+	let connections = connect_to_authorities(ctx, authorities, peer_set).await?;
 
 	Ok(ConnectionRequest {
 		validator_map,
@@ -87,6 +89,7 @@ pub async fn connect_to_validators<Context: SubsystemContext>(
 async fn connect_to_authorities<Context: SubsystemContext>(
 	ctx: &mut Context,
 	validator_ids: Vec<AuthorityDiscoveryId>,
+	peer_set: PeerSet,
 ) -> Result<mpsc::Receiver<(AuthorityDiscoveryId, PeerId)>, Error> {
 	const PEERS_CAPACITY: usize = 8;
 
@@ -95,6 +98,7 @@ async fn connect_to_authorities<Context: SubsystemContext>(
 	ctx.send_message(AllMessages::NetworkBridge(
 		NetworkBridgeMessage::ConnectToValidators {
 			validator_ids,
+			peer_set,
 			connected,
 		}
 	)).await;
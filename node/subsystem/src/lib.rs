@@ -28,7 +28,7 @@ use futures::prelude::*;
 use futures::channel::{mpsc, oneshot};
 use futures::future::BoxFuture;
 
-use polkadot_primitives::v1::Hash;
+use polkadot_primitives::v1::{Hash, BlockNumber, SessionIndex};
 use async_trait::async_trait;
 use smallvec::SmallVec;
 use thiserror::Error;
@@ -44,21 +44,55 @@ pub mod messages;
 /// If there are greater than this number of slots, then we fall back to a heap vector.
 const ACTIVE_LEAVES_SMALLVEC_CAPACITY: usize = 8;
 
+/// A relay chain block which became an active leaf, along with what the overseer knows about it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ActivatedLeaf {
+	/// The hash of the block.
+	pub hash: Hash,
+	/// The number of the block.
+	pub number: BlockNumber,
+	/// The hash of the parent of the block.
+	pub parent_hash: Hash,
+	/// The session of the children of the block, which is the session candidates backed in
+	/// the context of this block belong to.
+	///
+	/// `None` if it couldn't be determined, e.g. for blocks of runtimes without parachains.
+	pub session_index: Option<SessionIndex>,
+	/// Unfinalized ancestors of the block the overseer didn't see before, most recent first.
+	///
+	/// This is the part of the chain between the previous leaf on this fork and the block.
+	/// It is usually empty, as the parent of a new leaf is usually the previous leaf.
+	pub new_ancestry: Vec<Hash>,
+}
+
+impl ActivatedLeaf {
+	/// Create an `ActivatedLeaf` for a block which is known to have no new ancestry.
+	pub fn new(hash: Hash, number: BlockNumber, parent_hash: Hash, session_index: SessionIndex) -> Self {
+		ActivatedLeaf {
+			hash,
+			number,
+			parent_hash,
+			session_index: Some(session_index),
+			new_ancestry: Vec::new(),
+		}
+	}
+}
+
 /// Changes in the set of active leaves: the parachain heads which we care to work on.
 ///
 /// Note that the activated and deactivated fields indicate deltas, not complete sets.
 #[derive(Clone, Debug, Default, Eq)]
 pub struct ActiveLeavesUpdate {
-	/// New relay chain block hashes of interest.
-	pub activated: SmallVec<[Hash; ACTIVE_LEAVES_SMALLVEC_CAPACITY]>,
+	/// New relay chain blocks of interest.
+	pub activated: SmallVec<[ActivatedLeaf; ACTIVE_LEAVES_SMALLVEC_CAPACITY]>,
 	/// Relay chain block hashes no longer of interest.
 	pub deactivated: SmallVec<[Hash; ACTIVE_LEAVES_SMALLVEC_CAPACITY]>,
 }
 
 impl ActiveLeavesUpdate {
-	/// Create a ActiveLeavesUpdate with a single activated hash
-	pub fn start_work(hash: Hash) -> Self {
-		Self { activated: [hash][..].into(), ..Default::default() }
+	/// Create a ActiveLeavesUpdate with a single activated leaf
+	pub fn start_work(leaf: ActivatedLeaf) -> Self {
+		Self { activated: [leaf][..].into(), ..Default::default() }
 	}
 
 	/// Create a ActiveLeavesUpdate with a single deactivated hash
@@ -70,6 +104,11 @@ impl ActiveLeavesUpdate {
 	pub fn is_empty(&self) -> bool {
 		self.activated.is_empty() && self.deactivated.is_empty()
 	}
+
+	/// The hashes of the activated leaves.
+	pub fn activated_hashes(&self) -> impl Iterator<Item = Hash> + '_ {
+		self.activated.iter().map(|leaf| leaf.hash)
+	}
 }
 
 impl PartialEq for ActiveLeavesUpdate {
@@ -88,8 +127,8 @@ impl PartialEq for ActiveLeavesUpdate {
 pub enum OverseerSignal {
 	/// Subsystems should adjust their jobs to start and stop work on appropriate block hashes.
 	ActiveLeaves(ActiveLeavesUpdate),
-	/// `Subsystem` is informed of a finalized block by its block hash.
-	BlockFinalized(Hash),
+	/// `Subsystem` is informed of a finalized block by its block hash and number.
+	BlockFinalized(Hash, BlockNumber),
 	/// Conclude the work of the `Overseer` and all `Subsystem`s.
 	Conclude,
 }
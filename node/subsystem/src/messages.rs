@@ -24,8 +24,10 @@
 
 use futures::channel::{mpsc, oneshot};
 use thiserror::Error;
+use polkadot_overseer_gen::overlord;
 use polkadot_node_network_protocol::{
-	v1 as protocol_v1, NetworkBridgeEvent, ReputationChange, PeerId,
+	v1 as protocol_v1, NetworkBridgeEvent, ReputationChange, PeerId, PeerSet,
+	request_response::{Requests, IncomingRequest, v1 as req_res_v1},
 };
 use polkadot_node_primitives::{
 	CollationGenerationConfig, MisbehaviorReport, SignedFullStatement, ValidationResult,
@@ -37,7 +39,7 @@ use polkadot_primitives::v1::{
 	GroupRotationInfo, Hash, Id as ParaId, OccupiedCoreAssumption,
 	PersistedValidationData, PoV, SessionIndex, SignedAvailabilityBitfield,
 	ValidationCode, ValidatorId, ValidationData, CandidateHash,
-	ValidatorIndex, ValidatorSignature, InboundDownwardMessage, InboundHrmpMessage,
+	ValidatorIndex, ValidatorSignature, InboundDownwardMessage, InboundHrmpMessage, ParaLimits,
 };
 use std::sync::Arc;
 use std::collections::btree_map::BTreeMap;
@@ -176,6 +178,8 @@ pub enum CollatorProtocolMessage {
 	NoteGoodCollation(CollatorId),
 	/// Get a network bridge update.
 	NetworkBridgeUpdateV1(NetworkBridgeEvent<protocol_v1::CollatorProtocolMessage>),
+	/// A request for a collation we advertised, to be answered on the collator side.
+	CollationFetchingRequest(IncomingRequest<req_res_v1::CollationFetchingRequest>),
 }
 
 impl CollatorProtocolMessage {
@@ -188,6 +192,7 @@ impl CollatorProtocolMessage {
 			Self::ReportCollator(_) => None,
 			Self::NoteGoodCollation(_) => None,
 			Self::NetworkBridgeUpdateV1(_) => None,
+			Self::CollationFetchingRequest(req) => Some(req.payload.relay_parent),
 		}
 	}
 }
@@ -204,7 +209,7 @@ pub enum NetworkBridgeMessage {
 	/// Send a message to one or more peers on the collation peer-set.
 	SendCollationMessage(Vec<PeerId>, protocol_v1::CollationProtocol),
 
-	/// Connect to peers who represent the given `validator_ids`.
+	/// Connect to peers who represent the given `validator_ids` on the given peer-set.
 	///
 	/// Also ask the network to stay connected to these peers at least
 	/// until the request is revoked.
@@ -212,11 +217,18 @@ pub enum NetworkBridgeMessage {
 	ConnectToValidators {
 		/// Ids of the validators to connect to.
 		validator_ids: Vec<AuthorityDiscoveryId>,
+		/// The peer-set whose reserved peers the validators are added to.
+		peer_set: PeerSet,
 		/// Response sender by which the issuer can learn the `PeerId`s of
 		/// the validators as they are connected.
 		/// The response is sent immediately for already connected peers.
 		connected: mpsc::Sender<(AuthorityDiscoveryId, PeerId)>,
 	},
+
+	/// Send requests via the request/response protocols.
+	///
+	/// Responses are sent back on the channel contained in each request.
+	SendRequests(Vec<Requests>),
 }
 
 impl NetworkBridgeMessage {
@@ -227,6 +239,7 @@ impl NetworkBridgeMessage {
 			Self::SendValidationMessage(_, _) => None,
 			Self::SendCollationMessage(_, _) => None,
 			Self::ConnectToValidators { .. } => None,
+			Self::SendRequests(_) => None,
 		}
 	}
 }
@@ -236,6 +249,8 @@ impl NetworkBridgeMessage {
 pub enum AvailabilityDistributionMessage {
 	/// Event from the network bridge.
 	NetworkBridgeUpdateV1(NetworkBridgeEvent<protocol_v1::AvailabilityDistributionMessage>),
+	/// A request for one of our erasure chunks.
+	ChunkFetchingRequest(IncomingRequest<req_res_v1::ChunkFetchingRequest>),
 }
 
 impl AvailabilityDistributionMessage {
@@ -243,6 +258,7 @@ impl AvailabilityDistributionMessage {
 	pub fn relay_parent(&self) -> Option<Hash> {
 		match self {
 			Self::NetworkBridgeUpdateV1(_) => None,
+			Self::ChunkFetchingRequest(_) => None,
 		}
 	}
 }
@@ -453,6 +469,11 @@ pub enum RuntimeApiRequest {
 		ParaId,
 		RuntimeApiSender<BTreeMap<ParaId, Vec<InboundHrmpMessage<BlockNumber>>>>,
 	),
+	/// Get the limits that apply to the given para, including any per-para overrides.
+	ParaLimits(
+		ParaId,
+		RuntimeApiSender<Option<ParaLimits>>,
+	),
 }
 
 /// A message to the Runtime API subsystem.
@@ -555,6 +576,8 @@ pub enum PoVDistributionMessage {
 	DistributePoV(Hash, CandidateDescriptor, Arc<PoV>),
 	/// An update from the network bridge.
 	NetworkBridgeUpdateV1(NetworkBridgeEvent<protocol_v1::PoVDistributionMessage>),
+	/// A request for a PoV we know about.
+	PoVFetchingRequest(IncomingRequest<req_res_v1::PoVFetchingRequest>),
 }
 
 impl PoVDistributionMessage {
@@ -564,6 +587,7 @@ impl PoVDistributionMessage {
 			Self::FetchPoV(hash, _, _) => Some(*hash),
 			Self::DistributePoV(hash, _, _) => Some(*hash),
 			Self::NetworkBridgeUpdateV1(_) => None,
+			Self::PoVFetchingRequest(req) => Some(req.payload.relay_parent),
 		}
 	}
 }
@@ -583,36 +607,40 @@ impl CollationGenerationMessage {
 }
 
 /// A message type tying together all message types that are used across Subsystems.
-#[derive(Debug, derive_more::From)]
-pub enum AllMessages {
+///
+/// Generated from the same subsystems as the overseer's `OverseenSubsystems`, see
+/// `polkadot_overseer_gen`.
+#[overlord(message_wrapper = AllMessages, wrapper_only)]
+#[derive(Debug, derive_more::From, derive_more::TryInto)]
+pub struct OverseenSubsystems {
 	/// Message for the validation subsystem.
-	CandidateValidation(CandidateValidationMessage),
+	candidate_validation: CandidateValidationMessage,
 	/// Message for the candidate backing subsystem.
-	CandidateBacking(CandidateBackingMessage),
+	candidate_backing: CandidateBackingMessage,
 	/// Message for the candidate selection subsystem.
-	CandidateSelection(CandidateSelectionMessage),
+	candidate_selection: CandidateSelectionMessage,
 	/// Message for the Chain API subsystem.
-	ChainApi(ChainApiMessage),
+	chain_api: ChainApiMessage,
 	/// Message for the Collator Protocol subsystem.
-	CollatorProtocol(CollatorProtocolMessage),
+	collator_protocol: CollatorProtocolMessage,
 	/// Message for the statement distribution subsystem.
-	StatementDistribution(StatementDistributionMessage),
+	statement_distribution: StatementDistributionMessage,
 	/// Message for the availability distribution subsystem.
-	AvailabilityDistribution(AvailabilityDistributionMessage),
+	availability_distribution: AvailabilityDistributionMessage,
 	/// Message for the bitfield distribution subsystem.
-	BitfieldDistribution(BitfieldDistributionMessage),
+	bitfield_distribution: BitfieldDistributionMessage,
 	/// Message for the bitfield signing subsystem.
-	BitfieldSigning(BitfieldSigningMessage),
+	bitfield_signing: BitfieldSigningMessage,
 	/// Message for the Provisioner subsystem.
-	Provisioner(ProvisionerMessage),
+	provisioner: ProvisionerMessage,
 	/// Message for the PoV Distribution subsystem.
-	PoVDistribution(PoVDistributionMessage),
+	pov_distribution: PoVDistributionMessage,
 	/// Message for the Runtime API subsystem.
-	RuntimeApi(RuntimeApiMessage),
+	runtime_api: RuntimeApiMessage,
 	/// Message for the availability store subsystem.
-	AvailabilityStore(AvailabilityStoreMessage),
+	availability_store: AvailabilityStoreMessage,
 	/// Message for the network bridge subsystem.
-	NetworkBridge(NetworkBridgeMessage),
+	network_bridge: NetworkBridgeMessage,
 	/// Message for the Collation Generation subsystem
-	CollationGeneration(CollationGenerationMessage),
+	collation_generation: CollationGenerationMessage,
 }
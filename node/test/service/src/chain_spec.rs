@@ -170,6 +170,11 @@ fn polkadot_testnet_genesis(
 				max_pov_size: 50 * 1024 * 1024,
 				max_head_data_size: 32 * 1024,
 				group_rotation_frequency: 10,
+				max_downward_message_size: 1024,
+				max_downward_queue_count: 64,
+				max_downward_queue_size: 64 * 1024,
+				// approximately 20ms.
+				ump_max_individual_weight: 20 * 1_000_000_000,
 				..Default::default()
 			},
 		}),
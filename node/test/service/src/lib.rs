@@ -83,6 +83,8 @@ pub fn new_full(
 		is_collator,
 		None,
 		polkadot_parachain::wasm_executor::IsolationStrategy::InProcess,
+		Default::default(),
+		None,
 	).map_err(Into::into)
 }
 
@@ -59,6 +59,8 @@ fn main() -> Result<()> {
 							config,
 							polkadot_service::IsCollator::Yes(collator.collator_id()),
 							None,
+							Default::default(),
+							None,
 						)?;
 						let mut overseer_handler = full_node
 							.overseer_handler
@@ -57,6 +57,15 @@ pub use sp_authority_discovery::AuthorityId as AuthorityDiscoveryId;
 /// Unique identifier for the Inclusion Inherent
 pub const INCLUSION_INHERENT_IDENTIFIER: InherentIdentifier = *b"inclusn0";
 
+/// The child trie holding the contributions to the crowdloan fund with the given index, where `H`
+/// is the hashing algorithm of the runtime.
+pub fn crowdloan_fund_child_info<H: HashT>(index: u32) -> primitives::storage::ChildInfo {
+	let mut buf = Vec::new();
+	buf.extend_from_slice(b"crowdfund");
+	buf.extend_from_slice(&index.to_le_bytes()[..]);
+	primitives::storage::ChildInfo::new_default(H::hash(&buf[..]).as_ref())
+}
+
 /// Get a collator signature payload on a relay-parent, block-data combo.
 pub fn collator_signature_payload<H: AsRef<[u8]>>(
 	relay_parent: &H,
@@ -312,6 +321,60 @@ pub struct TransientValidationData<N = BlockNumber> {
 	pub dmq_length: u32,
 }
 
+/// The limits imposed on a particular para by the relay-chain.
+///
+/// These are derived from the host configuration, with any per-para overrides applied.
+#[derive(PartialEq, Eq, Clone, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug, Default))]
+pub struct ParaLimits {
+	/// The maximum validation code size, in bytes.
+	pub max_code_size: u32,
+	/// The maximum head-data size, in bytes.
+	pub max_head_data_size: u32,
+	/// The maximum PoV block size, in bytes.
+	pub max_pov_size: u32,
+	/// The maximum number of messages allowed in the para's upward message queue.
+	pub max_upward_queue_count: u32,
+	/// The maximum total size of messages allowed in the para's upward message queue.
+	pub max_upward_queue_size: u32,
+	/// The maximum size of an upward message that can be sent by a candidate.
+	pub max_upward_message_size: u32,
+	/// The maximum number of upward messages that a candidate can contain.
+	pub max_upward_message_num_per_candidate: u32,
+	/// The maximum number of inbound HRMP channels the para is allowed to accept.
+	pub hrmp_max_inbound_channels: u32,
+	/// The maximum number of outbound HRMP channels the para is allowed to open.
+	pub hrmp_max_outbound_channels: u32,
+	/// The maximum number of outbound HRMP messages that a candidate can send.
+	pub hrmp_max_message_num_per_candidate: u32,
+}
+
+/// A message queue chain (MQC) maintained by the relay-chain.
+#[derive(PartialEq, Eq, Clone, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub enum MessageQueueChain {
+	/// The downward message queue addressed to the given para.
+	Downward(Id),
+	/// The given HRMP channel.
+	Hrmp(HrmpChannelId),
+}
+
+/// The head of a message queue chain together with the relay-chain storage entry committing to it.
+///
+/// A proof of the storage entry against the state root of a relay-chain block is enough to verify
+/// the head without trusting the node which served it.
+#[derive(PartialEq, Eq, Clone, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct MessageQueueChainHead {
+	/// The head of the MQC. Zero if no message has ever been sent over the MQC.
+	pub head: Hash,
+	/// The key of the relay-chain storage entry which contains the head.
+	///
+	/// For a downward message queue the entry holds the head itself. For an HRMP channel the
+	/// entry holds the encoded channel metadata, which includes the head.
+	pub storage_key: Vec<u8>,
+}
+
 /// Outputs of validating a candidate.
 #[derive(Encode, Decode)]
 #[cfg_attr(feature = "std", derive(Clone, Debug, Default))]
@@ -502,6 +565,8 @@ pub enum CoreOccupied {
 	Parathread(ParathreadEntry),
 	/// A parachain.
 	Parachain,
+	/// A parachain on a core that is shared with other parachains.
+	SharedParachain(Id),
 }
 
 /// This is the data we keep available for each candidate included in the relay chain.
@@ -673,6 +738,7 @@ pub enum CandidateEvent<H = Hash> {
 
 sp_api::decl_runtime_apis! {
 	/// The API for querying the state of parachains on-chain.
+	#[api_version(2)]
 	pub trait ParachainHost<H: Decode = Hash, N: Encode + Decode = BlockNumber> {
 		/// Get the current validators.
 		fn validators() -> Vec<ValidatorId>;
@@ -750,6 +816,22 @@ sp_api::decl_runtime_apis! {
 		/// Get the contents of all channels addressed to the given recipient. Channels that have no
 		/// messages in them are also included.
 		fn inbound_hrmp_channels_contents(recipient: Id) -> BTreeMap<Id, Vec<InboundHrmpMessage<N>>>;
+
+		/// Get the limits that apply to the given para, taking any per-para configuration
+		/// overrides into account.
+		///
+		/// Returns `None` if the para is not registered.
+		///
+		/// Available since version 2 of the API.
+		fn para_limits(para_id: Id) -> Option<ParaLimits>;
+
+		/// Get the head of the given message queue chain together with the key of the relay-chain
+		/// storage entry committing to it.
+		///
+		/// Returns `None` if the queue is an HRMP channel which is not open.
+		///
+		/// Available since version 2 of the API.
+		fn mqc_head(queue: MessageQueueChain) -> Option<MessageQueueChainHead>;
 	}
 }
 
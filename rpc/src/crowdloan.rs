@@ -0,0 +1,133 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! RPCs for proving crowdloan contributions.
+
+use std::{marker::PhantomData, sync::Arc};
+
+use jsonrpc_core::Result;
+use jsonrpc_derive::rpc;
+use parity_scale_codec::{Decode, Encode};
+use polkadot_primitives::v0::{AccountId, Balance, Block, Hash};
+use polkadot_primitives::v1::crowdloan_fund_child_info;
+use sc_client_api::{ProofProvider, StorageProvider};
+use serde::{Deserialize, Serialize};
+use sp_blockchain::HeaderBackend;
+use sp_core::{storage::StorageKey, Bytes};
+use sp_runtime::{generic::BlockId, traits::HashFor};
+
+use crate::internal_error;
+
+/// A contribution to a crowdloan fund together with a proof of it against the fund's child trie
+/// root, and a proof of that root against the state of a relay-chain block.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContributionProof<BlockHash> {
+	/// The relay-chain block the proofs were created at.
+	pub at: BlockHash,
+	/// The total amount contributed.
+	pub amount: Balance,
+	/// The memo attached to the contribution.
+	pub memo: Bytes,
+	/// The root of the fund's child trie.
+	pub fund_root: Hash,
+	/// The trie nodes proving the fund's child trie root against the state root of `at`.
+	pub fund_root_proof: Vec<Bytes>,
+	/// The trie nodes proving the contribution against `fund_root`. The contribution is stored
+	/// under the encoded account ID of the contributor as the encoded pair of `amount` and `memo`,
+	/// or as the bare `amount` if it was made before memos were introduced.
+	pub proof: Vec<Bytes>,
+}
+
+/// Crowdloan RPC methods.
+#[rpc]
+pub trait CrowdloanApi<BlockHash> {
+	/// Returns the contribution of `who` to the fund with the given index together with proofs of
+	/// it at the given block, or the best block if none is given.
+	///
+	/// Returns `None` if `who` has no contribution to the fund at that block.
+	#[rpc(name = "crowdloan_contributionProof")]
+	fn contribution_proof(
+		&self,
+		index: u32,
+		who: AccountId,
+		at: Option<BlockHash>,
+	) -> Result<Option<ContributionProof<BlockHash>>>;
+}
+
+/// Implements the [`CrowdloanApi`] RPC trait for proving crowdloan contributions.
+pub struct Crowdloan<C, B> {
+	client: Arc<C>,
+	_marker: PhantomData<B>,
+}
+
+impl<C, B> Crowdloan<C, B> {
+	/// Create a new instance of the crowdloan RPC handler.
+	pub fn new(client: Arc<C>) -> Self {
+		Crowdloan { client, _marker: PhantomData }
+	}
+}
+
+impl<C, B> CrowdloanApi<Hash> for Crowdloan<C, B> where
+	C: HeaderBackend<Block> + ProofProvider<Block> + StorageProvider<Block, B> + Send + Sync + 'static,
+	B: sc_client_api::Backend<Block> + Send + Sync + 'static,
+{
+	fn contribution_proof(
+		&self,
+		index: u32,
+		who: AccountId,
+		at: Option<Hash>,
+	) -> Result<Option<ContributionProof<Hash>>> {
+		let at = at.unwrap_or_else(|| self.client.info().best_hash);
+		let block_id = BlockId::Hash(at);
+		let child_info = crowdloan_fund_child_info::<HashFor<Block>>(index);
+		let key = who.encode();
+
+		let contribution = self.client
+			.child_storage(&block_id, &child_info, &StorageKey(key.clone()))
+			.map_err(|e| internal_error("Unable to query the contribution.", e))?;
+		// Contributions made before memos were introduced are stored as the bare amount.
+		let (amount, memo) = match contribution {
+			Some(data) => <(Balance, Vec<u8>)>::decode(&mut &data.0[..])
+				.or_else(|_| Balance::decode(&mut &data.0[..]).map(|amount| (amount, Vec::new())))
+				.map_err(|e| internal_error("Unable to decode the contribution.", e))?,
+			None => return Ok(None),
+		};
+
+		let root_key = child_info.prefixed_storage_key().into_inner();
+		let fund_root = self.client
+			.storage(&block_id, &StorageKey(root_key.clone()))
+			.map_err(|e| internal_error("Unable to query the fund root.", e))?
+			.and_then(|data| Hash::decode(&mut &data.0[..]).ok())
+			.ok_or_else(|| internal_error("The fund root is missing.", index))?;
+		let fund_root_proof = self.client
+			.read_proof(&block_id, &mut std::iter::once(&root_key[..]))
+			.map_err(|e| internal_error("Unable to prove the fund root.", e))?;
+
+		let proof = self.client
+			.read_child_proof(&block_id, &child_info, &mut std::iter::once(&key[..]))
+			.map_err(|e| internal_error("Unable to prove the contribution.", e))?;
+
+		Ok(Some(ContributionProof {
+			at,
+			amount,
+			memo: memo.into(),
+			fund_root,
+			fund_root_proof: fund_root_proof.iter_nodes().map(Into::into).collect(),
+			proof: proof.iter_nodes().map(Into::into).collect(),
+		}))
+	}
+}
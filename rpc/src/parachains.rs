@@ -0,0 +1,151 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! RPCs for proving parachain-related relay-chain state.
+
+use std::sync::Arc;
+
+use jsonrpc_core::Result;
+use jsonrpc_derive::rpc;
+use polkadot_primitives::v1::{
+	Block, Hash, HrmpChannelId, Id as ParaId, MessageQueueChain, ParachainHost,
+};
+use sc_client_api::ProofProvider;
+use serde::{Deserialize, Serialize};
+use sp_api::{ApiExt, ProvideRuntimeApi};
+use sp_blockchain::HeaderBackend;
+use sp_core::Bytes;
+use sp_runtime::generic::BlockId;
+
+use crate::internal_error;
+
+/// The head of a message queue chain together with a proof of it against the state of a
+/// relay-chain block.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MqcHeadProof<BlockHash> {
+	/// The relay-chain block the proof was created at.
+	pub at: BlockHash,
+	/// The head of the message queue chain.
+	pub head: Hash,
+	/// The key of the storage entry committing to the head.
+	///
+	/// For a downward message queue the entry is the encoded head. For an HRMP channel it is the
+	/// encoded channel metadata, which includes the head.
+	pub storage_key: Bytes,
+	/// The trie nodes proving the storage entry against the state root of `at`.
+	pub proof: Vec<Bytes>,
+}
+
+/// Parachain RPC methods.
+#[rpc]
+pub trait ParachainApi<BlockHash> {
+	/// Returns the MQC head of the downward message queue of the given para together with a
+	/// storage proof of it at the given block, or the best block if none is given.
+	#[rpc(name = "parachain_dmqHeadProof")]
+	fn dmq_head_proof(
+		&self,
+		para: ParaId,
+		at: Option<BlockHash>,
+	) -> Result<MqcHeadProof<BlockHash>>;
+
+	/// Returns the MQC head of the HRMP channel between the given sender and recipient together
+	/// with a storage proof of it at the given block, or the best block if none is given.
+	///
+	/// Returns `None` if the channel is not open at that block.
+	#[rpc(name = "parachain_hrmpChannelHeadProof")]
+	fn hrmp_channel_head_proof(
+		&self,
+		sender: ParaId,
+		recipient: ParaId,
+		at: Option<BlockHash>,
+	) -> Result<Option<MqcHeadProof<BlockHash>>>;
+}
+
+/// Implements the [`ParachainApi`] RPC trait for interacting with parachain-related state.
+pub struct Parachain<C> {
+	client: Arc<C>,
+}
+
+impl<C> Parachain<C> {
+	/// Create a new instance of the parachain RPC handler.
+	pub fn new(client: Arc<C>) -> Self {
+		Parachain { client }
+	}
+}
+
+impl<C> Parachain<C> where
+	C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + ProofProvider<Block> + Send + Sync + 'static,
+	C::Api: ParachainHost<Block>,
+{
+	fn mqc_head_proof(
+		&self,
+		queue: MessageQueueChain,
+		at: Option<Hash>,
+	) -> Result<Option<MqcHeadProof<Hash>>> {
+		let at = at.unwrap_or_else(|| self.client.info().best_hash);
+		let block_id = BlockId::Hash(at);
+
+		let api = self.client.runtime_api();
+		let supported = api
+			.has_api_with::<dyn ParachainHost<Block>, _>(&block_id, |version| version >= 2)
+			.map_err(|e| internal_error("Unable to query the runtime API version.", e))?;
+		if !supported {
+			return Err(internal_error(
+				"The runtime doesn't support proving MQC heads.",
+				"`mqc_head` requires version 2 of the `ParachainHost` API",
+			));
+		}
+
+		let head = api
+			.mqc_head(&block_id, queue)
+			.map_err(|e| internal_error("Unable to query the MQC head.", e))?;
+		let head = match head {
+			Some(head) => head,
+			None => return Ok(None),
+		};
+
+		let proof = self.client
+			.read_proof(&block_id, &mut std::iter::once(&head.storage_key[..]))
+			.map_err(|e| internal_error("Unable to prove the MQC head.", e))?;
+
+		Ok(Some(MqcHeadProof {
+			at,
+			head: head.head,
+			storage_key: head.storage_key.into(),
+			proof: proof.iter_nodes().map(Into::into).collect(),
+		}))
+	}
+}
+
+impl<C> ParachainApi<Hash> for Parachain<C> where
+	C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + ProofProvider<Block> + Send + Sync + 'static,
+	C::Api: ParachainHost<Block>,
+{
+	fn dmq_head_proof(&self, para: ParaId, at: Option<Hash>) -> Result<MqcHeadProof<Hash>> {
+		self.mqc_head_proof(MessageQueueChain::Downward(para), at)?
+			.ok_or_else(|| internal_error("The runtime doesn't track downward message queues.", ""))
+	}
+
+	fn hrmp_channel_head_proof(
+		&self,
+		sender: ParaId,
+		recipient: ParaId,
+		at: Option<Hash>,
+	) -> Result<Option<MqcHeadProof<Hash>>> {
+		self.mqc_head_proof(MessageQueueChain::Hrmp(HrmpChannelId { sender, recipient }), at)
+	}
+}
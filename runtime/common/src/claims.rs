@@ -20,16 +20,15 @@ use sp_std::{prelude::*, fmt::Debug};
 use sp_io::{hashing::keccak_256, crypto::secp256k1_ecdsa_recover};
 use frame_support::{
 	decl_event, decl_storage, decl_module, decl_error, ensure,
-	traits::{Currency, Get, VestingSchedule, EnsureOrigin, IsSubType}, weights::{Pays, DispatchClass}
+	traits::{Currency, Get, VestingSchedule, EnsureOrigin, IsSubType},
+	weights::{Pays, DispatchClass, Weight},
 };
 use frame_system::{ensure_signed, ensure_root, ensure_none};
 use parity_scale_codec::{Encode, Decode};
 #[cfg(feature = "std")]
 use serde::{self, Serialize, Deserialize, Serializer, Deserializer};
-#[cfg(feature = "std")]
-use sp_runtime::traits::Zero;
 use sp_runtime::{
-	traits::{CheckedSub, SignedExtension, DispatchInfoOf}, RuntimeDebug, DispatchResult,
+	traits::{CheckedSub, SignedExtension, DispatchInfoOf, Zero}, RuntimeDebug, DispatchResult,
 	transaction_validity::{
 		TransactionLongevity, TransactionValidity, ValidTransaction, InvalidTransaction,
 		TransactionSource, TransactionValidityError,
@@ -127,6 +126,27 @@ impl sp_std::fmt::Debug for EcdsaSignature {
 	}
 }
 
+/// The set of Ethereum accounts allowed to sign claims on behalf of a contract wallet, as its
+/// EIP-1271 `isValidSignature` would accept them.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct SignerSet {
+	/// The accounts allowed to sign.
+	pub signers: Vec<EthereumAddress>,
+	/// The number of distinct signers needed to make a claim.
+	pub threshold: u32,
+}
+
+/// The EIP-712 type of the domain of claims.
+const EIP712_DOMAIN_TYPE: &[u8] = b"EIP712Domain(string name,string version,bytes32 salt)";
+/// The name of the EIP-712 domain of claims.
+const EIP712_DOMAIN_NAME: &[u8] = b"Claims";
+/// The version of the EIP-712 domain of claims.
+const EIP712_DOMAIN_VERSION: &[u8] = b"1";
+/// The EIP-712 type of a claim signed by the claiming account itself.
+const EIP712_CLAIM_TYPE: &[u8] = b"Claim(bytes dest,string statement)";
+/// The EIP-712 type of a claim signed on behalf of a contract wallet.
+const EIP712_CONTRACT_CLAIM_TYPE: &[u8] = b"ContractClaim(address wallet,bytes dest,string statement)";
+
 decl_event!(
 	pub enum Event<T> where
 		Balance = BalanceOf<T>,
@@ -152,6 +172,8 @@ decl_error! {
 		InvalidStatement,
 		/// The account already has a vested balance.
 		VestedBalanceExists,
+		/// The signer set is empty or its threshold can't be met.
+		InvalidSignerSet,
 	}
 }
 
@@ -187,6 +209,10 @@ decl_storage! {
 				.filter_map(|(a, _, i, _)| Some((i.clone()?, a.clone())))
 				.collect::<Vec<_>>()
 		}): map hasher(identity) T::AccountId => Option<EthereumAddress>;
+
+		/// The accounts allowed to sign claims on behalf of contract wallets.
+		ContractSigners get(fn contract_signers):
+			map hasher(identity) EthereumAddress => Option<SignerSet>;
 	}
 	add_extra_genesis {
 		config(claims): Vec<(EthereumAddress, BalanceOf<T>, Option<T::AccountId>, Option<StatementKind>)>;
@@ -394,7 +420,7 @@ decl_module! {
 		}
 
 		#[weight = (
-			T::DbWeight::get().reads_writes(4, 4) + 100_000_000_000,
+			T::DbWeight::get().reads_writes(5, 5) + 100_000_000_000,
 			DispatchClass::Normal,
 			Pays::No
 		)]
@@ -408,10 +434,123 @@ decl_module! {
 			Claims::<T>::take(&old).map(|c| Claims::<T>::insert(&new, c));
 			Vesting::<T>::take(&old).map(|c| Vesting::<T>::insert(&new, c));
 			Signing::take(&old).map(|c| Signing::insert(&new, c));
+			ContractSigners::take(&old).map(|c| ContractSigners::insert(&new, c));
 			maybe_preclaim.map(|preclaim| Preclaims::<T>::mutate(&preclaim, |maybe_o|
 				if maybe_o.as_ref().map_or(false, |o| o == &old) { *maybe_o = Some(new) }
 			));
 		}
+
+		/// Make a claim to collect your DOTs with an EIP-712 typed-data signature.
+		///
+		/// The dispatch origin for this call must be _None_.
+		///
+		/// Unsigned Validation:
+		/// A call to `claim_typed` is deemed valid if the signature provided matches
+		/// the EIP-712 typed data:
+		///
+		/// > Claim(bytes dest,string statement)
+		///
+		/// in the domain `EIP712Domain(string name,string version,bytes32 salt)` with name
+		/// `Claims`, version `1` and the genesis hash of this chain as salt, where `dest` is the
+		/// SCALE-encoded destination account. The `statement` must be empty unless the claim
+		/// requires one, in which case it must match that which is expected according to your
+		/// purchase arrangement.
+		///
+		/// Parameters:
+		/// - `dest`: The destination account to payout the claim.
+		/// - `ethereum_signature`: The signature of the typed data described above.
+		/// - `statement`: The identity of the statement which is being attested to in the signature.
+		///
+		/// <weight>
+		/// The weight of this call is invariant over the input parameters.
+		/// Same as `claim_attest`, with a few more keccak hashes for the typed data.
+		/// </weight>
+		#[weight = T::DbWeight::get().reads_writes(8, 7) + 270_000_000 + 190_000_000]
+		fn claim_typed(origin,
+			dest: T::AccountId,
+			ethereum_signature: EcdsaSignature,
+			statement: Vec<u8>,
+		) {
+			ensure_none(origin)?;
+
+			let digest = Self::eip712_claim_digest(&dest.encode(), None, &statement);
+			let signer = Self::eth_recover_digest(&ethereum_signature, &digest)
+				.ok_or(Error::<T>::InvalidEthereumSignature)?;
+			Self::check_statement(&signer, &statement)?;
+
+			Self::process_claim(signer, dest)?;
+		}
+
+		/// Make a claim on behalf of a contract wallet with EIP-712 typed-data signatures of its
+		/// registered signers.
+		///
+		/// The dispatch origin for this call must be _None_.
+		///
+		/// Unsigned Validation:
+		/// A call to `claim_contract` is deemed valid if at least the threshold of the signer set
+		/// registered for `wallet` provided distinct signatures matching the EIP-712 typed data:
+		///
+		/// > ContractClaim(address wallet,bytes dest,string statement)
+		///
+		/// in the same domain as for `claim_typed`. The `statement` is checked as for `claim_typed`.
+		///
+		/// Parameters:
+		/// - `dest`: The destination account to payout the claim.
+		/// - `wallet`: The contract wallet holding the claim.
+		/// - `signatures`: The signatures of the typed data described above.
+		/// - `statement`: The identity of the statement which is being attested to in the signatures.
+		///
+		/// <weight>
+		/// Same as `claim_typed`, with one `eth_recover` operation per signature.
+		/// </weight>
+		#[weight = T::DbWeight::get().reads_writes(9, 7)
+			+ 270_000_000
+			+ 190_000_000 * (signatures.len() as Weight).max(1)
+		]
+		fn claim_contract(origin,
+			dest: T::AccountId,
+			wallet: EthereumAddress,
+			signatures: Vec<EcdsaSignature>,
+			statement: Vec<u8>,
+		) {
+			ensure_none(origin)?;
+
+			ensure!(
+				Self::contract_signed(&wallet, &dest, &signatures, &statement),
+				Error::<T>::InvalidEthereumSignature,
+			);
+			Self::check_statement(&wallet, &statement)?;
+
+			Self::process_claim(wallet, dest)?;
+		}
+
+		/// Register the accounts allowed to sign claims on behalf of a contract wallet, replacing
+		/// any previously registered set. An empty set of `signers` removes the registration.
+		///
+		/// The dispatch origin for this call must be _Root_ or the `MoveClaimOrigin`.
+		///
+		/// Parameters:
+		/// - `wallet`: The contract wallet holding a claim.
+		/// - `signers`: The accounts allowed to sign on behalf of `wallet`.
+		/// - `threshold`: The number of distinct signers needed to make a claim.
+		#[weight = T::DbWeight::get().writes(1) + 10_000_000]
+		fn set_contract_signers(origin,
+			wallet: EthereumAddress,
+			signers: Vec<EthereumAddress>,
+			threshold: u32,
+		) {
+			T::MoveClaimOrigin::try_origin(origin).map(|_| ()).or_else(ensure_root)?;
+
+			if signers.is_empty() {
+				ContractSigners::remove(&wallet);
+			} else {
+				ensure!(
+					threshold > 0 && threshold as usize <= signers.len(),
+					Error::<T>::InvalidSignerSet,
+				);
+				ContractSigners::insert(&wallet, SignerSet { signers, threshold });
+			}
+		}
 	}
 }
 
@@ -448,11 +587,97 @@ impl<T: Trait> Module<T> {
 	// the Ethereum RPC's `personal_sign` and `eth_sign`.
 	fn eth_recover(s: &EcdsaSignature, what: &[u8], extra: &[u8]) -> Option<EthereumAddress> {
 		let msg = keccak_256(&Self::ethereum_signable_message(what, extra));
+		Self::eth_recover_digest(s, &msg)
+	}
+
+	// Attempts to recover the Ethereum address that signed the given 32-byte digest.
+	fn eth_recover_digest(s: &EcdsaSignature, digest: &[u8; 32]) -> Option<EthereumAddress> {
 		let mut res = EthereumAddress::default();
-		res.0.copy_from_slice(&keccak_256(&secp256k1_ecdsa_recover(&s.0, &msg).ok()?[..])[12..]);
+		res.0.copy_from_slice(&keccak_256(&secp256k1_ecdsa_recover(&s.0, digest).ok()?[..])[12..]);
 		Some(res)
 	}
 
+	// The EIP-712 domain separator of claims, tied to this chain by using its genesis hash as salt.
+	fn eip712_domain_separator() -> [u8; 32] {
+		let genesis_hash = <frame_system::Module<T>>::block_hash(T::BlockNumber::zero());
+		let genesis_hash = genesis_hash.as_ref();
+		let mut salt = [0u8; 32];
+		let len = genesis_hash.len().min(32);
+		salt[..len].copy_from_slice(&genesis_hash[..len]);
+
+		let mut v = keccak_256(EIP712_DOMAIN_TYPE).to_vec();
+		v.extend_from_slice(&keccak_256(EIP712_DOMAIN_NAME));
+		v.extend_from_slice(&keccak_256(EIP712_DOMAIN_VERSION));
+		v.extend_from_slice(&salt);
+		keccak_256(&v)
+	}
+
+	// Constructs the EIP-712 digest that Ethereum RPC's `eth_signTypedData` would sign for a claim
+	// paying out to the encoded account `dest`, made on behalf of the contract `wallet` if given.
+	fn eip712_claim_digest(
+		dest: &[u8],
+		wallet: Option<&EthereumAddress>,
+		statement: &[u8],
+	) -> [u8; 32] {
+		let mut v = Vec::new();
+		match wallet {
+			None => v.extend_from_slice(&keccak_256(EIP712_CLAIM_TYPE)),
+			Some(wallet) => {
+				v.extend_from_slice(&keccak_256(EIP712_CONTRACT_CLAIM_TYPE));
+				// Addresses are left-padded to 32 bytes.
+				v.extend_from_slice(&[0u8; 12]);
+				v.extend_from_slice(&wallet.0);
+			}
+		}
+		v.extend_from_slice(&keccak_256(dest));
+		v.extend_from_slice(&keccak_256(statement));
+		let struct_hash = keccak_256(&v);
+
+		let mut v = b"\x19\x01".to_vec();
+		v.extend_from_slice(&Self::eip712_domain_separator());
+		v.extend_from_slice(&struct_hash);
+		keccak_256(&v)
+	}
+
+	// Whether at least the threshold of the signers registered for `wallet` signed a claim to
+	// `dest` on its behalf.
+	fn contract_signed(
+		wallet: &EthereumAddress,
+		dest: &T::AccountId,
+		signatures: &[EcdsaSignature],
+		statement: &[u8],
+	) -> bool {
+		let set = match ContractSigners::get(wallet) {
+			Some(set) => set,
+			None => return false,
+		};
+		// Don't bother recovering more signatures than there can be approvals.
+		if signatures.len() > set.signers.len() {
+			return false;
+		}
+
+		let digest = Self::eip712_claim_digest(&dest.encode(), Some(wallet), statement);
+		let mut approvals = Vec::with_capacity(signatures.len());
+		for signature in signatures {
+			match Self::eth_recover_digest(signature, &digest) {
+				Some(signer) if set.signers.contains(&signer) && !approvals.contains(&signer) =>
+					approvals.push(signer),
+				_ => return false,
+			}
+		}
+
+		approvals.len() >= set.threshold as usize
+	}
+
+	// Checks that `statement` is the one required for the claim of `signer`, or empty if none is.
+	fn check_statement(signer: &EthereumAddress, statement: &[u8]) -> DispatchResult {
+		match Signing::get(signer) {
+			Some(s) => ensure!(s.to_text() == statement, Error::<T>::InvalidStatement),
+			None => ensure!(statement.is_empty(), Error::<T>::InvalidStatement),
+		}
+		Ok(())
+	}
+
 	fn process_claim(signer: EthereumAddress, dest: T::AccountId) -> DispatchResult {
 		let balance_due = <Claims<T>>::get(&signer)
 			.ok_or(Error::<T>::SignerHasNoClaim)?;
@@ -510,6 +735,24 @@ impl<T: Trait> sp_runtime::traits::ValidateUnsigned for Module<T> {
 				let data = account.using_encoded(to_ascii_hex);
 				(Self::eth_recover(&ethereum_signature, &data, &statement), Some(statement.as_slice()))
 			}
+			// <weight>
+			// Base Weight: 190.1 µs (includes the full logic of `validate_unsigned`)
+			// DB Weight: 3 Read (System BlockHash, Claims, Signing)
+			// </weight>
+			Call::claim_typed(account, ethereum_signature, statement) => {
+				let digest = Self::eip712_claim_digest(&account.encode(), None, &statement);
+				let statement = Some(statement.as_slice()).filter(|s| !s.is_empty());
+				(Self::eth_recover_digest(&ethereum_signature, &digest), statement)
+			}
+			// <weight>
+			// Base Weight: 190.1 µs per signature
+			// DB Weight: 4 Read (ContractSigners, System BlockHash, Claims, Signing)
+			// </weight>
+			Call::claim_contract(account, wallet, signatures, statement) => {
+				let signed = Self::contract_signed(&wallet, &account, &signatures, &statement);
+				let statement = Some(statement.as_slice()).filter(|s| !s.is_empty());
+				(Some(*wallet).filter(|_| signed), statement)
+			}
 			_ => return Err(InvalidTransaction::Call.into()),
 		};
 
@@ -623,6 +866,20 @@ mod secp_utils {
 		r[64] = recovery_id.serialize();
 		EcdsaSignature(r)
 	}
+	pub fn typed_sig<T: Trait>(
+		secret: &secp256k1::SecretKey,
+		dest: &[u8],
+		wallet: Option<&EthereumAddress>,
+		statement: &[u8],
+	) -> EcdsaSignature {
+		let msg = <super::Module<T>>::eip712_claim_digest(dest, wallet, statement);
+		let (sig, recovery_id) = secp256k1::sign(&secp256k1::Message::parse(&msg), secret);
+		let mut r = [0u8; 65];
+		r[0..64].copy_from_slice(&sig.serialize()[..]);
+		// Ethereum wallets produce recovery IDs offset by 27.
+		r[64] = recovery_id.serialize() + 27;
+		EcdsaSignature(r)
+	}
 }
 
 #[cfg(test)]
@@ -1085,6 +1342,108 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn typed_claiming_works() {
+		new_test_ext().execute_with(|| {
+			// a `personal_sign` signature isn't accepted as typed data.
+			assert_noop!(
+				Claims::claim_typed(Origin::none(), 42, sig::<Test>(&alice(), &42u64.encode(), &[][..]), vec![]),
+				Error::<Test>::SignerHasNoClaim,
+			);
+
+			let s = typed_sig::<Test>(&alice(), &42u64.encode(), None, &[][..]);
+			assert_ok!(Claims::claim_typed(Origin::none(), 42, s, vec![]));
+			assert_eq!(Balances::free_balance(&42), 100);
+			assert_eq!(Vesting::vesting_balance(&42), Some(50));
+			assert_eq!(Claims::total(), total_claims() - 100);
+
+			// the statement is part of the signed data and must be the expected one.
+			let s = typed_sig::<Test>(&dave(), &42u64.encode(), None, &[][..]);
+			assert_noop!(Claims::claim_typed(Origin::none(), 42, s, vec![]), Error::<Test>::InvalidStatement);
+			let s = typed_sig::<Test>(&dave(), &42u64.encode(), None, StatementKind::Saft.to_text());
+			assert_noop!(
+				Claims::claim_typed(Origin::none(), 42, s, StatementKind::Saft.to_text().to_vec()),
+				Error::<Test>::InvalidStatement,
+			);
+			let s = typed_sig::<Test>(&dave(), &42u64.encode(), None, StatementKind::Regular.to_text());
+			assert_ok!(Claims::claim_typed(Origin::none(), 42, s, StatementKind::Regular.to_text().to_vec()));
+			assert_eq!(Balances::free_balance(&42), 300);
+		});
+	}
+
+	#[test]
+	fn typed_signatures_are_tied_to_genesis() {
+		new_test_ext().execute_with(|| {
+			let s = typed_sig::<Test>(&alice(), &42u64.encode(), None, &[][..]);
+			frame_system::BlockHash::<Test>::insert(0, H256::repeat_byte(1));
+			assert_noop!(
+				Claims::claim_typed(Origin::none(), 42, s, vec![]),
+				Error::<Test>::SignerHasNoClaim,
+			);
+
+			let s = typed_sig::<Test>(&alice(), &42u64.encode(), None, &[][..]);
+			assert_ok!(Claims::claim_typed(Origin::none(), 42, s, vec![]));
+		});
+	}
+
+	#[test]
+	fn contract_claiming_works() {
+		new_test_ext().execute_with(|| {
+			let wallet = EthereumAddress([7; 20]);
+			let signers = vec![eth(&bob()), eth(&alice())];
+			assert_ok!(Claims::mint_claim(Origin::root(), wallet, 200, None, None));
+
+			assert_noop!(
+				Claims::set_contract_signers(Origin::signed(42), wallet, signers.clone(), 2),
+				BadOrigin,
+			);
+			assert_noop!(
+				Claims::set_contract_signers(Origin::root(), wallet, signers.clone(), 3),
+				Error::<Test>::InvalidSignerSet,
+			);
+			assert_noop!(
+				Claims::set_contract_signers(Origin::root(), wallet, signers.clone(), 0),
+				Error::<Test>::InvalidSignerSet,
+			);
+			assert_ok!(Claims::set_contract_signers(Origin::signed(6), wallet, signers.clone(), 2));
+			assert_eq!(Claims::contract_signers(&wallet), Some(SignerSet { signers, threshold: 2 }));
+
+			let bob_sig = typed_sig::<Test>(&bob(), &42u64.encode(), Some(&wallet), &[][..]);
+			let alice_sig = typed_sig::<Test>(&alice(), &42u64.encode(), Some(&wallet), &[][..]);
+			let dave_sig = typed_sig::<Test>(&dave(), &42u64.encode(), Some(&wallet), &[][..]);
+
+			// below the threshold.
+			assert_noop!(
+				Claims::claim_contract(Origin::none(), 42, wallet, vec![bob_sig.clone()], vec![]),
+				Error::<Test>::InvalidEthereumSignature,
+			);
+			// the same signer twice.
+			assert_noop!(
+				Claims::claim_contract(Origin::none(), 42, wallet, vec![bob_sig.clone(), bob_sig.clone()], vec![]),
+				Error::<Test>::InvalidEthereumSignature,
+			);
+			// not a registered signer.
+			assert_noop!(
+				Claims::claim_contract(Origin::none(), 42, wallet, vec![bob_sig.clone(), dave_sig], vec![]),
+				Error::<Test>::InvalidEthereumSignature,
+			);
+			// signatures of the typed data without a wallet aren't accepted.
+			let plain_sig = typed_sig::<Test>(&alice(), &42u64.encode(), None, &[][..]);
+			assert_noop!(
+				Claims::claim_contract(Origin::none(), 42, wallet, vec![bob_sig.clone(), plain_sig], vec![]),
+				Error::<Test>::InvalidEthereumSignature,
+			);
+
+			assert_ok!(Claims::claim_contract(Origin::none(), 42, wallet, vec![alice_sig, bob_sig], vec![]));
+			assert_eq!(Balances::free_balance(&42), 200);
+			assert_eq!(Claims::total(), total_claims());
+
+			// an empty set removes the registration.
+			assert_ok!(Claims::set_contract_signers(Origin::root(), wallet, vec![], 0));
+			assert_eq!(Claims::contract_signers(&wallet), None);
+		});
+	}
+
 	#[test]
 	fn real_eth_sig_works() {
 		new_test_ext().execute_with(|| {
@@ -1162,6 +1521,43 @@ mod tests {
 				<Module<Test>>::validate_unsigned(source, &call),
 				InvalidTransaction::Custom(ValidityError::InvalidStatement.into()).into(),
 			);
+
+			let s = typed_sig::<Test>(&alice(), &1u64.encode(), None, &[][..]);
+			assert_eq!(
+				<Module<Test>>::validate_unsigned(source, &ClaimsCall::claim_typed(1, s, vec![])),
+				Ok(ValidTransaction {
+					priority: 100,
+					requires: vec![],
+					provides: vec![("claims", eth(&alice())).encode()],
+					longevity: TransactionLongevity::max_value(),
+					propagate: true,
+				})
+			);
+			let s = typed_sig::<Test>(&dave(), &1u64.encode(), None, &[][..]);
+			assert_eq!(
+				<Module<Test>>::validate_unsigned(source, &ClaimsCall::claim_typed(1, s, vec![])),
+				InvalidTransaction::Custom(ValidityError::InvalidStatement.into()).into(),
+			);
+
+			let wallet = EthereumAddress([7; 20]);
+			assert_ok!(Claims::mint_claim(Origin::root(), wallet, 200, None, None));
+			assert_ok!(Claims::set_contract_signers(Origin::root(), wallet, vec![eth(&bob())], 1));
+			let s = typed_sig::<Test>(&bob(), &1u64.encode(), Some(&wallet), &[][..]);
+			assert_eq!(
+				<Module<Test>>::validate_unsigned(source, &ClaimsCall::claim_contract(1, wallet, vec![s], vec![])),
+				Ok(ValidTransaction {
+					priority: 100,
+					requires: vec![],
+					provides: vec![("claims", wallet).encode()],
+					longevity: TransactionLongevity::max_value(),
+					propagate: true,
+				})
+			);
+			let s = typed_sig::<Test>(&alice(), &1u64.encode(), Some(&wallet), &[][..]);
+			assert_eq!(
+				<Module<Test>>::validate_unsigned(source, &ClaimsCall::claim_contract(1, wallet, vec![s], vec![])),
+				InvalidTransaction::Custom(ValidityError::InvalidEthereumSignature.into()).into(),
+			);
 		});
 	}
 }
@@ -20,10 +20,12 @@
 //! deposit for the parachain. When the parachain is retired, the funds may be returned.
 //!
 //! Contributing funds is permissionless. Each fund has a child-trie which stores all
-//! contributors account IDs together with the amount they contributed; the root of this can then be
+//! contributors account IDs together with the amount they contributed and an optional memo (e.g. a
+//! referral code or an account on the parachain to be rewarded); the root of this can then be
 //! used by the parachain to allow contributors to prove that they made some particular contribution
-//! to the project (e.g. to be rewarded through some token or badge). The trie is retained for later
-//! (efficient) redistribution back to the contributors.
+//! to the project (e.g. to be rewarded through some token or badge). Nodes offer an RPC for
+//! creating such proofs. The trie is retained for later (efficient) redistribution back to the
+//! contributors.
 //!
 //! Contributions must be of at least `MinContribution` (to account for the resources taken in
 //! tracking contributions), and may never tally greater than the fund's `cap`, set and fixed at the
@@ -38,9 +40,12 @@
 //! of the last auction. Until a fund takes a further bid following the end of an auction, then it
 //! will be inactive.
 //!
-//! Contributors may get a refund of their contributions from retired funds. After a period (`RetirementPeriod`)
-//! the fund may be dissolved entirely. At this point any non-refunded contributions are considered
-//! `orphaned` and are disposed of through the `OrphanedFunds` handler (which may e.g. place them
+//! Contributors may get a refund of their contributions from retired funds, either by withdrawing
+//! them themselves or through the permissionless `refund` call, which returns the contributions in
+//! batches of at most `RemoveKeysLimit`. Once all contributions are returned, the fund is dissolved
+//! and the deposit is returned to its owner. A fund may only be dissolved once all contributions
+//! are returned. Any funds left in the fund account at that point were not contributed through
+//! this module and are disposed of through the `OrphanedFunds` handler (which may e.g. place them
 //! into the treasury).
 //!
 //! Funds may accept contributions at any point before their success or retirement. When a parachain
@@ -67,19 +72,21 @@
 //! funds ultimately end up in module's fund sub-account.
 
 use frame_support::{
-	decl_module, decl_storage, decl_event, decl_error, storage::child, ensure,
+	decl_module, decl_storage, decl_event, decl_error, storage::child, ensure, transactional,
 	traits::{
 		Currency, Get, OnUnbalanced, WithdrawReasons, ExistenceRequirement::AllowDeath
 	},
+	dispatch::DispatchResult,
+	weights::{Weight, constants::RocksDbWeight},
 };
 use frame_system::ensure_signed;
 use sp_runtime::{ModuleId,
-	traits::{AccountIdConversion, Hash, Saturating, Zero, CheckedAdd}
+	traits::{AccountIdConversion, Saturating, Zero, CheckedAdd}
 };
 use crate::slots;
 use parity_scale_codec::{Encode, Decode};
 use sp_std::vec::Vec;
-use primitives::v1::{Id as ParaId, HeadData};
+use primitives::v1::{Id as ParaId, HeadData, crowdloan_fund_child_info};
 
 pub type BalanceOf<T> =
 	<<T as slots::Trait>::Currency as Currency<<T as frame_system::Trait>::AccountId>>::Balance;
@@ -87,6 +94,29 @@ pub type BalanceOf<T> =
 pub type NegativeImbalanceOf<T> =
 	<<T as slots::Trait>::Currency as Currency<<T as frame_system::Trait>::AccountId>>::NegativeImbalance;
 
+/// Weight functions needed for this module.
+pub trait WeightInfo {
+	/// Refunding at most `k` contributions and dissolving the fund once all were refunded.
+	fn refund(k: u32) -> Weight;
+	/// Dissolving a fund whose contributions were all refunded.
+	fn dissolve() -> Weight;
+}
+
+impl WeightInfo for () {
+	fn refund(k: u32) -> Weight {
+		// Reading the fund and the next contribution, returning it and removing it, for each
+		// contribution. Plus returning the deposit and removing the fund.
+		(100_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads_writes(2, 3).saturating_mul(k as Weight))
+	}
+
+	fn dissolve() -> Weight {
+		// Reading the fund and checking for contributions, returning the deposit and the remaining
+		// funds, and removing the fund.
+		(50_000_000 as Weight).saturating_add(RocksDbWeight::get().reads_writes(4, 4))
+	}
+}
+
 pub trait Trait: slots::Trait {
 	type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
 
@@ -100,12 +130,17 @@ pub trait Trait: slots::Trait {
 	/// least ExistentialDeposit.
 	type MinContribution: Get<BalanceOf<Self>>;
 
-	/// The period of time (in blocks) after an unsuccessful crowdfund ending when
-	/// contributors are able to withdraw their funds. After this period, their funds are lost.
-	type RetirementPeriod: Get<Self::BlockNumber>;
+	/// The maximum number of contributions that are refunded in a single `refund` call.
+	type RemoveKeysLimit: Get<u32>;
 
-	/// What to do with funds that were not withdrawn.
+	/// What to do with funds left in the fund account once all contributions were refunded.
 	type OrphanedFunds: OnUnbalanced<NegativeImbalanceOf<Self>>;
+
+	/// The maximum length of the memo that may be attached to a contribution.
+	type MaxMemoLength: Get<u32>;
+
+	/// Weight information for the extrinsics of this module.
+	type WeightInfo: WeightInfo;
 }
 
 /// Simple index for identifying a fund.
@@ -191,12 +226,17 @@ decl_event! {
 		Created(FundIndex),
 		/// Contributed to a crowd sale. [who, fund_index, amount]
 		Contributed(AccountId, FundIndex, Balance),
+		/// A contributor attached a memo to their contribution. [who, fund_index, memo]
+		MemoUpdated(AccountId, FundIndex, Vec<u8>),
 		/// Withdrew full balance of a contributor. [who, fund_index, amount]
 		Withdrew(AccountId, FundIndex, Balance),
 		/// Fund is placed into retirement. [fund_index]
 		Retiring(FundIndex),
 		/// Fund is dissolved. [fund_index]
 		Dissolved(FundIndex),
+		/// Some of the contributions to the fund were refunded, but there are more to refund.
+		/// [fund_index]
+		PartiallyRefunded(FundIndex),
 		/// The deploy data of the funded parachain is setted. [fund_index]
 		DeployDataFixed(FundIndex),
 		/// Onboarding process for a winning parachain fund is completed. [find_index, parachain_id]
@@ -245,8 +285,12 @@ decl_error! {
 		NoContributions,
 		/// This crowdfund has an active parachain and cannot be dissolved.
 		HasActiveParachain,
-		/// The retirement period has not ended.
-		InRetirementPeriod,
+		/// Not all contributions to this crowdfund have been refunded yet.
+		NotReadyToDissolve,
+		/// The memo is longer than `MaxMemoLength`.
+		MemoTooLarge,
+		/// None of the contributions could be refunded from the fund account.
+		RefundFailed,
 	}
 }
 
@@ -303,11 +347,22 @@ decl_module! {
 		/// Contribute to a crowd sale. This will transfer some balance over to fund a parachain
 		/// slot. It will be withdrawable in two instances: the parachain becomes retired; or the
 		/// slot is unable to be purchased and the timeout expires.
+		///
+		/// If a `memo` is given, it replaces any memo previously attached to the contributions of
+		/// `origin` to this fund. It is stored in the fund's child trie along with the contributed
+		/// amount, so that the parachain may reward contributors based on it.
 		#[weight = 0]
-		fn contribute(origin, #[compact] index: FundIndex, #[compact] value: BalanceOf<T>) {
+		fn contribute(origin,
+			#[compact] index: FundIndex,
+			#[compact] value: BalanceOf<T>,
+			memo: Option<Vec<u8>>
+		) {
 			let who = ensure_signed(origin)?;
 
 			ensure!(value >= T::MinContribution::get(), Error::<T>::ContributionTooSmall);
+			if let Some(ref memo) = memo {
+				ensure!(memo.len() <= T::MaxMemoLength::get() as usize, Error::<T>::MemoTooLarge);
+			}
 			let mut fund = Self::funds(index).ok_or(Error::<T>::InvalidFundIndex)?;
 			fund.raised  = fund.raised.checked_add(&value).ok_or(Error::<T>::Overflow)?;
 			ensure!(fund.raised <= fund.cap, Error::<T>::CapExceeded);
@@ -318,9 +373,9 @@ decl_module! {
 
 			T::Currency::transfer(&who, &Self::fund_account_id(index), value, AllowDeath)?;
 
-			let balance = Self::contribution_get(index, &who);
+			let (balance, old_memo) = Self::contribution(index, &who);
 			let balance = balance.saturating_add(value);
-			Self::contribution_put(index, &who, &balance);
+			Self::contribution_put(index, &who, &balance, memo.as_ref().unwrap_or(&old_memo));
 
 			if <slots::Module<T>>::is_ending(now).is_some() {
 				match fund.last_contribution {
@@ -352,7 +407,10 @@ decl_module! {
 
 			<Funds<T>>::insert(index, &fund);
 
-			Self::deposit_event(RawEvent::Contributed(who, index, value));
+			Self::deposit_event(RawEvent::Contributed(who.clone(), index, value));
+			if let Some(memo) = memo {
+				Self::deposit_event(RawEvent::MemoUpdated(who, index, memo));
+			}
 		}
 
 		/// Set the deploy data of the funded parachain if not already set. Once set, this cannot
@@ -467,35 +525,75 @@ decl_module! {
 			Self::deposit_event(RawEvent::Withdrew(who, index, balance));
 		}
 
-		/// Remove a fund after either: it was unsuccessful and it timed out; or it was successful
-		/// but it has been retired from its parachain slot. This places any deposits that were not
-		/// withdrawn into the treasury.
-		#[weight = 0]
-		fn dissolve(origin, #[compact] index: FundIndex) {
+		/// Refund the contributions to an unsuccessful or off-boarded fund.
+		///
+		/// This may be called by anyone. At most `RemoveKeysLimit` contributions are refunded per
+		/// call, so it may need to be called multiple times. Once all contributions have been
+		/// refunded, the fund is dissolved. If that fails, or if none of the contributions could be
+		/// refunded, the call fails and nothing is refunded.
+		#[weight = T::WeightInfo::refund(T::RemoveKeysLimit::get())]
+		#[transactional]
+		fn refund(origin, #[compact] index: FundIndex) {
 			let _ = ensure_signed(origin)?;
 
-			let fund = Self::funds(index).ok_or(Error::<T>::InvalidFundIndex)?;
-			ensure!(fund.parachain.is_none(), Error::<T>::HasActiveParachain);
+			let mut fund = Self::funds(index).ok_or(Error::<T>::InvalidFundIndex)?;
+			ensure!(fund.parachain.is_none(), Error::<T>::FundNotRetired);
 			let now = <frame_system::Module<T>>::block_number();
-			ensure!(
-				now >= fund.end.saturating_add(T::RetirementPeriod::get()),
-				Error::<T>::InRetirementPeriod
-			);
 
-			let account = Self::fund_account_id(index);
+			// `fund.end` can represent the end of a failed crowdsale or the beginning of retirement
+			ensure!(now >= fund.end, Error::<T>::FundNotEnded);
 
-			// Avoid using transfer to ensure we don't pay any fees.
+			let fund_account = Self::fund_account_id(index);
 			let transfer = WithdrawReasons::TRANSFER;
-			let imbalance = T::Currency::withdraw(&account, fund.deposit, transfer, AllowDeath)?;
-			let _ = T::Currency::resolve_into_existing(&fund.owner, imbalance);
+			let mut refunded = 0u32;
+			for _ in 0..T::RemoveKeysLimit::get() {
+				let (who, balance) = match Self::first_contribution(index) {
+					Some(contribution) => contribution,
+					None => break,
+				};
+
+				// Avoid using transfer to ensure we don't pay any fees. The contributor might not
+				// exist anymore, hence it is re-created if necessary.
+				let imbalance = match T::Currency::withdraw(&fund_account, balance, transfer, AllowDeath) {
+					Ok(imbalance) => imbalance,
+					// The funds were not returned to the fund account in full. Keep the remaining
+					// contributions around.
+					Err(_) => break,
+				};
+				T::Currency::resolve_creating(&who, imbalance);
+
+				Self::contribution_kill(index, &who);
+				fund.raised = fund.raised.saturating_sub(balance);
+				refunded += 1;
+
+				Self::deposit_event(RawEvent::Withdrew(who, index, balance));
+			}
 
-			let imbalance = T::Currency::withdraw(&account, fund.raised, transfer, AllowDeath)?;
-			T::OrphanedFunds::on_unbalanced(imbalance);
+			if Self::first_contribution(index).is_none() {
+				Self::dissolve_fund(index, fund)?;
+			} else {
+				ensure!(refunded > 0, Error::<T>::RefundFailed);
 
-			Self::crowdfund_kill(index);
-			<Funds<T>>::remove(index);
+				<Funds<T>>::insert(index, &fund);
+				Self::deposit_event(RawEvent::PartiallyRefunded(index));
+			}
+		}
 
-			Self::deposit_event(RawEvent::Dissolved(index));
+		/// Remove a fund after either: it was unsuccessful and it timed out; or it was successful
+		/// but it has been retired from its parachain slot. All contributions must have been
+		/// refunded already.
+		#[weight = T::WeightInfo::dissolve()]
+		#[transactional]
+		fn dissolve(origin, #[compact] index: FundIndex) {
+			let _ = ensure_signed(origin)?;
+
+			let fund = Self::funds(index).ok_or(Error::<T>::InvalidFundIndex)?;
+			ensure!(fund.parachain.is_none(), Error::<T>::HasActiveParachain);
+			let now = <frame_system::Module<T>>::block_number();
+			ensure!(now >= fund.end, Error::<T>::FundNotEnded);
+			ensure!(Self::first_contribution(index).is_none(), Error::<T>::NotReadyToDissolve);
+
+			Self::dissolve_fund(index, fund)?;
 		}
 
 		fn on_finalize(n: T::BlockNumber) {
@@ -538,21 +636,43 @@ impl<T: Trait> Module<T> {
 	}
 
 	pub fn id_from_index(index: FundIndex) -> child::ChildInfo {
-		let mut buf = Vec::new();
-		buf.extend_from_slice(b"crowdfund");
-		buf.extend_from_slice(&index.to_le_bytes()[..]);
-		child::ChildInfo::new_default(T::Hashing::hash(&buf[..]).as_ref())
+		crowdloan_fund_child_info::<T::Hashing>(index)
+	}
+
+	/// Store the contribution of `who` to the fund with the given index.
+	///
+	/// The contribution is stored under the encoded account ID of `who` in the fund's child trie,
+	/// as the encoded pair of the contributed amount and the memo.
+	pub fn contribution_put(
+		index: FundIndex,
+		who: &T::AccountId,
+		balance: &BalanceOf<T>,
+		memo: &[u8],
+	) {
+		who.using_encoded(|b| child::put(&Self::id_from_index(index), b, &(balance, memo)));
+	}
+
+	/// The amount contributed by `who` to the fund with the given index, together with the memo
+	/// attached to it.
+	///
+	/// Contributions made before memos were introduced are stored as the bare amount. They are read
+	/// with an empty memo and stored in the current format the next time they are updated.
+	pub fn contribution(index: FundIndex, who: &T::AccountId) -> (BalanceOf<T>, Vec<u8>) {
+		who.using_encoded(|b| child::get_raw(&Self::id_from_index(index), b))
+			.map(|raw| Self::decode_contribution(&raw))
+			.unwrap_or_default()
 	}
 
-	pub fn contribution_put(index: FundIndex, who: &T::AccountId, balance: &BalanceOf<T>) {
-		who.using_encoded(|b| child::put(&Self::id_from_index(index), b, balance));
+	/// Decode a stored contribution, falling back to the legacy format of a bare amount. The two
+	/// can't be confused: a bare amount is always shorter than an amount followed by a memo.
+	fn decode_contribution(raw: &[u8]) -> (BalanceOf<T>, Vec<u8>) {
+		<(BalanceOf<T>, Vec<u8>)>::decode(&mut &raw[..])
+			.or_else(|_| BalanceOf::<T>::decode(&mut &raw[..]).map(|balance| (balance, Vec::new())))
+			.unwrap_or_default()
 	}
 
 	pub fn contribution_get(index: FundIndex, who: &T::AccountId) -> BalanceOf<T> {
-		who.using_encoded(|b| child::get_or_default::<BalanceOf<T>>(
-			&Self::id_from_index(index),
-			b,
-		))
+		Self::contribution(index, who).0
 	}
 
 	pub fn contribution_kill(index: FundIndex, who: &T::AccountId) {
@@ -562,6 +682,41 @@ impl<T: Trait> Module<T> {
 	pub fn crowdfund_kill(index: FundIndex) {
 		child::kill_storage(&Self::id_from_index(index));
 	}
+
+	/// The contributor and the amount of the first contribution in the fund's child trie, if
+	/// there are any contributions left.
+	fn first_contribution(index: FundIndex) -> Option<(T::AccountId, BalanceOf<T>)> {
+		let child_info = Self::id_from_index(index);
+		let key = sp_io::default_child_storage::next_key(child_info.storage_key(), &[])?;
+		let who = T::AccountId::decode(&mut &key[..])
+			.expect("contributions are stored under encoded account IDs; qed");
+		Some((who, Self::contribution_get(index, &who)))
+	}
+
+	/// Return the deposit of a fund whose contributions have all been refunded to its owner and
+	/// remove the fund.
+	fn dissolve_fund(
+		index: FundIndex,
+		fund: FundInfo<T::AccountId, BalanceOf<T>, T::Hash, T::BlockNumber>,
+	) -> DispatchResult {
+		let account = Self::fund_account_id(index);
+
+		// Avoid using transfer to ensure we don't pay any fees.
+		let transfer = WithdrawReasons::TRANSFER;
+		let imbalance = T::Currency::withdraw(&account, fund.deposit, transfer, AllowDeath)?;
+		let _ = T::Currency::resolve_into_existing(&fund.owner, imbalance);
+
+		// Whatever is left was not contributed through this module.
+		let remaining = T::Currency::free_balance(&account);
+		let imbalance = T::Currency::withdraw(&account, remaining, transfer, AllowDeath)?;
+		T::OrphanedFunds::on_unbalanced(imbalance);
+
+		Self::crowdfund_kill(index);
+		<Funds<T>>::remove(index);
+
+		Self::deposit_event(RawEvent::Dissolved(index));
+		Ok(())
+	}
 }
 
 #[cfg(test)]
@@ -580,7 +735,7 @@ mod tests {
 	// or public keys. `u64` is used as the `AccountId` and no `Signature`s are requried.
 	use sp_runtime::{
 		Perbill, Permill, Percent, testing::Header, DispatchResult,
-		traits::{BlakeTwo256, IdentityLookup},
+		traits::{BlakeTwo256, Hash, IdentityLookup},
 	};
 	use crate::slots::Registrar;
 
@@ -748,6 +903,17 @@ mod tests {
 	parameter_types!{
 		pub const LeasePeriod: u64 = 10;
 		pub const EndingPeriod: u64 = 3;
+		pub const SampleLength: u64 = 1;
+	}
+	pub struct TestRandomness;
+	impl slots::EpochRandomness<H256> for TestRandomness {
+		fn random(_subject: &[u8]) -> (H256, u64) {
+			(H256::zero(), Self::epoch_index())
+		}
+
+		fn epoch_index() -> u64 {
+			System::block_number()
+		}
 	}
 	impl slots::Trait for Test {
 		type Event = ();
@@ -755,21 +921,25 @@ mod tests {
 		type Parachains = TestParachains;
 		type LeasePeriod = LeasePeriod;
 		type EndingPeriod = EndingPeriod;
-		type Randomness = RandomnessCollectiveFlip;
+		type SampleLength = SampleLength;
+		type Randomness = TestRandomness;
 	}
 	parameter_types! {
 		pub const SubmissionDeposit: u64 = 1;
 		pub const MinContribution: u64 = 10;
-		pub const RetirementPeriod: u64 = 5;
+		pub const RemoveKeysLimit: u32 = 2;
 		pub const CrowdfundModuleId: ModuleId = ModuleId(*b"py/cfund");
+		pub const MaxMemoLength: u32 = 32;
 	}
 	impl Trait for Test {
 		type Event = ();
 		type SubmissionDeposit = SubmissionDeposit;
 		type MinContribution = MinContribution;
-		type RetirementPeriod = RetirementPeriod;
+		type RemoveKeysLimit = RemoveKeysLimit;
 		type OrphanedFunds = Treasury;
 		type ModuleId = CrowdfundModuleId;
+		type MaxMemoLength = MaxMemoLength;
+		type WeightInfo = ();
 	}
 
 	type System = frame_system::Module<Test>;
@@ -777,7 +947,6 @@ mod tests {
 	type Slots = slots::Module<Test>;
 	type Treasury = pallet_treasury::Module<Test>;
 	type Crowdfund = Module<Test>;
-	type RandomnessCollectiveFlip = pallet_randomness_collective_flip::Module<Test>;
 	use pallet_balances::Error as BalancesError;
 	use slots::Error as SlotsError;
 
@@ -884,7 +1053,7 @@ mod tests {
 			assert_eq!(Crowdfund::contribution_get(0, &1), 0);
 
 			// User 1 contributes to their own crowdfund
-			assert_ok!(Crowdfund::contribute(Origin::signed(1), 0, 49));
+			assert_ok!(Crowdfund::contribute(Origin::signed(1), 0, 49, None));
 			// User 1 has spent some funds to do this, transfer fees **are** taken
 			assert_eq!(Balances::free_balance(1), 950);
 			// Contributions are stored in the trie
@@ -902,26 +1071,80 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn contribute_with_memo_works() {
+		new_test_ext().execute_with(|| {
+			assert_ok!(Crowdfund::create(Origin::signed(1), 1000, 1, 4, 9));
+
+			assert_ok!(Crowdfund::contribute(Origin::signed(2), 0, 49, Some(b"referral".to_vec())));
+			assert_eq!(Crowdfund::contribution(0, &2), (49, b"referral".to_vec()));
+
+			// Contributing without a memo keeps the previous one.
+			assert_ok!(Crowdfund::contribute(Origin::signed(2), 0, 10, None));
+			assert_eq!(Crowdfund::contribution(0, &2), (59, b"referral".to_vec()));
+
+			// A new memo replaces the previous one.
+			assert_ok!(Crowdfund::contribute(Origin::signed(2), 0, 10, Some(b"reward".to_vec())));
+			assert_eq!(Crowdfund::contribution(0, &2), (69, b"reward".to_vec()));
+
+			// The contribution is stored under the encoded account ID in the fund's child trie,
+			// which the contribution proof RPC relies on.
+			let child_info = child::ChildInfo::new_default(
+				BlakeTwo256::hash(&b"crowdfund\0\0\0\0"[..]).as_ref(),
+			);
+			assert_eq!(
+				child::get::<(u64, Vec<u8>)>(&child_info, &2u64.encode()),
+				Some((69, b"reward".to_vec())),
+			);
+		});
+	}
+
+	#[test]
+	fn contributions_without_memo_format_are_still_read() {
+		new_test_ext().execute_with(|| {
+			assert_ok!(Crowdfund::create(Origin::signed(1), 1000, 1, 4, 9));
+			assert_ok!(Crowdfund::contribute(Origin::signed(2), 0, 49, None));
+
+			// Contributions made before memos were introduced are stored as the bare amount.
+			let child_info = Crowdfund::id_from_index(0);
+			child::put(&child_info, &2u64.encode(), &49u64);
+			assert_eq!(Crowdfund::contribution(0, &2), (49, Vec::new()));
+
+			// They are stored in the current format once updated.
+			assert_ok!(Crowdfund::contribute(Origin::signed(2), 0, 10, Some(b"referral".to_vec())));
+			assert_eq!(Crowdfund::contribution(0, &2), (59, b"referral".to_vec()));
+			assert_eq!(
+				child::get::<(u64, Vec<u8>)>(&child_info, &2u64.encode()),
+				Some((59, b"referral".to_vec())),
+			);
+		});
+	}
+
 	#[test]
 	fn contribute_handles_basic_errors() {
 		new_test_ext().execute_with(|| {
 			// Cannot contribute to non-existing fund
-			assert_noop!(Crowdfund::contribute(Origin::signed(1), 0, 49), Error::<Test>::InvalidFundIndex);
+			assert_noop!(Crowdfund::contribute(Origin::signed(1), 0, 49, None), Error::<Test>::InvalidFundIndex);
 			// Cannot contribute below minimum contribution
-			assert_noop!(Crowdfund::contribute(Origin::signed(1), 0, 9), Error::<Test>::ContributionTooSmall);
+			assert_noop!(Crowdfund::contribute(Origin::signed(1), 0, 9, None), Error::<Test>::ContributionTooSmall);
+			// Cannot attach a memo that is too large
+			assert_noop!(
+				Crowdfund::contribute(Origin::signed(1), 0, 49, Some(vec![0; 33])),
+				Error::<Test>::MemoTooLarge,
+			);
 
 			// Set up a crowdfund
 			assert_ok!(Crowdfund::create(Origin::signed(1), 1000, 1, 4, 9));
-			assert_ok!(Crowdfund::contribute(Origin::signed(1), 0, 101));
+			assert_ok!(Crowdfund::contribute(Origin::signed(1), 0, 101, None));
 
 			// Cannot contribute past the limit
-			assert_noop!(Crowdfund::contribute(Origin::signed(2), 0, 900), Error::<Test>::CapExceeded);
+			assert_noop!(Crowdfund::contribute(Origin::signed(2), 0, 900, None), Error::<Test>::CapExceeded);
 
 			// Move past end date
 			run_to_block(10);
 
 			// Cannot contribute to ended fund
-			assert_noop!(Crowdfund::contribute(Origin::signed(1), 0, 49), Error::<Test>::ContributionPeriodOver);
+			assert_noop!(Crowdfund::contribute(Origin::signed(1), 0, 49, None), Error::<Test>::ContributionPeriodOver);
 		});
 	}
 
@@ -1020,7 +1243,7 @@ mod tests {
 			));
 
 			// Fund crowdfund
-			assert_ok!(Crowdfund::contribute(Origin::signed(2), 0, 1000));
+			assert_ok!(Crowdfund::contribute(Origin::signed(2), 0, 1000, None));
 
 			run_to_block(10);
 
@@ -1047,7 +1270,7 @@ mod tests {
 			assert_eq!(Balances::free_balance(1), 999);
 
 			// Fund crowdfund
-			assert_ok!(Crowdfund::contribute(Origin::signed(2), 0, 1000));
+			assert_ok!(Crowdfund::contribute(Origin::signed(2), 0, 1000, None));
 
 			run_to_block(10);
 
@@ -1094,7 +1317,7 @@ mod tests {
 			));
 
 			// Fund crowdfund
-			assert_ok!(Crowdfund::contribute(Origin::signed(2), 0, 1000));
+			assert_ok!(Crowdfund::contribute(Origin::signed(2), 0, 1000, None));
 
 			run_to_block(10);
 
@@ -1137,7 +1360,7 @@ mod tests {
 			));
 
 			// Fund crowdfund
-			assert_ok!(Crowdfund::contribute(Origin::signed(2), 0, 1000));
+			assert_ok!(Crowdfund::contribute(Origin::signed(2), 0, 1000, None));
 
 			run_to_block(10);
 
@@ -1171,9 +1394,9 @@ mod tests {
 			assert_ok!(Slots::new_auction(Origin::root(), 5, 1));
 			assert_ok!(Crowdfund::create(Origin::signed(1), 1000, 1, 4, 9));
 			// Transfer fee is taken here
-			assert_ok!(Crowdfund::contribute(Origin::signed(1), 0, 100));
-			assert_ok!(Crowdfund::contribute(Origin::signed(2), 0, 200));
-			assert_ok!(Crowdfund::contribute(Origin::signed(3), 0, 300));
+			assert_ok!(Crowdfund::contribute(Origin::signed(1), 0, 100, None));
+			assert_ok!(Crowdfund::contribute(Origin::signed(2), 0, 200, None));
+			assert_ok!(Crowdfund::contribute(Origin::signed(3), 0, 300, None));
 
 			// Skip all the way to the end
 			run_to_block(50);
@@ -1197,7 +1420,7 @@ mod tests {
 			assert_ok!(Slots::new_auction(Origin::root(), 5, 1));
 			assert_ok!(Crowdfund::create(Origin::signed(1), 1000, 1, 4, 9));
 			// Transfer fee is taken here
-			assert_ok!(Crowdfund::contribute(Origin::signed(1), 0, 49));
+			assert_ok!(Crowdfund::contribute(Origin::signed(1), 0, 49, None));
 			assert_eq!(Balances::free_balance(1), 950);
 
 			run_to_block(5);
@@ -1214,6 +1437,105 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn refund_works() {
+		new_test_ext().execute_with(|| {
+			// Set up a crowdfund
+			assert_ok!(Slots::new_auction(Origin::root(), 5, 1));
+			assert_ok!(Crowdfund::create(Origin::signed(1), 1000, 1, 4, 9));
+			assert_ok!(Crowdfund::contribute(Origin::signed(1), 0, 100, None));
+			assert_ok!(Crowdfund::contribute(Origin::signed(2), 0, 200, None));
+			assert_ok!(Crowdfund::contribute(Origin::signed(3), 0, 300, None));
+
+			// Skip all the way to the end
+			run_to_block(50);
+
+			// Anyone can refund the contributors, at most `RemoveKeysLimit` at a time
+			assert_ok!(Crowdfund::refund(Origin::signed(4), 0));
+			assert_eq!(Balances::free_balance(1), 999);
+			assert_eq!(Balances::free_balance(2), 2000);
+			assert_eq!(Balances::free_balance(3), 2700);
+			assert_eq!(Crowdfund::funds(0).unwrap().raised, 300);
+
+			// The last batch dissolves the fund
+			assert_ok!(Crowdfund::refund(Origin::signed(4), 0));
+			assert_eq!(Balances::free_balance(3), 3000);
+			assert_eq!(Crowdfund::contribution_get(0, &3), 0);
+			// Deposit is returned
+			assert_eq!(Balances::free_balance(1), 1000);
+			assert_eq!(Balances::free_balance(Crowdfund::fund_account_id(0)), 0);
+			assert_eq!(Balances::free_balance(Treasury::account_id()), 0);
+			assert_eq!(Crowdfund::funds(0), None);
+		});
+	}
+
+	#[test]
+	fn failed_dissolution_refunds_nothing() {
+		new_test_ext().execute_with(|| {
+			// Set up a crowdfund
+			assert_ok!(Slots::new_auction(Origin::root(), 5, 1));
+			assert_ok!(Crowdfund::create(Origin::signed(1), 1000, 1, 4, 9));
+			assert_ok!(Crowdfund::contribute(Origin::signed(2), 0, 200, None));
+
+			run_to_block(50);
+
+			// The fund account can pay back the contribution, but not the deposit
+			let fund_account = Crowdfund::fund_account_id(0);
+			let _ = Balances::make_free_balance_be(&fund_account, 200);
+			assert!(Crowdfund::refund(Origin::signed(4), 0).is_err());
+			assert_eq!(Balances::free_balance(2), 1800);
+			assert_eq!(Balances::free_balance(&fund_account), 200);
+			assert_eq!(Crowdfund::contribution_get(0, &2), 200);
+			assert_eq!(Crowdfund::funds(0).unwrap().raised, 200);
+		});
+	}
+
+	#[test]
+	fn refund_fails_if_no_contribution_can_be_refunded() {
+		new_test_ext().execute_with(|| {
+			// Set up a crowdfund
+			assert_ok!(Slots::new_auction(Origin::root(), 5, 1));
+			assert_ok!(Crowdfund::create(Origin::signed(1), 1000, 1, 4, 9));
+			assert_ok!(Crowdfund::contribute(Origin::signed(2), 0, 200, None));
+
+			run_to_block(50);
+
+			// The fund account can't pay back the contribution
+			let fund_account = Crowdfund::fund_account_id(0);
+			let _ = Balances::make_free_balance_be(&fund_account, 100);
+			assert_noop!(Crowdfund::refund(Origin::signed(4), 0), Error::<Test>::RefundFailed);
+			assert_eq!(Crowdfund::contribution_get(0, &2), 200);
+		});
+	}
+
+	#[test]
+	fn refund_handles_basic_errors() {
+		new_test_ext().execute_with(|| {
+			// Set up a crowdfund
+			assert_ok!(Slots::new_auction(Origin::root(), 5, 1));
+			assert_ok!(Crowdfund::create(Origin::signed(1), 1000, 1, 4, 9));
+			assert_ok!(Crowdfund::contribute(Origin::signed(2), 0, 1000, None));
+
+			// Cannot refund before the fund ends
+			assert_noop!(Crowdfund::refund(Origin::signed(1), 0), Error::<Test>::FundNotEnded);
+			// Cannot refund a non-existent fund
+			assert_noop!(Crowdfund::refund(Origin::signed(1), 1), Error::<Test>::InvalidFundIndex);
+
+			run_to_block(10);
+
+			// Cannot refund an active fund
+			assert_ok!(Crowdfund::fix_deploy_data(
+				Origin::signed(1),
+				0,
+				<Test as frame_system::Trait>::Hash::default(),
+				0,
+				vec![0].into(),
+			));
+			assert_ok!(Crowdfund::onboard(Origin::signed(1), 0, 0.into()));
+			assert_noop!(Crowdfund::refund(Origin::signed(1), 0), Error::<Test>::FundNotRetired);
+		});
+	}
+
 	#[test]
 	fn dissolve_works() {
 		new_test_ext().execute_with(|| {
@@ -1221,9 +1543,9 @@ mod tests {
 			assert_ok!(Slots::new_auction(Origin::root(), 5, 1));
 			assert_ok!(Crowdfund::create(Origin::signed(1), 1000, 1, 4, 9));
 			// Transfer fee is taken here
-			assert_ok!(Crowdfund::contribute(Origin::signed(1), 0, 100));
-			assert_ok!(Crowdfund::contribute(Origin::signed(2), 0, 200));
-			assert_ok!(Crowdfund::contribute(Origin::signed(3), 0, 300));
+			assert_ok!(Crowdfund::contribute(Origin::signed(1), 0, 100, None));
+			assert_ok!(Crowdfund::contribute(Origin::signed(2), 0, 200, None));
+			assert_ok!(Crowdfund::contribute(Origin::signed(3), 0, 300, None));
 
 			// Skip all the way to the end
 			run_to_block(50);
@@ -1233,15 +1555,21 @@ mod tests {
 			// Check current funds (contributions + deposit)
 			assert_eq!(Balances::free_balance(Crowdfund::fund_account_id(0)), 601);
 
+			// All contributions must be returned before the crowdfund may be dissolved
+			assert_ok!(Crowdfund::withdraw(Origin::signed(1), 0));
+			assert_ok!(Crowdfund::withdraw(Origin::signed(2), 0));
+			assert_noop!(Crowdfund::dissolve(Origin::signed(1), 0), Error::<Test>::NotReadyToDissolve);
+			assert_ok!(Crowdfund::withdraw(Origin::signed(3), 0));
+
 			// Dissolve the crowdfund
 			assert_ok!(Crowdfund::dissolve(Origin::signed(1), 0));
 
 			// Fund account is emptied
 			assert_eq!(Balances::free_balance(Crowdfund::fund_account_id(0)), 0);
 			// Deposit is returned
-			assert_eq!(Balances::free_balance(1), 900);
-			// Treasury account is filled
-			assert_eq!(Balances::free_balance(Treasury::account_id()), 600);
+			assert_eq!(Balances::free_balance(1), 1000);
+			// Nothing is left for the treasury
+			assert_eq!(Balances::free_balance(Treasury::account_id()), 0);
 
 			// Storage trie is removed
 			assert_eq!(Crowdfund::contribution_get(0,&0), 0);
@@ -1258,14 +1586,14 @@ mod tests {
 			assert_ok!(Slots::new_auction(Origin::root(), 5, 1));
 			assert_ok!(Crowdfund::create(Origin::signed(1), 1000, 1, 4, 9));
 			// Transfer fee is taken here
-			assert_ok!(Crowdfund::contribute(Origin::signed(1), 0, 100));
-			assert_ok!(Crowdfund::contribute(Origin::signed(2), 0, 200));
-			assert_ok!(Crowdfund::contribute(Origin::signed(3), 0, 300));
+			assert_ok!(Crowdfund::contribute(Origin::signed(1), 0, 100, None));
+			assert_ok!(Crowdfund::contribute(Origin::signed(2), 0, 200, None));
+			assert_ok!(Crowdfund::contribute(Origin::signed(3), 0, 300, None));
 
 			// Cannot dissolve an invalid fund index
 			assert_noop!(Crowdfund::dissolve(Origin::signed(1), 1), Error::<Test>::InvalidFundIndex);
 			// Cannot dissolve a fund in progress
-			assert_noop!(Crowdfund::dissolve(Origin::signed(1), 0), Error::<Test>::InRetirementPeriod);
+			assert_noop!(Crowdfund::dissolve(Origin::signed(1), 0), Error::<Test>::FundNotEnded);
 
 			run_to_block(10);
 
@@ -1290,7 +1618,7 @@ mod tests {
 			// Create a crowdfund before an auction is created
 			assert_ok!(Crowdfund::create(Origin::signed(1), 1000, 1, 4, 9));
 			// Users can already contribute
-			assert_ok!(Crowdfund::contribute(Origin::signed(1), 0, 49));
+			assert_ok!(Crowdfund::contribute(Origin::signed(1), 0, 49, None));
 			// Fund added to NewRaise
 			assert_eq!(Crowdfund::new_raise(), vec![0]);
 
@@ -1334,8 +1662,8 @@ mod tests {
 			assert_ok!(Crowdfund::create(Origin::signed(2), 1000, 1, 4, 30));
 
 			// Contribute to all, but more money to 0, less to 1
-			assert_ok!(Crowdfund::contribute(Origin::signed(1), 0, 300));
-			assert_ok!(Crowdfund::contribute(Origin::signed(1), 1, 200));
+			assert_ok!(Crowdfund::contribute(Origin::signed(1), 0, 300, None));
+			assert_ok!(Crowdfund::contribute(Origin::signed(1), 1, 200, None));
 
 			// Add deploy data to all
 			assert_ok!(Crowdfund::fix_deploy_data(
@@ -1367,7 +1695,7 @@ mod tests {
 			// Create a second auction
 			assert_ok!(Slots::new_auction(Origin::root(), 5, 1));
 			// Contribute to existing funds add to NewRaise
-			assert_ok!(Crowdfund::contribute(Origin::signed(1), 1, 10));
+			assert_ok!(Crowdfund::contribute(Origin::signed(1), 1, 10, None));
 
 			// End the current auction, fund 1 wins!
 			run_to_block(20);
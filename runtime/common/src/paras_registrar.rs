@@ -16,24 +16,37 @@
 
 //! Module to handle parathread/parachain registration and related fund management.
 //! In essence this is a simple wrapper around `paras`.
+//!
+//! Besides the legacy parathread registration, anyone may `reserve` a fresh `ParaId` by placing a
+//! `ParaDeposit`. The reserving account becomes the para's manager and may `register` its genesis
+//! head and validation code (placing a further deposit per byte), hand management over to another
+//! account, or `deregister` the para to get the deposit back. A registered para is onboarded as a
+//! parathread and is upgraded to a parachain (and back) through `make_parachain` and
+//! `make_parathread` when its leases begin and end, which is how the `slots` module drives it
+//! through its `Registrar` implementation. IDs still known to `paras` are never handed out.
+//!
+//! Management may be locked by the manager, root or the para itself, after which only root or the
+//! para itself may manage it or lift the lock.
 
 use crate::WASM_MAGIC;
 use sp_std::{prelude::*, result};
+use sp_runtime::{RuntimeDebug, traits::Saturating};
 use frame_support::{
-	decl_storage, decl_module, decl_error, ensure,
-	dispatch::DispatchResult,
+	decl_storage, decl_module, decl_error, decl_event, ensure,
+	dispatch::{DispatchResult, DispatchError},
 	traits::{Get, Currency, ReservableCurrency},
 };
 use frame_system::{self, ensure_root, ensure_signed};
+use parity_scale_codec::{Encode, Decode};
 use primitives::v1::{
-	Id as ParaId, ValidationCode, HeadData,
+	Id as ParaId, ValidationCode, HeadData, LOWEST_USER_ID,
 };
 use runtime_parachains::{
 	paras::{
 		self,
 		ParaGenesisArgs,
 	},
-	dmp, ump, hrmp,
+	configuration, dmp, ump, hrmp,
 	ensure_parachain,
 	Origin,
 };
@@ -54,6 +67,27 @@ pub trait Trait: paras::Trait + dmp::Trait + ump::Trait + hrmp::Trait {
 
 	/// The deposit to be paid to run a parathread.
 	type ParathreadDeposit: Get<BalanceOf<Self>>;
+
+	/// The overarching event type.
+	type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
+
+	/// The deposit to be paid to reserve a `ParaId`.
+	type ParaDeposit: Get<BalanceOf<Self>>;
+
+	/// The deposit to be paid per byte of genesis head data and validation code registered for a
+	/// reserved para.
+	type DataDepositPerByte: Get<BalanceOf<Self>>;
+}
+
+/// Information about a para reserved with `reserve`.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct ParaInfo<AccountId, Balance> {
+	/// The account managing the para. It holds the deposit.
+	pub manager: AccountId,
+	/// The total amount reserved from the manager for this para.
+	pub deposit: Balance,
+	/// Whether management is locked. A locked para may only be managed by root or the para itself.
+	pub locked: bool,
 }
 
 decl_storage! {
@@ -69,6 +103,34 @@ decl_storage! {
 
 		/// Users who have paid a parathread's deposit.
 		Debtors: map hasher(twox_64_concat) ParaId => T::AccountId;
+
+		/// The manager, deposit and lock state of every reserved para.
+		ReservedParas get(fn reserved_paras):
+			map hasher(twox_64_concat) ParaId => Option<ParaInfo<T::AccountId, BalanceOf<T>>>;
+
+		/// Accounts proposed by the manager of a reserved para to take over its management. The
+		/// proposed account must accept before its funds are reserved.
+		PendingManager get(fn pending_manager): map hasher(twox_64_concat) ParaId => Option<T::AccountId>;
+
+		/// The lowest `ParaId` that may be handed out by the next reservation.
+		NextFreeParaId get(fn next_free_para_id): ParaId = LOWEST_USER_ID;
+	}
+}
+
+decl_event! {
+	pub enum Event<T> where
+		<T as frame_system::Trait>::AccountId,
+	{
+		/// A para ID was reserved. [para_id, manager]
+		Reserved(ParaId, AccountId),
+		/// Genesis data was registered for a reserved para. [para_id, manager]
+		Registered(ParaId, AccountId),
+		/// A reserved para was deregistered and its deposit returned. [para_id]
+		Deregistered(ParaId),
+		/// The manager of a reserved para changed. [para_id, new_manager]
+		ManagerChanged(ParaId, AccountId),
+		/// An account was proposed to take over management of a reserved para. [para_id, proposed]
+		ManagerProposed(ParaId, AccountId),
 	}
 }
 
@@ -88,6 +150,14 @@ decl_error! {
 		ParathreadsRegistrationDisabled,
 		/// The validation code provided doesn't start with the Wasm file magic string.
 		DefinitelyNotWasm,
+		/// The para ID has not been reserved.
+		NotReserved,
+		/// The origin is not allowed to manage the para.
+		NotOwner,
+		/// Management of the para is locked.
+		ParaLocked,
+		/// The origin was not proposed to take over management of the para.
+		NotProposedManager,
 	}
 }
 
@@ -95,6 +165,8 @@ decl_module! {
 	pub struct Module<T: Trait> for enum Call where origin: <T as frame_system::Trait>::Origin {
 		type Error = Error<T>;
 
+		fn deposit_event() = default;
+
 		/// Register a parathread with given code for immediate use.
 		///
 		/// Must be sent from a Signed origin that is able to have `ParathreadDeposit` reserved.
@@ -112,6 +184,7 @@ decl_module! {
 			ensure!(validation_code.0.starts_with(WASM_MAGIC), Error::<T>::DefinitelyNotWasm);
 
 			ensure!(!Paras::contains_key(id), Error::<T>::ParaAlreadyExists);
+			ensure!(!<ReservedParas<T>>::contains_key(id), Error::<T>::ParaAlreadyExists);
 
 			let outgoing = <paras::Module<T>>::outgoing_paras();
 
@@ -204,10 +277,164 @@ decl_module! {
 						sp_std::mem::swap(i, j)
 					)
 				);
+
+				Self::schedule_lifecycle(id);
+				Self::schedule_lifecycle(other);
 			} else {
 				PendingSwap::insert(id, other);
 			}
 		}
+
+		/// Reserve the next free para ID, placing `ParaDeposit` on deposit.
+		///
+		/// Must be sent from a Signed origin, which becomes the manager of the para.
+		#[weight = 0]
+		fn reserve(origin) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			let deposit = T::ParaDeposit::get();
+			<T as Trait>::Currency::reserve(&who, deposit)?;
+
+			let id = Self::next_free_id();
+			<ReservedParas<T>>::insert(id, ParaInfo { manager: who.clone(), deposit, locked: false });
+
+			Self::deposit_event(RawEvent::Reserved(id, who));
+
+			Ok(())
+		}
+
+		/// Register the genesis head and validation code of a reserved para, which will be onboarded
+		/// as a parathread at the start of the next session.
+		///
+		/// Must be sent from the manager of the unlocked para. A deposit of `DataDepositPerByte` per
+		/// byte of `genesis_head` and `validation_code` is placed in addition to the `ParaDeposit`.
+		#[weight = 0]
+		fn register(
+			origin,
+			id: ParaId,
+			genesis_head: HeadData,
+			validation_code: ValidationCode,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let mut info = <ReservedParas<T>>::get(id).ok_or(Error::<T>::NotReserved)?;
+			ensure!(info.manager == who, Error::<T>::NotOwner);
+			ensure!(!info.locked, Error::<T>::ParaLocked);
+
+			ensure!(!Paras::contains_key(id), Error::<T>::ParaAlreadyExists);
+			ensure!(!Self::is_known_to_paras(id), Error::<T>::ParaAlreadyExists);
+
+			let config = <configuration::Module<T>>::config();
+			ensure!(validation_code.0.len() <= config.max_code_size as usize, Error::<T>::CodeTooLarge);
+			ensure!(genesis_head.0.len() <= config.max_head_data_size as usize, Error::<T>::HeadDataTooLarge);
+			ensure!(validation_code.0.starts_with(WASM_MAGIC), Error::<T>::DefinitelyNotWasm);
+
+			let data_len = (genesis_head.0.len() + validation_code.0.len()) as u32;
+			let data_deposit = T::DataDepositPerByte::get().saturating_mul(data_len.into());
+			<T as Trait>::Currency::reserve(&who, data_deposit)?;
+
+			info.deposit = info.deposit.saturating_add(data_deposit);
+			<ReservedParas<T>>::insert(id, info);
+			Paras::insert(id, false);
+
+			let genesis = ParaGenesisArgs {
+				genesis_head,
+				validation_code,
+				parachain: false,
+			};
+
+			runtime_parachains::schedule_para_initialize::<T>(id, genesis);
+
+			Self::deposit_event(RawEvent::Registered(id, who));
+
+			Ok(())
+		}
+
+		/// Deregister a reserved para, returning the deposit to its manager. A registered para is
+		/// cleaned up at the start of the next session.
+		///
+		/// Must be sent from root, the para itself or the manager of the unlocked para. Parachains
+		/// may not be deregistered; they must be downgraded to a parathread first.
+		#[weight = 0]
+		fn deregister(origin, id: ParaId) -> DispatchResult {
+			let info = Self::ensure_root_para_or_manager(origin, id)?;
+
+			ensure!(Paras::get(id) != Some(true), Error::<T>::InvalidThreadId);
+
+			if Paras::take(id).is_some() {
+				runtime_parachains::schedule_para_cleanup::<T>(id);
+			}
+
+			<ReservedParas<T>>::remove(id);
+			<PendingManager<T>>::remove(id);
+			let _ = <T as Trait>::Currency::unreserve(&info.manager, info.deposit);
+
+			Self::deposit_event(RawEvent::Deregistered(id));
+
+			Ok(())
+		}
+
+		/// Hand management of a reserved para, together with its deposit, over to another account.
+		///
+		/// Must be sent from root, the para itself or the manager of the unlocked para. Root and the
+		/// para itself hand management over immediately: the deposit is reserved from `new_manager`
+		/// and returned to the previous manager. The manager may only propose `new_manager`, which
+		/// takes over once it calls `accept_manager`.
+		#[weight = 0]
+		fn set_manager(origin, id: ParaId, new_manager: T::AccountId) -> DispatchResult {
+			let signed = ensure_signed(origin.clone()).is_ok();
+			let info = Self::ensure_root_para_or_manager(origin, id)?;
+
+			if signed {
+				<PendingManager<T>>::insert(id, new_manager.clone());
+				Self::deposit_event(RawEvent::ManagerProposed(id, new_manager));
+				return Ok(());
+			}
+
+			Self::do_set_manager(id, info, new_manager)
+		}
+
+		/// Accept management of a reserved para, as proposed by its manager through `set_manager`.
+		///
+		/// Must be sent from the proposed account, from which the deposit is reserved. The para must
+		/// not have been locked since the proposal.
+		#[weight = 0]
+		fn accept_manager(origin, id: ParaId) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let info = <ReservedParas<T>>::get(id).ok_or(Error::<T>::NotReserved)?;
+			ensure!(<PendingManager<T>>::get(id).as_ref() == Some(&who), Error::<T>::NotProposedManager);
+			ensure!(!info.locked, Error::<T>::ParaLocked);
+
+			Self::do_set_manager(id, info, who)
+		}
+
+		/// Lock management of a reserved para, so that only root or the para itself may manage it.
+		///
+		/// Must be sent from root, the para itself or the manager of the unlocked para.
+		#[weight = 0]
+		fn add_lock(origin, id: ParaId) -> DispatchResult {
+			let mut info = Self::ensure_root_para_or_manager(origin, id)?;
+
+			info.locked = true;
+			<ReservedParas<T>>::insert(id, info);
+
+			Ok(())
+		}
+
+		/// Lift the management lock of a reserved para.
+		///
+		/// Must be sent from root or the para itself.
+		#[weight = 0]
+		fn remove_lock(origin, id: ParaId) -> DispatchResult {
+			Self::ensure_root_or_para(origin, id)?;
+
+			<ReservedParas<T>>::mutate(id, |info| match info {
+				Some(info) => {
+					info.locked = false;
+					Ok(())
+				}
+				None => Err(Error::<T>::NotReserved.into()),
+			})
+		}
 	}
 }
 
@@ -249,6 +476,166 @@ impl<T: Trait> Module<T> {
 
 		Ok(())
 	}
+
+	/// Upgrade the registered parathread with the given ID to a parachain at the start of the next
+	/// session. Called when a lease of the para begins.
+	pub fn make_parachain(id: ParaId) -> DispatchResult {
+		ensure!(Paras::get(id) == Some(false), Error::<T>::InvalidThreadId);
+
+		Paras::insert(id, true);
+		runtime_parachains::schedule_parathread_upgrade::<T>(id);
+
+		Ok(())
+	}
+
+	/// Downgrade the registered parachain with the given ID to a parathread at the start of the
+	/// next session. Called when the leases of the para have ended.
+	pub fn make_parathread(id: ParaId) -> DispatchResult {
+		ensure!(Paras::get(id) == Some(true), Error::<T>::InvalidChainId);
+
+		Paras::insert(id, false);
+		runtime_parachains::schedule_parachain_downgrade::<T>(id);
+
+		Ok(())
+	}
+
+	/// Take the lowest free para ID from `NextFreeParaId` onwards, skipping any ID that was
+	/// registered directly or is still known to the `paras` module.
+	fn next_free_id() -> ParaId {
+		let mut id = NextFreeParaId::get();
+		while Paras::contains_key(id)
+			|| <ReservedParas<T>>::contains_key(id)
+			|| Self::is_known_to_paras(id)
+		{
+			id = id + 1;
+		}
+		NextFreeParaId::put(id + 1);
+		id
+	}
+
+	/// Whether the `paras` module knows about the given ID: it is live, about to be onboarded or
+	/// about to be cleaned up.
+	fn is_known_to_paras(id: ParaId) -> bool {
+		<paras::Module<T>>::is_valid_para(id)
+			|| <paras::Module<T>>::is_upcoming_para(id)
+			|| <paras::Module<T>>::outgoing_paras().binary_search(&id).is_ok()
+	}
+
+	/// Schedule the `paras` module to bring the given para in line with its registered lifecycle.
+	fn schedule_lifecycle(id: ParaId) {
+		match Paras::get(id) {
+			Some(true) => runtime_parachains::schedule_parathread_upgrade::<T>(id),
+			Some(false) => runtime_parachains::schedule_parachain_downgrade::<T>(id),
+			None => {}
+		}
+	}
+
+	/// Move the deposit of a reserved para from its manager to `new_manager` and make it the
+	/// manager, dropping any pending proposal.
+	fn do_set_manager(
+		id: ParaId,
+		mut info: ParaInfo<T::AccountId, BalanceOf<T>>,
+		new_manager: T::AccountId,
+	) -> DispatchResult {
+		<T as Trait>::Currency::reserve(&new_manager, info.deposit)?;
+		let _ = <T as Trait>::Currency::unreserve(&info.manager, info.deposit);
+
+		info.manager = new_manager.clone();
+		<ReservedParas<T>>::insert(id, info);
+		<PendingManager<T>>::remove(id);
+
+		Self::deposit_event(RawEvent::ManagerChanged(id, new_manager));
+
+		Ok(())
+	}
+
+	/// Ensure that the origin is either root or the para with the given ID.
+	fn ensure_root_or_para(
+		origin: <T as frame_system::Trait>::Origin,
+		id: ParaId,
+	) -> DispatchResult {
+		if ensure_root(origin.clone()).is_ok() {
+			return Ok(());
+		}
+
+		let caller = ensure_parachain(<T as Trait>::Origin::from(origin))?;
+		ensure!(caller == id, Error::<T>::NotOwner);
+
+		Ok(())
+	}
+
+	/// Ensure that the origin is either root, the para with the given ID, or the manager of the
+	/// para, as long as it isn't locked. Returns the information about the reserved para.
+	fn ensure_root_para_or_manager(
+		origin: <T as frame_system::Trait>::Origin,
+		id: ParaId,
+	) -> result::Result<ParaInfo<T::AccountId, BalanceOf<T>>, DispatchError> {
+		let info = <ReservedParas<T>>::get(id).ok_or(Error::<T>::NotReserved)?;
+
+		match ensure_signed(origin.clone()) {
+			Ok(who) => {
+				ensure!(info.manager == who, Error::<T>::NotOwner);
+				ensure!(!info.locked, Error::<T>::ParaLocked);
+			}
+			Err(_) => Self::ensure_root_or_para(origin, id)?,
+		}
+
+		Ok(info)
+	}
+}
+
+impl<T: Trait> crate::slots::Registrar<T::AccountId> for Module<T> {
+	fn new_id() -> ParaId {
+		Self::next_free_id()
+	}
+
+	fn head_data_size_allowed(head_data_size: u32) -> bool {
+		head_data_size <= <configuration::Module<T>>::config().max_head_data_size
+	}
+
+	fn code_size_allowed(code_size: u32) -> bool {
+		code_size <= <configuration::Module<T>>::config().max_code_size
+	}
+
+	fn register_para(
+		id: ParaId,
+		parachain: bool,
+		code: ValidationCode,
+		initial_head_data: HeadData,
+	) -> DispatchResult {
+		match Paras::get(id) {
+			// A para that was registered as a parathread won a lease: upgrade it.
+			Some(false) if parachain => return Self::make_parachain(id),
+			Some(_) => return Err(Error::<T>::ParaAlreadyExists.into()),
+			None => {}
+		}
+
+		ensure!(!Self::is_known_to_paras(id), Error::<T>::ParaAlreadyExists);
+
+		Paras::insert(id, parachain);
+
+		let genesis = ParaGenesisArgs {
+			genesis_head: initial_head_data,
+			validation_code: code,
+			parachain,
+		};
+
+		runtime_parachains::schedule_para_initialize::<T>(id, genesis);
+
+		Ok(())
+	}
+
+	fn deregister_para(id: ParaId) -> DispatchResult {
+		// A reserved para keeps its registration and falls back to being a parathread.
+		if <ReservedParas<T>>::contains_key(id) {
+			return Self::make_parathread(id);
+		}
+
+		Paras::take(id).ok_or(Error::<T>::InvalidChainId)?;
+		runtime_parachains::schedule_para_cleanup::<T>(id);
+
+		Ok(())
+	}
 }
 
 #[cfg(test)]
@@ -266,7 +653,7 @@ mod tests {
 	};
 	use frame_support::{
 		traits::{Randomness, OnInitialize, OnFinalize},
-		impl_outer_origin, impl_outer_dispatch, assert_ok, parameter_types,
+		impl_outer_origin, impl_outer_dispatch, assert_ok, assert_noop, parameter_types,
 	};
 	use keyring::Sr25519Keyring;
 	use runtime_parachains::{initializer, configuration, inclusion, scheduler, dmp, ump, hrmp};
@@ -430,11 +817,14 @@ mod tests {
 	impl dmp::Trait for Test {}
 
 	impl ump::Trait for Test {
+		type Event = ();
 		type UmpSink = ();
 	}
 
 	impl hrmp::Trait for Test {
+		type Event = ();
 		type Origin = Origin;
+		type Currency = pallet_balances::Module<Test>;
 	}
 
 	impl pallet_session::historical::Trait for Test {
@@ -489,7 +879,9 @@ mod tests {
 		type Randomness = TestRandomness;
 	}
 
-	impl scheduler::Trait for Test { }
+	impl scheduler::Trait for Test {
+		type ClaimHandler = ();
+	}
 
 	type Extrinsic = TestXt<Call, ()>;
 
@@ -513,6 +905,8 @@ mod tests {
 
 	parameter_types! {
 		pub const ParathreadDeposit: Balance = 10;
+		pub const ParaDeposit: Balance = 10;
+		pub const DataDepositPerByte: Balance = 1;
 		pub const QueueSize: usize = 2;
 		pub const MaxRetries: u32 = 3;
 	}
@@ -521,6 +915,9 @@ mod tests {
 		type Origin = Origin;
 		type Currency = pallet_balances::Module<Test>;
 		type ParathreadDeposit = ParathreadDeposit;
+		type Event = ();
+		type ParaDeposit = ParaDeposit;
+		type DataDepositPerByte = DataDepositPerByte;
 	}
 
 	type Balances = pallet_balances::Module<Test>;
@@ -561,6 +958,14 @@ mod tests {
 			balances,
 		}.assimilate_storage(&mut t).unwrap();
 
+		configuration::GenesisConfig::<Test> {
+			config: configuration::HostConfiguration {
+				max_code_size: 100,
+				max_head_data_size: 10,
+				..Default::default()
+			},
+		}.assimilate_storage(&mut t).unwrap();
+
 		t.into()
 	}
 
@@ -719,4 +1124,353 @@ mod tests {
 			));
 		});
 	}
+
+	#[test]
+	fn reserve_register_deregister_works() {
+		new_test_ext().execute_with(|| {
+			run_to_block(1);
+
+			let orig_bal = Balances::free_balance(&3u64);
+			let id = LOWEST_USER_ID;
+
+			assert_ok!(Registrar::reserve(Origin::signed(3)));
+			assert_eq!(
+				Registrar::reserved_paras(id),
+				Some(ParaInfo { manager: 3, deposit: ParaDeposit::get(), locked: false }),
+			);
+			assert_eq!(Registrar::next_free_para_id(), id + 1);
+			assert_eq!(Balances::reserved_balance(3), ParaDeposit::get());
+
+			// only the manager may register the genesis data.
+			assert_noop!(
+				Registrar::register(Origin::signed(4), id, vec![3; 3].into(), WASM_MAGIC.to_vec().into()),
+				Error::<Test>::NotOwner,
+			);
+			assert_noop!(
+				Registrar::register(Origin::signed(3), id + 1, vec![3; 3].into(), WASM_MAGIC.to_vec().into()),
+				Error::<Test>::NotReserved,
+			);
+			assert_noop!(
+				Registrar::register(Origin::signed(3), id, vec![3; 11].into(), WASM_MAGIC.to_vec().into()),
+				Error::<Test>::HeadDataTooLarge,
+			);
+			assert_noop!(
+				Registrar::register(Origin::signed(3), id, vec![3; 3].into(), vec![0; 8].into()),
+				Error::<Test>::DefinitelyNotWasm,
+			);
+
+			assert_ok!(Registrar::register(
+				Origin::signed(3),
+				id,
+				vec![3; 3].into(),
+				WASM_MAGIC.to_vec().into(),
+			));
+
+			// the data deposit is taken on top of the para deposit.
+			let deposit = ParaDeposit::get() + 3 + WASM_MAGIC.len() as Balance;
+			assert_eq!(Balances::reserved_balance(3), deposit);
+			assert_eq!(Registrar::reserved_paras(id).unwrap().deposit, deposit);
+			assert_eq!(Registrar::paras(id), Some(false));
+
+			assert_noop!(
+				Registrar::register(Origin::signed(3), id, vec![3; 3].into(), WASM_MAGIC.to_vec().into()),
+				Error::<Test>::ParaAlreadyExists,
+			);
+
+			run_to_block(3);
+
+			assert!(Parachains::is_parathread(id));
+
+			assert_ok!(Registrar::deregister(Origin::signed(3), id));
+
+			assert_eq!(Registrar::paras(id), None);
+			assert_eq!(Registrar::reserved_paras(id), None);
+			assert_eq!(Balances::free_balance(3), orig_bal);
+			assert_eq!(Balances::reserved_balance(3), 0);
+
+			run_to_block(6);
+
+			assert!(!Parachains::is_valid_para(id));
+		});
+	}
+
+	#[test]
+	fn reserve_skips_registered_ids() {
+		new_test_ext().execute_with(|| {
+			run_to_block(1);
+
+			assert_ok!(Registrar::register_parachain(
+				LOWEST_USER_ID,
+				vec![1; 3].into(),
+				WASM_MAGIC.to_vec().into(),
+			));
+
+			assert_ok!(Registrar::reserve(Origin::signed(1)));
+			assert_ok!(Registrar::reserve(Origin::signed(2)));
+
+			assert_eq!(Registrar::reserved_paras(LOWEST_USER_ID), None);
+			assert_eq!(Registrar::reserved_paras(LOWEST_USER_ID + 1).unwrap().manager, 1);
+			assert_eq!(Registrar::reserved_paras(LOWEST_USER_ID + 2).unwrap().manager, 2);
+			assert_eq!(Registrar::next_free_para_id(), LOWEST_USER_ID + 3);
+
+			// reserved IDs can't be taken by a parathread registration.
+			assert_ok!(Registrar::enable_parathread_registration(Origin::root()));
+			assert_noop!(
+				Registrar::register_parathread(
+					Origin::signed(3),
+					LOWEST_USER_ID + 1,
+					vec![1; 3].into(),
+					WASM_MAGIC.to_vec().into(),
+				),
+				Error::<Test>::ParaAlreadyExists,
+			);
+		});
+	}
+
+	#[test]
+	fn set_manager_moves_deposit() {
+		new_test_ext().execute_with(|| {
+			run_to_block(1);
+
+			let id = LOWEST_USER_ID;
+			assert_ok!(Registrar::reserve(Origin::signed(1)));
+
+			assert_noop!(Registrar::set_manager(Origin::signed(2), id, 2), Error::<Test>::NotOwner);
+			assert_ok!(Registrar::set_manager(Origin::signed(1), id, 2));
+
+			// nothing moves until the proposed manager accepts.
+			assert_eq!(Registrar::reserved_paras(id).unwrap().manager, 1);
+			assert_eq!(Balances::reserved_balance(1), ParaDeposit::get());
+			assert_eq!(Balances::reserved_balance(2), 0);
+
+			assert_noop!(Registrar::accept_manager(Origin::signed(3), id), Error::<Test>::NotProposedManager);
+			assert_ok!(Registrar::accept_manager(Origin::signed(2), id));
+
+			assert_eq!(Registrar::reserved_paras(id).unwrap().manager, 2);
+			assert_eq!(Registrar::pending_manager(id), None);
+			assert_eq!(Balances::reserved_balance(1), 0);
+			assert_eq!(Balances::reserved_balance(2), ParaDeposit::get());
+
+			// the new manager receives the deposit on deregistration.
+			assert_ok!(Registrar::deregister(Origin::signed(2), id));
+			assert_eq!(Balances::reserved_balance(2), 0);
+		});
+	}
+
+	#[test]
+	fn proposed_managers_cannot_accept_once_superseded() {
+		new_test_ext().execute_with(|| {
+			run_to_block(1);
+
+			let id = LOWEST_USER_ID;
+			assert_ok!(Registrar::reserve(Origin::signed(1)));
+			assert_ok!(Registrar::set_manager(Origin::signed(1), id, 2));
+
+			// locking the para blocks the pending proposal.
+			assert_ok!(Registrar::add_lock(Origin::signed(1), id));
+			assert_noop!(Registrar::accept_manager(Origin::signed(2), id), Error::<Test>::ParaLocked);
+
+			// a forced change drops it altogether.
+			assert_ok!(Registrar::set_manager(Origin::root(), id, 3));
+			assert_eq!(Registrar::pending_manager(id), None);
+			assert_noop!(Registrar::accept_manager(Origin::signed(2), id), Error::<Test>::NotProposedManager);
+
+			assert_eq!(Balances::reserved_balance(1), 0);
+			assert_eq!(Balances::reserved_balance(2), 0);
+			assert_eq!(Balances::reserved_balance(3), ParaDeposit::get());
+		});
+	}
+
+	#[test]
+	fn locks_restrict_management() {
+		new_test_ext().execute_with(|| {
+			run_to_block(1);
+
+			let id = LOWEST_USER_ID;
+			let para_origin: Origin = runtime_parachains::Origin::Parachain(id).into();
+			let other_para_origin: Origin = runtime_parachains::Origin::Parachain(id + 1).into();
+
+			assert_ok!(Registrar::reserve(Origin::signed(1)));
+			assert_ok!(Registrar::add_lock(Origin::signed(1), id));
+
+			// the manager can no longer manage the para, nor lift the lock.
+			assert_noop!(
+				Registrar::register(Origin::signed(1), id, vec![1; 3].into(), WASM_MAGIC.to_vec().into()),
+				Error::<Test>::ParaLocked,
+			);
+			assert_noop!(Registrar::set_manager(Origin::signed(1), id, 2), Error::<Test>::ParaLocked);
+			assert_noop!(Registrar::deregister(Origin::signed(1), id), Error::<Test>::ParaLocked);
+			assert!(Registrar::remove_lock(Origin::signed(1), id).is_err());
+			assert_noop!(Registrar::remove_lock(other_para_origin, id), Error::<Test>::NotOwner);
+
+			// root and the para itself still can.
+			assert_ok!(Registrar::set_manager(Origin::root(), id, 2));
+			assert_ok!(Registrar::remove_lock(para_origin.clone(), id));
+			assert_ok!(Registrar::set_manager(Origin::signed(2), id, 3));
+			assert_ok!(Registrar::accept_manager(Origin::signed(3), id));
+			assert_ok!(Registrar::add_lock(Origin::root(), id));
+			assert_ok!(Registrar::deregister(para_origin, id));
+
+			assert_eq!(Balances::reserved_balance(3), 0);
+		});
+	}
+
+	#[test]
+	fn upgrade_and_downgrade_works() {
+		new_test_ext().execute_with(|| {
+			run_to_block(1);
+
+			let id = LOWEST_USER_ID;
+			assert_ok!(Registrar::reserve(Origin::signed(1)));
+			assert_ok!(Registrar::register(
+				Origin::signed(1),
+				id,
+				vec![1; 3].into(),
+				WASM_MAGIC.to_vec().into(),
+			));
+
+			assert_noop!(Registrar::make_parathread(id), Error::<Test>::InvalidChainId);
+
+			run_to_block(3);
+
+			assert!(Parachains::is_parathread(id));
+
+			assert_ok!(Registrar::make_parachain(id));
+			assert_noop!(Registrar::make_parachain(id), Error::<Test>::InvalidThreadId);
+			assert_eq!(Registrar::paras(id), Some(true));
+
+			// parachains can't be deregistered.
+			assert_noop!(Registrar::deregister(Origin::signed(1), id), Error::<Test>::InvalidThreadId);
+
+			run_to_block(6);
+
+			assert!(Parachains::is_parachain(id));
+			assert!(!Parachains::is_parathread(id));
+
+			assert_ok!(Registrar::make_parathread(id));
+
+			run_to_block(9);
+
+			assert!(!Parachains::is_parachain(id));
+			assert!(Parachains::is_parathread(id));
+
+			assert_ok!(Registrar::deregister(Origin::signed(1), id));
+			assert_eq!(Balances::reserved_balance(1), 0);
+		});
+	}
+
+	#[test]
+	fn reserve_and_register_skip_ids_known_to_paras() {
+		new_test_ext().execute_with(|| {
+			run_to_block(1);
+
+			let genesis = || ParaGenesisArgs {
+				genesis_head: vec![1; 3].into(),
+				validation_code: WASM_MAGIC.to_vec().into(),
+				parachain: false,
+			};
+
+			// an ID scheduled for onboarding behind the registrar's back is never handed out.
+			runtime_parachains::schedule_para_initialize::<Test>(LOWEST_USER_ID, genesis());
+			assert_ok!(Registrar::reserve(Origin::signed(1)));
+			assert_eq!(Registrar::reserved_paras(LOWEST_USER_ID), None);
+			assert_eq!(Registrar::reserved_paras(LOWEST_USER_ID + 1).unwrap().manager, 1);
+
+			// nor can a reserved ID be registered once `paras` knows about it.
+			runtime_parachains::schedule_para_initialize::<Test>(LOWEST_USER_ID + 1, genesis());
+			assert_noop!(
+				Registrar::register(
+					Origin::signed(1),
+					LOWEST_USER_ID + 1,
+					vec![1; 3].into(),
+					WASM_MAGIC.to_vec().into(),
+				),
+				Error::<Test>::ParaAlreadyExists,
+			);
+
+			// that still holds once the para is live.
+			run_to_block(3);
+
+			assert!(Parachains::is_valid_para(LOWEST_USER_ID));
+			assert_ok!(Registrar::reserve(Origin::signed(2)));
+			assert_eq!(Registrar::reserved_paras(LOWEST_USER_ID + 2).unwrap().manager, 2);
+		});
+	}
+
+	#[test]
+	fn slots_registrar_registers_and_deregisters_paras() {
+		use crate::slots::Registrar as _;
+
+		new_test_ext().execute_with(|| {
+			run_to_block(1);
+
+			assert!(Registrar::head_data_size_allowed(10));
+			assert!(!Registrar::head_data_size_allowed(11));
+			assert!(Registrar::code_size_allowed(100));
+			assert!(!Registrar::code_size_allowed(101));
+
+			let id = Registrar::new_id();
+			assert_eq!(id, LOWEST_USER_ID);
+			assert_eq!(Registrar::next_free_para_id(), id + 1);
+
+			assert_ok!(Registrar::register_para(id, true, WASM_MAGIC.to_vec().into(), vec![1; 3].into()));
+			assert_eq!(Registrar::paras(id), Some(true));
+			assert_noop!(
+				Registrar::register_para(id, true, WASM_MAGIC.to_vec().into(), vec![1; 3].into()),
+				Error::<Test>::ParaAlreadyExists,
+			);
+
+			run_to_block(3);
+
+			assert!(Parachains::is_parachain(id));
+
+			assert_ok!(Registrar::deregister_para(id));
+			assert_eq!(Registrar::paras(id), None);
+			assert_noop!(Registrar::deregister_para(id), Error::<Test>::InvalidChainId);
+
+			run_to_block(6);
+
+			assert!(!Parachains::is_valid_para(id));
+		});
+	}
+
+	#[test]
+	fn slots_registrar_upgrades_and_downgrades_reserved_paras() {
+		use crate::slots::Registrar as _;
+
+		new_test_ext().execute_with(|| {
+			run_to_block(1);
+
+			let id = LOWEST_USER_ID;
+			assert_ok!(Registrar::reserve(Origin::signed(1)));
+			assert_ok!(Registrar::register(
+				Origin::signed(1),
+				id,
+				vec![1; 3].into(),
+				WASM_MAGIC.to_vec().into(),
+			));
+
+			// the reserved ID is not handed out to the auction.
+			assert_eq!(Registrar::new_id(), id + 1);
+
+			run_to_block(3);
+
+			// winning a lease upgrades the parathread, keeping its genesis data.
+			assert_ok!(Registrar::register_para(id, true, vec![0; 8].into(), vec![0; 3].into()));
+			assert_eq!(Registrar::paras(id), Some(true));
+
+			run_to_block(6);
+
+			assert!(Parachains::is_parachain(id));
+
+			// losing it downgrades the para again, without deregistering it.
+			assert_ok!(Registrar::deregister_para(id));
+			assert_eq!(Registrar::paras(id), Some(false));
+			assert!(Registrar::reserved_paras(id).is_some());
+
+			run_to_block(9);
+
+			assert!(Parachains::is_parathread(id));
+		});
+	}
 }
@@ -44,6 +44,9 @@ decl_error! {
 		ExceedsMaxMessageSize,
 		/// The validation code provided doesn't start with the Wasm file magic string.
 		DefinitelyNotWasm,
+		/// A DMP message couldn't be sent because the downward message queue of the destination
+		/// para is full.
+		QueueFull,
 	}
 }
 
@@ -75,8 +78,9 @@ decl_module! {
 
 		/// Send a downward message to the given para.
 		///
-		/// The given parachain should exist and the payload should not exceed the preconfigured size
-		/// `config.max_downward_message_size`.
+		/// The given parachain should exist, the payload should not exceed the preconfigured size
+		/// `config.max_downward_message_size` and it should fit into the downward message queue of
+		/// the para.
 		#[weight = (1_000, DispatchClass::Operational)]
 		pub fn sudo_queue_downward_message(origin, id: ParaId, payload: Vec<u8>) -> DispatchResult {
 			ensure_root(origin)?;
@@ -86,6 +90,10 @@ decl_module! {
 				.map_err(|e| match e {
 					dmp::QueueDownwardMessageError::ExceedsMaxMessageSize =>
 						Error::<T>::ExceedsMaxMessageSize.into(),
+					dmp::QueueDownwardMessageError::QueueFull =>
+						Error::<T>::QueueFull.into(),
+					dmp::QueueDownwardMessageError::Unroutable =>
+						Error::<T>::ParaDoesntExist.into(),
 				})
 		}
 	}
@@ -50,13 +50,47 @@ pub trait Trait: frame_system::Trait {
 	/// The number of blocks over which an auction may be retroactively ended.
 	type EndingPeriod: Get<Self::BlockNumber>;
 
+	/// The number of blocks in a single sample of the ending period. The winning bids are recorded
+	/// once per sample and the auction is retroactively ended at one of the samples.
+	type SampleLength: Get<Self::BlockNumber>;
+
 	/// The number of blocks over which a single period lasts.
 	type LeasePeriod: Get<Self::BlockNumber>;
 
-	/// Something that provides randomness in the runtime.
-	type Randomness: Randomness<Self::Hash>;
+	/// Something that provides the randomness used to determine the retroactive end of an auction.
+	type Randomness: EpochRandomness<Self::Hash>;
+}
+
+/// A source of randomness which changes once per epoch, such as BABE.
+pub trait EpochRandomness<Output> {
+	/// Returns the randomness for the given `subject` together with the index of the epoch at the
+	/// start of which it became known. It must not be possible to predict it before that epoch.
+	fn random(subject: &[u8]) -> (Output, u64);
+
+	/// Returns the index of the current epoch.
+	fn epoch_index() -> u64;
+}
+
+/// The randomness of the current BABE epoch.
+///
+/// The randomness of an epoch is computed from the VRF outputs of the epoch two epochs before it
+/// and becomes known at the start of the epoch in between.
+pub struct BabeEpochRandomness<T>(sp_std::marker::PhantomData<T>);
+
+impl<T: pallet_babe::Trait> EpochRandomness<T::Hash> for BabeEpochRandomness<T> {
+	fn random(subject: &[u8]) -> (T::Hash, u64) {
+		let randomness = <pallet_babe::Module<T> as Randomness<T::Hash>>::random(subject);
+		(randomness, Self::epoch_index().saturating_sub(1))
+	}
+
+	fn epoch_index() -> u64 {
+		<pallet_babe::Module<T>>::epoch_index()
+	}
 }
 
+/// The subject used to draw the randomness that determines the retroactive end of an auction.
+const AUCTION_ENDING_SUBJECT: &[u8] = b"slots_auction_ending";
+
 /// Parachain registration API.
 pub trait Registrar<AccountId> {
 	/// Create a new unique parachain identity for later registration.
@@ -130,6 +164,10 @@ pub enum Bidder<AccountId> {
 	/// An existing parachain, funds coming from the amount locked as part of a previous bid topped
 	/// up with funds administered by the parachain.
 	Existing(ParaId),
+
+	/// An existing parachain whose lease is extended by its off-boarding account, funds coming
+	/// from the amount locked as part of a previous bid topped up with funds of that account.
+	Extension(ParaId, AccountId),
 }
 
 impl<AccountId: Clone + Default + Codec> Bidder<AccountId> {
@@ -138,6 +176,15 @@ impl<AccountId: Clone + Default + Codec> Bidder<AccountId> {
 		match self {
 			Bidder::New(new_bidder) => new_bidder.who.clone(),
 			Bidder::Existing(para_id) => para_id.into_account(),
+			Bidder::Extension(_, who) => who.clone(),
+		}
+	}
+
+	/// Get the existing parachain this bid is for, if any.
+	fn existing_para(&self) -> Option<ParaId> {
+		match self {
+			Bidder::New(_) => None,
+			Bidder::Existing(para_id) | Bidder::Extension(para_id, _) => Some(*para_id),
 		}
 	}
 }
@@ -168,7 +215,7 @@ type WinningData<T> =
 // Winners data type. This encodes each of the final winners of a parachain auction, the parachain
 // index assigned to them, their winning bid and the range that they won.
 type WinnersData<T> =
-	Vec<(Option<NewBidder<<T as frame_system::Trait>::AccountId>>, ParaId, BalanceOf<T>, SlotRange)>;
+	Vec<(Bidder<<T as frame_system::Trait>::AccountId>, ParaId, BalanceOf<T>, SlotRange)>;
 
 // This module's storage items.
 decl_storage! {
@@ -202,11 +249,17 @@ decl_storage! {
 		/// auction will "begin to end", i.e. the first block of the Ending Period of the auction.
 		pub AuctionInfo get(fn auction_info): Option<(LeasePeriodOf<T>, T::BlockNumber)>;
 
-		/// The winning bids for each of the 10 ranges at each block in the final Ending Period of
-		/// the current auction. The map's key is the 0-based index into the Ending Period. The
-		/// first block of the ending period is 0; the last is `EndingPeriod - 1`.
+		/// The winning bids for each of the 10 ranges at each sample in the final Ending Period of
+		/// the current auction. The map's key is the 0-based index of the sample in the Ending
+		/// Period. The first sample of the ending period is 0; the last is the number of samples
+		/// minus one. Bids placed before the ending period are recorded in the first sample.
 		pub Winning get(fn winning): map hasher(twox_64_concat) T::BlockNumber => Option<WinningData<T>>;
 
+		/// The index of the randomness epoch at the latest block of the Ending Period of the current
+		/// auction. Once the Ending Period is over, the auction is ended with the first randomness
+		/// that became known in a later epoch.
+		pub EndingEpoch get(fn ending_epoch): Option<u64>;
+
 		/// Amounts currently reserved in the accounts of the bidders currently winning
 		/// (sub-)ranges.
 		pub ReservedAmounts get(fn reserved_amounts):
@@ -319,6 +372,10 @@ decl_error! {
 		CodeTooLarge,
 		/// Given initial head data is too large.
 		HeadDataTooLarge,
+		/// The Ending Period of the auction is over and it is awaiting its randomness.
+		AuctionEnded,
+		/// The parachain is not managed by this module.
+		ParaNotManaged,
 	}
 }
 
@@ -352,16 +409,18 @@ decl_module! {
 
 		fn on_finalize(now: T::BlockNumber) {
 			// If the current auction is in it ending period, then ensure that the (sub-)range
-			// winner information is duplicated from the previous block in case no bids happened
-			// in this block.
+			// winner information is duplicated from the previous sample in case no bids happened
+			// in this sample so far.
 			if let Some(offset) = Self::is_ending(now) {
-				if !<Winning<T>>::contains_key(&offset) {
-					<Winning<T>>::insert(offset,
-						offset.checked_sub(&One::one())
+				let sample = Self::sample_index(offset);
+				if !<Winning<T>>::contains_key(&sample) {
+					<Winning<T>>::insert(sample,
+						sample.checked_sub(&One::one())
 							.and_then(<Winning<T>>::get)
 							.unwrap_or_default()
 					);
 				}
+				EndingEpoch::put(T::Randomness::epoch_index());
 			}
 		}
 
@@ -447,6 +506,38 @@ decl_module! {
 			Self::handle_bid(bidder, auction_index, first_slot, last_slot, amount)?;
 		}
 
+		/// Make a new bid on behalf of an existing parachain for extending its lease.
+		///
+		/// The origin *must* be the off-boarding account of the parachain. The bid is treated like
+		/// a renewal bid of the parachain itself, except that any amount beyond the deposit already
+		/// held for the parachain is funded by the origin rather than the parachain account. If it
+		/// wins, the parachain keeps running under the same ID across the lease period boundary
+		/// without having to be deployed again.
+		///
+		/// - `para_id` is the parachain whose lease is to be extended.
+		/// - `auction_index` is the index of the auction to bid on. Should just be the present
+		/// value of `AuctionCounter`.
+		/// - `first_slot` is the first lease period index of the range to bid on. This is the
+		/// absolute lease period index value, not an auction-specific offset.
+		/// - `last_slot` is the last lease period index of the range to bid on. This is the
+		/// absolute lease period index value, not an auction-specific offset.
+		/// - `amount` is the amount to bid to be held as deposit for the parachain should the
+		/// bid win. This amount is held throughout the range.
+		#[weight = 500_000_000]
+		pub fn bid_extend(origin,
+			#[compact] para_id: ParaId,
+			#[compact] auction_index: AuctionIndex,
+			#[compact] first_slot: LeasePeriodOf<T>,
+			#[compact] last_slot: LeasePeriodOf<T>,
+			#[compact] amount: BalanceOf<T>
+		) {
+			let who = ensure_signed(origin)?;
+			ensure!(<Offboarding<T>>::contains_key(&para_id), Error::<T>::ParaNotManaged);
+			ensure!(<Offboarding<T>>::get(&para_id) == who, Error::<T>::InvalidOrigin);
+			let bidder = Bidder::Extension(para_id, who);
+			Self::handle_bid(bidder, auction_index, first_slot, last_slot, amount)?;
+		}
+
 		/// Set the off-boarding information for a parachain.
 		///
 		/// The origin *must* be a parachain account.
@@ -571,30 +662,52 @@ impl<T: Trait> Module<T> {
 		(<frame_system::Module<T>>::block_number() / T::LeasePeriod::get()).into()
 	}
 
-	/// Some when the auction's end is known (with the end block number). None if it is unknown.
-	/// If `Some` then the block number must be at most the previous block and at least the
-	/// previous block minus `T::EndingPeriod::get()`.
+	/// Returns the index of the sample that the given offset into the ending period belongs to.
+	fn sample_index(offset: T::BlockNumber) -> T::BlockNumber {
+		offset / T::SampleLength::get().max(One::one())
+	}
+
+	/// Returns `true` if the ending period of the current auction is over.
+	fn is_ended(now: T::BlockNumber, early_end: T::BlockNumber) -> bool {
+		now >= early_end + T::EndingPeriod::get()
+	}
+
+	/// Some when the auction's end is known (with the winning bids of the sample the auction ended
+	/// at). None if it is unknown.
+	///
+	/// Once the ending period is over, the auction is ended as soon as randomness that became
+	/// known in an epoch after the last block of the ending period is available. Until then,
+	/// the auction remains in progress but doesn't accept bids anymore.
 	///
 	/// This mutates the state, cleaning up `AuctionInfo` and `Winning` in the case of an auction
 	/// ending. An immediately subsequent call with the same argument will always return `None`.
 	fn check_auction_end(now: T::BlockNumber) -> Option<(WinningData<T>, LeasePeriodOf<T>)> {
-		if let Some((lease_period_index, early_end)) = <AuctionInfo<T>>::get() {
-			if early_end + T::EndingPeriod::get() == now {
-				// Just ended!
-				let ending_period = T::EndingPeriod::get();
-				let offset = T::BlockNumber::decode(&mut T::Randomness::random_seed().as_ref())
-					.expect("secure hashes always bigger than block numbers; qed") % ending_period;
-				let res = <Winning<T>>::get(offset).unwrap_or_default();
-				let mut i = T::BlockNumber::zero();
-				while i < ending_period {
-					<Winning<T>>::remove(i);
-					i += One::one();
-				}
-				<AuctionInfo<T>>::kill();
-				return Some((res, lease_period_index))
-			}
+		let (lease_period_index, early_end) = <AuctionInfo<T>>::get()?;
+		if !Self::is_ended(now, early_end) {
+			return None
 		}
-		None
+
+		let (randomness, known_since) = T::Randomness::random(AUCTION_ENDING_SUBJECT);
+		if EndingEpoch::get().map_or(false, |ending_epoch| known_since <= ending_epoch) {
+			// The randomness could have been known by the bidders before the ending period was
+			// over. Wait for the next one.
+			return None
+		}
+
+		let last_sample = Self::sample_index(
+			T::EndingPeriod::get().checked_sub(&One::one()).unwrap_or_else(Zero::zero)
+		);
+		let sample = T::BlockNumber::decode(&mut randomness.as_ref())
+			.expect("secure hashes always bigger than block numbers; qed") % (last_sample + One::one());
+		let res = <Winning<T>>::get(sample).unwrap_or_default();
+		let mut i = T::BlockNumber::zero();
+		while i <= last_sample {
+			<Winning<T>>::remove(i);
+			i += One::one();
+		}
+		<AuctionInfo<T>>::kill();
+		EndingEpoch::kill();
+		Some((res, lease_period_index))
 	}
 
 	/// Auction just ended. We have the current lease period, the auction's lease period (which
@@ -622,9 +735,9 @@ impl<T: Trait> Module<T> {
 
 		// Go through those winners and deduct their bid, updating our table of deposits
 		// accordingly.
-		for (maybe_new_deploy, para_id, amount, range) in winners.into_iter() {
-			match maybe_new_deploy {
-				Some(bidder) => {
+		for (winner, para_id, amount, range) in winners.into_iter() {
+			match winner {
+				Bidder::New(bidder) => {
 					// For new deployments we ensure the full amount is deducted. This should always
 					// succeed as we just unreserved the same amount above.
 					if T::Currency::withdraw(
@@ -658,14 +771,14 @@ impl<T: Trait> Module<T> {
 					let entry = (begin_lease_period, IncomingParachain::Unset(bidder));
 					<Onboarding<T>>::insert(&para_id, entry);
 				}
-				None => {
+				Bidder::Existing(_) | Bidder::Extension(..) => {
 					// For renewals, reserve any extra on top of what we already have held
 					// on deposit for their chain.
 					let extra = if let Some(additional) =
 						amount.checked_sub(&Self::deposit_held(&para_id))
 					{
 						if T::Currency::withdraw(
-							&para_id.into_account(),
+							&winner.funding_account(),
 							additional,
 							WithdrawReasons::FEE,
 							ExistenceRequirement::AllowDeath
@@ -805,17 +918,24 @@ impl<T: Trait> Module<T> {
 		// Bidding on latest auction.
 		ensure!(auction_index == <AuctionCounter>::get(), Error::<T>::NotCurrentAuction);
 		// Assume it's actually an auction (this should never fail because of above).
-		let (first_lease_period, _) = <AuctionInfo<T>>::get().ok_or(Error::<T>::NotAuction)?;
+		let (first_lease_period, early_end) = <AuctionInfo<T>>::get().ok_or(Error::<T>::NotAuction)?;
+		let now = <frame_system::Module<T>>::block_number();
+		ensure!(!Self::is_ended(now, early_end), Error::<T>::AuctionEnded);
+
+		// Only parachains that we administer may renew their lease.
+		if let Some(para_id) = bidder.existing_para() {
+			ensure!(Self::managed_ids().binary_search(&para_id).is_ok(), Error::<T>::ParaNotManaged);
+		}
 
 		// Our range.
 		let range = SlotRange::new_bounded(first_lease_period, first_slot, last_slot)?;
 		// Range as an array index.
 		let range_index = range as u8 as usize;
-		// The offset into the auction ending set.
-		let offset = Self::is_ending(<frame_system::Module<T>>::block_number()).unwrap_or_default();
+		// The sample of the auction ending set.
+		let sample = Self::sample_index(Self::is_ending(now).unwrap_or_default());
 		// The current winning ranges.
-		let mut current_winning = <Winning<T>>::get(offset)
-			.or_else(|| offset.checked_sub(&One::one()).and_then(<Winning<T>>::get))
+		let mut current_winning = <Winning<T>>::get(sample)
+			.or_else(|| sample.checked_sub(&One::one()).and_then(<Winning<T>>::get))
 			.unwrap_or_default();
 		// If this bid beat the previous winner of our range.
 		if current_winning[range_index].as_ref().map_or(true, |last| amount > last.1) {
@@ -832,13 +952,10 @@ impl<T: Trait> Module<T> {
 
 			// Ok; we are the new winner of this range - reserve the additional amount and record.
 
-			// Get the amount already held on deposit on our behalf if this is a renewal bid from
+			// Get the amount already held on deposit on our behalf if this is a renewal bid for
 			// an existing parachain.
-			let deposit_held = if let Bidder::Existing(ref bidder_para_id) = bidder {
-				Self::deposit_held(bidder_para_id)
-			} else {
-				Zero::zero()
-			};
+			let deposit_held = bidder.existing_para()
+				.map_or_else(Zero::zero, |para_id| Self::deposit_held(&para_id));
 			// Get the amount already reserved in any prior and still active bids by us.
 			let already_reserved =
 				<ReservedAmounts<T>>::get(&bidder).unwrap_or_default() + deposit_held;
@@ -876,7 +993,7 @@ impl<T: Trait> Module<T> {
 				}
 			}
 			// Update the range winner.
-			<Winning<T>>::insert(offset, &current_winning);
+			<Winning<T>>::insert(sample, &current_winning);
 		}
 		Ok(())
 	}
@@ -927,10 +1044,8 @@ impl<T: Trait> Module<T> {
 			swap(&mut final_winner, winning[r as u8 as usize].as_mut()
 				.expect("none values are filtered out in previous logic; qed"));
 			let (slot_winner, bid) = final_winner;
-			match slot_winner {
-				Bidder::New(new_bidder) => (Some(new_bidder), new_id(), bid, r),
-				Bidder::Existing(para_id) => (None, para_id, bid, r),
-			}
+			let para_id = slot_winner.existing_para().unwrap_or_else(&new_id);
+			(slot_winner, para_id, bid, r)
 		}).collect::<Vec<_>>()
 	}
 }
@@ -1014,6 +1129,43 @@ mod tests {
 		pub static PARACHAIN_COUNT: RefCell<u32> = RefCell::new(0);
 		pub static PARACHAINS:
 			RefCell<HashMap<u32, (ValidationCode, HeadData)>> = RefCell::new(HashMap::new());
+		pub static RANDOMNESS: RefCell<H256> = RefCell::new(H256::zero());
+		pub static EPOCH_LENGTH: RefCell<BlockNumber> = RefCell::new(1);
+		pub static SAMPLE_LENGTH: RefCell<BlockNumber> = RefCell::new(1);
+	}
+
+	/// Randomness which is known from the start of the epoch it is drawn in.
+	pub struct TestRandomness;
+	impl EpochRandomness<H256> for TestRandomness {
+		fn random(_subject: &[u8]) -> (H256, u64) {
+			(RANDOMNESS.with(|r| *r.borrow()), Self::epoch_index())
+		}
+
+		fn epoch_index() -> u64 {
+			(System::block_number() / EPOCH_LENGTH.with(|l| *l.borrow())).into()
+		}
+	}
+
+	/// Make the auction end at the given sample when the randomness is drawn.
+	fn set_randomness(sample: u8) {
+		let mut randomness = H256::zero();
+		randomness.as_bytes_mut()[0] = sample;
+		RANDOMNESS.with(|r| *r.borrow_mut() = randomness);
+	}
+
+	fn set_epoch_length(length: BlockNumber) {
+		EPOCH_LENGTH.with(|l| *l.borrow_mut() = length);
+	}
+
+	pub struct SampleLength;
+	impl Get<BlockNumber> for SampleLength {
+		fn get() -> BlockNumber {
+			SAMPLE_LENGTH.with(|l| *l.borrow())
+		}
+	}
+
+	fn set_sample_length(length: BlockNumber) {
+		SAMPLE_LENGTH.with(|l| *l.borrow_mut() = length);
 	}
 
 	const MAX_CODE_SIZE: u32 = 100;
@@ -1080,13 +1232,13 @@ mod tests {
 		type Parachains = TestParachains;
 		type LeasePeriod = LeasePeriod;
 		type EndingPeriod = EndingPeriod;
-		type Randomness = RandomnessCollectiveFlip;
+		type SampleLength = SampleLength;
+		type Randomness = TestRandomness;
 	}
 
 	type System = frame_system::Module<Test>;
 	type Balances = pallet_balances::Module<Test>;
 	type Slots = Module<Test>;
-	type RandomnessCollectiveFlip = pallet_randomness_collective_flip::Module<Test>;
 
 	// This function basically just builds a genesis storage key/value store according to
 	// our desired mock up.
@@ -1469,6 +1621,63 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn manager_can_extend_lease() {
+		new_test_ext().execute_with(|| {
+			run_to_block(1);
+			assert_ok!(Slots::new_auction(Origin::root(), 5, 1));
+			assert_ok!(Slots::bid(Origin::signed(1), 0, 1, 1, 1, 1));
+
+			run_to_block(10);
+			let h = BlakeTwo256::hash(&[1u8][..]);
+			assert_ok!(Slots::fix_deploy_data(Origin::signed(1), 0, 0.into(), h, 1, vec![1].into()));
+			assert_ok!(Slots::elaborate_deploy_data(Origin::signed(0), 0.into(), vec![1].into()));
+			assert_eq!(Slots::deposits(ParaId::from(0)), vec![1]);
+
+			assert_ok!(Slots::new_auction(Origin::root(), 5, 2));
+			// Only the off-boarding account may bid on behalf of a parachain we administer.
+			assert_noop!(
+				Slots::bid_extend(Origin::signed(2), 0.into(), 2, 2, 2, 1),
+				Error::<Test>::InvalidOrigin,
+			);
+			assert_noop!(
+				Slots::bid_extend(Origin::signed(1), 1.into(), 2, 2, 2, 1),
+				Error::<Test>::ParaNotManaged,
+			);
+			assert_noop!(
+				Slots::bid_renew(Origin::signed(ParaId::from(1).into_account()), 2, 2, 2, 1),
+				Error::<Test>::ParaNotManaged,
+			);
+			// Only the amount beyond the deposit already held is reserved, and it comes from the
+			// off-boarding account rather than the parachain account.
+			assert_ok!(Slots::bid_extend(Origin::signed(1), 0.into(), 2, 2, 2, 2));
+			assert_eq!(Balances::reserved_balance(1), 1);
+			assert_eq!(Balances::reserved_balance(ParaId::from(0).into_account()), 0);
+
+			run_to_block(19);
+			assert_eq!(Slots::deposits(ParaId::from(0)), vec![1, 2]);
+			assert_eq!(Slots::onboarding(ParaId::from(0)), None);
+			assert_eq!(Balances::free_balance(1), 8);
+			assert_eq!(Balances::reserved_balance(1), 0);
+
+			// The parachain keeps running across the lease period boundary without being deployed
+			// again.
+			run_to_block(20);
+			assert_eq!(Slots::managed_ids(), vec![0.into()]);
+			assert_eq!(Slots::deposits(ParaId::from(0)), vec![2]);
+			with_parachains(|p| {
+				assert_eq!(p.len(), 1);
+				assert_eq!(p[&0], (vec![1].into(), vec![1].into()));
+			});
+
+			run_to_block(30);
+			with_parachains(|p| {
+				assert_eq!(p.len(), 0);
+			});
+			assert_eq!(Balances::free_balance(1), 10);
+		});
+	}
+
 	#[test]
 	fn renewal_with_lower_value_should_work() {
 		new_test_ext().execute_with(|| {
@@ -1575,6 +1784,77 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn auction_ends_with_randomness_drawn_after_ending_period() {
+		new_test_ext().execute_with(|| {
+			set_epoch_length(4);
+			run_to_block(1);
+
+			assert_ok!(Slots::new_auction(Origin::root(), 3, 1));
+
+			for i in 1..4u64 {
+				run_to_block((i + 3) as _);
+				assert_ok!(Slots::bid(Origin::signed(i), 0, 1, 1, 4, i));
+			}
+			// The randomness known during the ending period would end the auction at the first
+			// sample.
+			set_randomness(0);
+
+			// The ending period is over, but the randomness of the epoch it ended in is not used.
+			run_to_block(7);
+			assert_eq!(Slots::is_in_progress(), true);
+			assert_eq!(Slots::onboard_queue(1), vec![]);
+			assert_noop!(Slots::bid(Origin::signed(4), 0, 1, 1, 4, 4), Error::<Test>::AuctionEnded);
+
+			// The randomness of the next epoch decides the winner.
+			set_randomness(1);
+			run_to_block(8);
+			assert_eq!(Slots::is_in_progress(), false);
+			assert_eq!(Slots::ending_epoch(), None);
+			assert_eq!(Slots::onboard_queue(1), vec![0.into()]);
+			assert_eq!(
+				Slots::onboarding(ParaId::from(0)),
+				Some((1, IncomingParachain::Unset(NewBidder { who: 2, sub: 0 })))
+			);
+			assert_eq!(Slots::deposit_held(&0.into()), 2);
+			assert_eq!(Balances::reserved_balance(2), 0);
+			assert_eq!(Balances::free_balance(2), 18);
+		});
+	}
+
+	#[test]
+	fn winning_bids_are_recorded_per_sample() {
+		new_test_ext().execute_with(|| {
+			set_sample_length(2);
+			run_to_block(1);
+
+			assert_ok!(Slots::new_auction(Origin::root(), 5, 1));
+
+			for i in 1..4u64 {
+				run_to_block((i + 5) as _);
+				assert_ok!(Slots::bid(Origin::signed(i), 0, 1, 1, 4, i));
+			}
+
+			// The first two blocks of the ending period form the first sample and the last bid in
+			// it wins it.
+			let winner = |sample: BlockNumber| Slots::winning(sample)
+				.and_then(|w| w[SlotRange::ZeroThree as u8 as usize].clone());
+			assert_eq!(winner(0), Some((Bidder::New(NewBidder { who: 2, sub: 0 }), 2)));
+			assert_eq!(winner(1), Some((Bidder::New(NewBidder { who: 3, sub: 0 }), 3)));
+			assert_eq!(winner(2), None);
+
+			set_randomness(0);
+			run_to_block(9);
+			assert_eq!(Slots::is_in_progress(), false);
+			assert_eq!(Slots::winning(0), None);
+			assert_eq!(Slots::winning(1), None);
+			assert_eq!(
+				Slots::onboarding(ParaId::from(0)),
+				Some((1, IncomingParachain::Unset(NewBidder { who: 2, sub: 0 })))
+			);
+		});
+	}
+
 	#[test]
 	fn incomplete_calculate_winners_works() {
 		let winning = [
@@ -1590,7 +1870,7 @@ mod tests {
 			Some((Bidder::New(NewBidder{who: 1, sub: 0}), 1)),
 		];
 		let winners = vec![
-			(Some(NewBidder{who: 1, sub: 0}), 0.into(), 1, SlotRange::ThreeThree)
+			(Bidder::New(NewBidder{who: 1, sub: 0}), 0.into(), 1, SlotRange::ThreeThree)
 		];
 
 		assert_eq!(Slots::calculate_winners(winning, TestParachains::new_id), winners);
@@ -1611,7 +1891,7 @@ mod tests {
 			None,
 		];
 		let winners = vec![
-			(Some(NewBidder{who: 1, sub: 0}), 0.into(), 1, SlotRange::ZeroZero)
+			(Bidder::New(NewBidder{who: 1, sub: 0}), 0.into(), 1, SlotRange::ZeroZero)
 		];
 
 		assert_eq!(Slots::calculate_winners(winning, TestParachains::new_id), winners);
@@ -1643,10 +1923,10 @@ mod tests {
 			Some((Bidder::New(NewBidder{who: 5, sub: 0}), 1)),
 		];
 		let winners = vec![
-			(Some(NewBidder{who: 2,sub: 0}), 0.into(), 2, SlotRange::ZeroZero),
-			(Some(NewBidder{who: 3,sub: 0}), 1.into(), 1, SlotRange::OneOne),
-			(Some(NewBidder{who: 1,sub: 0}), 2.into(), 53, SlotRange::TwoTwo),
-			(Some(NewBidder{who: 5,sub: 0}), 3.into(), 1, SlotRange::ThreeThree)
+			(Bidder::New(NewBidder{who: 2,sub: 0}), 0.into(), 2, SlotRange::ZeroZero),
+			(Bidder::New(NewBidder{who: 3,sub: 0}), 1.into(), 1, SlotRange::OneOne),
+			(Bidder::New(NewBidder{who: 1,sub: 0}), 2.into(), 53, SlotRange::TwoTwo),
+			(Bidder::New(NewBidder{who: 5,sub: 0}), 3.into(), 1, SlotRange::ThreeThree)
 		];
 
 		assert_eq!(Slots::calculate_winners(winning.clone(), TestParachains::new_id), winners);
@@ -1654,19 +1934,19 @@ mod tests {
 		reset_count();
 		winning[SlotRange::ZeroThree as u8 as usize] = Some((Bidder::New(NewBidder{who: 1, sub: 0}), 2));
 		let winners = vec![
-			(Some(NewBidder{who: 2,sub: 0}), 0.into(), 2, SlotRange::ZeroZero),
-			(Some(NewBidder{who: 3,sub: 0}), 1.into(), 1, SlotRange::OneOne),
-			(Some(NewBidder{who: 1,sub: 0}), 2.into(), 53, SlotRange::TwoTwo),
-			(Some(NewBidder{who: 5,sub: 0}), 3.into(), 1, SlotRange::ThreeThree)
+			(Bidder::New(NewBidder{who: 2,sub: 0}), 0.into(), 2, SlotRange::ZeroZero),
+			(Bidder::New(NewBidder{who: 3,sub: 0}), 1.into(), 1, SlotRange::OneOne),
+			(Bidder::New(NewBidder{who: 1,sub: 0}), 2.into(), 53, SlotRange::TwoTwo),
+			(Bidder::New(NewBidder{who: 5,sub: 0}), 3.into(), 1, SlotRange::ThreeThree)
 		];
 		assert_eq!(Slots::calculate_winners(winning.clone(), TestParachains::new_id), winners);
 
 		reset_count();
 		winning[SlotRange::ZeroOne as u8 as usize] = Some((Bidder::New(NewBidder{who: 4, sub: 0}), 3));
 		let winners = vec![
-			(Some(NewBidder{who: 4,sub: 0}), 0.into(), 3, SlotRange::ZeroOne),
-			(Some(NewBidder{who: 1,sub: 0}), 1.into(), 53, SlotRange::TwoTwo),
-			(Some(NewBidder{who: 5,sub: 0}), 2.into(), 1, SlotRange::ThreeThree)
+			(Bidder::New(NewBidder{who: 4,sub: 0}), 0.into(), 3, SlotRange::ZeroOne),
+			(Bidder::New(NewBidder{who: 1,sub: 0}), 1.into(), 53, SlotRange::TwoTwo),
+			(Bidder::New(NewBidder{who: 5,sub: 0}), 2.into(), 1, SlotRange::ThreeThree)
 		];
 		assert_eq!(Slots::calculate_winners(winning.clone(), TestParachains::new_id), winners);
 	}
@@ -16,20 +16,27 @@
 
 //! Configuration manager for the Polkadot runtime parachains logic.
 //!
-//! Configuration can change only at session boundaries and is buffered until then.
+//! Configuration can change only at session boundaries and is buffered until then. Every change
+//! is checked for consistency before it is buffered.
+//!
+//! Selected members of the configuration can be overridden for individual paras. These overrides
+//! are buffered until the next session boundary as well. Changes to the base configuration are
+//! checked against the overrides of every para, too.
 
 use sp_std::prelude::*;
-use primitives::v1::{Balance, ValidatorId};
+use primitives::v1::{Balance, ValidatorId, Id as ParaId};
 use frame_support::{
 	decl_storage, decl_module, decl_error,
 	dispatch::DispatchResult,
-	weights::{DispatchClass, Weight},
+	storage::IterableStorageMap,
+	weights::{constants::WEIGHT_PER_MILLIS, DispatchClass, Weight},
 };
 use parity_scale_codec::{Encode, Decode};
 use frame_system::ensure_root;
+use sp_runtime::traits::Zero;
 
 /// All configuration of the runtime with respect to parachains and parathreads.
-#[derive(Clone, Encode, Decode, PartialEq, Default, sp_core::RuntimeDebug)]
+#[derive(Clone, Encode, Decode, PartialEq, sp_core::RuntimeDebug)]
 #[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
 pub struct HostConfiguration<BlockNumber> {
 	/// The minimum frequency at which parachains can update their validation code.
@@ -73,11 +80,22 @@ pub struct HostConfiguration<BlockNumber> {
 	/// decide to do with its PoV so this value in practice will be picked as a fraction of the PoV
 	/// size.
 	pub max_downward_message_size: u32,
+	/// Total number of messages allowed in the downward message queue of a single para at once.
+	///
+	/// No downward messages, including HRMP notifications, can be sent while this is 0.
+	pub max_downward_queue_count: u32,
+	/// Total size of messages, in bytes, allowed in the downward message queue of a single para at
+	/// once. Senders are charged an increasing delivery fee as the queue approaches this limit.
+	pub max_downward_queue_size: u32,
 	/// The amount of weight we wish to devote to the processing the dispatchable upward messages
 	/// stage.
 	///
 	/// NOTE that this is a soft limit and could be exceeded.
 	pub preferred_dispatchable_upward_messages_step_weight: Weight,
+	/// The maximum amount of weight any individual upward message may consume. Messages above this
+	/// weight go into the overweight queue and may only be serviced explicitly. Defaults to
+	/// `DEFAULT_UMP_MAX_INDIVIDUAL_WEIGHT`.
+	pub ump_max_individual_weight: Weight,
 	/// The maximum size of an upward message that can be sent by a candidate.
 	///
 	/// This parameter affects the size upper bound of the `CandidateCommitments`.
@@ -114,6 +132,393 @@ pub struct HostConfiguration<BlockNumber> {
 	pub hrmp_max_message_num_per_candidate: u32,
 }
 
+/// The default of `ump_max_individual_weight`, approximately 20ms. Without a limit every upward
+/// message would end up in the overweight queue.
+pub const DEFAULT_UMP_MAX_INDIVIDUAL_WEIGHT: Weight = 20 * WEIGHT_PER_MILLIS;
+
+impl<BlockNumber: Default> Default for HostConfiguration<BlockNumber> {
+	fn default() -> Self {
+		HostConfiguration {
+			validation_upgrade_frequency: Default::default(),
+			validation_upgrade_delay: Default::default(),
+			acceptance_period: Default::default(),
+			max_code_size: Default::default(),
+			max_head_data_size: Default::default(),
+			max_pov_size: Default::default(),
+			parathread_cores: Default::default(),
+			parathread_retries: Default::default(),
+			group_rotation_frequency: Default::default(),
+			chain_availability_period: Default::default(),
+			thread_availability_period: Default::default(),
+			scheduling_lookahead: Default::default(),
+			max_upward_queue_count: Default::default(),
+			max_upward_queue_size: Default::default(),
+			max_downward_message_size: Default::default(),
+			max_downward_queue_count: Default::default(),
+			max_downward_queue_size: Default::default(),
+			preferred_dispatchable_upward_messages_step_weight: Default::default(),
+			ump_max_individual_weight: DEFAULT_UMP_MAX_INDIVIDUAL_WEIGHT,
+			max_upward_message_size: Default::default(),
+			max_upward_message_num_per_candidate: Default::default(),
+			hrmp_open_request_ttl: Default::default(),
+			hrmp_sender_deposit: Default::default(),
+			hrmp_recipient_deposit: Default::default(),
+			hrmp_channel_max_capacity: Default::default(),
+			hrmp_channel_max_total_size: Default::default(),
+			hrmp_max_parachain_inbound_channels: Default::default(),
+			hrmp_max_parathread_inbound_channels: Default::default(),
+			hrmp_channel_max_message_size: Default::default(),
+			hrmp_max_parachain_outbound_channels: Default::default(),
+			hrmp_max_parathread_outbound_channels: Default::default(),
+			hrmp_max_message_num_per_candidate: Default::default(),
+		}
+	}
+}
+
+/// The `max_downward_queue_count` given to configurations stored before the downward queues were
+/// bounded.
+pub const MIGRATED_MAX_DOWNWARD_QUEUE_COUNT: u32 = 64;
+
+/// The `max_downward_queue_size` given to configurations stored before the downward queues were
+/// bounded.
+pub const MIGRATED_MAX_DOWNWARD_QUEUE_SIZE: u32 = 64 * 1024;
+
+/// A `HostConfiguration` as stored before the downward queues were bounded and before upward
+/// messages had an individual weight limit.
+#[derive(Decode)]
+#[cfg_attr(test, derive(Encode))]
+struct LegacyHostConfiguration<BlockNumber> {
+	validation_upgrade_frequency: BlockNumber,
+	validation_upgrade_delay: BlockNumber,
+	acceptance_period: BlockNumber,
+	max_code_size: u32,
+	max_head_data_size: u32,
+	max_pov_size: u32,
+	parathread_cores: u32,
+	parathread_retries: u32,
+	group_rotation_frequency: BlockNumber,
+	chain_availability_period: BlockNumber,
+	thread_availability_period: BlockNumber,
+	scheduling_lookahead: u32,
+	max_upward_queue_count: u32,
+	max_upward_queue_size: u32,
+	max_downward_message_size: u32,
+	preferred_dispatchable_upward_messages_step_weight: Weight,
+	max_upward_message_size: u32,
+	max_upward_message_num_per_candidate: u32,
+	hrmp_open_request_ttl: u32,
+	hrmp_sender_deposit: Balance,
+	hrmp_recipient_deposit: Balance,
+	hrmp_channel_max_capacity: u32,
+	hrmp_channel_max_total_size: u32,
+	hrmp_max_parachain_inbound_channels: u32,
+	hrmp_max_parathread_inbound_channels: u32,
+	hrmp_channel_max_message_size: u32,
+	hrmp_max_parachain_outbound_channels: u32,
+	hrmp_max_parathread_outbound_channels: u32,
+	hrmp_max_message_num_per_candidate: u32,
+}
+
+impl<BlockNumber> LegacyHostConfiguration<BlockNumber> {
+	/// Convert to the current layout, bounding the downward queues with the migration defaults and
+	/// limiting the weight of individual upward messages to `DEFAULT_UMP_MAX_INDIVIDUAL_WEIGHT`.
+	fn upgrade(self) -> HostConfiguration<BlockNumber> {
+		HostConfiguration {
+			validation_upgrade_frequency: self.validation_upgrade_frequency,
+			validation_upgrade_delay: self.validation_upgrade_delay,
+			acceptance_period: self.acceptance_period,
+			max_code_size: self.max_code_size,
+			max_head_data_size: self.max_head_data_size,
+			max_pov_size: self.max_pov_size,
+			parathread_cores: self.parathread_cores,
+			parathread_retries: self.parathread_retries,
+			group_rotation_frequency: self.group_rotation_frequency,
+			chain_availability_period: self.chain_availability_period,
+			thread_availability_period: self.thread_availability_period,
+			scheduling_lookahead: self.scheduling_lookahead,
+			max_upward_queue_count: self.max_upward_queue_count,
+			max_upward_queue_size: self.max_upward_queue_size,
+			max_downward_message_size: self.max_downward_message_size,
+			max_downward_queue_count: MIGRATED_MAX_DOWNWARD_QUEUE_COUNT,
+			max_downward_queue_size: MIGRATED_MAX_DOWNWARD_QUEUE_SIZE,
+			preferred_dispatchable_upward_messages_step_weight:
+				self.preferred_dispatchable_upward_messages_step_weight,
+			ump_max_individual_weight: DEFAULT_UMP_MAX_INDIVIDUAL_WEIGHT,
+			max_upward_message_size: self.max_upward_message_size,
+			max_upward_message_num_per_candidate: self.max_upward_message_num_per_candidate,
+			hrmp_open_request_ttl: self.hrmp_open_request_ttl,
+			hrmp_sender_deposit: self.hrmp_sender_deposit,
+			hrmp_recipient_deposit: self.hrmp_recipient_deposit,
+			hrmp_channel_max_capacity: self.hrmp_channel_max_capacity,
+			hrmp_channel_max_total_size: self.hrmp_channel_max_total_size,
+			hrmp_max_parachain_inbound_channels: self.hrmp_max_parachain_inbound_channels,
+			hrmp_max_parathread_inbound_channels: self.hrmp_max_parathread_inbound_channels,
+			hrmp_channel_max_message_size: self.hrmp_channel_max_message_size,
+			hrmp_max_parachain_outbound_channels: self.hrmp_max_parachain_outbound_channels,
+			hrmp_max_parathread_outbound_channels: self.hrmp_max_parathread_outbound_channels,
+			hrmp_max_message_num_per_candidate: self.hrmp_max_message_num_per_candidate,
+		}
+	}
+}
+
+/// The versions of the storage layout of this module.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, sp_core::RuntimeDebug)]
+pub enum Releases {
+	/// The downward queues are unbounded and upward messages have no individual weight limit.
+	V1,
+	/// The configuration bounds the downward queues and the weight of individual upward messages.
+	V2,
+}
+
+impl Default for Releases {
+	fn default() -> Self {
+		Releases::V1
+	}
+}
+
+/// Overrides of selected members of the `HostConfiguration` which only apply to a single para.
+///
+/// Members which are `None` fall back to the value of the active `HostConfiguration`.
+#[derive(Clone, Encode, Decode, PartialEq, Default, sp_core::RuntimeDebug)]
+pub struct ParaConfigOverrides {
+	/// Overrides `max_code_size`.
+	pub max_code_size: Option<u32>,
+	/// Overrides `max_head_data_size`.
+	pub max_head_data_size: Option<u32>,
+	/// Overrides `max_pov_size`.
+	pub max_pov_size: Option<u32>,
+	/// Overrides `max_upward_queue_count`.
+	pub max_upward_queue_count: Option<u32>,
+	/// Overrides `max_upward_queue_size`.
+	pub max_upward_queue_size: Option<u32>,
+	/// Overrides `max_upward_message_size`.
+	pub max_upward_message_size: Option<u32>,
+	/// Overrides `max_upward_message_num_per_candidate`.
+	pub max_upward_message_num_per_candidate: Option<u32>,
+	/// Overrides both `hrmp_max_parachain_inbound_channels` and
+	/// `hrmp_max_parathread_inbound_channels`.
+	pub hrmp_max_inbound_channels: Option<u32>,
+	/// Overrides both `hrmp_max_parachain_outbound_channels` and
+	/// `hrmp_max_parathread_outbound_channels`.
+	pub hrmp_max_outbound_channels: Option<u32>,
+	/// Overrides `hrmp_max_message_num_per_candidate`.
+	pub hrmp_max_message_num_per_candidate: Option<u32>,
+}
+
+impl ParaConfigOverrides {
+	/// Apply the overrides to the given configuration.
+	pub fn apply_to<BlockNumber>(&self, config: &mut HostConfiguration<BlockNumber>) {
+		fn apply(member: &mut u32, value: Option<u32>) {
+			if let Some(value) = value {
+				*member = value;
+			}
+		}
+
+		apply(&mut config.max_code_size, self.max_code_size);
+		apply(&mut config.max_head_data_size, self.max_head_data_size);
+		apply(&mut config.max_pov_size, self.max_pov_size);
+		apply(&mut config.max_upward_queue_count, self.max_upward_queue_count);
+		apply(&mut config.max_upward_queue_size, self.max_upward_queue_size);
+		apply(&mut config.max_upward_message_size, self.max_upward_message_size);
+		apply(
+			&mut config.max_upward_message_num_per_candidate,
+			self.max_upward_message_num_per_candidate,
+		);
+		apply(&mut config.hrmp_max_parachain_inbound_channels, self.hrmp_max_inbound_channels);
+		apply(&mut config.hrmp_max_parathread_inbound_channels, self.hrmp_max_inbound_channels);
+		apply(&mut config.hrmp_max_parachain_outbound_channels, self.hrmp_max_outbound_channels);
+		apply(&mut config.hrmp_max_parathread_outbound_channels, self.hrmp_max_outbound_channels);
+		apply(
+			&mut config.hrmp_max_message_num_per_candidate,
+			self.hrmp_max_message_num_per_candidate,
+		);
+	}
+}
+
+/// A reason for a `HostConfiguration` being inconsistent.
+#[derive(Clone, Copy, PartialEq, Eq, sp_core::RuntimeDebug)]
+pub enum InconsistentError {
+	/// `group_rotation_frequency` is zero.
+	ZeroGroupRotationFrequency,
+	/// `chain_availability_period` is zero.
+	ZeroChainAvailabilityPeriod,
+	/// `thread_availability_period` is zero.
+	ZeroThreadAvailabilityPeriod,
+	/// `max_upward_message_size` exceeds `max_upward_queue_size`.
+	UpwardMessageSizeExceedsQueueSize,
+	/// `max_upward_message_num_per_candidate` exceeds `max_upward_queue_count`.
+	UpwardMessageNumExceedsQueueCount,
+	/// `max_downward_message_size` exceeds `max_pov_size`.
+	DownwardMessageSizeExceedsPovSize,
+	/// `hrmp_channel_max_message_size` exceeds `hrmp_channel_max_total_size`.
+	HrmpMessageSizeExceedsChannelTotalSize,
+}
+
+impl<BlockNumber: Zero> HostConfiguration<BlockNumber> {
+	/// Returns all the reasons for which this configuration is inconsistent. An empty vector
+	/// means that the configuration is consistent.
+	pub fn inconsistencies(&self) -> Vec<InconsistentError> {
+		let mut errors = Vec::new();
+		let mut check = |is_consistent: bool, error| if !is_consistent {
+			errors.push(error);
+		};
+
+		check(
+			!self.group_rotation_frequency.is_zero(),
+			InconsistentError::ZeroGroupRotationFrequency,
+		);
+		check(
+			!self.chain_availability_period.is_zero(),
+			InconsistentError::ZeroChainAvailabilityPeriod,
+		);
+		check(
+			!self.thread_availability_period.is_zero(),
+			InconsistentError::ZeroThreadAvailabilityPeriod,
+		);
+		check(
+			self.max_upward_message_size <= self.max_upward_queue_size,
+			InconsistentError::UpwardMessageSizeExceedsQueueSize,
+		);
+		check(
+			self.max_upward_message_num_per_candidate <= self.max_upward_queue_count,
+			InconsistentError::UpwardMessageNumExceedsQueueCount,
+		);
+		check(
+			self.max_downward_message_size <= self.max_pov_size,
+			InconsistentError::DownwardMessageSizeExceedsPovSize,
+		);
+		check(
+			self.hrmp_channel_max_message_size <= self.hrmp_channel_max_total_size,
+			InconsistentError::HrmpMessageSizeExceedsChannelTotalSize,
+		);
+
+		errors
+	}
+}
+
+/// An update of a single member of the `HostConfiguration`.
+#[derive(Clone, Encode, Decode, PartialEq, sp_core::RuntimeDebug)]
+pub enum ConfigUpdate<BlockNumber> {
+	/// Sets `validation_upgrade_frequency`.
+	ValidationUpgradeFrequency(BlockNumber),
+	/// Sets `validation_upgrade_delay`.
+	ValidationUpgradeDelay(BlockNumber),
+	/// Sets `acceptance_period`.
+	AcceptancePeriod(BlockNumber),
+	/// Sets `max_code_size`.
+	MaxCodeSize(u32),
+	/// Sets `max_head_data_size`.
+	MaxHeadDataSize(u32),
+	/// Sets `max_pov_size`.
+	MaxPovSize(u32),
+	/// Sets `parathread_cores`.
+	ParathreadCores(u32),
+	/// Sets `parathread_retries`.
+	ParathreadRetries(u32),
+	/// Sets `group_rotation_frequency`.
+	GroupRotationFrequency(BlockNumber),
+	/// Sets `chain_availability_period`.
+	ChainAvailabilityPeriod(BlockNumber),
+	/// Sets `thread_availability_period`.
+	ThreadAvailabilityPeriod(BlockNumber),
+	/// Sets `scheduling_lookahead`.
+	SchedulingLookahead(u32),
+	/// Sets `max_upward_queue_count`.
+	MaxUpwardQueueCount(u32),
+	/// Sets `max_upward_queue_size`.
+	MaxUpwardQueueSize(u32),
+	/// Sets `max_downward_message_size`.
+	MaxDownwardMessageSize(u32),
+	/// Sets `max_downward_queue_count`.
+	MaxDownwardQueueCount(u32),
+	/// Sets `max_downward_queue_size`.
+	MaxDownwardQueueSize(u32),
+	/// Sets `preferred_dispatchable_upward_messages_step_weight`.
+	PreferredDispatchableUpwardMessagesStepWeight(Weight),
+	/// Sets `ump_max_individual_weight`.
+	UmpMaxIndividualWeight(Weight),
+	/// Sets `max_upward_message_size`.
+	MaxUpwardMessageSize(u32),
+	/// Sets `max_upward_message_num_per_candidate`.
+	MaxUpwardMessageNumPerCandidate(u32),
+	/// Sets `hrmp_open_request_ttl`.
+	HrmpOpenRequestTtl(u32),
+	/// Sets `hrmp_sender_deposit`.
+	HrmpSenderDeposit(Balance),
+	/// Sets `hrmp_recipient_deposit`.
+	HrmpRecipientDeposit(Balance),
+	/// Sets `hrmp_channel_max_capacity`.
+	HrmpChannelMaxCapacity(u32),
+	/// Sets `hrmp_channel_max_total_size`.
+	HrmpChannelMaxTotalSize(u32),
+	/// Sets `hrmp_max_parachain_inbound_channels`.
+	HrmpMaxParachainInboundChannels(u32),
+	/// Sets `hrmp_max_parathread_inbound_channels`.
+	HrmpMaxParathreadInboundChannels(u32),
+	/// Sets `hrmp_channel_max_message_size`.
+	HrmpChannelMaxMessageSize(u32),
+	/// Sets `hrmp_max_parachain_outbound_channels`.
+	HrmpMaxParachainOutboundChannels(u32),
+	/// Sets `hrmp_max_parathread_outbound_channels`.
+	HrmpMaxParathreadOutboundChannels(u32),
+	/// Sets `hrmp_max_message_num_per_candidate`.
+	HrmpMaxMessageNumPerCandidate(u32),
+}
+
+impl<BlockNumber: Copy + PartialEq> ConfigUpdate<BlockNumber> {
+	/// Applies the update to the given configuration. Returns whether the configuration changed.
+	pub fn apply_to(self, config: &mut HostConfiguration<BlockNumber>) -> bool {
+		macro_rules! set {
+			($member:ident, $new:expr) => {{
+				let new = $new;
+				sp_std::mem::replace(&mut config.$member, new) != new
+			}}
+		}
+
+		match self {
+			ConfigUpdate::ValidationUpgradeFrequency(new) => set!(validation_upgrade_frequency, new),
+			ConfigUpdate::ValidationUpgradeDelay(new) => set!(validation_upgrade_delay, new),
+			ConfigUpdate::AcceptancePeriod(new) => set!(acceptance_period, new),
+			ConfigUpdate::MaxCodeSize(new) => set!(max_code_size, new),
+			ConfigUpdate::MaxHeadDataSize(new) => set!(max_head_data_size, new),
+			ConfigUpdate::MaxPovSize(new) => set!(max_pov_size, new),
+			ConfigUpdate::ParathreadCores(new) => set!(parathread_cores, new),
+			ConfigUpdate::ParathreadRetries(new) => set!(parathread_retries, new),
+			ConfigUpdate::GroupRotationFrequency(new) => set!(group_rotation_frequency, new),
+			ConfigUpdate::ChainAvailabilityPeriod(new) => set!(chain_availability_period, new),
+			ConfigUpdate::ThreadAvailabilityPeriod(new) => set!(thread_availability_period, new),
+			ConfigUpdate::SchedulingLookahead(new) => set!(scheduling_lookahead, new),
+			ConfigUpdate::MaxUpwardQueueCount(new) => set!(max_upward_queue_count, new),
+			ConfigUpdate::MaxUpwardQueueSize(new) => set!(max_upward_queue_size, new),
+			ConfigUpdate::MaxDownwardMessageSize(new) => set!(max_downward_message_size, new),
+			ConfigUpdate::MaxDownwardQueueCount(new) => set!(max_downward_queue_count, new),
+			ConfigUpdate::MaxDownwardQueueSize(new) => set!(max_downward_queue_size, new),
+			ConfigUpdate::PreferredDispatchableUpwardMessagesStepWeight(new) =>
+				set!(preferred_dispatchable_upward_messages_step_weight, new),
+			ConfigUpdate::UmpMaxIndividualWeight(new) => set!(ump_max_individual_weight, new),
+			ConfigUpdate::MaxUpwardMessageSize(new) => set!(max_upward_message_size, new),
+			ConfigUpdate::MaxUpwardMessageNumPerCandidate(new) =>
+				set!(max_upward_message_num_per_candidate, new),
+			ConfigUpdate::HrmpOpenRequestTtl(new) => set!(hrmp_open_request_ttl, new),
+			ConfigUpdate::HrmpSenderDeposit(new) => set!(hrmp_sender_deposit, new),
+			ConfigUpdate::HrmpRecipientDeposit(new) => set!(hrmp_recipient_deposit, new),
+			ConfigUpdate::HrmpChannelMaxCapacity(new) => set!(hrmp_channel_max_capacity, new),
+			ConfigUpdate::HrmpChannelMaxTotalSize(new) => set!(hrmp_channel_max_total_size, new),
+			ConfigUpdate::HrmpMaxParachainInboundChannels(new) =>
+				set!(hrmp_max_parachain_inbound_channels, new),
+			ConfigUpdate::HrmpMaxParathreadInboundChannels(new) =>
+				set!(hrmp_max_parathread_inbound_channels, new),
+			ConfigUpdate::HrmpChannelMaxMessageSize(new) => set!(hrmp_channel_max_message_size, new),
+			ConfigUpdate::HrmpMaxParachainOutboundChannels(new) =>
+				set!(hrmp_max_parachain_outbound_channels, new),
+			ConfigUpdate::HrmpMaxParathreadOutboundChannels(new) =>
+				set!(hrmp_max_parathread_outbound_channels, new),
+			ConfigUpdate::HrmpMaxMessageNumPerCandidate(new) =>
+				set!(hrmp_max_message_num_per_candidate, new),
+		}
+	}
+}
+
 pub trait Trait: frame_system::Trait { }
 
 decl_storage! {
@@ -122,11 +527,52 @@ decl_storage! {
 		Config get(fn config) config(): HostConfiguration<T::BlockNumber>;
 		/// Pending configuration (if any) for the next session.
 		PendingConfig: Option<HostConfiguration<T::BlockNumber>>;
+		/// The active configuration overrides for individual paras.
+		ParaOverrides get(fn para_overrides): map hasher(twox_64_concat) ParaId => Option<ParaConfigOverrides>;
+		/// Changes to the per-para overrides to be applied at the next session. `None` removes the
+		/// overrides of the para.
+		PendingParaOverrides: Vec<(ParaId, Option<ParaConfigOverrides>)>;
+		/// The version of the storage layout. New chains start with the latest one.
+		StorageVersion build(|_: &GenesisConfig<T>| Releases::V2): Releases;
 	}
 }
 
 decl_error! {
-	pub enum Error for Module<T: Trait> { }
+	pub enum Error for Module<T: Trait> {
+		/// The group rotation frequency must be non-zero.
+		ZeroGroupRotationFrequency,
+		/// The availability period for parachains must be non-zero.
+		ZeroChainAvailabilityPeriod,
+		/// The availability period for parathreads must be non-zero.
+		ZeroThreadAvailabilityPeriod,
+		/// The maximum upward message size must not exceed the maximum upward queue size.
+		UpwardMessageSizeExceedsQueueSize,
+		/// The maximum number of upward messages per candidate must not exceed the maximum upward
+		/// queue count.
+		UpwardMessageNumExceedsQueueCount,
+		/// The maximum downward message size must not exceed the maximum PoV size.
+		DownwardMessageSizeExceedsPovSize,
+		/// The maximum HRMP message size must not exceed the maximum total size of an HRMP channel.
+		HrmpMessageSizeExceedsChannelTotalSize,
+	}
+}
+
+impl<T: Trait> From<InconsistentError> for Error<T> {
+	fn from(e: InconsistentError) -> Self {
+		match e {
+			InconsistentError::ZeroGroupRotationFrequency => Error::<T>::ZeroGroupRotationFrequency,
+			InconsistentError::ZeroChainAvailabilityPeriod => Error::<T>::ZeroChainAvailabilityPeriod,
+			InconsistentError::ZeroThreadAvailabilityPeriod => Error::<T>::ZeroThreadAvailabilityPeriod,
+			InconsistentError::UpwardMessageSizeExceedsQueueSize =>
+				Error::<T>::UpwardMessageSizeExceedsQueueSize,
+			InconsistentError::UpwardMessageNumExceedsQueueCount =>
+				Error::<T>::UpwardMessageNumExceedsQueueCount,
+			InconsistentError::DownwardMessageSizeExceedsPovSize =>
+				Error::<T>::DownwardMessageSizeExceedsPovSize,
+			InconsistentError::HrmpMessageSizeExceedsChannelTotalSize =>
+				Error::<T>::HrmpMessageSizeExceedsChannelTotalSize,
+		}
+	}
 }
 
 decl_module! {
@@ -134,13 +580,17 @@ decl_module! {
 	pub struct Module<T: Trait> for enum Call where origin: <T as frame_system::Trait>::Origin {
 		type Error = Error<T>;
 
+		fn on_runtime_upgrade() -> Weight {
+			Self::migrate_to_bounded_queues()
+		}
+
 		/// Set the validation upgrade frequency.
 		#[weight = (1_000, DispatchClass::Operational)]
 		pub fn set_validation_upgrade_frequency(origin, new: T::BlockNumber) -> DispatchResult {
 			ensure_root(origin)?;
 			Self::update_config_member(|config| {
 				sp_std::mem::replace(&mut config.validation_upgrade_frequency, new) != new
-			});
+			})?;
 			Ok(())
 		}
 
@@ -150,7 +600,7 @@ decl_module! {
 			ensure_root(origin)?;
 			Self::update_config_member(|config| {
 				sp_std::mem::replace(&mut config.validation_upgrade_delay, new) != new
-			});
+			})?;
 			Ok(())
 		}
 
@@ -160,7 +610,7 @@ decl_module! {
 			ensure_root(origin)?;
 			Self::update_config_member(|config| {
 				sp_std::mem::replace(&mut config.acceptance_period, new) != new
-			});
+			})?;
 			Ok(())
 		}
 
@@ -170,7 +620,7 @@ decl_module! {
 			ensure_root(origin)?;
 			Self::update_config_member(|config| {
 				sp_std::mem::replace(&mut config.max_code_size, new) != new
-			});
+			})?;
 			Ok(())
 		}
 
@@ -180,7 +630,7 @@ decl_module! {
 			ensure_root(origin)?;
 			Self::update_config_member(|config| {
 				sp_std::mem::replace(&mut config.max_pov_size, new) != new
-			});
+			})?;
 			Ok(())
 		}
 
@@ -190,7 +640,7 @@ decl_module! {
 			ensure_root(origin)?;
 			Self::update_config_member(|config| {
 				sp_std::mem::replace(&mut config.max_head_data_size, new) != new
-			});
+			})?;
 			Ok(())
 		}
 
@@ -200,7 +650,7 @@ decl_module! {
 			ensure_root(origin)?;
 			Self::update_config_member(|config| {
 				sp_std::mem::replace(&mut config.parathread_cores, new) != new
-			});
+			})?;
 			Ok(())
 		}
 
@@ -210,7 +660,7 @@ decl_module! {
 			ensure_root(origin)?;
 			Self::update_config_member(|config| {
 				sp_std::mem::replace(&mut config.parathread_retries, new) != new
-			});
+			})?;
 			Ok(())
 		}
 
@@ -221,7 +671,7 @@ decl_module! {
 			ensure_root(origin)?;
 			Self::update_config_member(|config| {
 				sp_std::mem::replace(&mut config.group_rotation_frequency, new) != new
-			});
+			})?;
 			Ok(())
 		}
 
@@ -231,7 +681,7 @@ decl_module! {
 			ensure_root(origin)?;
 			Self::update_config_member(|config| {
 				sp_std::mem::replace(&mut config.chain_availability_period, new) != new
-			});
+			})?;
 			Ok(())
 		}
 
@@ -241,7 +691,7 @@ decl_module! {
 			ensure_root(origin)?;
 			Self::update_config_member(|config| {
 				sp_std::mem::replace(&mut config.thread_availability_period, new) != new
-			});
+			})?;
 			Ok(())
 		}
 
@@ -251,7 +701,7 @@ decl_module! {
 			ensure_root(origin)?;
 			Self::update_config_member(|config| {
 				sp_std::mem::replace(&mut config.scheduling_lookahead, new) != new
-			});
+			})?;
 			Ok(())
 		}
 
@@ -261,7 +711,7 @@ decl_module! {
 			ensure_root(origin)?;
 			Self::update_config_member(|config| {
 				sp_std::mem::replace(&mut config.max_upward_queue_count, new) != new
-			});
+			})?;
 			Ok(())
 		}
 
@@ -271,7 +721,7 @@ decl_module! {
 			ensure_root(origin)?;
 			Self::update_config_member(|config| {
 				sp_std::mem::replace(&mut config.max_upward_queue_size, new) != new
-			});
+			})?;
 			Ok(())
 		}
 
@@ -281,7 +731,27 @@ decl_module! {
 			ensure_root(origin)?;
 			Self::update_config_member(|config| {
 				sp_std::mem::replace(&mut config.max_downward_message_size, new) != new
-			});
+			})?;
+			Ok(())
+		}
+
+		/// Sets the maximum number of messages that a downward message queue can hold at once.
+		#[weight = (1_000, DispatchClass::Operational)]
+		pub fn set_max_downward_queue_count(origin, new: u32) -> DispatchResult {
+			ensure_root(origin)?;
+			Self::update_config_member(|config| {
+				sp_std::mem::replace(&mut config.max_downward_queue_count, new) != new
+			})?;
+			Ok(())
+		}
+
+		/// Sets the maximum total size of messages that a downward message queue can hold at once.
+		#[weight = (1_000, DispatchClass::Operational)]
+		pub fn set_max_downward_queue_size(origin, new: u32) -> DispatchResult {
+			ensure_root(origin)?;
+			Self::update_config_member(|config| {
+				sp_std::mem::replace(&mut config.max_downward_queue_size, new) != new
+			})?;
 			Ok(())
 		}
 
@@ -291,7 +761,17 @@ decl_module! {
 			ensure_root(origin)?;
 			Self::update_config_member(|config| {
 				sp_std::mem::replace(&mut config.preferred_dispatchable_upward_messages_step_weight, new) != new
-			});
+			})?;
+			Ok(())
+		}
+
+		/// Sets the maximum amount of weight any individual upward message may consume.
+		#[weight = (1_000, DispatchClass::Operational)]
+		pub fn set_ump_max_individual_weight(origin, new: Weight) -> DispatchResult {
+			ensure_root(origin)?;
+			Self::update_config_member(|config| {
+				sp_std::mem::replace(&mut config.ump_max_individual_weight, new) != new
+			})?;
 			Ok(())
 		}
 
@@ -301,7 +781,7 @@ decl_module! {
 			ensure_root(origin)?;
 			Self::update_config_member(|config| {
 				sp_std::mem::replace(&mut config.max_upward_message_size, new) != new
-			});
+			})?;
 			Ok(())
 		}
 
@@ -311,7 +791,7 @@ decl_module! {
 			ensure_root(origin)?;
 			Self::update_config_member(|config| {
 				sp_std::mem::replace(&mut config.max_upward_message_num_per_candidate, new) != new
-			});
+			})?;
 			Ok(())
 		}
 
@@ -321,7 +801,7 @@ decl_module! {
 			ensure_root(origin)?;
 			Self::update_config_member(|config| {
 				sp_std::mem::replace(&mut config.hrmp_open_request_ttl, new) != new
-			});
+			})?;
 			Ok(())
 		}
 
@@ -331,7 +811,7 @@ decl_module! {
 			ensure_root(origin)?;
 			Self::update_config_member(|config| {
 				sp_std::mem::replace(&mut config.hrmp_sender_deposit, new) != new
-			});
+			})?;
 			Ok(())
 		}
 
@@ -342,7 +822,7 @@ decl_module! {
 			ensure_root(origin)?;
 			Self::update_config_member(|config| {
 				sp_std::mem::replace(&mut config.hrmp_recipient_deposit, new) != new
-			});
+			})?;
 			Ok(())
 		}
 
@@ -352,7 +832,7 @@ decl_module! {
 			ensure_root(origin)?;
 			Self::update_config_member(|config| {
 				sp_std::mem::replace(&mut config.hrmp_channel_max_capacity, new) != new
-			});
+			})?;
 			Ok(())
 		}
 
@@ -362,7 +842,7 @@ decl_module! {
 			ensure_root(origin)?;
 			Self::update_config_member(|config| {
 				sp_std::mem::replace(&mut config.hrmp_channel_max_total_size, new) != new
-			});
+			})?;
 			Ok(())
 		}
 
@@ -372,7 +852,7 @@ decl_module! {
 			ensure_root(origin)?;
 			Self::update_config_member(|config| {
 				sp_std::mem::replace(&mut config.hrmp_max_parachain_inbound_channels, new) != new
-			});
+			})?;
 			Ok(())
 		}
 
@@ -382,7 +862,7 @@ decl_module! {
 			ensure_root(origin)?;
 			Self::update_config_member(|config| {
 				sp_std::mem::replace(&mut config.hrmp_max_parathread_inbound_channels, new) != new
-			});
+			})?;
 			Ok(())
 		}
 
@@ -392,7 +872,7 @@ decl_module! {
 			ensure_root(origin)?;
 			Self::update_config_member(|config| {
 				sp_std::mem::replace(&mut config.hrmp_channel_max_message_size, new) != new
-			});
+			})?;
 			Ok(())
 		}
 
@@ -402,7 +882,7 @@ decl_module! {
 			ensure_root(origin)?;
 			Self::update_config_member(|config| {
 				sp_std::mem::replace(&mut config.hrmp_max_parachain_outbound_channels, new) != new
-			});
+			})?;
 			Ok(())
 		}
 
@@ -412,7 +892,7 @@ decl_module! {
 			ensure_root(origin)?;
 			Self::update_config_member(|config| {
 				sp_std::mem::replace(&mut config.hrmp_max_parathread_outbound_channels, new) != new
-			});
+			})?;
 			Ok(())
 		}
 
@@ -422,6 +902,49 @@ decl_module! {
 			ensure_root(origin)?;
 			Self::update_config_member(|config| {
 				sp_std::mem::replace(&mut config.hrmp_max_message_num_per_candidate, new) != new
+			})?;
+			Ok(())
+		}
+
+		/// Applies a batch of updates to the configuration at once.
+		///
+		/// The updates are applied in order and only the resulting configuration is checked for
+		/// consistency, so the batch is either buffered for the next session as a whole or rejected.
+		#[weight = ((updates.len() as Weight).saturating_mul(1_000), DispatchClass::Operational)]
+		pub fn set_config_batch(origin, updates: Vec<ConfigUpdate<T::BlockNumber>>) -> DispatchResult {
+			ensure_root(origin)?;
+			Self::update_config_member(|config| {
+				let mut changed = false;
+				for update in updates {
+					changed |= update.apply_to(config);
+				}
+				changed
+			})?;
+			Ok(())
+		}
+
+		/// Sets the configuration overrides for a particular para, or removes them if `None`.
+		///
+		/// The overrides must not make the configuration of the para inconsistent.
+		#[weight = (1_000, DispatchClass::Operational)]
+		pub fn set_para_overrides(
+			origin,
+			para: ParaId,
+			overrides: Option<ParaConfigOverrides>,
+		) -> DispatchResult {
+			ensure_root(origin)?;
+			if let Some(ref overrides) = overrides {
+				let base = <Self as Store>::PendingConfig::get().unwrap_or_else(Self::config);
+				let mut overridden = base.clone();
+				overrides.apply_to(&mut overridden);
+				Self::ensure_no_new_inconsistencies(&base, &overridden)?;
+			}
+
+			<Self as Store>::PendingParaOverrides::mutate(|pending| {
+				match pending.iter_mut().find(|(id, _)| id == &para) {
+					Some(entry) => entry.1 = overrides,
+					None => pending.push((para, overrides)),
+				}
 			});
 			Ok(())
 		}
@@ -437,21 +960,118 @@ impl<T: Trait> Module<T> {
 	/// Called by the initializer to finalize the configuration module.
 	pub(crate) fn initializer_finalize() { }
 
+	/// Convert the active and the pending configuration stored before the downward queues and the
+	/// weight of individual upward messages were bounded. Without this they would no longer decode
+	/// and fall back to the default, which doesn't allow any downward messages.
+	fn migrate_to_bounded_queues() -> Weight {
+		if <Self as Store>::StorageVersion::get() != Releases::V1 {
+			return T::DbWeight::get().reads(1);
+		}
+
+		let _ = <Self as Store>::Config::translate(
+			|config: Option<LegacyHostConfiguration<T::BlockNumber>>| config.map(|c| c.upgrade()),
+		);
+		let _ = <Self as Store>::PendingConfig::translate(
+			|config: Option<LegacyHostConfiguration<T::BlockNumber>>| config.map(|c| c.upgrade()),
+		);
+		<Self as Store>::StorageVersion::put(Releases::V2);
+
+		T::DbWeight::get().reads_writes(3, 3)
+	}
+
 	/// Called by the initializer to note that a new session has started.
 	pub(crate) fn initializer_on_new_session(_validators: &[ValidatorId], _queued: &[ValidatorId]) {
 		if let Some(pending) = <Self as Store>::PendingConfig::take() {
 			<Self as Store>::Config::set(pending);
 		}
+
+		for (para, overrides) in <Self as Store>::PendingParaOverrides::take() {
+			match overrides {
+				Some(overrides) => <Self as Store>::ParaOverrides::insert(&para, overrides),
+				None => <Self as Store>::ParaOverrides::remove(&para),
+			}
+		}
+	}
+
+	/// The configuration in effect for the given para. This is the active configuration with the
+	/// overrides of the para, if any, applied.
+	pub fn effective_config(para: ParaId) -> HostConfiguration<T::BlockNumber> {
+		Self::with_para_overrides(Self::config(), para)
+	}
+
+	/// Applies the active overrides of the given para, if any, to the given configuration.
+	pub(crate) fn with_para_overrides(
+		mut config: HostConfiguration<T::BlockNumber>,
+		para: ParaId,
+	) -> HostConfiguration<T::BlockNumber> {
+		if let Some(overrides) = <Self as Store>::ParaOverrides::get(&para) {
+			overrides.apply_to(&mut config);
+		}
+		config
+	}
+
+	/// Removes both the active and the pending overrides of the given para.
+	///
+	/// Called by the paras module when a para is offboarded.
+	pub(crate) fn clear_para_overrides(para: ParaId) {
+		<Self as Store>::ParaOverrides::remove(&para);
+		<Self as Store>::PendingParaOverrides::mutate(|pending| pending.retain(|(id, _)| id != &para));
+	}
+
+	/// The overrides of every para as they will be after the next session change, i.e. the active
+	/// overrides with the pending changes applied.
+	fn upcoming_para_overrides() -> Vec<(ParaId, ParaConfigOverrides)> {
+		let mut upcoming: Vec<_> = <Self as Store>::ParaOverrides::iter().collect();
+		for (para, overrides) in <Self as Store>::PendingParaOverrides::get() {
+			upcoming.retain(|(id, _)| id != &para);
+			if let Some(overrides) = overrides {
+				upcoming.push((para, overrides));
+			}
+		}
+		upcoming
 	}
 
+	/// Updates the pending configuration, or the active one if there is none pending, with the
+	/// given updater. The updater returns whether it changed anything.
+	///
+	/// Fails if the update makes the configuration inconsistent, either on its own or for any para
+	/// once its overrides are applied.
 	fn update_config_member(
 		updater: impl FnOnce(&mut HostConfiguration<T::BlockNumber>) -> bool,
-	) {
+	) -> DispatchResult {
 		let pending = <Self as Store>::PendingConfig::get();
-		let mut prev = pending.unwrap_or_else(Self::config);
+		let prev = pending.unwrap_or_else(Self::config);
+		let mut new = prev.clone();
+
+		if updater(&mut new) {
+			Self::ensure_no_new_inconsistencies(&prev, &new)?;
+
+			for (_, overrides) in Self::upcoming_para_overrides() {
+				let mut prev_overridden = prev.clone();
+				overrides.apply_to(&mut prev_overridden);
+				let mut new_overridden = new.clone();
+				overrides.apply_to(&mut new_overridden);
+				Self::ensure_no_new_inconsistencies(&prev_overridden, &new_overridden)?;
+			}
+
+			<Self as Store>::PendingConfig::set(Some(new));
+		}
+
+		Ok(())
+	}
 
-		if updater(&mut prev) {
-			<Self as Store>::PendingConfig::set(Some(prev));
+	/// Ensures that `new` has no inconsistencies which `prev` does not have already.
+	///
+	/// Inconsistencies which are already present, e.g. because of the genesis configuration, do
+	/// not block unrelated updates.
+	fn ensure_no_new_inconsistencies(
+		prev: &HostConfiguration<T::BlockNumber>,
+		new: &HostConfiguration<T::BlockNumber>,
+	) -> DispatchResult {
+		let prev_inconsistencies = prev.inconsistencies();
+		match new.inconsistencies().into_iter().find(|e| !prev_inconsistencies.contains(e)) {
+			Some(e) => Err(Error::<T>::from(e).into()),
+			None => Ok(()),
 		}
 	}
 }
@@ -459,9 +1079,26 @@ impl<T: Trait> Module<T> {
 #[cfg(test)]
 mod tests {
 	use super::*;
-	use crate::mock::{new_test_ext, Initializer, Configuration, Origin};
+	use crate::mock::{new_test_ext, Initializer, Configuration, Origin, Test};
 
-	use frame_support::traits::{OnFinalize, OnInitialize};
+	use frame_support::{assert_noop, assert_ok, traits::{OnFinalize, OnInitialize, OnRuntimeUpgrade}};
+
+	fn consistent_config() -> HostConfiguration<u32> {
+		HostConfiguration {
+			group_rotation_frequency: 10,
+			chain_availability_period: 3,
+			thread_availability_period: 5,
+			max_pov_size: 1024,
+			max_downward_message_size: 512,
+			max_upward_queue_count: 8,
+			max_upward_queue_size: 1024,
+			max_upward_message_size: 256,
+			max_upward_message_num_per_candidate: 4,
+			hrmp_channel_max_total_size: 1024,
+			hrmp_channel_max_message_size: 256,
+			..Default::default()
+		}
+	}
 
 	#[test]
 	fn config_changes_on_session_boundary() {
@@ -507,7 +1144,10 @@ mod tests {
 				max_upward_queue_count: 1337,
 				max_upward_queue_size: 228,
 				max_downward_message_size: 2048,
+				max_downward_queue_count: 64,
+				max_downward_queue_size: 16_384,
 				preferred_dispatchable_upward_messages_step_weight: 20000,
+				ump_max_individual_weight: 100_000,
 				max_upward_message_size: 448,
 				max_upward_message_num_per_candidate: 5,
 				hrmp_open_request_ttl: 1312,
@@ -567,15 +1207,30 @@ mod tests {
 			Configuration::set_max_upward_queue_size(
 				Origin::root(), new_config.max_upward_queue_size,
 			).unwrap();
-			Configuration::set_max_downward_message_size(
-				Origin::root(), new_config.max_downward_message_size,
+			assert_noop!(
+				Configuration::set_max_downward_message_size(
+					Origin::root(), new_config.max_downward_message_size,
+				),
+				Error::<Test>::DownwardMessageSizeExceedsPovSize,
+			);
+			Configuration::set_max_downward_queue_count(
+				Origin::root(), new_config.max_downward_queue_count,
+			).unwrap();
+			Configuration::set_max_downward_queue_size(
+				Origin::root(), new_config.max_downward_queue_size,
 			).unwrap();
 			Configuration::set_preferred_dispatchable_upward_messages_step_weight(
 				Origin::root(), new_config.preferred_dispatchable_upward_messages_step_weight,
 			).unwrap();
-			Configuration::set_max_upward_message_size(
-				Origin::root(), new_config.max_upward_message_size,
+			Configuration::set_ump_max_individual_weight(
+				Origin::root(), new_config.ump_max_individual_weight,
 			).unwrap();
+			assert_noop!(
+				Configuration::set_max_upward_message_size(
+					Origin::root(), new_config.max_upward_message_size,
+				),
+				Error::<Test>::UpwardMessageSizeExceedsQueueSize,
+			);
 			Configuration::set_max_upward_message_num_per_candidate(
 				Origin::root(), new_config.max_upward_message_num_per_candidate,
 			).unwrap();
@@ -607,10 +1262,13 @@ mod tests {
 				Origin::root(),
 				new_config.hrmp_max_parathread_inbound_channels,
 			).unwrap();
-			Configuration::set_hrmp_channel_max_message_size(
-				Origin::root(),
-				new_config.hrmp_channel_max_message_size,
-			).unwrap();
+			assert_noop!(
+				Configuration::set_hrmp_channel_max_message_size(
+					Origin::root(),
+					new_config.hrmp_channel_max_message_size,
+				),
+				Error::<Test>::HrmpMessageSizeExceedsChannelTotalSize,
+			);
 			Configuration::set_hrmp_max_parachain_outbound_channels(
 				Origin::root(),
 				new_config.hrmp_max_parachain_outbound_channels,
@@ -624,10 +1282,49 @@ mod tests {
 				new_config.hrmp_max_message_num_per_candidate,
 			).unwrap();
 
-			assert_eq!(<Configuration as Store>::PendingConfig::get(), Some(new_config));
+			// the members that would make the configuration inconsistent are left unchanged.
+			assert_eq!(<Configuration as Store>::PendingConfig::get(), Some(HostConfiguration {
+				max_downward_message_size: 0,
+				max_upward_message_size: 0,
+				hrmp_channel_max_message_size: 0,
+				..new_config
+			}));
 		})
 	}
 
+	#[test]
+	fn formerly_accepted_size_combination_is_rejected() {
+		new_test_ext(Default::default()).execute_with(|| {
+			<Configuration as Store>::Config::set(consistent_config());
+
+			// every message size used to be accepted regardless of the size of its queue or PoV.
+			assert_noop!(
+				Configuration::set_config_batch(Origin::root(), vec![
+					ConfigUpdate::MaxPovSize(1024),
+					ConfigUpdate::MaxDownwardMessageSize(2048),
+					ConfigUpdate::MaxUpwardQueueSize(228),
+					ConfigUpdate::MaxUpwardMessageSize(448),
+					ConfigUpdate::HrmpChannelMaxTotalSize(7687),
+					ConfigUpdate::HrmpChannelMaxMessageSize(8192),
+				]),
+				Error::<Test>::UpwardMessageSizeExceedsQueueSize,
+			);
+
+			let mut config = consistent_config();
+			config.max_pov_size = 1024;
+			config.max_downward_message_size = 2048;
+			config.max_upward_queue_size = 228;
+			config.max_upward_message_size = 448;
+			config.hrmp_channel_max_total_size = 7687;
+			config.hrmp_channel_max_message_size = 8192;
+			assert_eq!(config.inconsistencies(), vec![
+				InconsistentError::UpwardMessageSizeExceedsQueueSize,
+				InconsistentError::DownwardMessageSizeExceedsPovSize,
+				InconsistentError::HrmpMessageSizeExceedsChannelTotalSize,
+			]);
+		});
+	}
+
 	#[test]
 	fn non_root_cannot_set_config() {
 		new_test_ext(Default::default()).execute_with(|| {
@@ -642,4 +1339,286 @@ mod tests {
 			assert!(<Configuration as Store>::PendingConfig::get().is_none())
 		});
 	}
+
+	#[test]
+	fn para_overrides_change_on_session_boundary() {
+		new_test_ext(Default::default()).execute_with(|| {
+			let para_a = ParaId::from(1);
+			let para_b = ParaId::from(2);
+
+			let mut config = Configuration::config();
+			config.max_pov_size = 1024;
+			config.hrmp_max_parachain_inbound_channels = 4;
+			config.hrmp_max_parathread_inbound_channels = 2;
+			<Configuration as Store>::Config::set(config.clone());
+
+			let overrides = ParaConfigOverrides {
+				max_pov_size: Some(4096),
+				hrmp_max_inbound_channels: Some(10),
+				..Default::default()
+			};
+
+			assert!(Configuration::set_para_overrides(
+				Origin::signed(1), para_a, Some(overrides.clone()),
+			).is_err());
+			Configuration::set_para_overrides(Origin::root(), para_a, Some(overrides.clone())).unwrap();
+
+			// nothing changes until the session boundary.
+			assert_eq!(Configuration::effective_config(para_a), config);
+
+			Configuration::initializer_on_new_session(&[], &[]);
+
+			let effective = Configuration::effective_config(para_a);
+			assert_eq!(effective.max_pov_size, 4096);
+			assert_eq!(effective.hrmp_max_parachain_inbound_channels, 10);
+			assert_eq!(effective.hrmp_max_parathread_inbound_channels, 10);
+			assert_eq!(effective.max_code_size, config.max_code_size);
+
+			// other paras are not affected.
+			assert_eq!(Configuration::effective_config(para_b), config);
+
+			// removal is buffered as well, and the latest change for a para wins.
+			Configuration::set_para_overrides(Origin::root(), para_b, Some(overrides.clone())).unwrap();
+			Configuration::set_para_overrides(Origin::root(), para_a, None).unwrap();
+			Configuration::set_para_overrides(Origin::root(), para_b, None).unwrap();
+			assert_eq!(Configuration::para_overrides(para_a), Some(overrides));
+
+			Configuration::initializer_on_new_session(&[], &[]);
+
+			assert!(Configuration::para_overrides(para_a).is_none());
+			assert!(Configuration::para_overrides(para_b).is_none());
+			assert_eq!(Configuration::effective_config(para_a), config);
+		});
+	}
+
+	#[test]
+	fn base_updates_are_checked_against_para_overrides() {
+		new_test_ext(Default::default()).execute_with(|| {
+			let para_a = ParaId::from(1);
+			let para_b = ParaId::from(2);
+			<Configuration as Store>::Config::set(consistent_config());
+			Configuration::set_max_upward_queue_size(Origin::root(), 4096).unwrap();
+
+			let overrides = ParaConfigOverrides {
+				max_upward_message_size: Some(2048),
+				..Default::default()
+			};
+
+			// pending overrides are taken into account.
+			Configuration::set_para_overrides(Origin::root(), para_a, Some(overrides.clone())).unwrap();
+			assert_noop!(
+				Configuration::set_max_upward_queue_size(Origin::root(), 1024),
+				Error::<Test>::UpwardMessageSizeExceedsQueueSize,
+			);
+
+			// members which are overridden by every para can still be changed.
+			Configuration::set_max_upward_message_size(Origin::root(), 512).unwrap();
+
+			// active overrides are taken into account, by the batch setter too.
+			Configuration::initializer_on_new_session(&[], &[]);
+			assert_eq!(Configuration::para_overrides(para_a), Some(overrides.clone()));
+			assert_noop!(
+				Configuration::set_config_batch(
+					Origin::root(),
+					vec![ConfigUpdate::MaxUpwardQueueSize(1024)],
+				),
+				Error::<Test>::UpwardMessageSizeExceedsQueueSize,
+			);
+
+			// overrides scheduled for removal are not.
+			Configuration::set_para_overrides(Origin::root(), para_b, Some(overrides)).unwrap();
+			Configuration::set_para_overrides(Origin::root(), para_a, None).unwrap();
+			Configuration::set_para_overrides(Origin::root(), para_b, None).unwrap();
+			Configuration::set_max_upward_queue_size(Origin::root(), 1024).unwrap();
+		});
+	}
+
+	#[test]
+	fn inconsistent_updates_are_rejected() {
+		new_test_ext(Default::default()).execute_with(|| {
+			<Configuration as Store>::Config::set(consistent_config());
+			assert!(consistent_config().inconsistencies().is_empty());
+
+			assert_noop!(
+				Configuration::set_group_rotation_frequency(Origin::root(), 0),
+				Error::<Test>::ZeroGroupRotationFrequency,
+			);
+			assert_noop!(
+				Configuration::set_chain_availability_period(Origin::root(), 0),
+				Error::<Test>::ZeroChainAvailabilityPeriod,
+			);
+			assert_noop!(
+				Configuration::set_thread_availability_period(Origin::root(), 0),
+				Error::<Test>::ZeroThreadAvailabilityPeriod,
+			);
+			assert_noop!(
+				Configuration::set_max_upward_message_size(Origin::root(), 2048),
+				Error::<Test>::UpwardMessageSizeExceedsQueueSize,
+			);
+			assert_noop!(
+				Configuration::set_max_upward_queue_count(Origin::root(), 2),
+				Error::<Test>::UpwardMessageNumExceedsQueueCount,
+			);
+			assert_noop!(
+				Configuration::set_max_pov_size(Origin::root(), 256),
+				Error::<Test>::DownwardMessageSizeExceedsPovSize,
+			);
+			assert_noop!(
+				Configuration::set_hrmp_channel_max_total_size(Origin::root(), 128),
+				Error::<Test>::HrmpMessageSizeExceedsChannelTotalSize,
+			);
+
+			// the checks are against the pending configuration, if any.
+			assert_ok!(Configuration::set_max_upward_queue_size(Origin::root(), 4096));
+			assert_ok!(Configuration::set_max_upward_message_size(Origin::root(), 2048));
+			assert_noop!(
+				Configuration::set_max_upward_queue_size(Origin::root(), 1024),
+				Error::<Test>::UpwardMessageSizeExceedsQueueSize,
+			);
+
+			// overrides are checked as well.
+			assert_noop!(
+				Configuration::set_para_overrides(
+					Origin::root(),
+					ParaId::from(1),
+					Some(ParaConfigOverrides {
+						max_upward_message_num_per_candidate: Some(16),
+						..Default::default()
+					}),
+				),
+				Error::<Test>::UpwardMessageNumExceedsQueueCount,
+			);
+		});
+	}
+
+	#[test]
+	fn existing_inconsistencies_do_not_block_unrelated_updates() {
+		new_test_ext(Default::default()).execute_with(|| {
+			let mut config = consistent_config();
+			config.group_rotation_frequency = 0;
+			<Configuration as Store>::Config::set(config);
+
+			assert_ok!(Configuration::set_max_code_size(Origin::root(), 1024));
+			assert_noop!(
+				Configuration::set_chain_availability_period(Origin::root(), 0),
+				Error::<Test>::ZeroChainAvailabilityPeriod,
+			);
+		});
+	}
+
+	#[test]
+	fn config_batch_is_applied_atomically() {
+		new_test_ext(Default::default()).execute_with(|| {
+			let config = consistent_config();
+			<Configuration as Store>::Config::set(config.clone());
+
+			// the intermediate configuration after the first update would be inconsistent, but
+			// only the final result is checked.
+			assert_ok!(Configuration::set_config_batch(Origin::root(), vec![
+				ConfigUpdate::MaxUpwardMessageSize(2048),
+				ConfigUpdate::MaxUpwardQueueSize(4096),
+				ConfigUpdate::ValidationUpgradeDelay(20),
+			]));
+
+			let expected = HostConfiguration {
+				max_upward_message_size: 2048,
+				max_upward_queue_size: 4096,
+				validation_upgrade_delay: 20,
+				..config.clone()
+			};
+			assert_eq!(<Configuration as Store>::PendingConfig::get(), Some(expected.clone()));
+
+			// an inconsistent batch is rejected as a whole.
+			assert_noop!(
+				Configuration::set_config_batch(Origin::root(), vec![
+					ConfigUpdate::MaxCodeSize(4096),
+					ConfigUpdate::GroupRotationFrequency(0),
+				]),
+				Error::<Test>::ZeroGroupRotationFrequency,
+			);
+
+			assert!(Configuration::set_config_batch(
+				Origin::signed(1),
+				vec![ConfigUpdate::MaxCodeSize(4096)],
+			).is_err());
+
+			// nothing is applied before the session boundary.
+			assert_eq!(Configuration::config(), config);
+
+			Configuration::initializer_on_new_session(&[], &[]);
+
+			assert_eq!(Configuration::config(), expected);
+			assert!(<Configuration as Store>::PendingConfig::get().is_none());
+		});
+	}
+
+	fn legacy_config(max_code_size: u32) -> LegacyHostConfiguration<u32> {
+		LegacyHostConfiguration {
+			validation_upgrade_frequency: 100,
+			validation_upgrade_delay: 10,
+			acceptance_period: 5,
+			max_code_size,
+			max_head_data_size: 1024,
+			max_pov_size: 4096,
+			parathread_cores: 2,
+			parathread_retries: 3,
+			group_rotation_frequency: 20,
+			chain_availability_period: 4,
+			thread_availability_period: 4,
+			scheduling_lookahead: 1,
+			max_upward_queue_count: 8,
+			max_upward_queue_size: 2048,
+			max_downward_message_size: 1024,
+			preferred_dispatchable_upward_messages_step_weight: 1_000,
+			max_upward_message_size: 256,
+			max_upward_message_num_per_candidate: 4,
+			hrmp_open_request_ttl: 2,
+			hrmp_sender_deposit: 30,
+			hrmp_recipient_deposit: 40,
+			hrmp_channel_max_capacity: 8,
+			hrmp_channel_max_total_size: 2048,
+			hrmp_max_parachain_inbound_channels: 4,
+			hrmp_max_parathread_inbound_channels: 2,
+			hrmp_channel_max_message_size: 512,
+			hrmp_max_parachain_outbound_channels: 4,
+			hrmp_max_parathread_outbound_channels: 2,
+			hrmp_max_message_num_per_candidate: 6,
+		}
+	}
+
+	#[test]
+	fn configurations_stored_before_bounding_queues_are_migrated() {
+		new_test_ext(Default::default()).execute_with(|| {
+			sp_io::storage::set(
+				&<Configuration as Store>::Config::hashed_key(),
+				&legacy_config(4096).encode(),
+			);
+			sp_io::storage::set(
+				&<Configuration as Store>::PendingConfig::hashed_key(),
+				&legacy_config(8192).encode(),
+			);
+			<Configuration as Store>::StorageVersion::put(Releases::V1);
+
+			// the old layout doesn't decode as a current configuration.
+			assert_eq!(Configuration::config(), HostConfiguration::default());
+
+			Configuration::on_runtime_upgrade();
+			assert_eq!(<Configuration as Store>::StorageVersion::get(), Releases::V2);
+
+			let config = Configuration::config();
+			assert_eq!(config.max_code_size, 4096);
+			assert_eq!(config.group_rotation_frequency, 20);
+			assert_eq!(config.max_downward_queue_count, MIGRATED_MAX_DOWNWARD_QUEUE_COUNT);
+			assert_eq!(config.max_downward_queue_size, MIGRATED_MAX_DOWNWARD_QUEUE_SIZE);
+			assert_eq!(config.ump_max_individual_weight, DEFAULT_UMP_MAX_INDIVIDUAL_WEIGHT);
+			assert_eq!(config.hrmp_max_message_num_per_candidate, 6);
+
+			let pending = <Configuration as Store>::PendingConfig::get().unwrap();
+			assert_eq!(pending, HostConfiguration { max_code_size: 8192, ..config.clone() });
+
+			// the migration only runs once.
+			Configuration::on_runtime_upgrade();
+			assert_eq!(Configuration::config(), config);
+		});
+	}
 }
@@ -16,18 +16,34 @@
 
 use crate::{
 	configuration::{self, HostConfiguration},
-	initializer,
+	initializer, paras,
 };
 use frame_support::{decl_module, decl_storage, StorageMap, weights::Weight, traits::Get};
 use sp_std::{fmt, prelude::*};
-use sp_runtime::traits::{BlakeTwo256, Hash as HashT, SaturatedConversion};
+use sp_runtime::{
+	FixedU128,
+	traits::{BlakeTwo256, Hash as HashT, One, SaturatedConversion, Saturating},
+};
 use primitives::v1::{Id as ParaId, DownwardMessage, InboundDownwardMessage, Hash};
 
+/// The factor by which the delivery fee factor of a para is multiplied for every message queued
+/// while its downward message queue is congested, i.e. `1.05`.
+pub const EXPONENTIAL_FEE_BASE: FixedU128 = FixedU128::from_inner(1_050_000_000_000_000_000);
+
+/// A downward message queue is considered congested once the total size of its messages exceeds
+/// `config.max_downward_queue_size / THRESHOLD_FACTOR`.
+pub const THRESHOLD_FACTOR: u32 = 2;
+
 /// An error sending a downward message.
-#[cfg_attr(test, derive(Debug))]
+#[cfg_attr(test, derive(Debug, PartialEq))]
 pub enum QueueDownwardMessageError {
 	/// The message being sent exceeds the configured max message size.
 	ExceedsMaxMessageSize,
+	/// The downward message queue of the recipient is full, either by the number of messages or
+	/// by their total size.
+	QueueFull,
+	/// The recipient is not a registered para.
+	Unroutable,
 }
 
 /// An error returned by [`check_processed_downward_messages`] that indicates an acceptance check
@@ -62,7 +78,7 @@ impl fmt::Debug for ProcessedDownwardMessagesAcceptanceErr {
 	}
 }
 
-pub trait Trait: frame_system::Trait + configuration::Trait {}
+pub trait Trait: frame_system::Trait + configuration::Trait + paras::Trait {}
 
 decl_storage! {
 	trait Store for Module<T: Trait> as Dmp {
@@ -80,6 +96,14 @@ decl_storage! {
 		/// - `B`: is the relay-chain block number in which a message was appended.
 		/// - `H(M)`: is the hash of the message being appended.
 		DownwardMessageQueueHeads: map hasher(twox_64_concat) ParaId => Hash;
+		/// The total size, in bytes, of the messages in the downward message queue of a para.
+		DownwardMessageQueueSize: map hasher(twox_64_concat) ParaId => u32;
+		/// The factor by which the base delivery fee of a downward message to a para is multiplied.
+		///
+		/// It grows exponentially while the queue of the para is congested and decays back to one
+		/// as the para processes its messages.
+		DeliveryFeeFactor get(fn delivery_fee_factor):
+			map hasher(twox_64_concat) ParaId => FixedU128 = FixedU128::one();
 	}
 }
 
@@ -117,6 +141,8 @@ impl<T: Trait> Module<T> {
 	fn clean_dmp_after_outgoing(outgoing_para: ParaId) {
 		<Self as Store>::DownwardMessageQueues::remove(&outgoing_para);
 		<Self as Store>::DownwardMessageQueueHeads::remove(&outgoing_para);
+		<Self as Store>::DownwardMessageQueueSize::remove(&outgoing_para);
+		<Self as Store>::DeliveryFeeFactor::remove(&outgoing_para);
 	}
 
 	/// Schedule a para to be cleaned up at the start of the next session.
@@ -130,12 +156,13 @@ impl<T: Trait> Module<T> {
 
 	/// Enqueue a downward message to a specific recipient para.
 	///
-	/// When encoded, the message should not exceed the `config.max_downward_message_size`.
-	/// Otherwise, the message won't be sent and `Err` will be returned.
+	/// When encoded, the message should not exceed the `config.max_downward_message_size` and
+	/// should fit into the queue of the recipient, as bounded by `config.max_downward_queue_count`
+	/// and `config.max_downward_queue_size`. The recipient must be a registered para. Otherwise,
+	/// the message won't be sent and `Err` will be returned.
 	///
-	/// It is possible to send a downward message to a non-existent para. That, however, would lead
-	/// to a dangling storage. If the caller cannot statically prove that the recipient exists
-	/// then the caller should perform a runtime check.
+	/// If the queue of the recipient is congested after the message is added, the delivery fee
+	/// factor of the recipient is increased.
 	pub fn queue_downward_message(
 		config: &HostConfiguration<T::BlockNumber>,
 		para: ParaId,
@@ -146,6 +173,18 @@ impl<T: Trait> Module<T> {
 			return Err(QueueDownwardMessageError::ExceedsMaxMessageSize);
 		}
 
+		if !<paras::Module<T>>::is_valid_para(para) {
+			return Err(QueueDownwardMessageError::Unroutable);
+		}
+
+		let queue_size = <Self as Store>::DownwardMessageQueueSize::get(&para);
+		let new_queue_size = queue_size.saturating_add(serialized_len);
+		if Self::dmq_length(para) >= config.max_downward_queue_count
+			|| new_queue_size > config.max_downward_queue_size
+		{
+			return Err(QueueDownwardMessageError::QueueFull);
+		}
+
 		let inbound = InboundDownwardMessage {
 			msg,
 			sent_at: <frame_system::Module<T>>::block_number(),
@@ -161,6 +200,13 @@ impl<T: Trait> Module<T> {
 		<Self as Store>::DownwardMessageQueues::mutate(para, |v| {
 			v.push(inbound);
 		});
+		<Self as Store>::DownwardMessageQueueSize::insert(para, new_queue_size);
+
+		if new_queue_size > Self::congestion_threshold(config) {
+			<Self as Store>::DeliveryFeeFactor::mutate(para, |f| {
+				*f = f.saturating_mul(EXPONENTIAL_FEE_BASE);
+			});
+		}
 
 		Ok(())
 	}
@@ -186,8 +232,10 @@ impl<T: Trait> Module<T> {
 	}
 
 	/// Prunes the specified number of messages from the downward message queue of the given para.
+	///
+	/// The delivery fee factor of the para decays once its queue is no longer congested.
 	pub(crate) fn prune_dmq(para: ParaId, processed_downward_messages: u32) -> Weight {
-		<Self as Store>::DownwardMessageQueues::mutate(para, |q| {
+		let remaining_size = <Self as Store>::DownwardMessageQueues::mutate(para, |q| {
 			let processed_downward_messages = processed_downward_messages as usize;
 			if processed_downward_messages > q.len() {
 				// reaching this branch is unexpected due to the constraint established by
@@ -196,8 +244,26 @@ impl<T: Trait> Module<T> {
 			} else {
 				*q = q.split_off(processed_downward_messages);
 			}
+			q.iter().fold(0u32, |acc, m| acc.saturating_add(m.msg.len() as u32))
 		});
-		T::DbWeight::get().reads_writes(1, 1)
+		<Self as Store>::DownwardMessageQueueSize::insert(para, remaining_size);
+
+		let config = <configuration::Module<T>>::config();
+		if remaining_size <= Self::congestion_threshold(&config) {
+			let factor = Self::delivery_fee_factor(para) / EXPONENTIAL_FEE_BASE;
+			if factor > FixedU128::one() {
+				<Self as Store>::DeliveryFeeFactor::insert(para, factor);
+			} else {
+				<Self as Store>::DeliveryFeeFactor::remove(para);
+			}
+		}
+
+		T::DbWeight::get().reads_writes(3, 3)
+	}
+
+	/// The total size of the queue above which the queue is considered congested.
+	fn congestion_threshold(config: &HostConfiguration<T::BlockNumber>) -> u32 {
+		config.max_downward_queue_size / THRESHOLD_FACTOR
 	}
 
 	/// Returns the Head of Message Queue Chain for the given para or `None` if there is none
@@ -206,6 +272,12 @@ impl<T: Trait> Module<T> {
 		<Self as Store>::DownwardMessageQueueHeads::get(&para)
 	}
 
+	/// Returns the key of the storage entry holding the Head of Message Queue Chain for the given
+	/// para.
+	pub(crate) fn dmq_mqc_head_storage_key(para: ParaId) -> Vec<u8> {
+		<Self as Store>::DownwardMessageQueueHeads::hashed_key_for(&para)
+	}
+
 	/// Returns the number of pending downward messages addressed to the given para.
 	///
 	/// Returns 0 if the para doesn't have an associated downward message queue.
@@ -229,8 +301,9 @@ mod tests {
 	use primitives::v1::BlockNumber;
 	use frame_support::StorageValue;
 	use frame_support::traits::{OnFinalize, OnInitialize};
-	use parity_scale_codec::Encode;
+	use parity_scale_codec::{Decode, Encode};
 	use crate::mock::{Configuration, new_test_ext, System, Dmp, GenesisConfig as MockGenesisConfig};
+	use crate::paras::ParaGenesisArgs;
 
 	pub(crate) fn run_to_block(to: BlockNumber, new_session: Option<Vec<BlockNumber>>) {
 		while System::block_number() < to {
@@ -253,9 +326,19 @@ mod tests {
 			configuration: crate::configuration::GenesisConfig {
 				config: crate::configuration::HostConfiguration {
 					max_downward_message_size: 1024,
+					max_downward_queue_count: 16,
+					max_downward_queue_size: 16 * 1024,
 					..Default::default()
 				},
 			},
+			paras: crate::paras::GenesisConfig {
+				paras: vec![1312, 228, 123].into_iter().map(|id| (ParaId::from(id), ParaGenesisArgs {
+					genesis_head: vec![1].into(),
+					validation_code: vec![1].into(),
+					parachain: true,
+				})).collect(),
+				..Default::default()
+			},
 			..Default::default()
 		}
 	}
@@ -322,6 +405,21 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn dmq_mqc_head_storage_key_points_to_head() {
+		let a = ParaId::from(1312);
+
+		new_test_ext(default_genesis_config()).execute_with(|| {
+			let key = Dmp::dmq_mqc_head_storage_key(a);
+			assert!(sp_io::storage::get(&key).is_none());
+
+			queue_downward_message(a, vec![1, 2, 3]).unwrap();
+
+			let raw = sp_io::storage::get(&key).unwrap();
+			assert_eq!(Hash::decode(&mut &raw[..]).unwrap(), Dmp::dmq_mqc_head(a));
+		});
+	}
+
 	#[test]
 	fn check_processed_downward_messages() {
 		let a = ParaId::from(1312);
@@ -386,4 +484,89 @@ mod tests {
 			assert!(queue_downward_message(a, big).is_err());
 		});
 	}
+
+	#[test]
+	fn queue_downward_message_rejected_when_queue_full() {
+		let a = ParaId::from(1312);
+
+		let mut genesis = default_genesis_config();
+		genesis.configuration.config.max_downward_queue_count = 3;
+		genesis.configuration.config.max_downward_queue_size = 10;
+
+		new_test_ext(genesis).execute_with(|| {
+			// the size limit is hit first.
+			assert!(queue_downward_message(a, vec![0; 4]).is_ok());
+			assert!(queue_downward_message(a, vec![0; 4]).is_ok());
+			assert_eq!(
+				queue_downward_message(a, vec![0; 3]),
+				Err(QueueDownwardMessageError::QueueFull),
+			);
+
+			// but a smaller message still fits and then the count limit is hit.
+			assert!(queue_downward_message(a, vec![0; 2]).is_ok());
+			assert_eq!(
+				queue_downward_message(a, vec![]),
+				Err(QueueDownwardMessageError::QueueFull),
+			);
+
+			// pruning frees up space.
+			Dmp::prune_dmq(a, 2);
+			assert_eq!(<Dmp as Store>::DownwardMessageQueueSize::get(&a), 2);
+			assert!(queue_downward_message(a, vec![0; 8]).is_ok());
+		});
+	}
+
+	#[test]
+	fn queue_downward_message_to_unknown_para_is_unroutable() {
+		new_test_ext(default_genesis_config()).execute_with(|| {
+			assert_eq!(
+				queue_downward_message(ParaId::from(1), vec![1, 2, 3]),
+				Err(QueueDownwardMessageError::Unroutable),
+			);
+			assert_eq!(Dmp::dmq_length(ParaId::from(1)), 0);
+		});
+	}
+
+	#[test]
+	fn delivery_fee_factor_grows_when_congested_and_decays() {
+		let a = ParaId::from(1312);
+		let b = ParaId::from(228);
+
+		let mut genesis = default_genesis_config();
+		genesis.configuration.config.max_downward_queue_size = 32;
+
+		new_test_ext(genesis).execute_with(|| {
+			// below the threshold of 16 bytes the factor stays at one.
+			queue_downward_message(a, vec![0; 8]).unwrap();
+			queue_downward_message(a, vec![0; 8]).unwrap();
+			assert_eq!(Dmp::delivery_fee_factor(a), FixedU128::one());
+
+			// every message queued above the threshold bumps the factor.
+			queue_downward_message(a, vec![0; 8]).unwrap();
+			assert_eq!(Dmp::delivery_fee_factor(a), EXPONENTIAL_FEE_BASE);
+			queue_downward_message(a, vec![0; 8]).unwrap();
+			assert_eq!(
+				Dmp::delivery_fee_factor(a),
+				EXPONENTIAL_FEE_BASE * EXPONENTIAL_FEE_BASE,
+			);
+
+			// other paras are not affected.
+			assert_eq!(Dmp::delivery_fee_factor(b), FixedU128::one());
+
+			// pruning while still congested doesn't decay the factor.
+			Dmp::prune_dmq(a, 1);
+			assert_eq!(
+				Dmp::delivery_fee_factor(a),
+				EXPONENTIAL_FEE_BASE * EXPONENTIAL_FEE_BASE,
+			);
+
+			// but it does once the queue drains, never going below one.
+			Dmp::prune_dmq(a, 1);
+			assert_eq!(Dmp::delivery_fee_factor(a), EXPONENTIAL_FEE_BASE);
+			Dmp::prune_dmq(a, 1);
+			assert_eq!(Dmp::delivery_fee_factor(a), FixedU128::one());
+			Dmp::prune_dmq(a, 1);
+			assert_eq!(Dmp::delivery_fee_factor(a), FixedU128::one());
+		});
+	}
 }
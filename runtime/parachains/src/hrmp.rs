@@ -18,17 +18,20 @@ use crate::{
 	ensure_parachain,
 	configuration::{self, HostConfiguration},
 	initializer, paras, dmp,
+	ump::UmpSink,
 };
 use parity_scale_codec::{Decode, Encode};
 use frame_support::{
-	decl_storage, decl_module, decl_error, ensure, traits::Get, weights::Weight, StorageMap,
-	StorageValue, dispatch::DispatchResult,
+	decl_storage, decl_module, decl_event, decl_error, ensure, weights::Weight, StorageMap, StorageValue,
+	IterableStorageMap, dispatch::DispatchResult,
+	traits::{Get, ReservableCurrency},
 };
 use primitives::v1::{
 	Balance, Hash, HrmpChannelId, Id as ParaId, InboundHrmpMessage, OutboundHrmpMessage,
 	SessionIndex,
 };
-use sp_runtime::traits::{BlakeTwo256, Hash as HashT};
+use sp_runtime::traits::{AccountIdConversion, BlakeTwo256, Hash as HashT, SaturatedConversion};
+use xcm::{VersionedXcm, v0::Xcm};
 use sp_std::{
 	mem, fmt,
 	collections::{btree_map::BTreeMap, btree_set::BTreeSet},
@@ -50,15 +53,51 @@ pub struct HrmpOpenChannelRequest {
 	pub max_capacity: u32,
 	/// The maximum total size of the messages that can be pending in the channel at once.
 	pub max_total_size: u32,
+	/// The amount that the recipient supplied when confirming this request. Zero until confirmed.
+	pub recipient_deposit: Balance,
+}
+
+/// A request to open an HRMP channel as stored before the deposits were reserved.
+#[derive(Decode)]
+struct LegacyHrmpOpenChannelRequest {
+	confirmed: bool,
+	age: SessionIndex,
+	_sender_deposit: Balance,
+	max_message_size: u32,
+	max_capacity: u32,
+	max_total_size: u32,
+}
+
+/// A description of a request to resize an open HRMP channel.
+#[derive(Encode, Decode)]
+#[cfg_attr(test, derive(Debug))]
+pub struct HrmpResizeChannelRequest {
+	/// Indicates if this request was confirmed by the recipient.
+	pub confirmed: bool,
+	/// How many session boundaries ago this request was seen.
+	pub age: SessionIndex,
+	/// The deposit that the sender is going to hold once the channel is resized.
+	pub sender_deposit: Balance,
+	/// The deposit that the recipient is going to hold once the channel is resized. Equals the
+	/// current deposit of the recipient until the request is confirmed.
+	pub recipient_deposit: Balance,
+	/// The new maximum message size that could be put into the channel.
+	pub max_message_size: u32,
+	/// The new maximum number of messages that can be pending in the channel at once.
+	pub max_capacity: u32,
+	/// The new maximum total size of the messages that can be pending in the channel at once.
+	pub max_total_size: u32,
 }
 
 /// A metadata of an HRMP channel.
 #[derive(Encode, Decode)]
 #[cfg_attr(test, derive(Debug))]
 pub struct HrmpChannel {
-	/// The amount that the sender supplied as a deposit when opening this channel.
+	/// The amount that the sender supplied as a deposit when opening this channel. Zero for the
+	/// channels opened before the deposits were reserved.
 	pub sender_deposit: Balance,
 	/// The amount that the recipient supplied as a deposit when accepting opening this channel.
+	/// Zero for the channels opened before the deposits were reserved.
 	pub recipient_deposit: Balance,
 	/// The maximum number of messages that can be pending in the channel at once.
 	pub max_capacity: u32,
@@ -67,10 +106,12 @@ pub struct HrmpChannel {
 	/// The maximum message size that could be put into the channel.
 	pub max_message_size: u32,
 	/// The current number of messages pending in the channel.
-	/// Invariant: should be less or equal to `max_capacity`.s`.
+	/// Invariant: should be less or equal to `max_capacity`, unless the channel was shrunk by a
+	/// resize while it held more messages.
 	pub msg_count: u32,
 	/// The total size in bytes of all message payloads in the channel.
-	/// Invariant: should be less or equal to `max_total_size`.
+	/// Invariant: should be less or equal to `max_total_size`, unless the channel was shrunk by a
+	/// resize while it held more messages.
 	pub total_size: u32,
 	/// A head of the Message Queue Chain for this channel. Each link in this chain has a form:
 	/// `(prev_head, B, H(M))`, where
@@ -82,6 +123,62 @@ pub struct HrmpChannel {
 	pub mqc_head: Option<Hash>,
 }
 
+/// The versions of the storage layout of this module.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, sp_core::RuntimeDebug)]
+pub enum Releases {
+	/// The channel deposits are recorded, but they are not reserved.
+	V1,
+	/// The channel deposits are reserved from the sovereign accounts of the paras.
+	V2,
+}
+
+impl Default for Releases {
+	fn default() -> Self {
+		Releases::V1
+	}
+}
+
+/// An [`UmpSink`] which handles the HRMP channel management instructions sent by paras over UMP
+/// and passes on all other upward messages to `I`.
+///
+/// This allows paras to manage their channels with XCM instead of dispatching the HRMP calls with
+/// a parachain origin. The outcome of an instruction is communicated back by the notifications
+/// the relay-chain sends over DMP.
+pub struct XcmHrmpSink<T, I>(sp_std::marker::PhantomData<(T, I)>);
+
+impl<T: Trait, I: UmpSink> UmpSink for XcmHrmpSink<T, I> {
+	fn process_upward_message(origin: ParaId, msg: &[u8], max_weight: Weight) -> Result<Weight, Weight> {
+		let required_weight = Module::<T>::channel_management_weight();
+		let ensure_weight = || if required_weight > max_weight {
+			Err(required_weight)
+		} else {
+			Ok(())
+		};
+
+		// an instruction that fails has no effect. The para can tell by the lack of a notification.
+		let _ = match VersionedXcm::decode(&mut &msg[..]) {
+			Ok(VersionedXcm::V0(Xcm::HrmpInitOpenChannel { recipient, max_message_size, max_capacity })) => {
+				ensure_weight()?;
+				Module::<T>::init_open_channel(origin, recipient.into(), max_capacity, max_message_size)
+			}
+			Ok(VersionedXcm::V0(Xcm::HrmpAcceptOpenChannel { sender })) => {
+				ensure_weight()?;
+				Module::<T>::accept_open_channel(origin, sender.into())
+			}
+			Ok(VersionedXcm::V0(Xcm::HrmpCloseChannel { sender, recipient })) => {
+				ensure_weight()?;
+				Module::<T>::close_channel(origin, HrmpChannelId {
+					sender: sender.into(),
+					recipient: recipient.into(),
+				})
+			}
+			_ => return I::process_upward_message(origin, msg, max_weight),
+		};
+
+		Ok(required_weight)
+	}
+}
+
 /// An error returned by [`check_hrmp_watermark`] that indicates an acceptance criteria check
 /// didn't pass.
 pub enum HrmpWatermarkAcceptanceErr<BlockNumber> {
@@ -208,9 +305,16 @@ impl fmt::Debug for OutboundHrmpAcceptanceErr {
 }
 
 pub trait Trait: frame_system::Trait + configuration::Trait + paras::Trait + dmp::Trait {
+	/// The overarching event type.
+	type Event: From<Event> + Into<<Self as frame_system::Trait>::Event>;
+
 	type Origin: From<crate::Origin>
 		+ From<<Self as frame_system::Trait>::Origin>
 		+ Into<Result<crate::Origin, <Self as Trait>::Origin>>;
+
+	/// The currency in which the channel deposits are reserved from the sovereign accounts of
+	/// the paras.
+	type Currency: ReservableCurrency<Self::AccountId>;
 }
 
 decl_storage! {
@@ -248,6 +352,16 @@ decl_storage! {
 		HrmpCloseChannelRequests: map hasher(twox_64_concat) HrmpChannelId => Option<()>;
 		HrmpCloseChannelRequestsList: Vec<HrmpChannelId>;
 
+		/// The set of pending HRMP resize channel requests.
+		///
+		/// The set is accompanied by a list for iteration.
+		///
+		/// Invariants:
+		/// - There are no channels that exists in list but not in the set and vice versa.
+		/// - Each channel in the set is present in `HrmpChannels`.
+		HrmpResizeChannelRequests: map hasher(twox_64_concat) HrmpChannelId => Option<HrmpResizeChannelRequest>;
+		HrmpResizeChannelRequestsList: Vec<HrmpChannelId>;
+
 		/// The HRMP watermark associated with each para.
 		/// Invariant:
 		/// - each para `P` used here as a key should satisfy `Paras::is_valid_para(P)` within a session.
@@ -282,6 +396,10 @@ decl_storage! {
 		/// - The outer vector is sorted ascending by block number and cannot store two items with the same
 		///   block number.
 		HrmpChannelDigests: map hasher(twox_64_concat) ParaId => Vec<(T::BlockNumber, Vec<ParaId>)>;
+
+		/// The version of the storage layout. Chains that start with this module already reserve
+		/// the channel deposits.
+		StorageVersion build(|_: &GenesisConfig| Releases::V2): Releases;
 	}
 }
 
@@ -317,14 +435,54 @@ decl_error! {
 		CloseHrmpChannelDoesntExist,
 		/// The channel close request is already requested.
 		CloseHrmpChannelAlreadyUnderway,
+		/// The sender cannot afford the deposit for opening the channel.
+		OpenHrmpChannelInsufficientDeposit,
+		/// The recipient cannot afford the deposit for accepting the channel.
+		AcceptHrmpChannelInsufficientDeposit,
+		/// The channel to be resized doesn't exist.
+		ResizeHrmpChannelDoesntExist,
+		/// There is already a request to resize the same channel.
+		ResizeHrmpChannelAlreadyRequested,
+		/// The channel to be resized is being closed.
+		ResizeHrmpChannelClosing,
+		/// The requested capacity is zero.
+		ResizeHrmpChannelZeroCapacity,
+		/// The requested capacity exceeds the global limit.
+		ResizeHrmpChannelCapacityExceedsLimit,
+		/// The requested maximum message size is 0.
+		ResizeHrmpChannelZeroMessageSize,
+		/// The requested maximum message size exceeds the global limit.
+		ResizeHrmpChannelMessageSizeExceedsLimit,
+		/// The sender cannot afford the increased deposit of the resized channel.
+		ResizeHrmpChannelInsufficientDeposit,
+		/// There is no request to resize the channel from the sender to the origin.
+		AcceptHrmpResizeDoesntExist,
+		/// The resize request is already confirmed.
+		AcceptHrmpResizeAlreadyConfirmed,
+		/// The recipient cannot afford the increased deposit of the resized channel.
+		AcceptHrmpResizeInsufficientDeposit,
 	 }
 }
 
+decl_event! {
+	pub enum Event {
+		/// A notification about an HRMP channel couldn't be sent to a para, because its downward
+		/// message queue is full or it isn't routable. [para]
+		NotificationDropped(ParaId),
+	}
+}
+
 decl_module! {
 	/// The HRMP module.
 	pub struct Module<T: Trait> for enum Call where origin: <T as frame_system::Trait>::Origin {
 		type Error = Error<T>;
 
+		fn deposit_event() = default;
+
+		fn on_runtime_upgrade() -> Weight {
+			Self::migrate_unreserved_deposits()
+		}
+
 		#[weight = 0]
 		fn hrmp_init_open_channel(
 			origin,
@@ -355,6 +513,34 @@ decl_module! {
 			Self::close_channel(origin, channel_id)?;
 			Ok(())
 		}
+
+		/// Request to change the capacity and the maximum message size of an open channel from
+		/// the origin to the given recipient. Takes effect at a session boundary once accepted by
+		/// the recipient.
+		#[weight = 0]
+		fn hrmp_init_resize_channel(
+			origin,
+			recipient: ParaId,
+			proposed_max_capacity: u32,
+			proposed_max_message_size: u32,
+		) -> DispatchResult {
+			let origin = ensure_parachain(<T as Trait>::Origin::from(origin))?;
+			Self::init_resize_channel(
+				origin,
+				recipient,
+				proposed_max_capacity,
+				proposed_max_message_size,
+			)?;
+			Ok(())
+		}
+
+		/// Accept a pending request to resize the channel from the given sender to the origin.
+		#[weight = 0]
+		fn hrmp_accept_resize_channel(origin, sender: ParaId) -> DispatchResult {
+			let origin = ensure_parachain(<T as Trait>::Origin::from(origin))?;
+			Self::accept_resize_channel(origin, sender)?;
+			Ok(())
+		}
 	}
 }
 
@@ -375,6 +561,36 @@ impl<T: Trait> Module<T> {
 		Self::perform_outgoing_para_cleanup();
 		Self::process_hrmp_open_channel_requests(&notification.prev_config);
 		Self::process_hrmp_close_channel_requests();
+		Self::process_hrmp_resize_channel_requests(&notification.prev_config);
+	}
+
+	/// Zero the deposits recorded for the channels and the open channel requests that were created
+	/// before the deposits were reserved, so that only the deposits actually reserved are ever
+	/// returned.
+	fn migrate_unreserved_deposits() -> Weight {
+		if <Self as Store>::StorageVersion::get() != Releases::V1 {
+			return T::DbWeight::get().reads(1);
+		}
+
+		<Self as Store>::HrmpChannels::translate(|_, mut channel: HrmpChannel| {
+			channel.sender_deposit = 0;
+			channel.recipient_deposit = 0;
+			Some(channel)
+		});
+		<Self as Store>::HrmpOpenChannelRequests::translate(
+			|_, request: LegacyHrmpOpenChannelRequest| Some(HrmpOpenChannelRequest {
+				confirmed: request.confirmed,
+				age: request.age,
+				sender_deposit: 0,
+				max_message_size: request.max_message_size,
+				max_capacity: request.max_capacity,
+				max_total_size: request.max_total_size,
+				recipient_deposit: 0,
+			}),
+		);
+		<Self as Store>::StorageVersion::put(Releases::V2);
+
+		T::MaximumBlockWeight::get()
 	}
 
 	/// Iterate over all paras that were registered for offboarding and remove all the data
@@ -395,6 +611,11 @@ impl<T: Trait> Module<T> {
 		});
 	}
 
+	/// The weight of handling a channel management instruction received over UMP.
+	fn channel_management_weight() -> Weight {
+		T::DbWeight::get().reads_writes(8, 6)
+	}
+
 	/// Remove all storage entries associated with the given para.
 	pub(super) fn clean_hrmp_after_outgoing(outgoing_para: ParaId) {
 		<Self as Store>::HrmpOpenChannelRequestCount::remove(&outgoing_para);
@@ -449,7 +670,7 @@ impl<T: Trait> Module<T> {
 						&channel_id,
 						HrmpChannel {
 							sender_deposit: request.sender_deposit,
-							recipient_deposit: config.hrmp_recipient_deposit,
+							recipient_deposit: request.recipient_deposit,
 							max_capacity: request.max_capacity,
 							max_total_size: request.max_total_size,
 							max_message_size: request.max_message_size,
@@ -469,6 +690,9 @@ impl<T: Trait> Module<T> {
 							v.insert(i, channel_id.recipient);
 						}
 					});
+				} else {
+					Self::unreserve_deposit(channel_id.sender, request.sender_deposit);
+					Self::unreserve_deposit(channel_id.recipient, request.recipient_deposit);
 				}
 
 				let new_open_channel_req_cnt =
@@ -506,7 +730,7 @@ impl<T: Trait> Module<T> {
 						*v -= 1;
 					});
 
-					// TODO: return deposit https://github.com/paritytech/polkadot/issues/1907
+					Self::unreserve_deposit(channel_id.sender, request.sender_deposit);
 
 					let _ = open_req_channels.swap_remove(idx);
 					<Self as Store>::HrmpOpenChannelRequests::remove(&channel_id);
@@ -538,17 +762,121 @@ impl<T: Trait> Module<T> {
 		}
 	}
 
+	/// Iterate over all resize channel requests and:
+	///
+	/// - prune the stale requests
+	/// - enact the confirmed requests
+	pub(super) fn process_hrmp_resize_channel_requests(config: &HostConfiguration<T::BlockNumber>) {
+		let resize_req_channels = <Self as Store>::HrmpResizeChannelRequestsList::get();
+		if resize_req_channels.is_empty() {
+			return;
+		}
+
+		let mut pending = Vec::with_capacity(resize_req_channels.len());
+		for channel_id in resize_req_channels {
+			let mut request = <Self as Store>::HrmpResizeChannelRequests::get(&channel_id).expect(
+				"can't be `None` due to the invariant that the list contains the same items as the set; qed",
+			);
+			let mut channel = <Self as Store>::HrmpChannels::get(&channel_id).expect(
+				"can't be `None` since closing a channel removes its resize request; qed",
+			);
+
+			if request.confirmed {
+				// the increases of the deposits were reserved upfront, only the decreases are
+				// settled here.
+				Self::unreserve_deposit(
+					channel_id.sender,
+					channel.sender_deposit.saturating_sub(request.sender_deposit),
+				);
+				Self::unreserve_deposit(
+					channel_id.recipient,
+					channel.recipient_deposit.saturating_sub(request.recipient_deposit),
+				);
+
+				channel.sender_deposit = request.sender_deposit;
+				channel.recipient_deposit = request.recipient_deposit;
+				channel.max_capacity = request.max_capacity;
+				channel.max_message_size = request.max_message_size;
+				channel.max_total_size = request.max_total_size;
+				<Self as Store>::HrmpChannels::insert(&channel_id, channel);
+				<Self as Store>::HrmpResizeChannelRequests::remove(&channel_id);
+			} else {
+				request.age += 1;
+				if request.age == config.hrmp_open_request_ttl {
+					// got stale
+					Self::refund_resize_request(&channel_id, &channel, &request);
+					<Self as Store>::HrmpResizeChannelRequests::remove(&channel_id);
+				} else {
+					<Self as Store>::HrmpResizeChannelRequests::insert(&channel_id, request);
+					pending.push(channel_id);
+				}
+			}
+		}
+
+		<Self as Store>::HrmpResizeChannelRequestsList::put(pending);
+	}
+
 	/// Close and remove the designated HRMP channel.
 	///
-	/// This includes returning the deposits. However, it doesn't include updating the ingress/egress
-	/// indicies.
+	/// This includes returning the deposits, dropping a pending resize request of the channel and
+	/// notifying both parties. However, it doesn't include updating the ingress/egress indicies.
 	pub(super) fn close_hrmp_channel(channel_id: &HrmpChannelId) {
-		// TODO: return deposit https://github.com/paritytech/polkadot/issues/1907
+		if let Some(channel) = <Self as Store>::HrmpChannels::take(channel_id) {
+			// an offboarded party is not routable anymore and doesn't get the notification.
+			let config = <configuration::Module<T>>::config();
+			let notification = Xcm::HrmpChannelClosed {
+				sender: u32::from(channel_id.sender),
+				recipient: u32::from(channel_id.recipient),
+			};
+			Self::send_notification(&config, channel_id.sender, notification.clone());
+			Self::send_notification(&config, channel_id.recipient, notification);
+
+			if let Some(request) = <Self as Store>::HrmpResizeChannelRequests::take(channel_id) {
+				Self::refund_resize_request(channel_id, &channel, &request);
+				<Self as Store>::HrmpResizeChannelRequestsList::mutate(|v| {
+					v.retain(|c| c != channel_id);
+				});
+			}
+
+			Self::unreserve_deposit(channel_id.sender, channel.sender_deposit);
+			Self::unreserve_deposit(channel_id.recipient, channel.recipient_deposit);
+		}
 
-		<Self as Store>::HrmpChannels::remove(channel_id);
 		<Self as Store>::HrmpChannelContents::remove(channel_id);
 	}
 
+	/// Return the parts of the deposits that were reserved for a resize request in excess of the
+	/// deposits currently held for the channel.
+	fn refund_resize_request(
+		channel_id: &HrmpChannelId,
+		channel: &HrmpChannel,
+		request: &HrmpResizeChannelRequest,
+	) {
+		Self::unreserve_deposit(
+			channel_id.sender,
+			request.sender_deposit.saturating_sub(channel.sender_deposit),
+		);
+		Self::unreserve_deposit(
+			channel_id.recipient,
+			request.recipient_deposit.saturating_sub(channel.recipient_deposit),
+		);
+	}
+
+	/// The sovereign account of the given para, which holds its channel deposits.
+	fn para_account(para: ParaId) -> T::AccountId {
+		para.into_account()
+	}
+
+	/// Reserve the given deposit from the sovereign account of the given para.
+	fn reserve_deposit(para: ParaId, amount: Balance) -> DispatchResult {
+		T::Currency::reserve(&Self::para_account(para), amount.saturated_into())
+	}
+
+	/// Return the given deposit to the sovereign account of the given para.
+	fn unreserve_deposit(para: ParaId, amount: Balance) {
+		let _ = T::Currency::unreserve(&Self::para_account(para), amount.saturated_into());
+	}
+
 	/// Check that the candidate of the given recipient controls the HRMP watermark properly.
 	pub(crate) fn check_hrmp_watermark(
 		recipient: ParaId,
@@ -820,7 +1148,7 @@ impl<T: Trait> Module<T> {
 			Error::<T>::OpenHrmpChannelInvalidRecipient,
 		);
 
-		let config = <configuration::Module<T>>::config();
+		let config = <configuration::Module<T>>::effective_config(origin);
 		ensure!(
 			proposed_max_capacity > 0,
 			Error::<T>::OpenHrmpChannelZeroCapacity,
@@ -864,7 +1192,8 @@ impl<T: Trait> Module<T> {
 			Error::<T>::OpenHrmpChannelLimitExceeded,
 		);
 
-		// TODO: Deposit https://github.com/paritytech/polkadot/issues/1907
+		Self::reserve_deposit(origin, config.hrmp_sender_deposit)
+			.map_err(|_| Error::<T>::OpenHrmpChannelInsufficientDeposit)?;
 
 		<Self as Store>::HrmpOpenChannelRequestCount::insert(&origin, open_req_cnt + 1);
 		<Self as Store>::HrmpOpenChannelRequests::insert(
@@ -876,28 +1205,20 @@ impl<T: Trait> Module<T> {
 				max_capacity: proposed_max_capacity,
 				max_message_size: proposed_max_message_size,
 				max_total_size: config.hrmp_channel_max_total_size,
+				recipient_deposit: 0,
 			},
 		);
 		<Self as Store>::HrmpOpenChannelRequestsList::append(channel_id);
 
-		let notification_bytes = {
-			use xcm::v0::Xcm;
-			use parity_scale_codec::Encode as _;
-
-			Xcm::HrmpNewChannelOpenRequest {
-				sender: u32::from(origin),
-				max_capacity: proposed_max_capacity,
-				max_message_size: proposed_max_message_size,
-			}
-			.encode()
+		// both parties are notified so that the sender learns that the request went through even if
+		// it wasn't dispatched directly.
+		let notification = Xcm::HrmpNewChannelOpenRequest {
+			sender: u32::from(origin),
+			max_capacity: proposed_max_capacity,
+			max_message_size: proposed_max_message_size,
 		};
-		if let Err(dmp::QueueDownwardMessageError::ExceedsMaxMessageSize) =
-			<dmp::Module<T>>::queue_downward_message(&config, recipient, notification_bytes)
-		{
-			// this should never happen unless the max downward message size is configured to an
-			// jokingly small number.
-			debug_assert!(false);
-		}
+		Self::send_notification(&config, origin, notification.clone());
+		Self::send_notification(&config, recipient, notification);
 
 		Ok(())
 	}
@@ -916,7 +1237,7 @@ impl<T: Trait> Module<T> {
 
 		// check if by accepting this open channel request, this parachain would exceed the
 		// number of inbound channels.
-		let config = <configuration::Module<T>>::config();
+		let config = <configuration::Module<T>>::effective_config(origin);
 		let channel_num_limit = if <paras::Module<T>>::is_parathread(origin) {
 			config.hrmp_max_parathread_inbound_channels
 		} else {
@@ -930,30 +1251,21 @@ impl<T: Trait> Module<T> {
 			Error::<T>::AcceptHrmpChannelLimitExceeded,
 		);
 
-		// TODO: Deposit https://github.com/paritytech/polkadot/issues/1907
+		Self::reserve_deposit(origin, config.hrmp_recipient_deposit)
+			.map_err(|_| Error::<T>::AcceptHrmpChannelInsufficientDeposit)?;
 
 		// persist the updated open channel request and then increment the number of accepted
 		// channels.
 		channel_req.confirmed = true;
+		channel_req.recipient_deposit = config.hrmp_recipient_deposit;
 		<Self as Store>::HrmpOpenChannelRequests::insert(&channel_id, channel_req);
 		<Self as Store>::HrmpAcceptedChannelRequestCount::insert(&origin, accepted_cnt + 1);
 
-		let notification_bytes = {
-			use parity_scale_codec::Encode as _;
-			use xcm::v0::Xcm;
-
-			Xcm::HrmpChannelAccepted {
-				recipient: u32::from(origin),
-			}
-			.encode()
+		let notification = Xcm::HrmpChannelAccepted {
+			recipient: u32::from(origin),
 		};
-		if let Err(dmp::QueueDownwardMessageError::ExceedsMaxMessageSize) =
-			<dmp::Module<T>>::queue_downward_message(&config, sender, notification_bytes)
-		{
-			// this should never happen unless the max downward message size is configured to an
-			// jokingly small number.
-			debug_assert!(false);
-		}
+		Self::send_notification(&config, sender, notification.clone());
+		Self::send_notification(&config, origin, notification);
 
 		Ok(())
 	}
@@ -980,30 +1292,131 @@ impl<T: Trait> Module<T> {
 		<Self as Store>::HrmpCloseChannelRequests::insert(&channel_id, ());
 		<Self as Store>::HrmpCloseChannelRequestsList::append(channel_id.clone());
 
+		// both parties are notified so that the initiator learns that the close request went
+		// through even if it wasn't dispatched directly.
 		let config = <configuration::Module<T>>::config();
-		let notification_bytes = {
-			use parity_scale_codec::Encode as _;
-			use xcm::v0::Xcm;
+		let notification = Xcm::HrmpChannelClosing {
+			initiator: u32::from(origin),
+			sender: u32::from(channel_id.sender),
+			recipient: u32::from(channel_id.recipient),
+		};
+		Self::send_notification(&config, channel_id.sender, notification.clone());
+		Self::send_notification(&config, channel_id.recipient, notification);
 
-			Xcm::HrmpChannelClosing {
-				initiator: u32::from(origin),
-				sender: u32::from(channel_id.sender),
-				recipient: u32::from(channel_id.recipient),
+		Ok(())
+	}
+
+	/// Send the given notification to the given para over DMP.
+	fn send_notification(
+		config: &HostConfiguration<T::BlockNumber>,
+		para: ParaId,
+		notification: Xcm,
+	) {
+		match <dmp::Module<T>>::queue_downward_message(config, para, notification.encode()) {
+			Ok(()) => {}
+			Err(dmp::QueueDownwardMessageError::ExceedsMaxMessageSize) => {
+				// this should never happen unless the max downward message size is configured to
+				// an jokingly small number.
+				debug_assert!(false);
 			}
-			.encode()
+			Err(dmp::QueueDownwardMessageError::QueueFull)
+			| Err(dmp::QueueDownwardMessageError::Unroutable) => {
+				// the notification is dropped. The para can still learn about the change by
+				// inspecting the relay-chain state.
+				Self::deposit_event(Event::NotificationDropped(para));
+			}
+		}
+	}
+
+	pub(super) fn init_resize_channel(
+		origin: ParaId,
+		recipient: ParaId,
+		proposed_max_capacity: u32,
+		proposed_max_message_size: u32,
+	) -> Result<(), Error<T>> {
+		let channel_id = HrmpChannelId {
+			sender: origin,
+			recipient,
 		};
-		let opposite_party = if origin == channel_id.sender {
-			channel_id.recipient
-		} else {
-			channel_id.sender
+		let channel = <Self as Store>::HrmpChannels::get(&channel_id)
+			.ok_or(Error::<T>::ResizeHrmpChannelDoesntExist)?;
+		ensure!(
+			<Self as Store>::HrmpCloseChannelRequests::get(&channel_id).is_none(),
+			Error::<T>::ResizeHrmpChannelClosing,
+		);
+		ensure!(
+			<Self as Store>::HrmpResizeChannelRequests::get(&channel_id).is_none(),
+			Error::<T>::ResizeHrmpChannelAlreadyRequested,
+		);
+
+		let config = <configuration::Module<T>>::effective_config(origin);
+		ensure!(
+			proposed_max_capacity > 0,
+			Error::<T>::ResizeHrmpChannelZeroCapacity,
+		);
+		ensure!(
+			proposed_max_capacity <= config.hrmp_channel_max_capacity,
+			Error::<T>::ResizeHrmpChannelCapacityExceedsLimit,
+		);
+		ensure!(
+			proposed_max_message_size > 0,
+			Error::<T>::ResizeHrmpChannelZeroMessageSize,
+		);
+		ensure!(
+			proposed_max_message_size <= config.hrmp_channel_max_message_size,
+			Error::<T>::ResizeHrmpChannelMessageSizeExceedsLimit,
+		);
+
+		// only an increase of the deposit is reserved upfront. A decrease is returned once the
+		// resize is enacted.
+		Self::reserve_deposit(
+			origin,
+			config.hrmp_sender_deposit.saturating_sub(channel.sender_deposit),
+		)
+		.map_err(|_| Error::<T>::ResizeHrmpChannelInsufficientDeposit)?;
+
+		<Self as Store>::HrmpResizeChannelRequests::insert(
+			&channel_id,
+			HrmpResizeChannelRequest {
+				confirmed: false,
+				age: 0,
+				sender_deposit: config.hrmp_sender_deposit,
+				recipient_deposit: channel.recipient_deposit,
+				max_capacity: proposed_max_capacity,
+				max_message_size: proposed_max_message_size,
+				max_total_size: config.hrmp_channel_max_total_size,
+			},
+		);
+		<Self as Store>::HrmpResizeChannelRequestsList::append(channel_id);
+
+		Ok(())
+	}
+
+	pub(super) fn accept_resize_channel(origin: ParaId, sender: ParaId) -> Result<(), Error<T>> {
+		let channel_id = HrmpChannelId {
+			sender,
+			recipient: origin,
 		};
-		if let Err(dmp::QueueDownwardMessageError::ExceedsMaxMessageSize) =
-			<dmp::Module<T>>::queue_downward_message(&config, opposite_party, notification_bytes)
-		{
-			// this should never happen unless the max downward message size is configured to an
-			// jokingly small number.
-			debug_assert!(false);
-		}
+		let mut request = <Self as Store>::HrmpResizeChannelRequests::get(&channel_id)
+			.ok_or(Error::<T>::AcceptHrmpResizeDoesntExist)?;
+		ensure!(
+			!request.confirmed,
+			Error::<T>::AcceptHrmpResizeAlreadyConfirmed,
+		);
+		let channel = <Self as Store>::HrmpChannels::get(&channel_id).expect(
+			"can't be `None` since closing a channel removes its resize request; qed",
+		);
+
+		let config = <configuration::Module<T>>::effective_config(origin);
+		Self::reserve_deposit(
+			origin,
+			config.hrmp_recipient_deposit.saturating_sub(channel.recipient_deposit),
+		)
+		.map_err(|_| Error::<T>::AcceptHrmpResizeInsufficientDeposit)?;
+
+		request.confirmed = true;
+		request.recipient_deposit = config.hrmp_recipient_deposit;
+		<Self as Store>::HrmpResizeChannelRequests::insert(&channel_id, request);
 
 		Ok(())
 	}
@@ -1028,6 +1441,14 @@ impl<T: Trait> Module<T> {
 		mqc_heads
 	}
 
+	/// Returns the MQC head of the given channel paired with the key of the storage entry holding
+	/// the channel metadata, which includes the head. Returns `None` if the channel is not open.
+	pub(crate) fn hrmp_channel_mqc_head(channel_id: &HrmpChannelId) -> Option<(Hash, Vec<u8>)> {
+		let channel = <Self as Store>::HrmpChannels::get(channel_id)?;
+		let mqc_head = channel.mqc_head.unwrap_or(Hash::default());
+		Some((mqc_head, <Self as Store>::HrmpChannels::hashed_key_for(channel_id)))
+	}
+
 	/// Returns contents of all channels addressed to the given recipient. Channels that have no
 	/// messages in them are also included.
 	pub(crate) fn inbound_hrmp_channels_contents(
@@ -1050,9 +1471,11 @@ impl<T: Trait> Module<T> {
 mod tests {
 	use super::*;
 	use crate::mock::{
-		new_test_ext, Configuration, Paras, Hrmp, System, GenesisConfig as MockGenesisConfig,
+		new_test_ext, Balances, Configuration, Dmp, Paras, Hrmp, System, Test, TestEvent, Origin,
+		GenesisConfig as MockGenesisConfig,
 	};
 	use primitives::v1::BlockNumber;
+	use frame_support::traits::Currency as _;
 	use std::collections::{BTreeMap, HashSet};
 
 	fn run_to_block(to: BlockNumber, new_session: Option<Vec<BlockNumber>>) {
@@ -1091,6 +1514,8 @@ mod tests {
 		hrmp_max_parachain_inbound_channels: u32,
 		hrmp_max_message_num_per_candidate: u32,
 		hrmp_channel_max_total_size: u32,
+		hrmp_sender_deposit: Balance,
+		hrmp_recipient_deposit: Balance,
 	}
 
 	impl Default for GenesisConfigBuilder {
@@ -1104,6 +1529,8 @@ mod tests {
 				hrmp_max_parachain_inbound_channels: 2,
 				hrmp_max_message_num_per_candidate: 2,
 				hrmp_channel_max_total_size: 16,
+				hrmp_sender_deposit: 0,
+				hrmp_recipient_deposit: 0,
 			}
 		}
 	}
@@ -1121,6 +1548,8 @@ mod tests {
 			config.hrmp_max_parachain_inbound_channels = self.hrmp_max_parachain_inbound_channels;
 			config.hrmp_max_message_num_per_candidate = self.hrmp_max_message_num_per_candidate;
 			config.hrmp_channel_max_total_size = self.hrmp_channel_max_total_size;
+			config.hrmp_sender_deposit = self.hrmp_sender_deposit;
+			config.hrmp_recipient_deposit = self.hrmp_recipient_deposit;
 			genesis
 		}
 	}
@@ -1130,6 +1559,8 @@ mod tests {
 			configuration: crate::configuration::GenesisConfig {
 				config: crate::configuration::HostConfiguration {
 					max_downward_message_size: 1024,
+					max_downward_queue_count: 16,
+					max_downward_queue_size: 16 * 1024,
 					..Default::default()
 				},
 			},
@@ -1148,6 +1579,15 @@ mod tests {
 		);
 	}
 
+	fn register_parachain_with_balance(id: ParaId, balance: Balance) {
+		register_parachain(id);
+		Balances::make_free_balance_be(&Hrmp::para_account(id), balance);
+	}
+
+	fn reserved_deposit(id: ParaId) -> Balance {
+		Balances::reserved_balance(&Hrmp::para_account(id))
+	}
+
 	fn deregister_parachain(id: ParaId) {
 		Paras::schedule_para_cleanup(id);
 	}
@@ -1159,6 +1599,20 @@ mod tests {
 	fn assert_storage_consistency_exhaustive() {
 		use frame_support::IterableStorageMap;
 
+		assert_eq!(
+			<Hrmp as Store>::HrmpResizeChannelRequests::iter()
+				.map(|(k, _)| k)
+				.collect::<HashSet<_>>(),
+			<Hrmp as Store>::HrmpResizeChannelRequestsList::get()
+				.into_iter()
+				.collect::<HashSet<_>>(),
+		);
+
+		// A resize request can only exist for an open channel.
+		for (channel_id, _) in <Hrmp as Store>::HrmpResizeChannelRequests::iter() {
+			assert!(<Hrmp as Store>::HrmpChannels::contains_key(&channel_id));
+		}
+
 		assert_eq!(
 			<Hrmp as Store>::HrmpOpenChannelRequests::iter()
 				.map(|(k, _)| k)
@@ -1401,6 +1855,117 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn channel_management_via_xcm_works() {
+		let para_a = 5.into();
+		let para_b = 2.into();
+
+		fn process(origin: ParaId, instruction: Xcm) -> Result<Weight, Weight> {
+			XcmHrmpSink::<Test, ()>::process_upward_message(
+				origin,
+				&VersionedXcm::V0(instruction).encode(),
+				Weight::max_value(),
+			)
+		}
+
+		fn notifications(para: ParaId) -> Vec<Xcm> {
+			Dmp::dmq_contents(para)
+				.into_iter()
+				.map(|m| Xcm::decode(&mut &m.msg[..]).unwrap())
+				.collect()
+		}
+
+		new_test_ext(GenesisConfigBuilder::default().build()).execute_with(|| {
+			register_parachain(para_a);
+			register_parachain(para_b);
+
+			run_to_block(5, Some(vec![5]));
+			assert!(process(para_a, Xcm::HrmpInitOpenChannel {
+				recipient: 2,
+				max_message_size: 8,
+				max_capacity: 2,
+			}).is_ok());
+			let requested = Xcm::HrmpNewChannelOpenRequest { sender: 5, max_message_size: 8, max_capacity: 2 };
+			assert_eq!(notifications(para_a), vec![requested.clone()]);
+			assert_eq!(notifications(para_b), vec![requested.clone()]);
+
+			assert!(process(para_b, Xcm::HrmpAcceptOpenChannel { sender: 5 }).is_ok());
+			let accepted = Xcm::HrmpChannelAccepted { recipient: 2 };
+			assert_eq!(notifications(para_a), vec![requested.clone(), accepted.clone()]);
+			assert_eq!(notifications(para_b), vec![requested, accepted]);
+
+			run_to_block(6, Some(vec![6]));
+			assert!(channel_exists(para_a, para_b));
+
+			// The instruction is still consumed if it fails, but it has no effect.
+			assert!(process(para_a, Xcm::HrmpInitOpenChannel {
+				recipient: 2,
+				max_message_size: 8,
+				max_capacity: 2,
+			}).is_ok());
+			assert_eq!(notifications(para_b).len(), 2);
+
+			assert!(process(para_a, Xcm::HrmpCloseChannel { sender: 5, recipient: 2 }).is_ok());
+			let closing = Xcm::HrmpChannelClosing { initiator: 5, sender: 5, recipient: 2 };
+			assert_eq!(notifications(para_a).last(), Some(&closing));
+			assert_eq!(notifications(para_b).last(), Some(&closing));
+
+			// the actual close is announced at the session boundary.
+			run_to_block(8, Some(vec![8]));
+			assert!(!channel_exists(para_a, para_b));
+			let closed = Xcm::HrmpChannelClosed { sender: 5, recipient: 2 };
+			assert_eq!(notifications(para_a).last(), Some(&closed));
+			assert_eq!(notifications(para_b).last(), Some(&closed));
+			assert_storage_consistency_exhaustive();
+		});
+	}
+
+	#[test]
+	fn notifications_to_a_full_queue_are_dropped_with_an_event() {
+		let para_a = 5.into();
+		let para_b = 2.into();
+
+		new_test_ext(GenesisConfigBuilder::default().build()).execute_with(|| {
+			register_parachain(para_a);
+			register_parachain(para_b);
+
+			run_to_block(5, Some(vec![5]));
+			let config = Configuration::config();
+			for _ in 0..config.max_downward_queue_count {
+				assert!(Dmp::queue_downward_message(&config, para_b, vec![1]).is_ok());
+			}
+
+			// the request still goes through, only the recipient misses the notification.
+			assert!(Hrmp::init_open_channel(para_a, para_b, 2, 8).is_ok());
+			assert_eq!(Dmp::dmq_length(para_a), 1);
+			assert_eq!(Dmp::dmq_length(para_b), config.max_downward_queue_count);
+			assert!(System::events().iter().any(|record| {
+				record.event == TestEvent::hrmp(Event::NotificationDropped(para_b))
+			}));
+			assert!(!System::events().iter().any(|record| {
+				record.event == TestEvent::hrmp(Event::NotificationDropped(para_a))
+			}));
+		});
+	}
+
+	#[test]
+	fn xcm_sink_respects_weight_limit() {
+		new_test_ext(GenesisConfigBuilder::default().build()).execute_with(|| {
+			let msg = VersionedXcm::V0(Xcm::HrmpAcceptOpenChannel { sender: 5 }).encode();
+			let required = Hrmp::channel_management_weight();
+			assert_eq!(
+				XcmHrmpSink::<Test, ()>::process_upward_message(2.into(), &msg, required - 1),
+				Err(required),
+			);
+
+			// Anything else is passed on to the inner sink.
+			assert_eq!(
+				XcmHrmpSink::<Test, ()>::process_upward_message(2.into(), &[1, 2, 3], 0),
+				Ok(0),
+			);
+		});
+	}
+
 	#[test]
 	fn send_recv_messages() {
 		let para_a = 32.into();
@@ -1439,6 +2004,260 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn open_channel_reserves_deposits() {
+		let para_a = 32.into();
+		let para_b = 64.into();
+
+		let mut genesis = GenesisConfigBuilder::default();
+		genesis.hrmp_sender_deposit = 20;
+		genesis.hrmp_recipient_deposit = 15;
+		new_test_ext(genesis.build()).execute_with(|| {
+			register_parachain_with_balance(para_a, 100);
+			register_parachain_with_balance(para_b, 10);
+
+			run_to_block(5, Some(vec![5]));
+			Hrmp::init_open_channel(para_a, para_b, 2, 8).unwrap();
+			assert_eq!(reserved_deposit(para_a), 20);
+
+			// B cannot afford the recipient deposit yet.
+			assert!(matches!(
+				Hrmp::accept_open_channel(para_b, para_a),
+				Err(Error::<Test>::AcceptHrmpChannelInsufficientDeposit)
+			));
+			Balances::make_free_balance_be(&Hrmp::para_account(para_b), 100);
+			Hrmp::accept_open_channel(para_b, para_a).unwrap();
+			assert_eq!(reserved_deposit(para_b), 15);
+
+			run_to_block(6, Some(vec![6]));
+			assert!(channel_exists(para_a, para_b));
+
+			// closing the channel returns both deposits.
+			Hrmp::close_channel(para_b, HrmpChannelId { sender: para_a, recipient: para_b })
+				.unwrap();
+			run_to_block(7, Some(vec![7]));
+			assert!(!channel_exists(para_a, para_b));
+			assert_eq!(reserved_deposit(para_a), 0);
+			assert_eq!(reserved_deposit(para_b), 0);
+			assert_storage_consistency_exhaustive();
+		});
+	}
+
+	#[test]
+	fn deposits_of_legacy_channels_are_not_returned() {
+		use frame_support::traits::OnRuntimeUpgrade;
+
+		let para_a = 32.into();
+		let para_b = 64.into();
+		let para_c = 128.into();
+
+		let mut genesis = GenesisConfigBuilder::default();
+		genesis.hrmp_sender_deposit = 20;
+		genesis.hrmp_recipient_deposit = 15;
+		new_test_ext(genesis.build()).execute_with(|| {
+			register_parachain_with_balance(para_a, 100);
+			register_parachain_with_balance(para_b, 100);
+			register_parachain_with_balance(para_c, 100);
+
+			run_to_block(5, Some(vec![5]));
+			Hrmp::init_open_channel(para_a, para_b, 2, 8).unwrap();
+			Hrmp::accept_open_channel(para_b, para_a).unwrap();
+			run_to_block(6, Some(vec![6]));
+
+			// a channel and a request recorded before the deposits were reserved.
+			let legacy_channel = HrmpChannelId { sender: para_b, recipient: para_a };
+			<Hrmp as Store>::HrmpChannels::insert(&legacy_channel, HrmpChannel {
+				sender_deposit: 20,
+				recipient_deposit: 15,
+				max_capacity: 2,
+				max_total_size: 16,
+				max_message_size: 8,
+				msg_count: 0,
+				total_size: 0,
+				mqc_head: None,
+			});
+			<Hrmp as Store>::HrmpEgressChannelsIndex::insert(&para_b, vec![para_a]);
+			<Hrmp as Store>::HrmpIngressChannelsIndex::insert(&para_a, vec![para_b]);
+
+			let legacy_request = HrmpChannelId { sender: para_c, recipient: para_a };
+			sp_io::storage::set(
+				&<Hrmp as Store>::HrmpOpenChannelRequests::hashed_key_for(&legacy_request),
+				&(false, 0 as SessionIndex, 20 as Balance, 8u32, 2u32, 16u32).encode(),
+			);
+			<Hrmp as Store>::HrmpOpenChannelRequestsList::append(legacy_request.clone());
+			<Hrmp as Store>::HrmpOpenChannelRequestCount::insert(&para_c, 1);
+
+			<Hrmp as Store>::StorageVersion::put(Releases::V1);
+			Hrmp::on_runtime_upgrade();
+			assert_eq!(<Hrmp as Store>::StorageVersion::get(), Releases::V2);
+
+			let channel = <Hrmp as Store>::HrmpChannels::get(&legacy_channel).unwrap();
+			assert_eq!((channel.sender_deposit, channel.recipient_deposit), (0, 0));
+			let request = <Hrmp as Store>::HrmpOpenChannelRequests::get(&legacy_request).unwrap();
+			assert_eq!((request.sender_deposit, request.max_total_size), (0, 16));
+
+			// the deposits of the channel opened after the upgrade are left untouched.
+			let channel = <Hrmp as Store>::HrmpChannels::get(
+				&HrmpChannelId { sender: para_a, recipient: para_b },
+			).unwrap();
+			assert_eq!((channel.sender_deposit, channel.recipient_deposit), (20, 15));
+
+			// closing the legacy channel doesn't return the deposits of the other channel.
+			Hrmp::close_channel(para_a, legacy_channel.clone()).unwrap();
+			run_to_block(7, Some(vec![7]));
+			assert!(!channel_exists(para_b, para_a));
+			assert_eq!(reserved_deposit(para_a), 20);
+			assert_eq!(reserved_deposit(para_b), 15);
+			assert_storage_consistency_exhaustive();
+		});
+	}
+
+	#[test]
+	fn resize_channel_works() {
+		let para_a = 32.into();
+		let para_b = 64.into();
+		let channel_id = HrmpChannelId { sender: para_a, recipient: para_b };
+
+		let mut genesis = GenesisConfigBuilder::default();
+		genesis.hrmp_sender_deposit = 20;
+		genesis.hrmp_recipient_deposit = 15;
+		new_test_ext(genesis.build()).execute_with(|| {
+			register_parachain_with_balance(para_a, 100);
+			register_parachain_with_balance(para_b, 100);
+
+			run_to_block(5, Some(vec![5]));
+			Hrmp::init_open_channel(para_a, para_b, 2, 8).unwrap();
+			Hrmp::accept_open_channel(para_b, para_a).unwrap();
+			run_to_block(6, Some(vec![6]));
+			assert!(channel_exists(para_a, para_b));
+
+			// only the sender can initiate a resize and only of an open channel.
+			assert!(matches!(
+				Hrmp::init_resize_channel(para_b, para_a, 4, 16),
+				Err(Error::<Test>::ResizeHrmpChannelDoesntExist)
+			));
+
+			// the configuration changes after the channel was opened.
+			Configuration::set_hrmp_channel_max_capacity(Origin::root(), 4).unwrap();
+			Configuration::set_hrmp_channel_max_message_size(Origin::root(), 16).unwrap();
+			Configuration::set_hrmp_sender_deposit(Origin::root(), 30).unwrap();
+			Configuration::set_hrmp_recipient_deposit(Origin::root(), 10).unwrap();
+			Configuration::initializer_on_new_session(&[], &[]);
+
+			// the increase of the sender deposit is reserved upfront.
+			Hrmp::init_resize_channel(para_a, para_b, 4, 16).unwrap();
+			assert_eq!(reserved_deposit(para_a), 30);
+			assert!(matches!(
+				Hrmp::init_resize_channel(para_a, para_b, 3, 16),
+				Err(Error::<Test>::ResizeHrmpChannelAlreadyRequested)
+			));
+
+			// the request isn't enacted until it is accepted.
+			run_to_block(7, Some(vec![7]));
+			let channel = <Hrmp as Store>::HrmpChannels::get(&channel_id).unwrap();
+			assert_eq!(channel.max_capacity, 2);
+			assert_eq!(channel.max_message_size, 8);
+
+			Hrmp::accept_resize_channel(para_b, para_a).unwrap();
+			assert!(matches!(
+				Hrmp::accept_resize_channel(para_b, para_a),
+				Err(Error::<Test>::AcceptHrmpResizeAlreadyConfirmed)
+			));
+			assert_eq!(reserved_deposit(para_b), 15);
+			assert_storage_consistency_exhaustive();
+
+			// the decrease of the recipient deposit is returned once enacted.
+			run_to_block(8, Some(vec![8]));
+			let channel = <Hrmp as Store>::HrmpChannels::get(&channel_id).unwrap();
+			assert_eq!(channel.max_capacity, 4);
+			assert_eq!(channel.max_message_size, 16);
+			assert_eq!(channel.sender_deposit, 30);
+			assert_eq!(channel.recipient_deposit, 10);
+			assert_eq!(reserved_deposit(para_a), 30);
+			assert_eq!(reserved_deposit(para_b), 10);
+			assert!(<Hrmp as Store>::HrmpResizeChannelRequests::get(&channel_id).is_none());
+			assert_storage_consistency_exhaustive();
+		});
+	}
+
+	#[test]
+	fn closing_channel_drops_resize_request() {
+		let para_a = 32.into();
+		let para_b = 64.into();
+		let channel_id = HrmpChannelId { sender: para_a, recipient: para_b };
+
+		let mut genesis = GenesisConfigBuilder::default();
+		genesis.hrmp_sender_deposit = 20;
+		new_test_ext(genesis.build()).execute_with(|| {
+			register_parachain_with_balance(para_a, 45);
+			register_parachain_with_balance(para_b, 45);
+
+			run_to_block(5, Some(vec![5]));
+			Hrmp::init_open_channel(para_a, para_b, 2, 8).unwrap();
+			Hrmp::accept_open_channel(para_b, para_a).unwrap();
+			run_to_block(6, Some(vec![6]));
+
+			// A can afford an increase to 40 but not to 50.
+			Configuration::set_hrmp_sender_deposit(Origin::root(), 50).unwrap();
+			Configuration::initializer_on_new_session(&[], &[]);
+			assert!(matches!(
+				Hrmp::init_resize_channel(para_a, para_b, 2, 4),
+				Err(Error::<Test>::ResizeHrmpChannelInsufficientDeposit)
+			));
+			Configuration::set_hrmp_sender_deposit(Origin::root(), 40).unwrap();
+			Configuration::initializer_on_new_session(&[], &[]);
+			Hrmp::init_resize_channel(para_a, para_b, 2, 4).unwrap();
+			assert_eq!(reserved_deposit(para_a), 40);
+
+			Hrmp::close_channel(para_b, channel_id.clone()).unwrap();
+			assert!(matches!(
+				Hrmp::accept_resize_channel(para_b, para_a),
+				Ok(())
+			));
+			run_to_block(7, Some(vec![7]));
+
+			assert!(!channel_exists(para_a, para_b));
+			assert!(<Hrmp as Store>::HrmpResizeChannelRequests::get(&channel_id).is_none());
+			assert_eq!(reserved_deposit(para_a), 0);
+			assert_storage_consistency_exhaustive();
+		});
+	}
+
+	#[test]
+	fn channel_mqc_head_storage_key_points_to_channel() {
+		let para_a = 32.into();
+		let para_b = 64.into();
+		let channel_id = HrmpChannelId { sender: para_a, recipient: para_b };
+
+		let mut genesis = GenesisConfigBuilder::default();
+		genesis.hrmp_channel_max_message_size = 20;
+		genesis.hrmp_channel_max_total_size = 20;
+		new_test_ext(genesis.build()).execute_with(|| {
+			register_parachain(para_a);
+			register_parachain(para_b);
+
+			run_to_block(5, Some(vec![5]));
+			assert!(Hrmp::hrmp_channel_mqc_head(&channel_id).is_none());
+			Hrmp::init_open_channel(para_a, para_b, 2, 20).unwrap();
+			Hrmp::accept_open_channel(para_b, para_a).unwrap();
+
+			run_to_block(6, Some(vec![6]));
+			let (head, _) = Hrmp::hrmp_channel_mqc_head(&channel_id).unwrap();
+			assert_eq!(head, Hash::default());
+
+			let _ = Hrmp::queue_outbound_hrmp(para_a, vec![OutboundHrmpMessage {
+				recipient: para_b,
+				data: b"knock knock".to_vec(),
+			}]);
+
+			let (head, storage_key) = Hrmp::hrmp_channel_mqc_head(&channel_id).unwrap();
+			assert_ne!(head, Hash::default());
+			let raw = sp_io::storage::get(&storage_key).unwrap();
+			let channel = HrmpChannel::decode(&mut &raw[..]).unwrap();
+			assert_eq!(channel.mqc_head, Some(head));
+		});
+	}
+
 	#[test]
 	fn accept_incoming_request_and_offboard() {
 		let para_a = 32.into();
@@ -1461,6 +2280,33 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn offboarding_announces_closed_channels() {
+		let para_a = 32.into();
+		let para_b = 64.into();
+
+		new_test_ext(GenesisConfigBuilder::default().build()).execute_with(|| {
+			register_parachain(para_a);
+			register_parachain(para_b);
+
+			run_to_block(5, Some(vec![5]));
+			Hrmp::init_open_channel(para_a, para_b, 2, 8).unwrap();
+			Hrmp::accept_open_channel(para_b, para_a).unwrap();
+
+			run_to_block(6, Some(vec![6]));
+			assert!(channel_exists(para_a, para_b));
+			deregister_parachain(para_a);
+
+			run_to_block(7, Some(vec![7]));
+			assert!(!channel_exists(para_a, para_b));
+			let last_notification = Dmp::dmq_contents(para_b)
+				.pop()
+				.map(|m| Xcm::decode(&mut &m.msg[..]).unwrap());
+			assert_eq!(last_notification, Some(Xcm::HrmpChannelClosed { sender: 32, recipient: 64 }));
+			assert_storage_consistency_exhaustive();
+		});
+	}
+
 	#[test]
 	fn check_sent_messages() {
 		let para_a = 32.into();
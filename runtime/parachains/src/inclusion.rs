@@ -36,7 +36,7 @@ use bitvec::{order::Lsb0 as BitOrderLsb0, vec::BitVec};
 use sp_staking::SessionIndex;
 use sp_runtime::{DispatchError, traits::{One, Saturating}};
 
-use crate::{configuration, paras, dmp, ump, hrmp, scheduler::CoreAssignment};
+use crate::{configuration, paras, dmp, ump, hrmp, scheduler::{self, CoreAssignment}};
 
 /// A bitfield signed by a validator indicating that it is keeping its piece of the erasure-coding
 /// for any backed candidates referred to by a `1` bit available.
@@ -92,6 +92,7 @@ pub trait Trait:
 	+ ump::Trait
 	+ hrmp::Trait
 	+ configuration::Trait
+	+ scheduler::Trait
 {
 	type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
 }
@@ -223,9 +224,9 @@ impl<T: Trait> Module<T> {
 		let validators = Validators::get();
 		let session_index = CurrentSessionIndex::get();
 		let config = <configuration::Module<T>>::config();
-		let parachains = <paras::Module<T>>::parachains();
+		let parachain_cores = <scheduler::Module<T>>::parachain_cores();
 
-		let n_bits = parachains.len() + config.parathread_cores as usize;
+		let n_bits = parachain_cores.len() + config.parathread_cores as usize;
 
 		let mut assigned_paras_record: Vec<_> = (0..n_bits)
 			.map(|bit_index| core_lookup(CoreIndex::from(bit_index as u32)))
@@ -776,8 +777,10 @@ impl<T: Trait> CandidateCheckContext<T> {
 		hrmp_watermark: T::BlockNumber,
 		horizontal_messages: &[primitives::v1::OutboundHrmpMessage<ParaId>],
 	) -> Result<(), AcceptanceCheckErr<T::BlockNumber>> {
+		let config = <configuration::Module<T>>::with_para_overrides(self.config.clone(), para_id);
+
 		ensure!(
-			head_data.0.len() <= self.config.max_head_data_size as _,
+			head_data.0.len() <= config.max_head_data_size as _,
 			AcceptanceCheckErr::HeadDataTooLarge,
 		);
 
@@ -787,14 +790,14 @@ impl<T: Trait> CandidateCheckContext<T> {
 				.map_or(true, |last| {
 					last <= self.relay_parent_number
 						&& self.relay_parent_number.saturating_sub(last)
-							>= self.config.validation_upgrade_frequency
+							>= config.validation_upgrade_frequency
 				});
 			ensure!(
 				valid_upgrade_attempt,
 				AcceptanceCheckErr::PrematureCodeUpgrade,
 			);
 			ensure!(
-				new_validation_code.0.len() <= self.config.max_code_size as _,
+				new_validation_code.0.len() <= config.max_code_size as _,
 				AcceptanceCheckErr::NewCodeTooLarge,
 			);
 		}
@@ -804,13 +807,13 @@ impl<T: Trait> CandidateCheckContext<T> {
 			para_id,
 			processed_downward_messages,
 		)?;
-		<ump::Module<T>>::check_upward_messages(&self.config, para_id, upward_messages)?;
+		<ump::Module<T>>::check_upward_messages(&config, para_id, upward_messages)?;
 		<hrmp::Module<T>>::check_hrmp_watermark(
 			para_id,
 			self.relay_parent_number,
 			hrmp_watermark,
 		)?;
-		<hrmp::Module<T>>::check_outbound_hrmp(&self.config, para_id, horizontal_messages)?;
+		<hrmp::Module<T>>::check_outbound_hrmp(&config, para_id, horizontal_messages)?;
 
 		Ok(())
 	}
@@ -2184,6 +2187,39 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn acceptance_checks_use_the_para_overrides() {
+		let chain_a = ParaId::from(1);
+		let chain_b = ParaId::from(2);
+
+		let paras = vec![(chain_a, true), (chain_b, true)];
+		new_test_ext(genesis_config(paras)).execute_with(|| {
+			System::set_block_number(5);
+
+			// the code is too large for the configured `max_code_size` of 3.
+			let outputs = primitives::v1::ValidationOutputs {
+				new_validation_code: Some(vec![0; 5].into()),
+				hrmp_watermark: 4,
+				..Default::default()
+			};
+			assert!(!Inclusion::check_validation_outputs(chain_a, outputs.clone()));
+
+			let overrides = crate::configuration::ParaConfigOverrides {
+				max_code_size: Some(5),
+				..Default::default()
+			};
+			Configuration::set_para_overrides(
+				crate::mock::Origin::root(),
+				chain_a,
+				Some(overrides),
+			).unwrap();
+			Configuration::initializer_on_new_session(&[], &[]);
+
+			assert!(Inclusion::check_validation_outputs(chain_a, outputs.clone()));
+			assert!(!Inclusion::check_validation_outputs(chain_b, outputs));
+		});
+	}
+
 	#[test]
 	fn session_change_wipes_and_updates_session_info() {
 		let chain_a = ParaId::from(1);
@@ -33,6 +33,7 @@ pub mod origin;
 pub mod dmp;
 pub mod ump;
 pub mod hrmp;
+pub mod parathread_claims;
 
 pub mod runtime_api_impl;
 
@@ -51,6 +52,16 @@ pub fn schedule_para_initialize<T: paras::Trait>(
 	<paras::Module<T>>::schedule_para_initialize(id, genesis);
 }
 
+/// Schedule a parathread to be upgraded to a parachain at the start of the next session.
+pub fn schedule_parathread_upgrade<T: paras::Trait>(id: primitives::v1::Id) {
+	<paras::Module<T>>::schedule_parathread_upgrade(id);
+}
+
+/// Schedule a parachain to be downgraded to a parathread at the start of the next session.
+pub fn schedule_parachain_downgrade<T: paras::Trait>(id: primitives::v1::Id) {
+	<paras::Module<T>>::schedule_parachain_downgrade(id);
+}
+
 /// Schedule a para to be cleaned up at the start of the next session.
 pub fn schedule_para_cleanup<T>(id: primitives::v1::Id)
 where
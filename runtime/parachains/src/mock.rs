@@ -29,7 +29,7 @@ use frame_support::{
 	impl_outer_origin, impl_outer_dispatch, impl_outer_event, parameter_types,
 	weights::Weight, traits::Randomness as RandomnessT,
 };
-use crate::inclusion;
+use crate::{inclusion, ump, hrmp, parathread_claims};
 use crate as parachains;
 
 /// A test runtime struct.
@@ -51,7 +51,11 @@ impl_outer_dispatch! {
 impl_outer_event! {
 	pub enum TestEvent for Test {
 		frame_system<T>,
+		pallet_balances<T>,
 		inclusion<T>,
+		ump,
+		hrmp,
+		parathread_claims<T>,
 	}
 }
 
@@ -98,6 +102,20 @@ impl frame_system::Trait for Test {
 	type SystemWeightInfo = ();
 }
 
+parameter_types! {
+	pub const ExistentialDeposit: u128 = 1;
+}
+
+impl pallet_balances::Trait for Test {
+	type Balance = u128;
+	type DustRemoval = ();
+	type Event = TestEvent;
+	type ExistentialDeposit = ExistentialDeposit;
+	type AccountStore = System;
+	type MaxLocks = ();
+	type WeightInfo = ();
+}
+
 impl crate::initializer::Trait for Test {
 	type Randomness = TestRandomness;
 }
@@ -111,14 +129,31 @@ impl crate::paras::Trait for Test {
 impl crate::dmp::Trait for Test { }
 
 impl crate::ump::Trait for Test {
+	type Event = TestEvent;
 	type UmpSink = crate::ump::mock_sink::MockUmpSink;
 }
 
 impl crate::hrmp::Trait for Test {
+	type Event = TestEvent;
 	type Origin = Origin;
+	type Currency = Balances;
 }
 
-impl crate::scheduler::Trait for Test { }
+impl crate::scheduler::Trait for Test {
+	type ClaimHandler = ParathreadClaims;
+}
+
+parameter_types! {
+	pub const BaseClaimFee: u128 = 10;
+	pub const DroppedClaimPenalty: Perbill = Perbill::from_percent(50);
+}
+
+impl crate::parathread_claims::Trait for Test {
+	type Event = TestEvent;
+	type Currency = Balances;
+	type BaseClaimFee = BaseClaimFee;
+	type DroppedClaimPenalty = DroppedClaimPenalty;
+}
 
 impl crate::inclusion::Trait for Test {
 	type Event = TestEvent;
@@ -126,6 +161,9 @@ impl crate::inclusion::Trait for Test {
 
 pub type System = frame_system::Module<Test>;
 
+/// Mocked balances.
+pub type Balances = pallet_balances::Module<Test>;
+
 /// Mocked initializer.
 pub type Initializer = crate::initializer::Module<Test>;
 
@@ -150,6 +188,9 @@ pub type Scheduler = crate::scheduler::Module<Test>;
 /// Mocked inclusion module.
 pub type Inclusion = crate::inclusion::Module<Test>;
 
+/// Mocked parathread claims module.
+pub type ParathreadClaims = crate::parathread_claims::Module<Test>;
+
 /// Create a new set of test externalities.
 pub fn new_test_ext(state: GenesisConfig) -> TestExternalities {
 	let mut t = state.system.build_storage::<Test>().unwrap();
@@ -22,6 +22,10 @@
 //!
 //! A para is not considered live until it is registered and activated in this module. Activation can
 //! only occur at session boundaries.
+//!
+//! This module doesn't check the size of the head data and validation code it is handed. New heads
+//! and code upgrades are checked by the inclusion module against the limits in effect for the para,
+//! i.e. with any per-para configuration overrides applied, before they are noted here.
 
 use sp_std::prelude::*;
 use sp_std::result;
@@ -214,6 +218,10 @@ decl_storage! {
 		UpcomingParasGenesis: map hasher(twox_64_concat) ParaId => Option<ParaGenesisArgs>;
 		/// Paras that are to be cleaned up at the end of the session.
 		OutgoingParas get(fn outgoing_paras): Vec<ParaId>;
+		/// Parathreads that are to be upgraded to parachains at the end of the session.
+		UpcomingUpgrades get(fn upcoming_upgrades): Vec<ParaId>;
+		/// Parachains that are to be downgraded to parathreads at the end of the session.
+		UpcomingDowngrades get(fn upcoming_downgrades): Vec<ParaId>;
 
 	}
 	add_extra_genesis {
@@ -268,6 +276,7 @@ impl<T: Trait> Module<T> {
 		let now = <frame_system::Module<T>>::block_number();
 		let mut parachains = Self::clean_up_outgoing(now);
 		Self::apply_incoming(&mut parachains);
+		Self::apply_lifecycle_changes(&mut parachains);
 		<Self as Store>::Parachains::set(parachains);
 	}
 
@@ -286,6 +295,7 @@ impl<T: Trait> Module<T> {
 			<Self as Store>::Heads::remove(&outgoing_para);
 			<Self as Store>::FutureCodeUpgrades::remove(&outgoing_para);
 			<Self as Store>::FutureCode::remove(&outgoing_para);
+			<configuration::Module<T>>::clear_para_overrides(outgoing_para);
 
 			let removed_code = <Self as Store>::CurrentCode::take(&outgoing_para);
 			if let Some(removed_code) = removed_code {
@@ -318,6 +328,27 @@ impl<T: Trait> Module<T> {
 		}
 	}
 
+	/// Applies all scheduled upgrades and downgrades, updating the parachains list accordingly.
+	///
+	/// Paras that are no longer in the expected state, e.g. because they were cleaned up in the
+	/// meantime, are ignored.
+	fn apply_lifecycle_changes(parachains: &mut Vec<ParaId>) {
+		for upgrading in <Self as Store>::UpcomingUpgrades::take() {
+			if <Self as Store>::Parathreads::take(&upgrading).is_some() {
+				if let Err(i) = parachains.binary_search(&upgrading) {
+					parachains.insert(i, upgrading);
+				}
+			}
+		}
+
+		for downgrading in <Self as Store>::UpcomingDowngrades::take() {
+			if let Ok(i) = parachains.binary_search(&downgrading) {
+				parachains.remove(i);
+				<Self as Store>::Parathreads::insert(&downgrading, ());
+			}
+		}
+	}
+
 	// note replacement of the code of para with given `id`, which occured in the
 	// context of the given relay-chain block number. provide the replaced code.
 	//
@@ -417,6 +448,34 @@ impl<T: Trait> Module<T> {
 		T::DbWeight::get().reads_writes(1, 2)
 	}
 
+	/// Schedule a parathread to be upgraded to a parachain at the start of the next session.
+	///
+	/// Cancels any pending downgrade of the para.
+	pub(crate) fn schedule_parathread_upgrade(id: ParaId) -> Weight {
+		UpcomingDowngrades::mutate(|v| if let Ok(i) = v.binary_search(&id) {
+			v.remove(i);
+		});
+		UpcomingUpgrades::mutate(|v| if let Err(i) = v.binary_search(&id) {
+			v.insert(i, id);
+		});
+
+		T::DbWeight::get().reads_writes(2, 2)
+	}
+
+	/// Schedule a parachain to be downgraded to a parathread at the start of the next session.
+	///
+	/// Cancels any pending upgrade of the para.
+	pub(crate) fn schedule_parachain_downgrade(id: ParaId) -> Weight {
+		UpcomingUpgrades::mutate(|v| if let Ok(i) = v.binary_search(&id) {
+			v.remove(i);
+		});
+		UpcomingDowngrades::mutate(|v| if let Err(i) = v.binary_search(&id) {
+			v.insert(i, id);
+		});
+
+		T::DbWeight::get().reads_writes(2, 2)
+	}
+
 	/// Schedule a para to be cleaned up at the start of the next session.
 	pub(crate) fn schedule_para_cleanup(id: ParaId) -> Weight {
 		let upcoming_weight = UpcomingParas::mutate(|v| {
@@ -547,11 +606,21 @@ impl<T: Trait> Module<T> {
 			|| Self::is_parathread(id)
 	}
 
+	/// Whether a para ID corresponds to any live parachain.
+	pub fn is_parachain(id: ParaId) -> bool {
+		Self::parachains().binary_search(&id).is_ok()
+	}
+
 	/// Whether a para ID corresponds to any live parathread.
-	pub(crate) fn is_parathread(id: ParaId) -> bool {
+	pub fn is_parathread(id: ParaId) -> bool {
 		Parathreads::get(&id).is_some()
 	}
 
+	/// Whether a para ID is scheduled to be onboarded at the start of the next session.
+	pub fn is_upcoming_para(id: ParaId) -> bool {
+		UpcomingParas::get().binary_search(&id).is_ok()
+	}
+
 	/// The block number of the last scheduled upgrade of the requested para. Includes future upgrades
 	/// if the flag is set. This is the `expected_at` number, not the `activated_at` number.
 	pub(crate) fn last_code_upgrade(id: ParaId, include_future: bool) -> Option<T::BlockNumber> {
@@ -1210,6 +1279,75 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn para_upgrade_and_downgrade_at_session() {
+		new_test_ext(Default::default()).execute_with(|| {
+			run_to_block(1, None);
+
+			let a = ParaId::from(999);
+			let b = ParaId::from(525);
+
+			Paras::schedule_para_initialize(
+				a,
+				ParaGenesisArgs {
+					parachain: false,
+					genesis_head: vec![1].into(),
+					validation_code: vec![1].into(),
+				},
+			);
+
+			Paras::schedule_para_initialize(
+				b,
+				ParaGenesisArgs {
+					parachain: true,
+					genesis_head: vec![2].into(),
+					validation_code: vec![2].into(),
+				},
+			);
+
+			run_to_block(2, Some(vec![2]));
+
+			assert_eq!(Paras::parachains(), vec![b]);
+			assert!(Paras::is_parathread(a));
+
+			Paras::schedule_parathread_upgrade(a);
+			Paras::schedule_parachain_downgrade(b);
+
+			assert_eq!(Paras::upcoming_upgrades(), vec![a]);
+			assert_eq!(Paras::upcoming_downgrades(), vec![b]);
+
+			// nothing changes until the session does.
+			run_to_block(3, None);
+
+			assert_eq!(Paras::parachains(), vec![b]);
+			assert!(Paras::is_parathread(a));
+
+			run_to_block(4, Some(vec![4]));
+
+			assert_eq!(Paras::parachains(), vec![a]);
+			assert!(Paras::is_parachain(a));
+			assert!(!Paras::is_parathread(a));
+			assert!(Paras::is_parathread(b));
+			assert!(Paras::upcoming_upgrades().is_empty());
+			assert!(Paras::upcoming_downgrades().is_empty());
+
+			// scheduling the opposite change cancels a pending one.
+			Paras::schedule_parathread_upgrade(b);
+			Paras::schedule_parachain_downgrade(b);
+
+			assert!(Paras::upcoming_upgrades().is_empty());
+			assert_eq!(Paras::upcoming_downgrades(), vec![b]);
+
+			// downgrading a parathread has no effect.
+			run_to_block(5, Some(vec![5]));
+
+			assert_eq!(Paras::parachains(), vec![a]);
+			assert!(Paras::is_parathread(b));
+			assert_eq!(Paras::current_code(&a), Some(vec![1].into()));
+			assert_eq!(Paras::current_code(&b), Some(vec![2].into()));
+		});
+	}
+
 	#[test]
 	fn code_at_with_intermediate() {
 		let acceptance_period = 10;
@@ -0,0 +1,348 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! The parathread claims module.
+//!
+//! Allows collators of parathreads to buy a slot on one of the parathread multiplexer cores. A claim
+//! costs a fee that rises with the number of claims already waiting in the parathread queue. The fee
+//! is reserved when the claim is placed and is either burned once a candidate for the claim gets
+//! included or, if the scheduler drops the claim, e.g. after `parathread_retries`, refunded less a
+//! `DroppedClaimPenalty`. The penalty keeps claims that are never meant to be served, which would
+//! only block the parathread's claim slot, from being free.
+
+use sp_std::prelude::*;
+use primitives::v1::{Id as ParaId, CollatorId, ParathreadClaim};
+use frame_support::{
+	decl_storage, decl_module, decl_error, decl_event, ensure,
+	dispatch::DispatchResult,
+	traits::{Get, Currency, ReservableCurrency},
+};
+use frame_system::ensure_signed;
+use sp_runtime::{Perbill, traits::Saturating};
+
+use crate::{configuration, paras, scheduler};
+
+type BalanceOf<T> =
+	<<T as Trait>::Currency as Currency<<T as frame_system::Trait>::AccountId>>::Balance;
+
+pub trait Trait: frame_system::Trait + configuration::Trait + paras::Trait + scheduler::Trait {
+	/// The outer event type.
+	type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
+
+	/// The currency used to pay for parathread claims.
+	type Currency: ReservableCurrency<Self::AccountId>;
+
+	/// The fee for a claim placed while the parathread queue is empty.
+	type BaseClaimFee: Get<BalanceOf<Self>>;
+
+	/// The portion of the fee that is burned rather than refunded when a claim is dropped.
+	type DroppedClaimPenalty: Get<Perbill>;
+}
+
+decl_storage! {
+	trait Store for Module<T: Trait> as ParathreadClaims {
+		/// The account that paid for the outstanding claim on a parathread and the reserved fee.
+		///
+		/// The scheduler allows at most one claim per parathread, so the para id is a sufficient key.
+		ClaimFees get(fn claim_fee): map hasher(twox_64_concat) ParaId => Option<(T::AccountId, BalanceOf<T>)>;
+	}
+}
+
+decl_event! {
+	pub enum Event<T> where
+		AccountId = <T as frame_system::Trait>::AccountId,
+		Balance = BalanceOf<T>,
+	{
+		/// A claim on a parathread core was bought. [para_id, collator, who, fee]
+		ClaimPlaced(ParaId, CollatorId, AccountId, Balance),
+		/// A candidate for the claim was included and the fee was burned. [para_id, who, fee]
+		ClaimConcluded(ParaId, AccountId, Balance),
+		/// The claim was dropped by the scheduler and the fee was refunded, less the burned penalty.
+		/// [para_id, who, refunded, penalty]
+		ClaimRefunded(ParaId, AccountId, Balance, Balance),
+	}
+}
+
+decl_error! {
+	pub enum Error for Module<T: Trait> {
+		/// The current spot price is higher than the fee the caller is willing to pay.
+		FeeExceedsLimit,
+		/// The caller cannot afford the current spot price.
+		InsufficientBalance,
+	}
+}
+
+decl_module! {
+	/// The parathread claims module.
+	pub struct Module<T: Trait> for enum Call where origin: <T as frame_system::Trait>::Origin {
+		type Error = Error<T>;
+
+		fn deposit_event() = default;
+
+		/// Buy a claim for the given parathread to be collated by `collator` on one of the
+		/// parathread cores.
+		///
+		/// The current spot price is reserved from the origin, unless it exceeds `max_fee`. The fee is
+		/// refunded, less the `DroppedClaimPenalty`, if the claim is dropped before a candidate for it
+		/// is included.
+		#[weight = 50_000_000 + T::DbWeight::get().reads_writes(6, 4)]
+		fn claim(
+			origin,
+			para_id: ParaId,
+			collator: CollatorId,
+			max_fee: BalanceOf<T>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			let fee = Self::spot_price();
+			ensure!(fee <= max_fee, Error::<T>::FeeExceedsLimit);
+			ensure!(T::Currency::can_reserve(&who, fee), Error::<T>::InsufficientBalance);
+
+			<scheduler::Module<T>>::try_add_parathread_claim(
+				ParathreadClaim(para_id, collator.clone()),
+			)?;
+
+			T::Currency::reserve(&who, fee)?;
+			<ClaimFees<T>>::insert(para_id, (who.clone(), fee));
+
+			Self::deposit_event(RawEvent::ClaimPlaced(para_id, collator, who, fee));
+
+			Ok(())
+		}
+	}
+}
+
+impl<T: Trait> Module<T> {
+	/// The fee to be paid for a parathread claim placed now.
+	///
+	/// Grows linearly with the number of claims already waiting in the parathread queue.
+	pub fn spot_price() -> BalanceOf<T> {
+		let queued = <scheduler::Module<T>>::parathread_queue_len();
+		T::BaseClaimFee::get().saturating_mul(queued.saturating_add(1).into())
+	}
+}
+
+impl<T: Trait> scheduler::ParathreadClaimHandler for Module<T> {
+	fn claim_concluded(claim: &ParathreadClaim) {
+		if let Some((who, fee)) = <ClaimFees<T>>::take(&claim.0) {
+			let _ = T::Currency::slash_reserved(&who, fee);
+			Self::deposit_event(RawEvent::ClaimConcluded(claim.0, who, fee));
+		}
+	}
+
+	fn claim_dropped(claim: &ParathreadClaim) {
+		if let Some((who, fee)) = <ClaimFees<T>>::take(&claim.0) {
+			let penalty = T::DroppedClaimPenalty::get() * fee;
+			let _ = T::Currency::slash_reserved(&who, penalty);
+
+			let refund = fee.saturating_sub(penalty);
+			let _ = T::Currency::unreserve(&who, refund);
+			Self::deposit_event(RawEvent::ClaimRefunded(claim.0, who, refund, penalty));
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use primitives::v1::{BlockNumber, ValidatorId};
+	use frame_support::{assert_ok, assert_noop, traits::{OnFinalize, OnInitialize}};
+	use keyring::Sr25519Keyring;
+
+	use crate::mock::{
+		new_test_ext, Balances, Paras, ParathreadClaims, Scheduler, System, Origin, Test,
+		BaseClaimFee, DroppedClaimPenalty, GenesisConfig as MockGenesisConfig,
+	};
+	use crate::initializer::SessionChangeNotification;
+	use crate::configuration::HostConfiguration;
+	use crate::paras::ParaGenesisArgs;
+	use crate::scheduler::FreedReason;
+
+	fn run_to_block(
+		to: BlockNumber,
+		new_session: impl Fn(BlockNumber) -> Option<SessionChangeNotification<BlockNumber>>,
+	) {
+		while System::block_number() < to {
+			let b = System::block_number();
+
+			Scheduler::initializer_finalize();
+			Paras::initializer_finalize();
+
+			System::on_finalize(b);
+
+			System::on_initialize(b + 1);
+			System::set_block_number(b + 1);
+
+			if let Some(notification) = new_session(b + 1) {
+				Paras::initializer_on_new_session(&notification);
+				Scheduler::initializer_on_new_session(&notification);
+			}
+
+			Paras::initializer_initialize(b + 1);
+			Scheduler::initializer_initialize(b + 1);
+		}
+	}
+
+	fn default_config() -> HostConfiguration<BlockNumber> {
+		HostConfiguration {
+			parathread_cores: 1,
+			group_rotation_frequency: 10,
+			chain_availability_period: 3,
+			thread_availability_period: 5,
+			scheduling_lookahead: 2,
+			parathread_retries: 1,
+			..Default::default()
+		}
+	}
+
+	fn genesis_config() -> MockGenesisConfig {
+		MockGenesisConfig {
+			configuration: crate::configuration::GenesisConfig {
+				config: default_config(),
+				..Default::default()
+			},
+			..Default::default()
+		}
+	}
+
+	fn register_threads_and_start_session(threads: &[ParaId]) {
+		for id in threads {
+			Paras::schedule_para_initialize(*id, ParaGenesisArgs {
+				genesis_head: Vec::new().into(),
+				validation_code: Vec::new().into(),
+				parachain: false,
+			});
+		}
+
+		run_to_block(1, |number| match number {
+			1 => Some(SessionChangeNotification {
+				new_config: default_config(),
+				validators: vec![ValidatorId::from(Sr25519Keyring::Alice.public())],
+				..Default::default()
+			}),
+			_ => None,
+		});
+	}
+
+	#[test]
+	fn spot_price_rises_with_queue_fill() {
+		let thread_a = ParaId::from(1);
+		let thread_b = ParaId::from(2);
+		let collator = CollatorId::from(Sr25519Keyring::Alice.public());
+
+		new_test_ext(genesis_config()).execute_with(|| {
+			register_threads_and_start_session(&[thread_a, thread_b]);
+			Balances::make_free_balance_be(&1, 1_000);
+
+			assert_eq!(ParathreadClaims::spot_price(), BaseClaimFee::get());
+
+			assert_ok!(ParathreadClaims::claim(
+				Origin::signed(1), thread_a, collator.clone(), BaseClaimFee::get(),
+			));
+			assert_eq!(Balances::reserved_balance(1), BaseClaimFee::get());
+			assert_eq!(ParathreadClaims::claim_fee(thread_a), Some((1, BaseClaimFee::get())));
+
+			assert_eq!(ParathreadClaims::spot_price(), 2 * BaseClaimFee::get());
+			assert_noop!(
+				ParathreadClaims::claim(Origin::signed(1), thread_b, collator, BaseClaimFee::get()),
+				Error::<Test>::FeeExceedsLimit,
+			);
+		});
+	}
+
+	#[test]
+	fn invalid_claims_are_rejected_without_charging() {
+		let thread_a = ParaId::from(1);
+		let not_a_thread = ParaId::from(2);
+		let collator = CollatorId::from(Sr25519Keyring::Alice.public());
+
+		new_test_ext(genesis_config()).execute_with(|| {
+			register_threads_and_start_session(&[thread_a]);
+			Balances::make_free_balance_be(&1, 1_000);
+			Balances::make_free_balance_be(&2, 1);
+
+			assert_noop!(
+				ParathreadClaims::claim(Origin::signed(1), not_a_thread, collator.clone(), 1_000),
+				scheduler::Error::<Test>::NotParathread,
+			);
+			assert_noop!(
+				ParathreadClaims::claim(Origin::signed(2), thread_a, collator.clone(), 1_000),
+				Error::<Test>::InsufficientBalance,
+			);
+
+			assert_ok!(ParathreadClaims::claim(Origin::signed(1), thread_a, collator.clone(), 1_000));
+			assert_noop!(
+				ParathreadClaims::claim(Origin::signed(1), thread_a, collator, 1_000),
+				scheduler::Error::<Test>::ParathreadAlreadyClaimed,
+			);
+			assert_eq!(Balances::reserved_balance(1), BaseClaimFee::get());
+		});
+	}
+
+	#[test]
+	fn fee_is_refunded_less_penalty_when_claim_runs_out_of_retries() {
+		let thread_a = ParaId::from(1);
+		let collator = CollatorId::from(Sr25519Keyring::Alice.public());
+		let max_retries = default_config().parathread_retries;
+
+		new_test_ext(genesis_config()).execute_with(|| {
+			register_threads_and_start_session(&[thread_a]);
+			Balances::make_free_balance_be(&1, 1_000);
+
+			assert_ok!(ParathreadClaims::claim(Origin::signed(1), thread_a, collator.clone(), 1_000));
+
+			run_to_block(2, |_| None);
+			assert_eq!(Scheduler::scheduled().len(), 1);
+			assert_eq!(Balances::reserved_balance(1), BaseClaimFee::get());
+
+			run_to_block(2 + max_retries + 1, |_| None);
+			assert!(Scheduler::scheduled().is_empty());
+
+			// dropping the claim burns the penalty, so that blocking the claim slot isn't free.
+			let penalty = DroppedClaimPenalty::get() * BaseClaimFee::get();
+			assert!(penalty > 0);
+			assert_eq!(Balances::reserved_balance(1), 0);
+			assert_eq!(Balances::free_balance(1), 1_000 - penalty);
+			assert!(ParathreadClaims::claim_fee(thread_a).is_none());
+
+			// the parathread can be claimed again.
+			assert_ok!(ParathreadClaims::claim(Origin::signed(1), thread_a, collator, 1_000));
+		});
+	}
+
+	#[test]
+	fn fee_is_burned_when_claim_concludes() {
+		let thread_a = ParaId::from(1);
+		let collator = CollatorId::from(Sr25519Keyring::Alice.public());
+
+		new_test_ext(genesis_config()).execute_with(|| {
+			register_threads_and_start_session(&[thread_a]);
+			Balances::make_free_balance_be(&1, 1_000);
+
+			assert_ok!(ParathreadClaims::claim(Origin::signed(1), thread_a, collator, 1_000));
+
+			run_to_block(2, |_| None);
+			let core = Scheduler::scheduled()[0].core;
+			Scheduler::occupied(&[core]);
+			Scheduler::schedule(vec![(core, FreedReason::Concluded)]);
+
+			assert_eq!(Balances::reserved_balance(1), 0);
+			assert_eq!(Balances::free_balance(1), 1_000 - BaseClaimFee::get());
+			assert!(ParathreadClaims::claim_fee(thread_a).is_none());
+		});
+	}
+}
@@ -24,7 +24,8 @@ use primitives::v1::{
 	Id as ParaId, OccupiedCoreAssumption, SessionIndex, ValidationCode,
 	CommittedCandidateReceipt, ScheduledCore, OccupiedCore, CoreOccupied, CoreIndex,
 	GroupIndex, CandidateEvent, PersistedValidationData, AuthorityDiscoveryId,
-	InboundDownwardMessage, InboundHrmpMessage,
+	InboundDownwardMessage, InboundHrmpMessage, ParaLimits, MessageQueueChain,
+	MessageQueueChainHead,
 };
 use sp_runtime::traits::Zero;
 use frame_support::debug;
@@ -49,7 +50,7 @@ pub fn validator_groups<T: initializer::Trait>() -> (
 /// Implementation for the `availability_cores` function of the runtime API.
 pub fn availability_cores<T: initializer::Trait>() -> Vec<CoreState<T::BlockNumber>> {
 	let cores = <scheduler::Module<T>>::availability_cores();
-	let parachains = <paras::Module<T>>::parachains();
+	let parachain_cores = <scheduler::Module<T>>::parachain_cores();
 	let config = <configuration::Module<T>>::config();
 
 	let rotation_info = <scheduler::Module<T>>::group_rotation_info();
@@ -90,63 +91,48 @@ pub fn availability_cores<T: initializer::Trait>() -> Vec<CoreState<T::BlockNumb
 		}
 	};
 
+	let occupied_core = |core_index: CoreIndex, para_id, availability_period| {
+		let pending_availability = <inclusion::Module<T>>
+			::pending_availability(para_id)
+			.expect("Occupied core always has pending availability; qed");
+
+		let backed_in_number = pending_availability.backed_in_number().clone();
+		OccupiedCore {
+			para_id,
+			next_up_on_available: <scheduler::Module<T>>::next_up_on_available(core_index),
+			occupied_since: backed_in_number,
+			time_out_at: time_out_at(
+				backed_in_number,
+				availability_period,
+			),
+			next_up_on_time_out: <scheduler::Module<T>>::next_up_on_time_out(core_index),
+			availability: pending_availability.availability_votes().clone(),
+			group_responsible: group_responsible_for(
+				backed_in_number,
+				pending_availability.core_occupied(),
+			),
+		}
+	};
+
 	let mut core_states: Vec<_> = cores.into_iter().enumerate().map(|(i, core)| match core {
 		Some(occupied) => {
+			let core_index = CoreIndex(i as u32);
 			CoreState::Occupied(match occupied {
-				CoreOccupied::Parachain => {
-					let para_id = parachains[i];
-					let pending_availability = <inclusion::Module<T>>
-						::pending_availability(para_id)
-						.expect("Occupied core always has pending availability; qed");
-
-					let backed_in_number = pending_availability.backed_in_number().clone();
-					OccupiedCore {
-						para_id,
-						next_up_on_available: <scheduler::Module<T>>::next_up_on_available(
-							CoreIndex(i as u32)
-						),
-						occupied_since: backed_in_number,
-						time_out_at: time_out_at(
-							backed_in_number,
-							config.chain_availability_period,
-						),
-						next_up_on_time_out: <scheduler::Module<T>>::next_up_on_time_out(
-							CoreIndex(i as u32)
-						),
-						availability: pending_availability.availability_votes().clone(),
-						group_responsible: group_responsible_for(
-							backed_in_number,
-							pending_availability.core_occupied(),
-						),
-					}
-				}
-				CoreOccupied::Parathread(p) => {
-					let para_id = p.claim.0;
-					let pending_availability = <inclusion::Module<T>>
-						::pending_availability(para_id)
-						.expect("Occupied core always has pending availability; qed");
-
-					let backed_in_number = pending_availability.backed_in_number().clone();
-					OccupiedCore {
-						para_id,
-						next_up_on_available: <scheduler::Module<T>>::next_up_on_available(
-							CoreIndex(i as u32)
-						),
-						occupied_since: backed_in_number,
-						time_out_at: time_out_at(
-							backed_in_number,
-							config.thread_availability_period,
-						),
-						next_up_on_time_out: <scheduler::Module<T>>::next_up_on_time_out(
-							CoreIndex(i as u32)
-						),
-						availability: pending_availability.availability_votes().clone(),
-						group_responsible: group_responsible_for(
-							backed_in_number,
-							pending_availability.core_occupied(),
-						),
-					}
-				}
+				CoreOccupied::Parachain => occupied_core(
+					core_index,
+					parachain_cores[i].paras[0].0,
+					config.chain_availability_period,
+				),
+				CoreOccupied::SharedParachain(para_id) => occupied_core(
+					core_index,
+					para_id,
+					config.chain_availability_period,
+				),
+				CoreOccupied::Parathread(p) => occupied_core(
+					core_index,
+					p.claim.0,
+					config.thread_availability_period,
+				),
 			})
 		}
 		None => CoreState::Free,
@@ -322,3 +308,45 @@ pub fn inbound_hrmp_channels_contents<T: hrmp::Trait>(
 ) -> BTreeMap<ParaId, Vec<InboundHrmpMessage<T::BlockNumber>>> {
 	<hrmp::Module<T>>::inbound_hrmp_channels_contents(recipient)
 }
+
+/// Implementation for the `para_limits` function of the runtime API.
+pub fn para_limits<T: initializer::Trait>(para_id: ParaId) -> Option<ParaLimits> {
+	if !<paras::Module<T>>::is_valid_para(para_id) {
+		return None;
+	}
+
+	let config = <configuration::Module<T>>::effective_config(para_id);
+	let (hrmp_max_inbound_channels, hrmp_max_outbound_channels) =
+		if <paras::Module<T>>::is_parathread(para_id) {
+			(config.hrmp_max_parathread_inbound_channels, config.hrmp_max_parathread_outbound_channels)
+		} else {
+			(config.hrmp_max_parachain_inbound_channels, config.hrmp_max_parachain_outbound_channels)
+		};
+
+	Some(ParaLimits {
+		max_code_size: config.max_code_size,
+		max_head_data_size: config.max_head_data_size,
+		max_pov_size: config.max_pov_size,
+		max_upward_queue_count: config.max_upward_queue_count,
+		max_upward_queue_size: config.max_upward_queue_size,
+		max_upward_message_size: config.max_upward_message_size,
+		max_upward_message_num_per_candidate: config.max_upward_message_num_per_candidate,
+		hrmp_max_inbound_channels,
+		hrmp_max_outbound_channels,
+		hrmp_max_message_num_per_candidate: config.hrmp_max_message_num_per_candidate,
+	})
+}
+
+/// Implementation for the `mqc_head` function of the runtime API.
+pub fn mqc_head<T: dmp::Trait + hrmp::Trait>(
+	queue: MessageQueueChain,
+) -> Option<MessageQueueChainHead> {
+	match queue {
+		MessageQueueChain::Downward(para) => Some(MessageQueueChainHead {
+			head: <dmp::Module<T>>::dmq_mqc_head(para),
+			storage_key: <dmp::Module<T>>::dmq_mqc_head_storage_key(para),
+		}),
+		MessageQueueChain::Hrmp(channel_id) => <hrmp::Module<T>>::hrmp_channel_mqc_head(&channel_id)
+			.map(|(head, storage_key)| MessageQueueChainHead { head, storage_key }),
+	}
+}
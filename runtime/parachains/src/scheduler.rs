@@ -30,8 +30,9 @@
 //! - High or close to optimal throughput of parachains and parathreads. Work among validator groups should be balanced.
 //!
 //! The Scheduler manages resource allocation using the concept of "Availability Cores".
-//! There will be one availability core for each parachain, and a fixed number of cores
-//! used for multiplexing parathreads. Validators will be partitioned into groups, with the same
+//! There will be one availability core for each parachain, except for parachains that were
+//! configured to share a core with others, and a fixed number of cores used for multiplexing
+//! parathreads. Validators will be partitioned into groups, with the same
 //! number of groups as availability cores. Validator groups will be assigned to different availability cores
 //! over time.
 
@@ -42,9 +43,11 @@ use primitives::v1::{
 	GroupIndex, ParathreadClaim, ParathreadEntry, GroupRotationInfo, ScheduledCore,
 };
 use frame_support::{
-	decl_storage, decl_module, decl_error,
-	weights::Weight,
+	decl_storage, decl_module, decl_error, ensure,
+	dispatch::DispatchResult,
+	weights::{DispatchClass, Weight},
 };
+use frame_system::ensure_root;
 use parity_scale_codec::{Encode, Decode};
 use sp_runtime::traits::{Saturating, Zero};
 
@@ -97,6 +100,50 @@ impl ParathreadClaimQueue {
 	}
 }
 
+/// An availability core shared by several parachains.
+///
+/// The parachains take turns on the core in a weighted round-robin fashion: each of them is
+/// assigned to the core as many consecutive times as its weight before the next one takes over.
+/// E.g. `[(a, 2), (b, 1)]` gives `a` two out of every three assignments and `b` the remaining one.
+#[derive(Clone, Encode, Decode, Default)]
+#[cfg_attr(feature = "std", derive(PartialEq, Debug))]
+pub struct SharedCore {
+	/// The parachains sharing the core along with their weights.
+	pub paras: Vec<(ParaId, u32)>,
+}
+
+impl SharedCore {
+	fn dedicated(para_id: ParaId) -> Self {
+		SharedCore { paras: vec![(para_id, 1)] }
+	}
+
+	/// Whether the core is used by a single parachain only.
+	fn is_dedicated(&self) -> bool {
+		self.paras.len() == 1
+	}
+
+	/// The sum of the weights of the parachains, i.e. the length of a full rotation.
+	fn total_weight(&self) -> u32 {
+		self.paras.iter().fold(0u32, |acc, (_, w)| acc.saturating_add(*w))
+	}
+
+	/// The parachain whose turn it is at the given position of the rotation, if any.
+	fn para_at(&self, position: u32) -> Option<ParaId> {
+		let total_weight = self.total_weight();
+		if total_weight == 0 { return None }
+
+		let mut offset = position % total_weight;
+		for (para_id, weight) in &self.paras {
+			if offset < *weight {
+				return Some(*para_id);
+			}
+			offset -= weight;
+		}
+
+		None
+	}
+}
+
 /// Reasons a core might be freed
 pub enum FreedReason {
 	/// The core's work concluded and the parablock assigned to it is considered available.
@@ -112,6 +159,8 @@ pub enum FreedReason {
 pub enum AssignmentKind {
 	/// A parachain.
 	Parachain,
+	/// A parachain on a core shared with other parachains.
+	SharedParachain,
 	/// A parathread.
 	Parathread(CollatorId, u32),
 }
@@ -134,7 +183,7 @@ impl CoreAssignment {
 	/// Get the ID of a collator who is required to collate this block.
 	pub fn required_collator(&self) -> Option<&CollatorId> {
 		match self.kind {
-			AssignmentKind::Parachain => None,
+			AssignmentKind::Parachain | AssignmentKind::SharedParachain => None,
 			AssignmentKind::Parathread(ref id, _) => Some(id),
 		}
 	}
@@ -143,6 +192,7 @@ impl CoreAssignment {
 	pub fn to_core_occupied(&self) -> CoreOccupied {
 		match self.kind {
 			AssignmentKind::Parachain => CoreOccupied::Parachain,
+			AssignmentKind::SharedParachain => CoreOccupied::SharedParachain(self.para_id),
 			AssignmentKind::Parathread(ref collator, retries) => CoreOccupied::Parathread(
 				ParathreadEntry {
 					claim: ParathreadClaim(self.para_id, collator.clone()),
@@ -153,7 +203,26 @@ impl CoreAssignment {
 	}
 }
 
-pub trait Trait: frame_system::Trait + configuration::Trait + paras::Trait { }
+/// Something that should be notified when a parathread claim leaves the scheduler.
+pub trait ParathreadClaimHandler {
+	/// A candidate backed for the claim was included.
+	fn claim_concluded(claim: &ParathreadClaim);
+
+	/// The claim was dropped without a candidate for it ever being included, e.g. because it
+	/// exceeded `parathread_retries` or the parathread is no longer live.
+	fn claim_dropped(claim: &ParathreadClaim);
+}
+
+impl ParathreadClaimHandler for () {
+	fn claim_concluded(_: &ParathreadClaim) { }
+
+	fn claim_dropped(_: &ParathreadClaim) { }
+}
+
+pub trait Trait: frame_system::Trait + configuration::Trait + paras::Trait {
+	/// The handler notified about claims that leave the parathread queue for good.
+	type ClaimHandler: ParathreadClaimHandler;
+}
 
 decl_storage! {
 	trait Store for Module<T: Trait> as ParaScheduler {
@@ -170,8 +239,8 @@ decl_storage! {
 		ParathreadQueue: ParathreadClaimQueue;
 		/// One entry for each availability core. Entries are `None` if the core is not currently occupied. Can be
 		/// temporarily `Some` if scheduled but not occupied.
-		/// The i'th parachain belongs to the i'th core, with the remaining cores all being
-		/// parathread-multiplexers.
+		/// The first cores belong to parachains in the order given by `parachain_cores`, with the remaining
+		/// cores all being parathread-multiplexers.
 		///
 		/// Bounded by the number of cores: one for each parachain and parathread multiplexer.
 		AvailabilityCores get(fn availability_cores): Vec<Option<CoreOccupied>>;
@@ -186,17 +255,67 @@ decl_storage! {
 		///
 		/// Bounded by the number of cores: one for each parachain and parathread multiplexer.
 		Scheduled get(fn scheduled): Vec<CoreAssignment>; // sorted ascending by CoreIndex.
+		/// Cores shared by several parachains. Parachains not part of any shared core get a dedicated core.
+		///
+		/// Bounded by the number of parachains.
+		SharedCores get(fn shared_cores): Vec<SharedCore>;
+		/// An update of `SharedCores` to be applied at the next session change.
+		PendingSharedCores: Option<Vec<SharedCore>>;
+		/// The position of each parachain core in its rotation, by core index. Advanced every time
+		/// the core is assigned, and reset at each session change.
+		///
+		/// Bounded by the number of parachain cores.
+		SharedCoreCursors: Vec<u32>;
 	}
 }
 
 decl_error! {
-	pub enum Error for Module<T: Trait> { }
+	pub enum Error for Module<T: Trait> {
+		/// The claimed para is not a live parathread.
+		NotParathread,
+		/// The parathread queue is full or there are no parathread cores.
+		ParathreadQueueFull,
+		/// There already is a claim for the parathread in the queue or occupying a core.
+		ParathreadAlreadyClaimed,
+		/// A shared core has no parachains assigned.
+		EmptySharedCore,
+		/// A parachain was given a weight of zero on a shared core.
+		ZeroSharedCoreWeight,
+		/// A parachain appears on more than one shared core or more than once on the same core.
+		DuplicateSharedCorePara,
+	}
 }
 
 decl_module! {
 	/// The scheduler module.
 	pub struct Module<T: Trait> for enum Call where origin: <T as frame_system::Trait>::Origin {
 		type Error = Error<T>;
+
+		/// Set the parachains which share availability cores, replacing the current configuration.
+		///
+		/// The change is applied at the next session change. Parachains that are not mentioned get
+		/// a dedicated core.
+		#[weight = (1_000, DispatchClass::Operational)]
+		pub fn set_shared_cores(origin, shared_cores: Vec<SharedCore>) -> DispatchResult {
+			ensure_root(origin)?;
+
+			let mut seen = Vec::new();
+			for shared_core in &shared_cores {
+				ensure!(!shared_core.paras.is_empty(), Error::<T>::EmptySharedCore);
+
+				for (para_id, weight) in &shared_core.paras {
+					ensure!(*weight > 0, Error::<T>::ZeroSharedCoreWeight);
+
+					let i = seen.binary_search(para_id)
+						.err()
+						.ok_or(Error::<T>::DuplicateSharedCorePara)?;
+					seen.insert(i, *para_id);
+				}
+			}
+
+			PendingSharedCores::put(shared_cores);
+			Ok(())
+		}
 	}
 }
 
@@ -222,11 +341,18 @@ impl<T: Trait> Module<T> {
 
 					if entry.retries <= config.parathread_retries {
 						queue.enqueue_entry(entry, config.parathread_cores);
+					} else {
+						ParathreadClaimIndex::mutate(|index| {
+							if let Ok(i) = index.binary_search(&entry.claim.0) {
+								index.remove(i);
+							}
+						});
+
+						T::ClaimHandler::claim_dropped(&entry.claim);
 					}
 				}
 			}
 		})
-
 	}
 
 	/// Called by the initializer to note that a new session has started.
@@ -239,9 +365,14 @@ impl<T: Trait> Module<T> {
 		} = notification;
 		let config = new_config;
 
+		if let Some(pending) = PendingSharedCores::take() {
+			SharedCores::put(pending);
+		}
+		SharedCoreCursors::kill();
+
 		let mut thread_queue = ParathreadQueue::get();
-		let n_parachains = <paras::Module<T>>::parachains().len() as u32;
-		let n_cores = n_parachains + config.parathread_cores;
+		let n_parachain_cores = Self::parachain_cores().len() as u32;
+		let n_cores = n_parachain_cores + config.parathread_cores;
 
 		<SessionStartBlock<T>>::set(<frame_system::Module<T>>::block_number());
 		AvailabilityCores::mutate(|cores| {
@@ -293,6 +424,10 @@ impl<T: Trait> Module<T> {
 		ParathreadClaimIndex::mutate(|claim_index| {
 			// wipe all parathread metadata if no parathread cores are configured.
 			if config.parathread_cores == 0 {
+				for queued in thread_queue.queue.drain(..) {
+					T::ClaimHandler::claim_dropped(&queued.claim.claim);
+				}
+
 				thread_queue = ParathreadClaimQueue {
 					queue: Vec::new(),
 					next_core_offset: 0,
@@ -313,6 +448,8 @@ impl<T: Trait> Module<T> {
 					if let Ok(i) = claim_index.binary_search(&claim_para) {
 						claim_index.remove(i);
 					}
+
+					T::ClaimHandler::claim_dropped(&queued.claim.claim);
 				}
 
 				will_keep
@@ -337,33 +474,42 @@ impl<T: Trait> Module<T> {
 	/// Fails if the claim does not correspond to any live parathread.
 	#[allow(unused)]
 	pub fn add_parathread_claim(claim: ParathreadClaim) {
-		if !<paras::Module<T>>::is_parathread(claim.0) { return }
+		let _ = Self::try_add_parathread_claim(claim);
+	}
+
+	/// Same as `add_parathread_claim`, but reports the reason why the claim was not added.
+	pub fn try_add_parathread_claim(claim: ParathreadClaim) -> Result<(), Error<T>> {
+		ensure!(<paras::Module<T>>::is_parathread(claim.0), Error::<T>::NotParathread);
 
 		let config = <configuration::Module<T>>::config();
 		let queue_max_size = config.parathread_cores * config.scheduling_lookahead;
 
-		ParathreadQueue::mutate(|queue| {
-			if queue.queue.len() >= queue_max_size as usize { return }
+		ParathreadQueue::try_mutate(|queue| {
+			ensure!(queue.queue.len() < queue_max_size as usize, Error::<T>::ParathreadQueueFull);
 
 			let para_id = claim.0;
 
-			let competes_with_another = ParathreadClaimIndex::mutate(|index| {
+			ParathreadClaimIndex::try_mutate(|index| {
 				match index.binary_search(&para_id) {
-					Ok(_) => true,
+					Ok(_) => Err(Error::<T>::ParathreadAlreadyClaimed),
 					Err(i) => {
 						index.insert(i, para_id);
-						false
+						Ok(())
 					}
 				}
-			});
-
-			if competes_with_another { return }
+			})?;
 
 			let entry = ParathreadEntry { claim, retries: 0 };
 			queue.enqueue_entry(entry, config.parathread_cores);
+			Ok(())
 		})
 	}
 
+	/// The number of claims currently waiting in the parathread queue.
+	pub fn parathread_queue_len() -> u32 {
+		ParathreadQueue::get().queue.len() as u32
+	}
+
 	/// Schedule all unassigned cores, where possible. Provide a list of cores that should be considered
 	/// newly-freed along with the reason for them being freed. The list is assumed to be sorted in
 	/// ascending order by core index.
@@ -375,7 +521,7 @@ impl<T: Trait> Module<T> {
 			if (freed_index.0 as usize) < cores.len() {
 				match cores[freed_index.0 as usize].take() {
 					None => continue,
-					Some(CoreOccupied::Parachain) => {},
+					Some(CoreOccupied::Parachain) | Some(CoreOccupied::SharedParachain(_)) => {},
 					Some(CoreOccupied::Parathread(entry)) => {
 						match freed_reason {
 							FreedReason::Concluded => {
@@ -385,7 +531,9 @@ impl<T: Trait> Module<T> {
 									if let Ok(i) = index.binary_search(&entry.claim.0) {
 										index.remove(i);
 									}
-								})
+								});
+
+								T::ClaimHandler::claim_concluded(&entry.claim);
 							}
 							FreedReason::TimedOut => {
 								// If a parathread candidate times out, it's not the collator's fault,
@@ -400,10 +548,12 @@ impl<T: Trait> Module<T> {
 			}
 		}
 
-		let parachains = <paras::Module<T>>::parachains();
+		let parachain_cores = Self::parachain_cores();
 		let mut scheduled = Scheduled::get();
 		let mut parathread_queue = ParathreadQueue::get();
 		let now = <frame_system::Module<T>>::block_number();
+		let mut cursors = SharedCoreCursors::get();
+		cursors.resize(parachain_cores.len(), 0);
 
 		if ValidatorGroups::get().is_empty() { return }
 
@@ -448,11 +598,22 @@ impl<T: Trait> Module<T> {
 
 				let core = CoreIndex(core_index as u32);
 
-				let core_assignment = if core_index < parachains.len() {
+				let core_assignment = if core_index < parachain_cores.len() {
 					// parachain core.
-					Some(CoreAssignment {
-						kind: AssignmentKind::Parachain,
-						para_id: parachains[core_index],
+					let parachain_core = &parachain_cores[core_index];
+					let kind = if parachain_core.is_dedicated() {
+						AssignmentKind::Parachain
+					} else {
+						AssignmentKind::SharedParachain
+					};
+
+					let cursor = &mut cursors[core_index];
+					let para_id = parachain_core.para_at(*cursor);
+					*cursor = cursor.saturating_add(1) % parachain_core.total_weight().max(1);
+
+					para_id.map(|para_id| CoreAssignment {
+						kind,
+						para_id,
 						core: core.clone(),
 						group_idx: Self::group_assigned_to_core(core, now)
 							.expect("core is not out of bounds and we are guaranteed \
@@ -460,7 +621,7 @@ impl<T: Trait> Module<T> {
 					})
 				} else {
 					// parathread core offset, rel. to beginning.
-					let core_offset = (core_index - parachains.len()) as u32;
+					let core_offset = (core_index - parachain_cores.len()) as u32;
 
 					parathread_queue.take_next_on_core(core_offset).map(|entry| CoreAssignment {
 						kind: AssignmentKind::Parathread(entry.claim.1, entry.retries),
@@ -496,6 +657,7 @@ impl<T: Trait> Module<T> {
 		Scheduled::set(scheduled);
 		ParathreadQueue::set(parathread_queue);
 		AvailabilityCores::set(cores);
+		SharedCoreCursors::set(cursors);
 	}
 
 	/// Note that the given cores have become occupied. Behavior undefined if any of the given cores were not scheduled
@@ -539,9 +701,12 @@ impl<T: Trait> Module<T> {
 		match cores.get(core_index.0 as usize).and_then(|c| c.as_ref()) {
 			None => None,
 			Some(CoreOccupied::Parachain) => {
-				let parachains = <paras::Module<T>>::parachains();
-				Some(parachains[core_index.0 as usize])
+				Self::parachain_cores()
+					.get(core_index.0 as usize)
+					.and_then(|c| c.paras.first())
+					.map(|(para_id, _)| *para_id)
 			}
+			Some(CoreOccupied::SharedParachain(para_id)) => Some(*para_id),
 			Some(CoreOccupied::Parathread(ref entry)) => Some(entry.claim.0),
 		}
 	}
@@ -623,7 +788,7 @@ impl<T: Trait> Module<T> {
 				match availability_cores.get(core_index.0 as usize) {
 					None => true, // out-of-bounds, doesn't really matter what is returned.
 					Some(None) => true, // core not occupied, still doesn't really matter.
-					Some(Some(CoreOccupied::Parachain)) => {
+					Some(Some(CoreOccupied::Parachain)) | Some(Some(CoreOccupied::SharedParachain(_))) => {
 						if blocks_since_last_rotation >= config.chain_availability_period {
 							false // no pruning except recently after rotation.
 						} else {
@@ -659,19 +824,16 @@ impl<T: Trait> Module<T> {
 	/// Return the next thing that will be scheduled on this core assuming it is currently
 	/// occupied and the candidate occupying it became available.
 	///
-	/// For parachains, this is always the ID of the parachain and no specified collator.
-	/// For parathreads, this is based on the next item in the ParathreadQueue assigned to that
-	/// core, and is None if there isn't one.
+	/// For parachains, this is always the ID of the parachain whose turn on the core is next and no
+	/// specified collator. For parathreads, this is based on the next item in the ParathreadQueue
+	/// assigned to that core, and is None if there isn't one.
 	pub(crate) fn next_up_on_available(core: CoreIndex) -> Option<ScheduledCore> {
-		let parachains = <paras::Module<T>>::parachains();
-		if (core.0 as usize) < parachains.len() {
-			Some(ScheduledCore {
-				para_id: parachains[core.0 as usize],
-				collator: None,
-			})
+		let parachain_cores = Self::parachain_cores();
+		if (core.0 as usize) < parachain_cores.len() {
+			Self::next_up_on_parachain_core(core, &parachain_cores[core.0 as usize])
 		} else {
 			let queue = ParathreadQueue::get();
-			let core_offset = (core.0 as usize - parachains.len()) as u32;
+			let core_offset = (core.0 as usize - parachain_cores.len()) as u32;
 			queue.get_next_on_core(core_offset).map(|entry| ScheduledCore {
 				para_id: entry.claim.0,
 				collator: Some(entry.claim.1.clone()),
@@ -682,22 +844,19 @@ impl<T: Trait> Module<T> {
 	/// Return the next thing that will be scheduled on this core assuming it is currently
 	/// occupied and the candidate occupying it became available.
 	///
-	/// For parachains, this is always the ID of the parachain and no specified collator.
-	/// For parathreads, this is based on the next item in the ParathreadQueue assigned to that
-	/// core, or if there isn't one, the claim that is currently occupying the core, as long
-	/// as the claim's retries would not exceed the limit. Otherwise None.
+	/// For parachains, this is always the ID of the parachain whose turn on the core is next and no
+	/// specified collator. For parathreads, this is based on the next item in the ParathreadQueue
+	/// assigned to that core, or if there isn't one, the claim that is currently occupying the core,
+	/// as long as the claim's retries would not exceed the limit. Otherwise None.
 	pub(crate) fn next_up_on_time_out(core: CoreIndex) -> Option<ScheduledCore> {
-		let parachains = <paras::Module<T>>::parachains();
-		if (core.0 as usize) < parachains.len() {
-			Some(ScheduledCore {
-				para_id: parachains[core.0 as usize],
-				collator: None,
-			})
+		let parachain_cores = Self::parachain_cores();
+		if (core.0 as usize) < parachain_cores.len() {
+			Self::next_up_on_parachain_core(core, &parachain_cores[core.0 as usize])
 		} else {
 			let queue = ParathreadQueue::get();
 
 			// This is the next scheduled para on this core.
-			let core_offset = (core.0 as usize - parachains.len()) as u32;
+			let core_offset = (core.0 as usize - parachain_cores.len()) as u32;
 			queue.get_next_on_core(core_offset)
 				.map(|entry| ScheduledCore {
 					para_id: entry.claim.0,
@@ -715,12 +874,51 @@ impl<T: Trait> Module<T> {
 									collator: Some(entry.claim.1.clone()),
 								})
 							}
-							CoreOccupied::Parachain => None, // defensive; not possible.
+							// defensive; not possible.
+							CoreOccupied::Parachain | CoreOccupied::SharedParachain(_) => None,
 						}
 					})
 				})
 		}
 	}
+
+	/// The parachains assigned to each of the parachain cores, ordered by core index.
+	///
+	/// Every parachain which is not part of a shared core gets a dedicated core, in the order of
+	/// `Paras::parachains`. These are followed by one core for each of the `SharedCores` with at
+	/// least one live parachain.
+	pub(crate) fn parachain_cores() -> Vec<SharedCore> {
+		let parachains = <paras::Module<T>>::parachains();
+		let shared_cores = SharedCores::get();
+
+		let is_shared = |para_id: &ParaId| shared_cores.iter()
+			.any(|shared_core| shared_core.paras.iter().any(|(p, _)| p == para_id));
+
+		let mut cores: Vec<_> = parachains.iter()
+			.filter(|para_id| !is_shared(para_id))
+			.map(|para_id| SharedCore::dedicated(*para_id))
+			.collect();
+
+		cores.extend(shared_cores.into_iter().filter_map(|mut shared_core| {
+			shared_core.paras.retain(|(para_id, _)| parachains.binary_search(para_id).is_ok());
+			if shared_core.paras.is_empty() {
+				None
+			} else {
+				Some(shared_core)
+			}
+		}));
+
+		cores
+	}
+
+	/// The parachain that will be assigned to the given parachain core next.
+	fn next_up_on_parachain_core(core: CoreIndex, parachain_core: &SharedCore) -> Option<ScheduledCore> {
+		let cursor = SharedCoreCursors::get().get(core.0 as usize).copied().unwrap_or(0);
+		parachain_core.para_at(cursor).map(|para_id| ScheduledCore {
+			para_id,
+			collator: None,
+		})
+	}
 }
 
 #[cfg(test)]
@@ -728,10 +926,13 @@ mod tests {
 	use super::*;
 
 	use primitives::v1::{BlockNumber, ValidatorId, CollatorId};
-	use frame_support::traits::{OnFinalize, OnInitialize};
+	use frame_support::{assert_ok, assert_noop, traits::{OnFinalize, OnInitialize}};
 	use keyring::Sr25519Keyring;
 
-	use crate::mock::{new_test_ext, Configuration, Paras, System, Scheduler, GenesisConfig as MockGenesisConfig};
+	use crate::mock::{
+		new_test_ext, Configuration, Paras, System, Scheduler, Origin, Test,
+		GenesisConfig as MockGenesisConfig,
+	};
 	use crate::initializer::SessionChangeNotification;
 	use crate::configuration::HostConfiguration;
 	use crate::paras::ParaGenesisArgs;
@@ -2060,4 +2261,211 @@ mod tests {
 			}
 		});
 	}
+
+	#[test]
+	fn set_shared_cores_rejects_invalid_configuration() {
+		let chain_a = ParaId::from(1);
+		let chain_b = ParaId::from(2);
+
+		new_test_ext(Default::default()).execute_with(|| {
+			assert!(Scheduler::set_shared_cores(Origin::signed(1), Vec::new()).is_err());
+
+			assert_noop!(
+				Scheduler::set_shared_cores(Origin::root(), vec![SharedCore::default()]),
+				Error::<Test>::EmptySharedCore,
+			);
+			assert_noop!(
+				Scheduler::set_shared_cores(
+					Origin::root(),
+					vec![SharedCore { paras: vec![(chain_a, 1), (chain_b, 0)] }],
+				),
+				Error::<Test>::ZeroSharedCoreWeight,
+			);
+			assert_noop!(
+				Scheduler::set_shared_cores(
+					Origin::root(),
+					vec![
+						SharedCore { paras: vec![(chain_a, 1), (chain_b, 1)] },
+						SharedCore { paras: vec![(chain_b, 1)] },
+					],
+				),
+				Error::<Test>::DuplicateSharedCorePara,
+			);
+
+			assert_ok!(Scheduler::set_shared_cores(
+				Origin::root(),
+				vec![SharedCore { paras: vec![(chain_a, 1), (chain_b, 1)] }],
+			));
+
+			// only applied at the next session.
+			assert!(Scheduler::shared_cores().is_empty());
+		});
+	}
+
+	#[test]
+	fn shared_core_is_scheduled_round_robin() {
+		let mut config = default_config();
+		config.parathread_cores = 0;
+
+		let genesis_config = MockGenesisConfig {
+			configuration: crate::configuration::GenesisConfig {
+				config: config.clone(),
+				..Default::default()
+			},
+			..Default::default()
+		};
+
+		let chain_a = ParaId::from(1);
+		let chain_b = ParaId::from(2);
+		let chain_c = ParaId::from(3);
+
+		let schedule_blank_para = |id, is_chain| Paras::schedule_para_initialize(id, ParaGenesisArgs {
+			genesis_head: Vec::new().into(),
+			validation_code: Vec::new().into(),
+			parachain: is_chain,
+		});
+
+		new_test_ext(genesis_config).execute_with(|| {
+			schedule_blank_para(chain_a, true);
+			schedule_blank_para(chain_b, true);
+			schedule_blank_para(chain_c, true);
+
+			// a gets 2 out of 3 blocks, b the remaining one.
+			assert_ok!(Scheduler::set_shared_cores(
+				Origin::root(),
+				vec![SharedCore { paras: vec![(chain_a, 2), (chain_b, 1)] }],
+			));
+
+			run_to_block(1, |number| match number {
+				1 => Some(SessionChangeNotification {
+					new_config: config.clone(),
+					validators: vec![
+						ValidatorId::from(Sr25519Keyring::Alice.public()),
+						ValidatorId::from(Sr25519Keyring::Eve.public()),
+					],
+					..Default::default()
+				}),
+				_ => None,
+			});
+
+			// c has a dedicated core, a and b share the other one.
+			assert_eq!(Scheduler::availability_cores().len(), 2);
+
+			let scheduled_on_shared_core = || {
+				let scheduled = Scheduler::scheduled();
+				assert_eq!(scheduled.len(), 2);
+
+				assert_eq!(scheduled[0].para_id, chain_c);
+				assert_eq!(scheduled[0].kind, AssignmentKind::Parachain);
+
+				assert_eq!(scheduled[1].core, CoreIndex(1));
+				assert_eq!(scheduled[1].kind, AssignmentKind::SharedParachain);
+				scheduled[1].para_id
+			};
+
+			assert_eq!(scheduled_on_shared_core(), chain_a);
+
+			// while the core is free, it is assigned anew every block.
+			let mut schedule = Vec::new();
+			for block in 2..=7 {
+				run_to_block(block, |_| None);
+				schedule.push(scheduled_on_shared_core());
+			}
+			assert_eq!(schedule, vec![chain_a, chain_b, chain_a, chain_a, chain_b, chain_a]);
+
+			// the next block's assignment is announced.
+			assert_eq!(
+				Scheduler::next_up_on_available(CoreIndex(1)).unwrap(),
+				ScheduledCore {
+					para_id: chain_a,
+					collator: None,
+				},
+			);
+
+			// the occupied core remembers which of the paras occupies it.
+			run_to_block(8, |_| None);
+			assert_eq!(scheduled_on_shared_core(), chain_a);
+			Scheduler::occupied(&[CoreIndex(1)]);
+
+			assert_eq!(
+				Scheduler::availability_cores()[1],
+				Some(CoreOccupied::SharedParachain(chain_a)),
+			);
+			assert_eq!(Scheduler::core_para(CoreIndex(1)), Some(chain_a));
+			assert_eq!(Scheduler::core_para(CoreIndex(0)), None);
+
+			assert_eq!(
+				Scheduler::next_up_on_available(CoreIndex(1)).unwrap(),
+				ScheduledCore {
+					para_id: chain_b,
+					collator: None,
+				},
+			);
+		});
+	}
+
+	#[test]
+	fn shared_core_rotation_is_fair_whatever_the_occupation_time() {
+		let mut config = default_config();
+		config.parathread_cores = 0;
+
+		let genesis_config = MockGenesisConfig {
+			configuration: crate::configuration::GenesisConfig {
+				config: config.clone(),
+				..Default::default()
+			},
+			..Default::default()
+		};
+
+		let chain_a = ParaId::from(1);
+		let chain_b = ParaId::from(2);
+
+		new_test_ext(genesis_config).execute_with(|| {
+			for chain in &[chain_a, chain_b] {
+				Paras::schedule_para_initialize(*chain, ParaGenesisArgs {
+					genesis_head: Vec::new().into(),
+					validation_code: Vec::new().into(),
+					parachain: true,
+				});
+			}
+
+			assert_ok!(Scheduler::set_shared_cores(
+				Origin::root(),
+				vec![SharedCore { paras: vec![(chain_a, 2), (chain_b, 1)] }],
+			));
+
+			run_to_block(1, |number| match number {
+				1 => Some(SessionChangeNotification {
+					new_config: config.clone(),
+					validators: vec![ValidatorId::from(Sr25519Keyring::Alice.public())],
+					..Default::default()
+				}),
+				_ => None,
+			});
+
+			// every candidate occupies the core for as many blocks as a full rotation, so any
+			// schedule driven by the block number would hand the core to the same para every time.
+			let mut schedule = Vec::new();
+			for _ in 0..9 {
+				let scheduled = Scheduler::scheduled();
+				assert_eq!(scheduled.len(), 1);
+				schedule.push(scheduled[0].para_id);
+
+				Scheduler::occupied(&[CoreIndex(0)]);
+				assert_eq!(
+					Scheduler::next_up_on_available(CoreIndex(0)).map(|next| next.para_id),
+					SharedCore { paras: vec![(chain_a, 2), (chain_b, 1)] }.para_at(schedule.len() as u32),
+				);
+
+				run_to_block(System::block_number() + 3, |_| None);
+				Scheduler::schedule(vec![(CoreIndex(0), FreedReason::Concluded)]);
+			}
+
+			assert_eq!(schedule, vec![
+				chain_a, chain_a, chain_b,
+				chain_a, chain_a, chain_b,
+				chain_a, chain_a, chain_b,
+			]);
+		});
+	}
 }
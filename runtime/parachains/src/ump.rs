@@ -20,7 +20,11 @@ use crate::{
 };
 use sp_std::{fmt, prelude::*};
 use sp_std::collections::{btree_map::BTreeMap, vec_deque::VecDeque};
-use frame_support::{decl_module, decl_storage, StorageMap, StorageValue, weights::Weight, traits::Get};
+use frame_support::{
+	decl_module, decl_storage, decl_event, decl_error, StorageMap, StorageValue,
+	dispatch::DispatchResultWithPostInfo, weights::Weight, traits::Get,
+};
+use frame_system::ensure_root;
 use primitives::v1::{Id as ParaId, UpwardMessage};
 
 /// All upward messages coming from parachains will be funneled into an implementation of this trait.
@@ -32,6 +36,12 @@ use primitives::v1::{Id as ParaId, UpwardMessage};
 /// returns the amount of weight consumed in the process of handling. Ignoring a message is a valid
 /// strategy.
 ///
+/// Each message is processed with a weight limit of `config.ump_max_individual_weight`. If handling
+/// the message would require more weight than that, the implementation must not handle it at all but
+/// report the weight it requires instead. UMP then removes the message from the queue of its origin
+/// and puts it into the overweight queue, where it stays until it is executed explicitly with
+/// `service_overweight`. It is never retried automatically.
+///
 /// There are no guarantees on how much time it takes for the message sent by a candidate to end up
 /// in the sink after the candidate was enacted. That typically depends on the UMP traffic, the sizes
 /// of upward messages and the configuration of UMP.
@@ -39,19 +49,23 @@ use primitives::v1::{Id as ParaId, UpwardMessage};
 /// It is possible that by the time the message is sank the origin parachain was offboarded. It is
 /// up to the implementer to check that if it cares.
 pub trait UmpSink {
-	/// Process an incoming upward message and return the amount of weight it consumed.
+	/// Process an incoming upward message and return the amount of weight it consumed, or the
+	/// amount of weight it requires if that exceeds `max_weight`.
 	///
 	/// See the trait docs for more details.
-	fn process_upward_message(origin: ParaId, msg: Vec<u8>) -> Weight;
+	fn process_upward_message(origin: ParaId, msg: &[u8], max_weight: Weight) -> Result<Weight, Weight>;
 }
 
 /// An implementation of a sink that just swallows the message without consuming any weight.
 impl UmpSink for () {
-	fn process_upward_message(_: ParaId, _: Vec<u8>) -> Weight {
-		0
+	fn process_upward_message(_: ParaId, _: &[u8], _: Weight) -> Result<Weight, Weight> {
+		Ok(0)
 	}
 }
 
+/// The index of an upward message in the overweight queue.
+pub type OverweightIndex = u64;
+
 /// An error returned by [`check_upward_messages`] that indicates a violation of one of acceptance
 /// criteria rules.
 pub enum AcceptanceCheckErr {
@@ -106,6 +120,9 @@ impl fmt::Debug for AcceptanceCheckErr {
 }
 
 pub trait Trait: frame_system::Trait + configuration::Trait {
+	/// The overarching event type.
+	type Event: From<Event> + Into<<Self as frame_system::Trait>::Event>;
+
 	/// A place where all received upward messages are funneled.
 	type UmpSink: UmpSink;
 }
@@ -147,12 +164,65 @@ decl_storage! {
 		/// Invariant:
 		/// - If `Some(para)`, then `para` must be present in `NeedsDispatch`.
 		NextDispatchRoundStartWith: Option<ParaId>;
+		/// The upward messages which required more weight than `config.ump_max_individual_weight`
+		/// along with their origin, keyed by their index in the overweight queue.
+		///
+		/// These messages are only executed explicitly via `service_overweight`.
+		Overweight get(fn overweight): map hasher(twox_64_concat) OverweightIndex => Option<(ParaId, UpwardMessage)>;
+		/// The number of messages that were ever put into the overweight queue. This is also the
+		/// index of the next overweight message.
+		OverweightCount: OverweightIndex;
+	}
+}
+
+decl_event! {
+	pub enum Event {
+		/// An upward message required more weight than is allowed for an individual message and
+		/// was put into the overweight queue. [origin, overweight_index, required_weight]
+		OverweightEnqueued(ParaId, OverweightIndex, Weight),
+		/// An upward message from the overweight queue was executed. [overweight_index, used_weight]
+		OverweightServiced(OverweightIndex, Weight),
+	}
+}
+
+decl_error! {
+	pub enum Error for Module<T: Trait> {
+		/// There is no overweight message with the given index.
+		UnknownMessageIndex,
+		/// The overweight message requires more weight than the given limit.
+		WeightOverLimit,
 	}
 }
 
 decl_module! {
 	/// The UMP module.
 	pub struct Module<T: Trait> for enum Call where origin: <T as frame_system::Trait>::Origin {
+		type Error = Error<T>;
+
+		fn deposit_event() = default;
+
+		/// Execute an upward message from the overweight queue.
+		///
+		/// The message is executed with the given weight limit. If it requires more than that, the
+		/// call fails and the message stays in the overweight queue.
+		#[weight = weight_limit.saturating_add(T::DbWeight::get().reads_writes(1, 1))]
+		pub fn service_overweight(
+			origin,
+			index: OverweightIndex,
+			weight_limit: Weight,
+		) -> DispatchResultWithPostInfo {
+			ensure_root(origin)?;
+
+			let (sender, upward_message) = <Self as Store>::Overweight::get(index)
+				.ok_or(Error::<T>::UnknownMessageIndex)?;
+			let used = T::UmpSink::process_upward_message(sender, &upward_message, weight_limit)
+				.map_err(|_| Error::<T>::WeightOverLimit)?;
+
+			<Self as Store>::Overweight::remove(index);
+			Self::deposit_event(Event::OverweightServiced(index, used));
+
+			Ok(Some(used.saturating_add(T::DbWeight::get().reads_writes(1, 1))).into())
+		}
 	}
 }
 
@@ -311,8 +381,19 @@ impl<T: Trait> Module<T> {
 			// dequeue the next message from the queue of the dispatchee
 			let (upward_message, became_empty) = queue_cache.dequeue::<T>(dispatchee);
 			if let Some(upward_message) = upward_message {
-				used_weight_so_far +=
-					T::UmpSink::process_upward_message(dispatchee, upward_message);
+				match T::UmpSink::process_upward_message(
+					dispatchee,
+					&upward_message,
+					config.ump_max_individual_weight,
+				) {
+					Ok(used) => used_weight_so_far += used,
+					Err(required) => {
+						// the message would block the queue of the dispatchee for good, so set it
+						// aside.
+						let index = Self::stash_overweight(dispatchee, upward_message);
+						Self::deposit_event(Event::OverweightEnqueued(dispatchee, index, required));
+					}
+				}
 			}
 
 			if became_empty {
@@ -326,6 +407,18 @@ impl<T: Trait> Module<T> {
 		cursor.flush::<T>();
 		queue_cache.flush::<T>();
 	}
+
+	/// Puts the given message into the overweight queue and returns its index.
+	fn stash_overweight(sender: ParaId, upward_message: UpwardMessage) -> OverweightIndex {
+		let index = <Self as Store>::OverweightCount::mutate(|count| {
+			let index = *count;
+			*count += 1;
+			index
+		});
+
+		<Self as Store>::Overweight::insert(index, (sender, upward_message));
+		index
+	}
 }
 
 /// To avoid constant fetching, deserializing and serialization the queues are cached.
@@ -495,6 +588,9 @@ pub(crate) mod mock_sink {
 	//!
 	//! A default behavior of the UMP sink is to ignore an incoming message and return 0 weight.
 	//!
+	//! A message whose mocked weight exceeds the weight limit it is processed with is reported as
+	//! requiring that weight. It still counts as received by the probe.
+	//!
 	//! A probe can be attached to the mock UMP sink. When attached, the mock sink would consult the
 	//! probe to check whether the received message was expected and what weight it should return.
 	//!
@@ -525,7 +621,11 @@ pub(crate) mod mock_sink {
 
 	pub struct MockUmpSink;
 	impl UmpSink for MockUmpSink {
-		fn process_upward_message(actual_origin: ParaId, actual_msg: Vec<u8>) -> Weight {
+		fn process_upward_message(
+			actual_origin: ParaId,
+			actual_msg: &[u8],
+			max_weight: Weight,
+		) -> Result<Weight, Weight> {
 			HOOK.with(|opt_hook| match &mut *opt_hook.borrow_mut() {
 				Some(hook) => {
 					let UmpExpectation {
@@ -542,10 +642,14 @@ pub(crate) mod mock_sink {
 						}
 					};
 					assert_eq!(expected_origin, actual_origin);
-					assert_eq!(expected_msg, actual_msg);
-					mock_weight
+					assert_eq!(&expected_msg[..], actual_msg);
+					if mock_weight > max_weight {
+						Err(mock_weight)
+					} else {
+						Ok(mock_weight)
+					}
 				}
-				None => 0,
+				None => Ok(0),
 			})
 		}
 	}
@@ -623,8 +727,11 @@ pub(crate) mod mock_sink {
 mod tests {
 	use super::*;
 	use super::mock_sink::Probe;
-	use crate::mock::{Configuration, Ump, new_test_ext, GenesisConfig as MockGenesisConfig};
-	use frame_support::IterableStorageMap;
+	use crate::mock::{
+		Configuration, Ump, System, Origin, Test, TestEvent, new_test_ext,
+		GenesisConfig as MockGenesisConfig,
+	};
+	use frame_support::{assert_noop, assert_ok, IterableStorageMap};
 	use std::collections::HashSet;
 
 	struct GenesisConfigBuilder {
@@ -633,6 +740,7 @@ mod tests {
 		max_upward_queue_count: u32,
 		max_upward_queue_size: u32,
 		preferred_dispatchable_upward_messages_step_weight: Weight,
+		ump_max_individual_weight: Weight,
 	}
 
 	impl Default for GenesisConfigBuilder {
@@ -643,6 +751,7 @@ mod tests {
 				max_upward_queue_count: 4,
 				max_upward_queue_size: 64,
 				preferred_dispatchable_upward_messages_step_weight: 1000,
+				ump_max_individual_weight: 1000,
 			}
 		}
 	}
@@ -658,6 +767,7 @@ mod tests {
 			config.max_upward_queue_size = self.max_upward_queue_size;
 			config.preferred_dispatchable_upward_messages_step_weight =
 				self.preferred_dispatchable_upward_messages_step_weight;
+			config.ump_max_individual_weight = self.ump_max_individual_weight;
 			genesis
 		}
 	}
@@ -871,4 +981,82 @@ mod tests {
 			}
 		});
 	}
+
+	#[test]
+	fn overweight_messages_are_set_aside() {
+		let a = ParaId::from(2020);
+
+		let a_msg_1 = vec![1, 2, 3];
+		let a_msg_2 = vec![4, 5, 6];
+		let a_msg_3 = vec![7, 8, 9];
+
+		new_test_ext(
+			GenesisConfigBuilder {
+				ump_max_individual_weight: 500,
+				..Default::default()
+			}
+			.build(),
+		)
+		.execute_with(|| {
+			// events are not deposited in the genesis block.
+			System::set_block_number(1);
+
+			queue_upward_msg(a, a_msg_1.clone());
+			queue_upward_msg(a, a_msg_2.clone());
+			queue_upward_msg(a, a_msg_3.clone());
+
+			// the second message is too heavy, but it doesn't block the rest of the queue.
+			{
+				let mut probe = Probe::new();
+
+				probe.assert_msg(a, a_msg_1.clone(), 300);
+				probe.assert_msg(a, a_msg_2.clone(), 700);
+				probe.assert_msg(a, a_msg_3.clone(), 300);
+				Ump::process_pending_upward_messages();
+				assert_storage_consistency_exhaustive();
+
+				drop(probe);
+			}
+
+			assert!(<Ump as Store>::RelayDispatchQueues::get(&a).is_empty());
+			assert_eq!(<Ump as Store>::OverweightCount::get(), 1);
+			assert_eq!(Ump::overweight(0), Some((a, a_msg_2.clone())));
+			assert!(System::events().iter().any(|record| {
+				record.event == TestEvent::ump(Event::OverweightEnqueued(a, 0, 700))
+			}));
+
+			// servicing requires root and enough weight.
+			assert!(Ump::service_overweight(Origin::signed(1), 0, 1000).is_err());
+			{
+				let mut probe = Probe::new();
+
+				probe.assert_msg(a, a_msg_2.clone(), 700);
+				assert_noop!(
+					Ump::service_overweight(Origin::root(), 0, 600),
+					Error::<Test>::WeightOverLimit,
+				);
+
+				drop(probe);
+			}
+
+			{
+				let mut probe = Probe::new();
+
+				probe.assert_msg(a, a_msg_2.clone(), 700);
+				assert_ok!(Ump::service_overweight(Origin::root(), 0, 1000));
+
+				drop(probe);
+			}
+
+			assert!(Ump::overweight(0).is_none());
+			assert!(System::events().iter().any(|record| {
+				record.event == TestEvent::ump(Event::OverweightServiced(0, 700))
+			}));
+
+			assert_noop!(
+				Ump::service_overweight(Origin::root(), 0, 1000),
+				Error::<Test>::UnknownMessageIndex,
+			);
+		});
+	}
 }
@@ -28,7 +28,7 @@ use crate::{configuration, paras, dmp, hrmp};
 pub fn make_persisted_validation_data<T: paras::Trait + hrmp::Trait>(
 	para_id: ParaId,
 ) -> Option<PersistedValidationData<T::BlockNumber>> {
-	let config = <configuration::Module<T>>::config();
+	let config = <configuration::Module<T>>::effective_config(para_id);
 	let relay_parent_number = <frame_system::Module<T>>::block_number() - One::one();
 
 	Some(PersistedValidationData {
@@ -46,7 +46,7 @@ pub fn make_persisted_validation_data<T: paras::Trait + hrmp::Trait>(
 pub fn make_transient_validation_data<T: paras::Trait + dmp::Trait>(
 	para_id: ParaId,
 ) -> Option<TransientValidationData<T::BlockNumber>> {
-	let config = <configuration::Module<T>>::config();
+	let config = <configuration::Module<T>>::effective_config(para_id);
 	let relay_parent_number = <frame_system::Module<T>>::block_number() - One::one();
 
 	let freq = config.validation_upgrade_frequency;
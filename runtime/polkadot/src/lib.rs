@@ -37,7 +37,8 @@ use primitives::v1::{
 	AccountId, AccountIndex, Balance, BlockNumber, CandidateEvent, CommittedCandidateReceipt,
 	CoreState, GroupRotationInfo, Hash, Id, Moment, Nonce, OccupiedCoreAssumption,
 	PersistedValidationData, Signature, ValidationCode, ValidationData, ValidatorId, ValidatorIndex,
-	InboundDownwardMessage, InboundHrmpMessage,
+	InboundDownwardMessage, InboundHrmpMessage, ParaLimits,
+	MessageQueueChain, MessageQueueChainHead,
 };
 use sp_runtime::{
 	create_runtime_str, generic, impl_opaque_keys, ModuleId, ApplyExtrinsicResult,
@@ -93,13 +94,13 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	spec_name: create_runtime_str!("polkadot"),
 	impl_name: create_runtime_str!("parity-polkadot"),
 	authoring_version: 0,
-	spec_version: 26,
+	spec_version: 27,
 	impl_version: 0,
 	#[cfg(not(feature = "disable-runtime-api"))]
 	apis: RUNTIME_API_VERSIONS,
 	#[cfg(feature = "disable-runtime-api")]
 	apis: version::create_apis_vec![[]],
-	transaction_version: 5,
+	transaction_version: 6,
 };
 
 /// Native version.
@@ -1118,6 +1119,14 @@ sp_api::impl_runtime_apis! {
 			BTreeMap::new()
 		}
 
+		fn para_limits(_para_id: Id) -> Option<ParaLimits> {
+			None
+		}
+
+		fn mqc_head(_queue: MessageQueueChain) -> Option<MessageQueueChainHead> {
+			None
+		}
+
 	}
 
 	impl fg_primitives::GrandpaApi<Block> for Runtime {
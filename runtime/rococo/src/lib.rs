@@ -28,7 +28,8 @@ use primitives::v1::{
 	AccountId, AccountIndex, Balance, BlockNumber, Hash, Nonce, Signature, Moment,
 	GroupRotationInfo, CoreState, Id, ValidationData, ValidationCode, CandidateEvent,
 	ValidatorId, ValidatorIndex, CommittedCandidateReceipt, OccupiedCoreAssumption,
-	PersistedValidationData, InboundDownwardMessage, InboundHrmpMessage,
+	PersistedValidationData, InboundDownwardMessage, InboundHrmpMessage, ParaLimits,
+	MessageQueueChain, MessageQueueChainHead,
 };
 use runtime_common::{
 	SlowAdjustingFeeUpdate,
@@ -77,6 +78,7 @@ use runtime_parachains::dmp as parachains_dmp;
 use runtime_parachains::ump as parachains_ump;
 use runtime_parachains::hrmp as parachains_hrmp;
 use runtime_parachains::scheduler as parachains_scheduler;
+use runtime_parachains::parathread_claims as parachains_parathread_claims;
 
 pub use pallet_balances::Call as BalancesCall;
 pub use pallet_staking::StakerStatus;
@@ -94,7 +96,7 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	spec_name: create_runtime_str!("rococo"),
 	impl_name: create_runtime_str!("parity-rococo-v1"),
 	authoring_version: 0,
-	spec_version: 10,
+	spec_version: 11,
 	impl_version: 0,
 	#[cfg(not(feature = "disable-runtime-api"))]
 	apis: RUNTIME_API_VERSIONS,
@@ -187,10 +189,11 @@ construct_runtime! {
 		Paras: parachains_paras::{Module, Call, Storage},
 		Initializer: parachains_initializer::{Module, Call, Storage},
 		Dmp: parachains_dmp::{Module, Call, Storage},
-		Ump: parachains_ump::{Module, Call, Storage},
-		Hrmp: parachains_hrmp::{Module, Call, Storage},
+		Ump: parachains_ump::{Module, Call, Storage, Event},
+		Hrmp: parachains_hrmp::{Module, Call, Storage, Event, Config},
+		ParathreadClaims: parachains_parathread_claims::{Module, Call, Storage, Event<T>},
 
-		Registrar: paras_registrar::{Module, Call, Storage},
+		Registrar: paras_registrar::{Module, Call, Storage, Event<T>},
 		ParasSudoWrapper: paras_sudo_wrapper::{Module, Call},
 
 		// Sudo
@@ -537,18 +540,36 @@ impl parachains_paras::Trait for Runtime {
 }
 
 impl parachains_ump::Trait for Runtime {
-	type UmpSink = (); // TODO: #1873 To be handled by the XCM receiver.
+	type Event = Event;
+	// TODO: #1873 All other messages are to be handled by the XCM receiver.
+	type UmpSink = parachains_hrmp::XcmHrmpSink<Runtime, ()>;
 }
 
 impl parachains_dmp::Trait for Runtime {}
 
 impl parachains_hrmp::Trait for Runtime {
+	type Event = Event;
 	type Origin = Origin;
+	type Currency = Balances;
 }
 
 impl parachains_inclusion_inherent::Trait for Runtime {}
 
-impl parachains_scheduler::Trait for Runtime {}
+impl parachains_scheduler::Trait for Runtime {
+	type ClaimHandler = ParathreadClaims;
+}
+
+parameter_types! {
+	pub const BaseParathreadClaimFee: Balance = 10 * CENTS;
+	pub const DroppedParathreadClaimPenalty: Perbill = Perbill::from_percent(50);
+}
+
+impl parachains_parathread_claims::Trait for Runtime {
+	type Event = Event;
+	type Currency = Balances;
+	type BaseClaimFee = BaseParathreadClaimFee;
+	type DroppedClaimPenalty = DroppedParathreadClaimPenalty;
+}
 
 impl parachains_initializer::Trait for Runtime {
 	type Randomness = Babe;
@@ -556,10 +577,18 @@ impl parachains_initializer::Trait for Runtime {
 
 impl paras_sudo_wrapper::Trait for Runtime {}
 
+parameter_types! {
+	pub const ParaDeposit: Balance = 5 * DOLLARS;
+	pub const DataDepositPerByte: Balance = 5 * MILLICENTS;
+}
+
 impl paras_registrar::Trait for Runtime {
 	type Currency = Balances;
 	type ParathreadDeposit = ParathreadDeposit;
 	type Origin = Origin;
+	type Event = Event;
+	type ParaDeposit = ParaDeposit;
+	type DataDepositPerByte = DataDepositPerByte;
 }
 
 impl pallet_sudo::Trait for Runtime {
@@ -701,6 +730,14 @@ sp_api::impl_runtime_apis! {
 		) -> BTreeMap<Id, Vec<InboundHrmpMessage<BlockNumber>>> {
 			runtime_api_impl::inbound_hrmp_channels_contents::<Runtime>(recipient)
 		}
+
+		fn para_limits(para_id: Id) -> Option<ParaLimits> {
+			runtime_api_impl::para_limits::<Runtime>(para_id)
+		}
+
+		fn mqc_head(queue: MessageQueueChain) -> Option<MessageQueueChainHead> {
+			runtime_api_impl::mqc_head::<Runtime>(queue)
+		}
 	}
 
 	impl fg_primitives::GrandpaApi<Block> for Runtime {
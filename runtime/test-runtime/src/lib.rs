@@ -40,7 +40,8 @@ use primitives::v1::{
 	AccountId, AccountIndex, Balance, BlockNumber, CandidateEvent, CommittedCandidateReceipt,
 	CoreState, GroupRotationInfo, Hash as HashT, Id as ParaId, Moment, Nonce, OccupiedCoreAssumption,
 	PersistedValidationData, Signature, ValidationCode, ValidationData, ValidatorId, ValidatorIndex,
-	InboundDownwardMessage, InboundHrmpMessage,
+	InboundDownwardMessage, InboundHrmpMessage, ParaLimits,
+	MessageQueueChain, MessageQueueChainHead,
 };
 use runtime_common::{
 	claims, SlowAdjustingFeeUpdate, paras_sudo_wrapper,
@@ -464,14 +465,19 @@ impl parachains_paras::Trait for Runtime {
 impl parachains_dmp::Trait for Runtime {}
 
 impl parachains_ump::Trait for Runtime {
-	type UmpSink = ();
+	type Event = Event;
+	type UmpSink = parachains_hrmp::XcmHrmpSink<Runtime, ()>;
 }
 
 impl parachains_hrmp::Trait for Runtime {
+	type Event = Event;
 	type Origin = Origin;
+	type Currency = Balances;
 }
 
-impl parachains_scheduler::Trait for Runtime {}
+impl parachains_scheduler::Trait for Runtime {
+	type ClaimHandler = ();
+}
 
 impl paras_sudo_wrapper::Trait for Runtime {}
 
@@ -515,6 +521,8 @@ construct_runtime! {
 		Initializer: parachains_initializer::{Module, Call, Storage},
 		Paras: parachains_paras::{Module, Call, Storage, Origin},
 		Scheduler: parachains_scheduler::{Module, Call, Storage},
+		Ump: parachains_ump::{Module, Call, Storage, Event},
+		Hrmp: parachains_hrmp::{Module, Call, Storage, Event},
 		ParasSudoWrapper: paras_sudo_wrapper::{Module, Call},
 
 		Sudo: pallet_sudo::{Module, Call, Storage, Config<T>, Event<T>},
@@ -693,6 +701,14 @@ sp_api::impl_runtime_apis! {
 		) -> BTreeMap<ParaId, Vec<InboundHrmpMessage<BlockNumber>>> {
 			runtime_impl::inbound_hrmp_channels_contents::<Runtime>(recipient)
 		}
+
+		fn para_limits(para_id: ParaId) -> Option<ParaLimits> {
+			runtime_impl::para_limits::<Runtime>(para_id)
+		}
+
+		fn mqc_head(queue: MessageQueueChain) -> Option<MessageQueueChainHead> {
+			runtime_impl::mqc_head::<Runtime>(queue)
+		}
 	}
 
 	impl fg_primitives::GrandpaApi<Block> for Runtime {
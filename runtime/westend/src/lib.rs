@@ -28,7 +28,8 @@ use primitives::v1::{
 	AccountId, AccountIndex, Balance, BlockNumber, CandidateEvent, CommittedCandidateReceipt,
 	CoreState, GroupRotationInfo, Hash, Id, Moment, Nonce, OccupiedCoreAssumption,
 	PersistedValidationData, Signature, ValidationCode, ValidationData, ValidatorId, ValidatorIndex,
-	InboundDownwardMessage, InboundHrmpMessage,
+	InboundDownwardMessage, InboundHrmpMessage, ParaLimits,
+	MessageQueueChain, MessageQueueChainHead,
 };
 use runtime_common::{
 	SlowAdjustingFeeUpdate, CurrencyToVote,
@@ -867,6 +868,14 @@ sp_api::impl_runtime_apis! {
 		) -> BTreeMap<Id, Vec<InboundHrmpMessage<BlockNumber>>> {
 			BTreeMap::new()
 		}
+
+		fn para_limits(_para_id: Id) -> Option<ParaLimits> {
+			None
+		}
+
+		fn mqc_head(_queue: MessageQueueChain) -> Option<MessageQueueChainHead> {
+			None
+		}
 	}
 
 	impl fg_primitives::GrandpaApi<Block> for Runtime {
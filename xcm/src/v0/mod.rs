@@ -188,10 +188,10 @@ pub enum Xcm {
 		#[codec(compact)] recipient: u32,
 	},
 
-	/// A message to notify that the other party in an open channel decided to close it. In particular,
+	/// A message to notify that a party in an open channel decided to close it. In particular,
 	/// `inititator` is going to close the channel opened from `sender` to the `recipient`. The close
 	/// will be enacted at the next relay-chain session change. This message is meant to be sent by
-	/// the relay-chain to a para.
+	/// the relay-chain to both paras of the channel.
 	///
 	/// Safety: The message should originate directly from the relay-chain.
 	///
@@ -203,6 +203,68 @@ pub enum Xcm {
 		#[codec(compact)] sender: u32,
 		#[codec(compact)] recipient: u32,
 	},
+
+	/// A request of the `origin` para to open an HRMP channel from itself to the `recipient`. This
+	/// message is meant to be sent by a para to the relay-chain.
+	///
+	/// - `recipient`: The recipient in the to-be opened channel.
+	/// - `max_message_size`: The maximum size of a message proposed by the sender.
+	/// - `max_capacity`: The maximum number of messages that can be queued in the channel.
+	///
+	/// Safety: The message should originate directly from the para which is going to be the sender
+	/// in the channel.
+	///
+	/// Kind: *Instruction*
+	///
+	/// Errors:
+	HrmpInitOpenChannel {
+		#[codec(compact)] recipient: u32,
+		#[codec(compact)] max_message_size: u32,
+		#[codec(compact)] max_capacity: u32,
+	},
+
+	/// An acceptance by the `origin` para of a pending request to open an HRMP channel from the
+	/// `sender` to itself. This message is meant to be sent by a para to the relay-chain.
+	///
+	/// - `sender`: The sender in the to-be opened channel.
+	///
+	/// Safety: The message should originate directly from the para which is going to be the
+	/// recipient in the channel.
+	///
+	/// Kind: *Instruction*
+	///
+	/// Errors:
+	HrmpAcceptOpenChannel {
+		#[codec(compact)] sender: u32,
+	},
+
+	/// A request of the `origin` para to close the HRMP channel opened from `sender` to `recipient`.
+	/// This message is meant to be sent by a para to the relay-chain.
+	///
+	/// Safety: The message should originate directly from either the sender or the recipient in the
+	/// channel.
+	///
+	/// Kind: *Instruction*
+	///
+	/// Errors:
+	HrmpCloseChannel {
+		#[codec(compact)] sender: u32,
+		#[codec(compact)] recipient: u32,
+	},
+
+	/// A message to notify that the HRMP channel opened from `sender` to `recipient` has been
+	/// closed, either because it was requested to or because one of the paras was offboarded. This
+	/// message is meant to be sent by the relay-chain to both paras of the channel.
+	///
+	/// Safety: The message should originate directly from the relay-chain.
+	///
+	/// Kind: *System Notification*
+	///
+	/// Errors:
+	HrmpChannelClosed {
+		#[codec(compact)] sender: u32,
+		#[codec(compact)] recipient: u32,
+	},
 }
 
 impl From<Xcm> for VersionedXcm {